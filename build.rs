@@ -0,0 +1,32 @@
+//! Generates `guest_limits.rs` for the `limits` module from the guest
+//! program's `#[jolt::provable]` attributes, and under the `sp1` feature
+//! builds the SP1 program embedded by the `sp1` module.
+
+use std::{env, fs, path::PathBuf};
+
+#[path = "src/jolt/guest/build_limits.rs"]
+mod build_limits;
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("set by cargo"));
+    let guest_source = manifest_dir.join("src/jolt/guest/src/lib.rs");
+    println!("cargo:rerun-if-changed={}", guest_source.display());
+    println!("cargo:rerun-if-changed=src/jolt/guest/build_limits.rs");
+
+    let source = fs::read_to_string(&guest_source).expect("guest source is readable");
+    let entry_points = build_limits::parse_entry_points(&source)
+        .unwrap_or_else(|e| panic!("Invalid guest limits: {}", e));
+    assert!(!entry_points.is_empty(), "Guest declares no entry points");
+    let tier_capacities = build_limits::parse_tier_capacities(&source)
+        .unwrap_or_else(|e| panic!("Invalid guest tiers: {}", e));
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("set by cargo"));
+    fs::write(
+        out_dir.join("guest_limits.rs"),
+        build_limits::generate(&entry_points, &tier_capacities),
+    )
+    .expect("OUT_DIR is writable");
+
+    #[cfg(feature = "sp1")]
+    sp1_build::build_program("src/sp1/program");
+}
@@ -1,8 +1,10 @@
 // Aggregation validation and batch preparation logic
 
+use crate::commitment::{KeyFingerprint, public_key_fingerprint};
 use crate::error::AggregationError;
-use crate::types::{AggregationBatch, VerificationItem};
-use std::collections::HashSet;
+use crate::types::{AggregationBatch, AggregationProof, SchemeItem, VerificationItem};
+use hashsig::signature::SignatureScheme;
+use std::collections::{BTreeMap, HashSet};
 
 /// Validates aggregation batch constraints.
 ///
@@ -41,6 +43,13 @@ use std::collections::HashSet;
 /// Each (public_key, epoch) combination must be unique to prevent XMSS signature
 /// reuse within the aggregated batch.
 pub fn validate(items: &[VerificationItem]) -> Result<(), AggregationError> {
+    validate_items(items)
+}
+
+/// [`validate`] for items of any instantiation.
+pub(crate) fn validate_items<S: SignatureScheme>(
+    items: &[SchemeItem<S>],
+) -> Result<(), AggregationError> {
     if items.is_empty() {
         return Err(AggregationError::EmptyBatch);
     }
@@ -50,27 +59,125 @@ pub fn validate(items: &[VerificationItem]) -> Result<(), AggregationError> {
     let mut key_epoch_pairs: HashSet<(Vec<u8>, u32)> = HashSet::new();
 
     for item in items {
-        // Serialize the public key to use as a hash key
-        let pk_bytes = bincode::serialize(&item.public_key).map_err(|e| {
-            AggregationError::SerializationError {
-                message: format!("Failed to serialize public key: {}", e),
-            }
+        insert_key_epoch_pair(&mut key_epoch_pairs, item)?;
+    }
+
+    Ok(())
+}
+
+/// Records the item's (public_key, epoch) pair, failing if it was already seen.
+fn insert_key_epoch_pair<S: SignatureScheme>(
+    key_epoch_pairs: &mut HashSet<(Vec<u8>, u32)>,
+    item: &SchemeItem<S>,
+) -> Result<(), AggregationError> {
+    // Serialize the public key to use as a hash key
+    let pk_bytes =
+        bincode::serialize(&item.public_key).map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to serialize public key: {}", e),
         })?;
 
-        let pair = (pk_bytes.clone(), item.epoch);
-        if !key_epoch_pairs.insert(pair) {
-            // Format public key for error message (truncated hex)
-            let pk_str = format!("{}...", hex::encode(&pk_bytes[..8.min(pk_bytes.len())]));
-            return Err(AggregationError::DuplicateKeyEpochPair {
-                public_key: pk_str,
-                epoch: item.epoch,
-            });
-        }
+    let duplicate = duplicate_pair(&pk_bytes, item.epoch);
+    if !key_epoch_pairs.insert((pk_bytes, item.epoch)) {
+        return Err(duplicate);
     }
-
     Ok(())
 }
 
+/// [`AggregationError::DuplicateKeyEpochPair`] for a serialized public key.
+pub(crate) fn duplicate_pair(pk_bytes: &[u8], epoch: u32) -> AggregationError {
+    // Format public key for error message (truncated hex)
+    let pk_str = format!("{}...", hex::encode(&pk_bytes[..8.min(pk_bytes.len())]));
+    AggregationError::DuplicateKeyEpochPair {
+        public_key: pk_str,
+        epoch,
+    }
+}
+
+/// Incrementally builds a batch, enforcing the same constraints as
+/// [`validate`] as each item arrives.
+///
+/// Streaming ingestion rejects a duplicate (public_key, epoch) pair the
+/// moment it is submitted, instead of failing the whole batch at
+/// [`aggregate`] time.
+///
+/// # Examples
+///
+/// ```no_run
+/// use sig_agg::aggregator::BatchBuilder;
+/// # let feed: Vec<sig_agg::VerificationItem> = vec![];
+///
+/// let mut builder = BatchBuilder::new();
+/// for item in feed {
+///     if let Err(e) = builder.add_item(item) {
+///         eprintln!("Rejected item: {}", e);
+///     }
+///     if builder.estimated_payload_size() > 4 * 1024 * 1024 {
+///         break;
+///     }
+/// }
+/// let batch = builder.build().expect("No items accepted");
+/// ```
+#[derive(Debug, Default)]
+pub struct BatchBuilder {
+    items: Vec<VerificationItem>,
+    key_epoch_pairs: HashSet<(Vec<u8>, u32)>,
+    items_size: usize,
+}
+
+impl BatchBuilder {
+    /// Creates an empty builder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `item` to the batch.
+    ///
+    /// On error the item is not added and the builder is unchanged.
+    ///
+    /// # Errors
+    ///
+    /// * `DuplicateKeyEpochPair` - the item's (key, epoch) pair was already added
+    /// * `SerializationError` - the public key or item could not be encoded
+    pub fn add_item(&mut self, item: VerificationItem) -> Result<(), AggregationError> {
+        let size = item.encoded_size()?;
+        insert_key_epoch_pair(&mut self.key_epoch_pairs, &item)?;
+        self.items_size += size;
+        self.items.push(item);
+        Ok(())
+    }
+
+    /// Number of items added so far.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if no item was added yet.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Encoded size in bytes the batch would have if built now, as given by
+    /// [`AggregationBatch::encoded_size`].
+    #[must_use]
+    pub const fn estimated_payload_size(&self) -> usize {
+        // bincode length prefix of the item vector
+        8 + self.items_size
+    }
+
+    /// Finishes the batch.
+    ///
+    /// Fails with [`AggregationError::EmptyBatch`] if no item was added.
+    pub fn build(self) -> Result<AggregationBatch, AggregationError> {
+        if self.items.is_empty() {
+            return Err(AggregationError::EmptyBatch);
+        }
+        Ok(AggregationBatch { items: self.items })
+    }
+}
+
 /// Aggregates multiple XMSS signatures into a batch ready for zkVM verification.
 ///
 /// This is the main entry point for signature aggregation. It validates the input
@@ -143,9 +250,73 @@ pub fn aggregate(items: Vec<VerificationItem>) -> Result<AggregationBatch, Aggre
     Ok(AggregationBatch { items })
 }
 
+/// Splits a batch into one sub-batch per signer.
+///
+/// Sub-batches are keyed by the signer's [public key
+/// fingerprint](crate::commitment::public_key_fingerprint) and keep the items
+/// in their original order. Use this when consumers want one proof per signer
+/// rather than one proof over the whole batch.
+///
+/// # Errors
+///
+/// * `SerializationError` - a public key could not be fingerprinted
+pub fn split_by_signer(
+    batch: AggregationBatch,
+) -> Result<BTreeMap<KeyFingerprint, AggregationBatch>, AggregationError> {
+    let mut sub_batches: BTreeMap<KeyFingerprint, AggregationBatch> = BTreeMap::new();
+    for item in batch.items {
+        sub_batches
+            .entry(public_key_fingerprint(&item.public_key)?)
+            .or_insert_with(|| AggregationBatch { items: Vec::new() })
+            .items
+            .push(item);
+    }
+    Ok(sub_batches)
+}
+
+/// Proves every signer's sub-batch of `batch`, returning one proof per signer.
+///
+/// `prove` is called once per signer, in fingerprint order. It should capture
+/// the compiled guest and prover preprocessing so they are built once and
+/// shared across all runs instead of being redone per signer. Proving stops
+/// at the first error.
+///
+/// # Examples
+///
+/// ```no_run
+/// use sig_agg::aggregator::prove_per_signer;
+/// # let batch: sig_agg::AggregationBatch = unimplemented!();
+/// # let prove_with_shared_preprocessing =
+/// #     |_: sig_agg::AggregationBatch| -> Result<sig_agg::AggregationProof, sig_agg::AggregationError> { unimplemented!() };
+///
+/// let proofs = prove_per_signer(batch, |_signer, sub_batch| {
+///     prove_with_shared_preprocessing(sub_batch)
+/// })
+/// .expect("Proving failed");
+/// println!("{} signers proven", proofs.len());
+/// ```
+pub fn prove_per_signer<F>(
+    batch: AggregationBatch,
+    mut prove: F,
+) -> Result<BTreeMap<KeyFingerprint, AggregationProof>, AggregationError>
+where
+    F: FnMut(&KeyFingerprint, AggregationBatch) -> Result<AggregationProof, AggregationError>,
+{
+    split_by_signer(batch)?
+        .into_iter()
+        .map(|(signer, sub_batch)| {
+            let proof = prove(&signer, sub_batch)?;
+            Ok((signer, proof))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        commitment::CommitmentAlgorithm, profile::ComplianceProfile, types::ProofMetadata,
+    };
     use hashsig::MESSAGE_LENGTH;
     use hashsig::signature::generalized_xmss::instantiations_poseidon::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W1;
     use hashsig::signature::SignatureScheme;
@@ -388,4 +559,99 @@ mod tests {
         let batch = result.unwrap();
         assert_eq!(batch.items.len(), 10);
     }
+
+    fn create_test_item_2(epoch: u32) -> VerificationItem {
+        let (pk, sk) = get_test_keypair_2();
+        let message = [epoch as u8; MESSAGE_LENGTH];
+        VerificationItem {
+            message,
+            epoch,
+            signature: XMSSSignature::sign(sk, epoch, &message).expect("Signing should succeed"),
+            public_key: bincode::deserialize(&bincode::serialize(pk).unwrap()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_batch_builder_rejects_duplicates_incrementally() {
+        let builder = BatchBuilder::new();
+        assert!(builder.is_empty());
+        assert!(matches!(builder.build(), Err(AggregationError::EmptyBatch)));
+
+        let mut builder = BatchBuilder::new();
+        builder.add_item(create_test_item(0)).unwrap();
+        builder.add_item(create_test_item(1)).unwrap();
+        assert!(matches!(
+            builder.add_item(create_test_item(1)),
+            Err(AggregationError::DuplicateKeyEpochPair { epoch: 1, .. })
+        ));
+        assert_eq!(builder.len(), 2);
+
+        let size = builder.estimated_payload_size();
+        let batch = builder.build().unwrap();
+        assert_eq!(size, batch.encoded_size().unwrap());
+        assert!(validate(&batch.items).is_ok());
+    }
+
+    #[test]
+    fn test_split_by_signer() {
+        let items = vec![
+            create_test_item(0),
+            create_test_item_2(100),
+            create_test_item(1),
+            create_test_item_2(101),
+            create_test_item(2),
+        ];
+        let first_signer = public_key_fingerprint(&items[0].public_key).unwrap();
+
+        let sub_batches = split_by_signer(AggregationBatch { items }).unwrap();
+        assert_eq!(sub_batches.len(), 2);
+
+        let epochs: Vec<_> = sub_batches[&first_signer]
+            .items
+            .iter()
+            .map(|item| item.epoch)
+            .collect();
+        assert_eq!(epochs, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_prove_per_signer() {
+        let items = vec![
+            create_test_item(0),
+            create_test_item_2(100),
+            create_test_item(1),
+        ];
+        let batch = AggregationBatch { items };
+
+        let mut runs = 0;
+        let proofs = prove_per_signer(batch, |_, sub_batch| {
+            runs += 1;
+            Ok(AggregationProof {
+                proof: vec![],
+                verified_count: sub_batch.items.len() as u32,
+                metadata: ProofMetadata {
+                    timestamp: 0,
+                    batch_size: sub_batch.items.len(),
+                    memory_size: 0,
+                    trace_length: 0,
+                    max_trace_length: 0,
+                    proof_size: 0,
+                    guest_hash: [0u8; 32],
+                    urs_digest: [0u8; 32],
+                    commitment_hash: CommitmentAlgorithm::Sha256,
+                    template: None,
+                    cost: None,
+                    profile: ComplianceProfile::Standard,
+                },
+                failed: vec![],
+                verified_root: [0u8; 32],
+            })
+        })
+        .unwrap();
+
+        assert_eq!(runs, 2);
+        let mut counts: Vec<_> = proofs.values().map(|proof| proof.verified_count).collect();
+        counts.sort_unstable();
+        assert_eq!(counts, vec![1, 2]);
+    }
 }
@@ -0,0 +1,316 @@
+//! Batch announcements published before proving.
+//!
+//! Proving a batch takes minutes. If the collector only reveals the batch
+//! when the proof arrives, participants cannot tell whether the proven batch
+//! is the one that was cut, or whether items were swapped or dropped in
+//! between. A [`BatchAnnouncement`] fixes the batch commitment and the digest
+//! of every item at cut time. Participants check that their items are
+//! included ([`BatchAnnouncement::includes`]), and once the proof arrives
+//! anyone can check the proven batch against the announcement
+//! ([`BatchAnnouncement::check_batch`]).
+//!
+//! [`BatchAnnouncement::with_policy`] additionally announces the policies the
+//! batch was aggregated under and commits to them, so the rules are fixed
+//! before proving as well.
+//!
+//! Announcements are handed to an [`AnnouncementSink`]:
+//!
+//! - [`JsonLinesSink`] appends one JSON line per announcement to any writer,
+//!   e.g. a log file.
+//! - `WebhookSink` (behind the `http` feature) POSTs the JSON to a URL.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::announce::{AnnouncementSink, BatchAnnouncement, JsonLinesSink};
+//! # let batch: sig_agg::AggregationBatch = unimplemented!();
+//!
+//! let announcement = BatchAnnouncement::new(&batch).expect("Commitment failed");
+//! let mut sink = JsonLinesSink::new(std::io::stdout());
+//! sink.publish(&announcement).expect("Publishing failed");
+//! // ... start proving ...
+//! ```
+
+use std::{
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::item_digest,
+    commitment::{
+        BatchCommitment, CommitmentAlgorithm, CommitmentHash, PolicyMetadata, Sha256Hash,
+        batch_commitment_using, batch_commitment_with_policy_using,
+    },
+    error::AggregationError,
+    types::{AggregationBatch, VerificationItem},
+};
+
+/// Commitment and item digests of a batch, fixed at cut time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchAnnouncement {
+    /// Batch commitment the proof will be checked against
+    #[serde(with = "crate::serde_hex")]
+    pub commitment: BatchCommitment,
+    /// Hash backend of `commitment`
+    pub commitment_hash: CommitmentAlgorithm,
+    /// Number of items in the batch
+    pub batch_size: usize,
+    /// Digest of each item, in batch order (see [`item_digest`])
+    #[serde(
+        serialize_with = "crate::serde_hex::serialize_list",
+        deserialize_with = "crate::serde_hex::deserialize_list"
+    )]
+    pub item_digests: Vec<[u8; 32]>,
+    /// Unix timestamp (seconds) of the batch cut
+    pub announced_at: u64,
+    /// Policies bound into `commitment`, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<PolicyMetadata>,
+}
+
+impl BatchAnnouncement {
+    /// Announces `batch` with a SHA-256 commitment.
+    pub fn new(batch: &AggregationBatch) -> Result<Self, AggregationError> {
+        Self::new_using::<Sha256Hash>(batch)
+    }
+
+    /// Announces `batch` with a commitment under hash backend `H`.
+    pub fn new_using<H: CommitmentHash>(
+        batch: &AggregationBatch,
+    ) -> Result<Self, AggregationError> {
+        Self::announce::<H>(batch, batch_commitment_using::<H>(batch)?, None)
+    }
+
+    /// Announces `batch` with a SHA-256 commitment bound to `policy`.
+    pub fn with_policy(
+        batch: &AggregationBatch,
+        policy: PolicyMetadata,
+    ) -> Result<Self, AggregationError> {
+        Self::with_policy_using::<Sha256Hash>(batch, policy)
+    }
+
+    /// Announces `batch` with a commitment bound to `policy` under hash
+    /// backend `H`.
+    pub fn with_policy_using<H: CommitmentHash>(
+        batch: &AggregationBatch,
+        policy: PolicyMetadata,
+    ) -> Result<Self, AggregationError> {
+        let commitment = batch_commitment_with_policy_using::<H>(batch, &policy)?;
+        Self::announce::<H>(batch, commitment, Some(policy))
+    }
+
+    fn announce<H: CommitmentHash>(
+        batch: &AggregationBatch,
+        commitment: BatchCommitment,
+        policy: Option<PolicyMetadata>,
+    ) -> Result<Self, AggregationError> {
+        Ok(Self {
+            commitment,
+            commitment_hash: H::ALGORITHM,
+            batch_size: batch.items.len(),
+            item_digests: batch
+                .items
+                .iter()
+                .map(item_digest)
+                .collect::<Result<_, _>>()?,
+            announced_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            policy,
+        })
+    }
+
+    /// Returns `true` if `item` is part of the announced batch.
+    pub fn includes(&self, item: &VerificationItem) -> Result<bool, AggregationError> {
+        Ok(self.item_digests.contains(&item_digest(item)?))
+    }
+
+    /// Checks that `batch` is exactly the announced batch.
+    ///
+    /// Fails with [`AggregationError::ProofVerificationError`] if any item
+    /// differs, was added or was removed, or if the commitment differs.
+    /// Only SHA-256 announcements can be rechecked this way; for other
+    /// backends recompute the commitment with the matching `*_using`
+    /// function and compare it to `commitment`.
+    pub fn check_batch(&self, batch: &AggregationBatch) -> Result<(), AggregationError> {
+        let digests = batch
+            .items
+            .iter()
+            .map(item_digest)
+            .collect::<Result<Vec<_>, _>>()?;
+        if digests != self.item_digests {
+            return Err(AggregationError::ProofVerificationError {
+                message: "Batch items differ from the announcement".to_string(),
+            });
+        }
+
+        let commitment = match &self.policy {
+            Some(policy) => batch_commitment_with_policy_using::<Sha256Hash>(batch, policy)?,
+            None => batch_commitment_using::<Sha256Hash>(batch)?,
+        };
+        if self.commitment_hash == CommitmentAlgorithm::Sha256 && commitment != self.commitment {
+            return Err(AggregationError::ProofVerificationError {
+                message: "Batch commitment differs from the announcement".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Renders the announcement as compact JSON.
+    pub fn to_json(&self) -> Result<String, AggregationError> {
+        serde_json::to_string(self).map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to serialize announcement: {}", e),
+        })
+    }
+}
+
+/// Destination for batch announcements.
+pub trait AnnouncementSink {
+    /// Publishes `announcement`. Proving should not start if this fails.
+    fn publish(&mut self, announcement: &BatchAnnouncement) -> Result<(), AggregationError>;
+}
+
+/// Appends announcements as JSON lines to a writer.
+#[derive(Debug)]
+pub struct JsonLinesSink<W> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesSink<W> {
+    /// Creates a sink writing to `writer`.
+    pub const fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> AnnouncementSink for JsonLinesSink<W> {
+    fn publish(&mut self, announcement: &BatchAnnouncement) -> Result<(), AggregationError> {
+        writeln!(self.writer, "{}", announcement.to_json()?)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// POSTs announcements as JSON to a webhook URL.
+///
+/// Any non-2xx response is reported as [`AggregationError::IoError`].
+#[cfg(feature = "http")]
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    url: String,
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "http")]
+impl WebhookSink {
+    /// Creates a sink posting to `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            agent: ureq::Agent::new(),
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl AnnouncementSink for WebhookSink {
+    fn publish(&mut self, announcement: &BatchAnnouncement) -> Result<(), AggregationError> {
+        self.agent
+            .post(&self.url)
+            .set("Content-Type", "application/json")
+            .send_string(&announcement.to_json()?)
+            .map_err(|err| AggregationError::IoError {
+                message: format!("Announcement webhook {} failed: {}", self.url, err),
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commitment::batch_commitment, test_support::test_batch};
+
+    #[test]
+    fn test_announcement_covers_batch() {
+        let batch = test_batch(3);
+        let announcement = BatchAnnouncement::new(&batch).unwrap();
+
+        assert_eq!(announcement.commitment, batch_commitment(&batch).unwrap());
+        assert_eq!(announcement.batch_size, 3);
+        assert!(announcement.includes(&batch.items[1]).unwrap());
+        assert!(announcement.check_batch(&batch).is_ok());
+    }
+
+    #[test]
+    fn test_detects_post_hoc_tampering() {
+        let mut batch = test_batch(3);
+        let announcement = BatchAnnouncement::new(&batch).unwrap();
+
+        let dropped = batch.items.pop().unwrap();
+        assert!(announcement.includes(&dropped).unwrap());
+        assert!(matches!(
+            announcement.check_batch(&batch),
+            Err(AggregationError::ProofVerificationError { .. })
+        ));
+
+        let mut swapped = test_batch(3);
+        swapped.items[0].message[0] ^= 1;
+        assert!(!announcement.includes(&swapped.items[0]).unwrap());
+        assert!(announcement.check_batch(&swapped).is_err());
+    }
+
+    #[test]
+    fn test_policy_is_bound_into_the_commitment() {
+        let batch = test_batch(2);
+        let policy = PolicyMetadata {
+            epoch_window: Some(0..=1),
+            threshold: Some(2),
+            ..PolicyMetadata::default()
+        };
+        let announcement = BatchAnnouncement::with_policy(&batch, policy.clone()).unwrap();
+        assert_eq!(
+            announcement.commitment,
+            crate::commitment::batch_commitment_with_policy(&batch, &policy).unwrap()
+        );
+        assert!(announcement.check_batch(&batch).is_ok());
+
+        let parsed: BatchAnnouncement =
+            serde_json::from_str(&announcement.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.policy, Some(policy));
+
+        // Claiming a looser policy after the fact breaks the commitment
+        let mut loosened = announcement;
+        loosened.policy.as_mut().unwrap().threshold = Some(1);
+        assert!(matches!(
+            loosened.check_batch(&batch),
+            Err(AggregationError::ProofVerificationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_json_lines_sink() {
+        let batch = test_batch(2);
+        let announcement = BatchAnnouncement::new(&batch).unwrap();
+
+        let mut sink = JsonLinesSink::new(Vec::new());
+        sink.publish(&announcement).unwrap();
+        sink.publish(&announcement).unwrap();
+        let output = String::from_utf8(sink.into_inner()).unwrap();
+
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains(&hex::encode(announcement.commitment)));
+
+        let decoded: BatchAnnouncement = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(decoded, announcement);
+    }
+}
@@ -0,0 +1,348 @@
+//! Self-describing headers of serialized artifacts.
+//!
+//! A bincode payload carries no type or version information: a stale batch
+//! file, one written for another instantiation, or a file of another kind
+//! altogether often decodes without error into garbage. Batches and proof
+//! envelopes, the artifacts that carry signatures between processes and
+//! builds, therefore start with a small header that is checked before the
+//! payload is decoded:
+//!
+//! | Field | Encoding |
+//! |---|---|
+//! | magic | 4 bytes, one per [`ArtifactKind`] |
+//! | format version | u16 little-endian |
+//! | scheme length `s` | u8 |
+//! | scheme | `s` bytes, the XMSS instantiation name |
+//! | crate version length `c` | u8 |
+//! | crate version | `c` bytes, the `sig-agg` version that wrote the artifact |
+//!
+//! The magic and format version come first and are checked before anything
+//! else is read, so artifacts of older formats without the scheme and crate
+//! version fields are still rejected cleanly. Mismatches surface as
+//! [`UnexpectedArtifact`](AggregationError::UnexpectedArtifact),
+//! [`UnsupportedFormatVersion`](AggregationError::UnsupportedFormatVersion)
+//! and [`SchemeMismatch`](AggregationError::SchemeMismatch). The crate
+//! version is informational: it is reported on scheme mismatches but never
+//! rejected on its own.
+//!
+//! [Envelopes](crate::envelope) start with this header, and batches are
+//! written with it by [`encode_batch_artifact`] and
+//! [`encode_scheme_batch_artifact`]. Other formats do not use it: [proof
+//! files](crate::proof_file) have their own magic, version and checksum,
+//! [deltas](crate::delta) and [staging snapshots](crate::staging) record a
+//! format version in their payload, and [spool](crate::spool) files are
+//! scratch space read back only by the process that wrote them.
+//!
+//! # Examples
+//!
+//! ```
+//! use sig_agg::{AggregationError, artifact};
+//! # let batch = sig_agg::AggregationBatch { items: vec![] };
+//!
+//! let bytes = artifact::encode_batch_artifact(&batch).expect("Encoding failed");
+//! let header = artifact::read_header(&mut &bytes[..], &artifact::BATCH, artifact::DEFAULT_SCHEME)
+//!     .expect("Not a batch");
+//! assert_eq!(header.crate_version, env!("CARGO_PKG_VERSION"));
+//!
+//! // A reader expecting W2 signatures refuses the batch
+//! match artifact::read_header(&mut &bytes[..], &artifact::BATCH, "SIGWinternitzLifetime18W2") {
+//!     Err(AggregationError::SchemeMismatch { found, .. }) => {
+//!         assert_eq!(found, artifact::DEFAULT_SCHEME);
+//!     }
+//!     other => panic!("Unexpected result: {:?}", other),
+//! }
+//! ```
+
+use std::io::{Read, Write};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    error::AggregationError,
+    scheme::{Instantiation, SchemeBatch},
+    types::AggregationBatch,
+};
+
+/// Scheme name of [`VerificationItem`](crate::VerificationItem) signatures.
+pub const DEFAULT_SCHEME: &str = crate::types::SIGNATURE_SCHEME;
+
+/// Kind of a serialized artifact, identified by its magic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArtifactKind {
+    /// Human-readable name used in error messages
+    pub name: &'static str,
+    /// Leading bytes of every artifact of this kind
+    pub magic: [u8; 4],
+    /// Format version written, and the only one accepted on read
+    pub format_version: u16,
+}
+
+/// Batches written by [`encode_batch_artifact`] and
+/// [`encode_scheme_batch_artifact`].
+pub const BATCH: ArtifactKind = ArtifactKind {
+    name: "batch",
+    magic: *b"SAGB",
+    format_version: 1,
+};
+
+/// [Proof envelopes](crate::envelope).
+pub const ENVELOPE: ArtifactKind = ArtifactKind {
+    name: "proof envelope",
+    magic: crate::envelope::ENVELOPE_MAGIC,
+    format_version: crate::envelope::ENVELOPE_VERSION,
+};
+
+/// Every artifact kind, used to name the kind of a misplaced artifact.
+const KINDS: [ArtifactKind; 2] = [BATCH, ENVELOPE];
+
+/// Header read from an artifact that matched the expected kind and scheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactHeader {
+    /// Format version of the payload
+    pub format_version: u16,
+    /// XMSS instantiation of the signatures the artifact holds
+    pub scheme: String,
+    /// Version of `sig-agg` that wrote the artifact
+    pub crate_version: String,
+}
+
+/// Writes the header of an artifact of `kind` holding `scheme` signatures.
+///
+/// # Errors
+///
+/// * `SerializationError` - `scheme` is longer than 255 bytes
+/// * `IoError` - writing to `writer` failed
+pub fn write_header<W: Write>(
+    writer: &mut W,
+    kind: &ArtifactKind,
+    scheme: &str,
+) -> Result<(), AggregationError> {
+    writer.write_all(&kind.magic)?;
+    writer.write_all(&kind.format_version.to_le_bytes())?;
+    write_short_str(writer, scheme)?;
+    write_short_str(writer, env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
+
+/// Reads the header of an artifact that must be of `kind` and hold
+/// `scheme` signatures, leaving `reader` at the start of the payload.
+///
+/// # Errors
+///
+/// * `UnexpectedArtifact` - the magic is not `kind`'s
+/// * `UnsupportedFormatVersion` - the format version is not `kind`'s
+/// * `SchemeMismatch` - the artifact holds signatures of another scheme
+/// * `SerializationError` - the header is truncated or malformed
+/// * `IoError` - reading from `reader` failed
+pub fn read_header<R: Read>(
+    reader: &mut R,
+    kind: &ArtifactKind,
+    scheme: &str,
+) -> Result<ArtifactHeader, AggregationError> {
+    let mut magic = [0u8; 4];
+    read_exact(reader, kind, &mut magic)?;
+    if magic != kind.magic {
+        let found = KINDS.iter().find(|other| other.magic == magic).map_or_else(
+            || format!("unknown magic {}", hex::encode(magic)),
+            |other| other.name.to_string(),
+        );
+        return Err(AggregationError::UnexpectedArtifact {
+            expected: kind.name.to_string(),
+            found,
+        });
+    }
+
+    let mut version = [0u8; 2];
+    read_exact(reader, kind, &mut version)?;
+    let format_version = u16::from_le_bytes(version);
+    if format_version != kind.format_version {
+        return Err(AggregationError::UnsupportedFormatVersion {
+            artifact: kind.name.to_string(),
+            version: format_version,
+            supported: kind.format_version,
+        });
+    }
+
+    let found = read_short_str(reader, kind)?;
+    let crate_version = read_short_str(reader, kind)?;
+    if found != scheme {
+        return Err(AggregationError::SchemeMismatch {
+            artifact: kind.name.to_string(),
+            expected: scheme.to_string(),
+            found,
+            crate_version,
+        });
+    }
+
+    Ok(ArtifactHeader {
+        format_version,
+        scheme: found,
+        crate_version,
+    })
+}
+
+/// Encodes `batch` as a [`BATCH`] artifact.
+pub fn encode_batch_artifact(batch: &AggregationBatch) -> Result<Vec<u8>, AggregationError> {
+    encode_artifact(&BATCH, DEFAULT_SCHEME, batch)
+}
+
+/// Decodes a batch written by [`encode_batch_artifact`].
+///
+/// # Errors
+///
+/// Any error of [`read_header`], or `SerializationError` if the payload is
+/// malformed.
+pub fn decode_batch_artifact(bytes: &[u8]) -> Result<AggregationBatch, AggregationError> {
+    decode_artifact(&BATCH, DEFAULT_SCHEME, bytes)
+}
+
+/// Encodes a batch of another instantiation as a [`BATCH`] artifact.
+pub fn encode_scheme_batch_artifact<S: Instantiation>(
+    batch: &SchemeBatch<S>,
+) -> Result<Vec<u8>, AggregationError> {
+    encode_artifact(&BATCH, S::NAME, batch)
+}
+
+/// Decodes a batch written by [`encode_scheme_batch_artifact`] for the same
+/// instantiation.
+///
+/// # Errors
+///
+/// Any error of [`read_header`], or `SerializationError` if the payload is
+/// malformed.
+pub fn decode_scheme_batch_artifact<S: Instantiation>(
+    bytes: &[u8],
+) -> Result<SchemeBatch<S>, AggregationError> {
+    decode_artifact(&BATCH, S::NAME, bytes)
+}
+
+fn encode_artifact<T: Serialize>(
+    kind: &ArtifactKind,
+    scheme: &str,
+    value: &T,
+) -> Result<Vec<u8>, AggregationError> {
+    let mut bytes = Vec::new();
+    write_header(&mut bytes, kind, scheme)?;
+    bincode::serialize_into(&mut bytes, value).map_err(|e| {
+        AggregationError::SerializationError {
+            message: format!("Failed to serialize {}: {}", kind.name, e),
+        }
+    })?;
+    Ok(bytes)
+}
+
+fn decode_artifact<T: DeserializeOwned>(
+    kind: &ArtifactKind,
+    scheme: &str,
+    mut bytes: &[u8],
+) -> Result<T, AggregationError> {
+    read_header(&mut bytes, kind, scheme)?;
+    bincode::deserialize(bytes).map_err(|e| AggregationError::SerializationError {
+        message: format!("Invalid {}: {}", kind.name, e),
+    })
+}
+
+fn write_short_str<W: Write>(writer: &mut W, value: &str) -> Result<(), AggregationError> {
+    let len = u8::try_from(value.len()).map_err(|_| AggregationError::SerializationError {
+        message: format!("Artifact header field '{}' is too long", value),
+    })?;
+    writer.write_all(&[len])?;
+    writer.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+fn read_short_str<R: Read>(
+    reader: &mut R,
+    kind: &ArtifactKind,
+) -> Result<String, AggregationError> {
+    let mut len = [0u8; 1];
+    read_exact(reader, kind, &mut len)?;
+    let mut value = vec![0u8; usize::from(len[0])];
+    read_exact(reader, kind, &mut value)?;
+    String::from_utf8(value).map_err(|_| AggregationError::SerializationError {
+        message: format!("Malformed {} header: field is not UTF-8", kind.name),
+    })
+}
+
+fn read_exact<R: Read>(
+    reader: &mut R,
+    kind: &ArtifactKind,
+    buf: &mut [u8],
+) -> Result<(), AggregationError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        std::io::ErrorKind::UnexpectedEof => AggregationError::SerializationError {
+            message: format!("Truncated {} header", kind.name),
+        },
+        _ => e.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_batch;
+
+    #[test]
+    fn test_batch_roundtrip() {
+        let batch = test_batch(1);
+        let bytes = encode_batch_artifact(&batch).unwrap();
+        assert_eq!(&bytes[..4], b"SAGB");
+
+        let header = read_header(&mut &bytes[..], &BATCH, DEFAULT_SCHEME).unwrap();
+        assert_eq!(header.format_version, BATCH.format_version);
+        assert_eq!(header.scheme, DEFAULT_SCHEME);
+        assert_eq!(header.crate_version, env!("CARGO_PKG_VERSION"));
+
+        let decoded = decode_batch_artifact(&bytes).unwrap();
+        assert_eq!(decoded.items.len(), 1);
+        assert_eq!(decoded.items[0].epoch, 0);
+    }
+
+    #[test]
+    fn test_rejects_mismatched_headers() {
+        let bytes = encode_batch_artifact(&test_batch(1)).unwrap();
+
+        // A bare bincode batch, as written before artifacts had headers
+        let legacy = bincode::serialize(&test_batch(1)).unwrap();
+        assert!(matches!(
+            decode_batch_artifact(&legacy),
+            Err(AggregationError::UnexpectedArtifact { .. })
+        ));
+
+        let mut envelope = bytes.clone();
+        envelope[..4].copy_from_slice(&ENVELOPE.magic);
+        assert_eq!(
+            decode_batch_artifact(&envelope).unwrap_err(),
+            AggregationError::UnexpectedArtifact {
+                expected: "batch".to_string(),
+                found: "proof envelope".to_string(),
+            }
+        );
+
+        let mut future = bytes.clone();
+        future[4] = 2;
+        assert_eq!(
+            decode_batch_artifact(&future).unwrap_err(),
+            AggregationError::UnsupportedFormatVersion {
+                artifact: "batch".to_string(),
+                version: 2,
+                supported: 1,
+            }
+        );
+
+        assert_eq!(
+            read_header(&mut &bytes[..], &BATCH, "SIGWinternitzLifetime18W2").unwrap_err(),
+            AggregationError::SchemeMismatch {
+                artifact: "batch".to_string(),
+                expected: "SIGWinternitzLifetime18W2".to_string(),
+                found: DEFAULT_SCHEME.to_string(),
+                crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            }
+        );
+
+        assert!(matches!(
+            decode_batch_artifact(&bytes[..10]),
+            Err(AggregationError::SerializationError { .. })
+        ));
+    }
+}
@@ -0,0 +1,328 @@
+//! Tamper-evident log of staging admission decisions.
+//!
+//! When a submitter claims their signature was dropped unfairly, the operator
+//! needs more than a log line to show what happened. Every admit/reject
+//! decision taken by the [staging pool](crate::staging), and every withdrawal
+//! of a staged item by its submitter, is appended to an
+//! [`AuditLog`], a SHA-256 hash chain in which each entry commits to the item
+//! digest, the decision (including the rule that rejected the item) and the
+//! hash of the previous entry. Rewriting, dropping or reordering an entry
+//! breaks every later hash, which [`verify_chain`] detects.
+//!
+//! Logs are exported as JSON lines ([`AuditLog::export_jsonl`]) so a disputed
+//! entry can be handed to a third party together with the chain head.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::audit::{AuditLog, item_digest};
+//! use sig_agg::staging::{StagingConfig, StagingPool};
+//! # let item: sig_agg::VerificationItem = unimplemented!();
+//!
+//! let mut pool = StagingPool::new(StagingConfig::default());
+//! let digest = item_digest(&item).expect("Serialization failed");
+//! let _ = pool.admit(item);
+//!
+//! let mut export = Vec::new();
+//! pool.audit_log().export_jsonl(&mut export).expect("Export failed");
+//!
+//! let imported = AuditLog::import_jsonl(&export[..]).expect("Chain is broken");
+//! assert!(imported.entries().iter().any(|entry| entry.item_digest == digest));
+//! ```
+
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{error::AggregationError, types::VerificationItem};
+
+/// Domain separator for audit entry hashes.
+pub const AUDIT_DOMAIN: &[u8] = b"sig-agg/admission-audit/v1";
+
+/// `prev_hash` of the first entry in a chain.
+pub const GENESIS_HASH: [u8; 32] = [0u8; 32];
+
+/// Rule of the staging pool that rejected an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdmissionRule {
+    /// The (public_key, epoch) pair is already staged
+    DuplicateKeyEpochPair,
+    /// The pool holds `max_items` items
+    PoolFull,
+    /// The signature does not verify against the item's public key
+    InvalidSignature,
+}
+
+impl AdmissionRule {
+    const fn code(self) -> u8 {
+        match self {
+            Self::DuplicateKeyEpochPair => 1,
+            Self::PoolFull => 2,
+            Self::InvalidSignature => 3,
+        }
+    }
+}
+
+/// Outcome of offering one item to the staging pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdmissionDecision {
+    /// The item was staged
+    Admit,
+    /// The item was dropped by `rule`
+    Reject { rule: AdmissionRule },
+    /// The submitter withdrew the staged item before the batch was cut
+    Withdraw,
+}
+
+impl AdmissionDecision {
+    const fn code(self) -> u8 {
+        match self {
+            Self::Admit => 0,
+            Self::Reject { rule } => rule.code(),
+            Self::Withdraw => 0x80,
+        }
+    }
+}
+
+/// One link of the audit hash chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the chain, starting at 0
+    pub sequence: u64,
+    /// SHA-256 of the item's bincode encoding (see [`item_digest`])
+    #[serde(with = "crate::serde_hex")]
+    pub item_digest: [u8; 32],
+    /// Decision taken for the item
+    pub decision: AdmissionDecision,
+    /// `entry_hash` of the previous entry, or [`GENESIS_HASH`]
+    #[serde(with = "crate::serde_hex")]
+    pub prev_hash: [u8; 32],
+    /// Hash of this entry's fields, chaining it to `prev_hash`
+    #[serde(with = "crate::serde_hex")]
+    pub entry_hash: [u8; 32],
+}
+
+impl AuditEntry {
+    /// Recomputes the hash this entry should carry.
+    ///
+    /// The hash is `SHA-256(AUDIT_DOMAIN || sequence || item_digest ||
+    /// decision || prev_hash)` with the sequence as `u64` little-endian and the
+    /// decision as a one-byte code (0 = admit, 1.. = rejecting rule, 0x80 =
+    /// withdrawal).
+    #[must_use]
+    pub fn compute_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(AUDIT_DOMAIN);
+        hasher.update(self.sequence.to_le_bytes());
+        hasher.update(self.item_digest);
+        hasher.update([self.decision.code()]);
+        hasher.update(self.prev_hash);
+        hasher.finalize().into()
+    }
+}
+
+/// Append-only hash chain of admission decisions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditLog {
+    entries: Vec<AuditEntry>,
+}
+
+impl AuditLog {
+    /// Creates an empty log.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Appends a decision for the item with digest `item_digest`.
+    pub fn append(&mut self, item_digest: [u8; 32], decision: AdmissionDecision) -> &AuditEntry {
+        let mut entry = AuditEntry {
+            sequence: self.entries.len() as u64,
+            item_digest,
+            decision,
+            prev_hash: self.head(),
+            entry_hash: [0u8; 32],
+        };
+        entry.entry_hash = entry.compute_hash();
+        self.entries.push(entry);
+        &self.entries[self.entries.len() - 1]
+    }
+
+    /// Hash of the latest entry, or [`GENESIS_HASH`] for an empty log.
+    ///
+    /// Publishing the head pins every decision taken so far.
+    #[must_use]
+    pub fn head(&self) -> [u8; 32] {
+        self.entries
+            .last()
+            .map_or(GENESIS_HASH, |entry| entry.entry_hash)
+    }
+
+    /// All entries in chain order.
+    #[must_use]
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// Number of recorded decisions.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no decision has been recorded.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Writes the log as JSON lines, one entry per line.
+    pub fn export_jsonl<W: Write>(&self, mut writer: W) -> Result<(), AggregationError> {
+        for entry in &self.entries {
+            serde_json::to_writer(&mut writer, entry).map_err(|e| {
+                AggregationError::SerializationError {
+                    message: format!("Failed to serialize audit entry: {}", e),
+                }
+            })?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Rebuilds a log from `entries`, verifying their hash chain.
+    pub fn from_entries(entries: Vec<AuditEntry>) -> Result<Self, AggregationError> {
+        verify_chain(&entries)?;
+        Ok(Self { entries })
+    }
+
+    /// Reads a JSON-lines export and verifies its hash chain.
+    pub fn import_jsonl<R: BufRead>(reader: R) -> Result<Self, AggregationError> {
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line).map_err(|e| {
+                AggregationError::SerializationError {
+                    message: format!("Invalid audit entry: {}", e),
+                }
+            })?);
+        }
+
+        Self::from_entries(entries)
+    }
+}
+
+/// Checks that `entries` form an unbroken chain starting at [`GENESIS_HASH`].
+///
+/// Returns [`AggregationError::AuditChainBroken`] with the sequence number of
+/// the first entry whose position, predecessor or hash does not match.
+pub fn verify_chain(entries: &[AuditEntry]) -> Result<(), AggregationError> {
+    let mut prev_hash = GENESIS_HASH;
+    for (position, entry) in entries.iter().enumerate() {
+        if entry.sequence != position as u64
+            || entry.prev_hash != prev_hash
+            || entry.entry_hash != entry.compute_hash()
+        {
+            return Err(AggregationError::AuditChainBroken {
+                sequence: position as u64,
+            });
+        }
+        prev_hash = entry.entry_hash;
+    }
+    Ok(())
+}
+
+/// SHA-256 of the item's bincode encoding.
+///
+/// A submitter can recompute this from the item they sent to locate its entry
+/// in an exported log.
+pub fn item_digest(item: &VerificationItem) -> Result<[u8; 32], AggregationError> {
+    let bytes = bincode::serialize(item).map_err(|e| AggregationError::SerializationError {
+        message: format!("Failed to serialize item: {}", e),
+    })?;
+    Ok(Sha256::digest(bytes).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_log() -> AuditLog {
+        let mut log = AuditLog::new();
+        log.append([1u8; 32], AdmissionDecision::Admit);
+        log.append(
+            [2u8; 32],
+            AdmissionDecision::Reject {
+                rule: AdmissionRule::DuplicateKeyEpochPair,
+            },
+        );
+        log.append(
+            [3u8; 32],
+            AdmissionDecision::Reject {
+                rule: AdmissionRule::InvalidSignature,
+            },
+        );
+        log
+    }
+
+    #[test]
+    fn test_entries_are_chained() {
+        let log = sample_log();
+        let entries = log.entries();
+
+        assert_eq!(entries[0].prev_hash, GENESIS_HASH);
+        assert_eq!(entries[1].prev_hash, entries[0].entry_hash);
+        assert_eq!(entries[2].prev_hash, entries[1].entry_hash);
+        assert_eq!(log.head(), entries[2].entry_hash);
+        assert!(verify_chain(entries).is_ok());
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let log = sample_log();
+
+        // Flipping a rejection into an admission
+        let mut entries = log.entries().to_vec();
+        entries[1].decision = AdmissionDecision::Admit;
+        assert_eq!(
+            verify_chain(&entries),
+            Err(AggregationError::AuditChainBroken { sequence: 1 })
+        );
+
+        // Dropping an entry
+        let mut entries = log.entries().to_vec();
+        entries.remove(1);
+        assert_eq!(
+            verify_chain(&entries),
+            Err(AggregationError::AuditChainBroken { sequence: 1 })
+        );
+    }
+
+    #[test]
+    fn test_jsonl_roundtrip() {
+        let log = sample_log();
+        let mut export = Vec::new();
+        log.export_jsonl(&mut export).unwrap();
+
+        let text = String::from_utf8(export.clone()).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.contains("\"rule\":\"duplicate_key_epoch_pair\""));
+        assert!(text.contains(&hex::encode([2u8; 32])));
+
+        assert_eq!(AuditLog::import_jsonl(&export[..]).unwrap(), log);
+
+        let tampered = text.replace("invalid_signature", "pool_full");
+        assert_eq!(
+            AuditLog::import_jsonl(tampered.as_bytes()),
+            Err(AggregationError::AuditChainBroken { sequence: 2 })
+        );
+    }
+}
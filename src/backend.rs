@@ -0,0 +1,283 @@
+//! Proving systems behind a common interface.
+//!
+//! A [`ZkVmBackend`] turns a configuration into a compiled guest program,
+//! preprocesses it into a prover and a verifier key, and then proves and
+//! verifies batches as [`AggregationProof`]s. The `jolt`, `sp1` and `risc0`
+//! features implement it with `prover::JoltBackend`, `sp1::Sp1Backend` and
+//! `risc0::Risc0Backend`, so code written against the trait, like
+//! [`prove_batch`], runs on any of them.
+//!
+//! Compilation and preprocessing are separate steps because their cost and
+//! output differ per backend: Jolt compiles the guest at runtime and
+//! preprocesses it into large proving and verifier data, while SP1 and RISC
+//! Zero embed a guest built by the build script and derive keys from it.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # #[cfg(feature = "jolt")] {
+//! use sig_agg::{backend, prover::{JoltBackend, ProverConfig}};
+//! # let batch: sig_agg::AggregationBatch = unimplemented!();
+//!
+//! let proof = backend::prove_batch::<JoltBackend>(&ProverConfig::default(), batch)
+//!     .expect("Proving failed");
+//! # }
+//! ```
+
+use crate::{
+    aggregator::validate,
+    error::AggregationError,
+    types::{AggregationBatch, AggregationProof},
+};
+#[cfg(feature = "native")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "native")]
+use crate::{
+    commitment::CommitmentAlgorithm,
+    guest::{InputDigest, input_digest},
+    native::GuestOutput,
+    profile::ComplianceProfile,
+    report::CostReport,
+    types::ProofMetadata,
+};
+
+/// A zkVM proving system that can prove and verify aggregation batches.
+pub trait ZkVmBackend {
+    /// Backend name, for logs and reports
+    const NAME: &'static str;
+
+    /// Build and proving settings
+    type Config;
+    /// Compiled guest program
+    type Program;
+    /// Preprocessed prover, ready to prove any number of batches
+    type Prover;
+    /// What a verifier needs to check proofs of the preprocessed guest
+    type VerifierKey;
+
+    /// Compiles (or loads the prebuilt) guest program for `config`.
+    fn compile(config: &Self::Config) -> Result<Self::Program, AggregationError>;
+
+    /// Preprocesses `program` into a prover and its verifier key.
+    fn preprocess(
+        config: &Self::Config,
+        program: Self::Program,
+    ) -> Result<(Self::Prover, Self::VerifierKey), AggregationError>;
+
+    /// Validates and proves `batch`.
+    fn prove(
+        prover: &Self::Prover,
+        batch: AggregationBatch,
+    ) -> Result<AggregationProof, AggregationError>;
+
+    /// Verifies `proof` over `batch`, returning the number of valid
+    /// signatures it attests.
+    fn verify(
+        key: &Self::VerifierKey,
+        proof: &AggregationProof,
+        batch: &AggregationBatch,
+    ) -> Result<u32, AggregationError>;
+}
+
+/// Compiles, preprocesses and proves `batch` with backend `B`.
+///
+/// The batch is validated before compiling, so an invalid batch fails
+/// without paying for compilation and preprocessing. Use the backend's
+/// prover directly to amortize them across batches.
+pub fn prove_batch<B: ZkVmBackend>(
+    config: &B::Config,
+    batch: AggregationBatch,
+) -> Result<AggregationProof, AggregationError> {
+    validate(&batch.items)?;
+    let program = B::compile(config)?;
+    let (prover, _) = B::preprocess(config, program)?;
+    B::prove(&prover, batch)
+}
+
+/// Fails with `LengthMismatch` unless `batch` has the size recorded in
+/// `proof`'s metadata.
+pub(crate) fn check_batch_size(
+    proof: &AggregationProof,
+    batch: &AggregationBatch,
+) -> Result<(), AggregationError> {
+    if batch.items.len() != proof.metadata.batch_size {
+        return Err(AggregationError::LengthMismatch {
+            what: "batch items".to_string(),
+            expected: proof.metadata.batch_size,
+            actual: batch.items.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Output a guest must have committed for `proof` to hold over `batch`.
+///
+/// Fails with `LengthMismatch` if the batch size differs from the proof
+/// metadata.
+#[cfg(feature = "native")]
+pub(crate) fn expected_output(
+    proof: &AggregationProof,
+    batch: &AggregationBatch,
+) -> Result<GuestOutput, AggregationError> {
+    check_batch_size(proof, batch)?;
+    Ok(GuestOutput {
+        verified_count: proof.verified_count,
+        input_digest: input_digest(batch)?,
+        verified_root: proof.verified_root,
+        failed: proof.failed.clone(),
+    })
+}
+
+/// Fails with `ProofGenerationError` unless the guest that produced `output`
+/// ran on the input with `expected_digest`.
+#[cfg(feature = "native")]
+pub(crate) fn check_input_digest(
+    output: &GuestOutput,
+    expected_digest: &InputDigest,
+) -> Result<(), AggregationError> {
+    if output.input_digest != *expected_digest {
+        return Err(AggregationError::ProofGenerationError {
+            message: format!(
+                "Guest executed on input {} instead of {}",
+                hex::encode(output.input_digest),
+                hex::encode(expected_digest)
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Proof of a `batch_size`-item batch by a backend that needs no URS and
+/// whose guest has no fixed memory size or trace limit, so those metadata
+/// are zero.
+#[cfg(feature = "native")]
+pub(crate) fn build_proof(
+    output: GuestOutput,
+    bytes: Vec<u8>,
+    batch_size: usize,
+    trace_length: usize,
+    guest_hash: [u8; 32],
+    cost: CostReport,
+) -> AggregationProof {
+    AggregationProof {
+        verified_count: output.verified_count,
+        metadata: ProofMetadata {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            batch_size,
+            memory_size: 0,
+            trace_length,
+            max_trace_length: 0,
+            proof_size: bytes.len(),
+            guest_hash,
+            urs_digest: [0u8; 32],
+            commitment_hash: CommitmentAlgorithm::Sha256,
+            template: None,
+            cost: Some(cost),
+            profile: ComplianceProfile::ACTIVE,
+        },
+        proof: bytes,
+        failed: output.failed,
+        verified_root: output.verified_root,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commitment::CommitmentAlgorithm, profile::ComplianceProfile, test_support::test_batch,
+        types::ProofMetadata,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COMPILATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    /// "Proves" by counting the items, with the configured guest hash.
+    struct CountingBackend;
+
+    impl ZkVmBackend for CountingBackend {
+        const NAME: &'static str = "counting";
+
+        type Config = [u8; 32];
+        type Program = [u8; 32];
+        type Prover = [u8; 32];
+        type VerifierKey = [u8; 32];
+
+        fn compile(config: &[u8; 32]) -> Result<[u8; 32], AggregationError> {
+            COMPILATIONS.fetch_add(1, Ordering::SeqCst);
+            Ok(*config)
+        }
+
+        fn preprocess(
+            _config: &[u8; 32],
+            program: [u8; 32],
+        ) -> Result<([u8; 32], [u8; 32]), AggregationError> {
+            Ok((program, program))
+        }
+
+        fn prove(
+            prover: &[u8; 32],
+            batch: AggregationBatch,
+        ) -> Result<AggregationProof, AggregationError> {
+            let batch_size = batch.items.len();
+            Ok(AggregationProof {
+                proof: vec![],
+                verified_count: batch_size as u32,
+                metadata: ProofMetadata {
+                    timestamp: 0,
+                    batch_size,
+                    memory_size: 0,
+                    trace_length: 0,
+                    max_trace_length: 0,
+                    proof_size: 0,
+                    guest_hash: *prover,
+                    urs_digest: [0u8; 32],
+                    commitment_hash: CommitmentAlgorithm::Sha256,
+                    template: None,
+                    cost: None,
+                    profile: ComplianceProfile::Standard,
+                },
+                failed: vec![],
+                verified_root: [0u8; 32],
+            })
+        }
+
+        fn verify(
+            key: &[u8; 32],
+            proof: &AggregationProof,
+            batch: &AggregationBatch,
+        ) -> Result<u32, AggregationError> {
+            if proof.metadata.guest_hash != *key || batch.items.len() != proof.metadata.batch_size {
+                return Err(AggregationError::InvalidProof);
+            }
+            Ok(proof.verified_count)
+        }
+    }
+
+    #[test]
+    fn test_prove_batch_runs_every_stage() {
+        let batch = test_batch(1);
+        let proven: AggregationBatch =
+            bincode::deserialize(&bincode::serialize(&batch).unwrap()).unwrap();
+
+        let compilations = COMPILATIONS.load(Ordering::SeqCst);
+        let proof = prove_batch::<CountingBackend>(&[7u8; 32], proven).unwrap();
+        assert_eq!(COMPILATIONS.load(Ordering::SeqCst), compilations + 1);
+        assert_eq!(CountingBackend::verify(&[7u8; 32], &proof, &batch), Ok(1));
+        assert_eq!(
+            CountingBackend::verify(&[8u8; 32], &proof, &batch),
+            Err(AggregationError::InvalidProof)
+        );
+
+        // Invalid batches are rejected before compiling
+        assert_eq!(
+            prove_batch::<CountingBackend>(&[7u8; 32], AggregationBatch { items: vec![] })
+                .unwrap_err(),
+            AggregationError::EmptyBatch
+        );
+        assert_eq!(COMPILATIONS.load(Ordering::SeqCst), compilations + 1);
+    }
+}
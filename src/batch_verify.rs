@@ -0,0 +1,123 @@
+//! Parallel native verification of whole batches, without a proof.
+//!
+//! [`verify_batch_native`] checks every signature of a batch on the host,
+//! spread over all available cores, and reports the result per item. It
+//! serves as a full pre-flight check before proving, where a
+//! [spot check](crate::spot_check) is not enough, and as the verification
+//! mode of deployments that trust the verifying host and need no proof.
+//!
+//! Unlike the guest, it does not check (public key, epoch) uniqueness; the
+//! batch should come from [`aggregate`](crate::aggregate) or pass
+//! [`validate`](crate::validate()) first.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::verify_batch_native;
+//! # let batch: sig_agg::AggregationBatch = unimplemented!();
+//!
+//! let result = verify_batch_native(&batch);
+//! if !result.all_valid() {
+//!     eprintln!("Invalid signatures at {:?}", result.failed_indices());
+//! }
+//! ```
+
+use std::{num::NonZeroUsize, panic, thread};
+
+use hashsig::signature::SignatureScheme;
+
+use crate::types::{AggregationBatch, VerificationItem, XMSSSignature};
+
+/// Per-item outcome of [`verify_batch_native`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchVerificationResult {
+    /// Whether each item's signature verified, in batch order
+    pub valid: Vec<bool>,
+}
+
+impl BatchVerificationResult {
+    /// Number of items whose signature verified, as a proof of the batch
+    /// would report in [`AggregationProof::verified_count`](crate::AggregationProof::verified_count).
+    #[must_use]
+    pub fn verified_count(&self) -> u32 {
+        let count = self.valid.iter().filter(|&&valid| valid).count();
+        u32::try_from(count).unwrap_or(u32::MAX)
+    }
+
+    /// Indices of the items whose signature did not verify, ascending.
+    #[must_use]
+    pub fn failed_indices(&self) -> Vec<usize> {
+        self.valid
+            .iter()
+            .enumerate()
+            .filter(|(_, valid)| !**valid)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Returns `true` if every signature verified.
+    #[must_use]
+    pub fn all_valid(&self) -> bool {
+        self.valid.iter().all(|&valid| valid)
+    }
+}
+
+/// Verifies every signature of `batch` natively, in parallel.
+///
+/// The items are split into one contiguous range per available core.
+#[must_use]
+pub fn verify_batch_native(batch: &AggregationBatch) -> BatchVerificationResult {
+    let items = &batch.items;
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(items.len())
+        .max(1);
+    let chunk_size = items.len().div_ceil(workers).max(1);
+
+    let valid = thread::scope(|scope| {
+        // Every worker is spawned before the first one is joined
+        let mut handles = Vec::with_capacity(workers);
+        for chunk in items.chunks(chunk_size) {
+            handles.push(scope.spawn(|| chunk.iter().map(verify_item).collect::<Vec<_>>()));
+        }
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    });
+
+    BatchVerificationResult { valid }
+}
+
+fn verify_item(item: &VerificationItem) -> bool {
+    XMSSSignature::verify(&item.public_key, item.epoch, &item.message, &item.signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_batch;
+
+    #[test]
+    fn test_reports_each_item_in_order() {
+        let mut batch = test_batch(33);
+        let result = verify_batch_native(&batch);
+        assert_eq!(result.valid.len(), 33);
+        assert!(result.all_valid());
+        assert_eq!(result.verified_count(), 33);
+
+        batch.items[0].message[0] ^= 1;
+        batch.items[32].message[0] ^= 1;
+        let result = verify_batch_native(&batch);
+        assert!(!result.all_valid());
+        assert_eq!(result.failed_indices(), vec![0, 32]);
+        assert_eq!(result.verified_count(), 31);
+    }
+
+    #[test]
+    fn test_empty_batch() {
+        let result = verify_batch_native(&AggregationBatch { items: vec![] });
+        assert!(result.valid.is_empty());
+        assert!(result.all_valid());
+    }
+}
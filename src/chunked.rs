@@ -0,0 +1,235 @@
+//! Chunked proving of large batches and parallel verification of the chunks.
+//!
+//! A batch too large for one guest run is split into consecutive chunks
+//! ([`split_into_chunks`]), each proven on its own ([`prove_chunks`]). The
+//! resulting proof set is only as good as its weakest proof, but a single
+//! boolean for the whole set tells an operator nothing about what to
+//! re-prove. [`verify_chunks`] checks the proofs on all available cores and,
+//! if any fails, reports [`AggregationError::ChunkUnproven`] with the chunk
+//! and the range of batch items it covers.
+//!
+//! Once a chunk fails, chunks after it that have not started yet are
+//! skipped; the lowest failing chunk is always the one reported.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::chunked::{prove_chunks, split_into_chunks, verify_chunks};
+//! # let batch: sig_agg::AggregationBatch = unimplemented!();
+//! # let prove = |_: &sig_agg::AggregationBatch| -> Result<sig_agg::AggregationProof, sig_agg::AggregationError> { unimplemented!() };
+//! # let verify = |_: &sig_agg::AggregationProof, _: &sig_agg::AggregationBatch| -> Result<u32, sig_agg::AggregationError> { unimplemented!() };
+//!
+//! let chunks = split_into_chunks(batch, 256);
+//! let proofs = prove_chunks(&chunks, |_index, chunk| prove(chunk)).expect("Proving failed");
+//!
+//! match verify_chunks(&chunks, &proofs, verify) {
+//!     Ok(verified) => println!("{} signatures verified", verified),
+//!     Err(e) => eprintln!("{}", e),
+//! }
+//! ```
+
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use crate::{
+    error::AggregationError,
+    types::{AggregationBatch, AggregationProof},
+};
+
+/// Splits `batch` into consecutive chunks of at most `chunk_size` items,
+/// keeping the item order.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is zero.
+#[must_use]
+pub fn split_into_chunks(batch: AggregationBatch, chunk_size: usize) -> Vec<AggregationBatch> {
+    assert!(chunk_size > 0, "chunk size must be non-zero");
+    let mut chunks = Vec::with_capacity(batch.items.len().div_ceil(chunk_size));
+    let mut items = batch.items.into_iter().peekable();
+    while items.peek().is_some() {
+        chunks.push(AggregationBatch {
+            items: items.by_ref().take(chunk_size).collect(),
+        });
+    }
+    chunks
+}
+
+/// Proves every chunk, returning the proofs in chunk order.
+///
+/// `prove` is called once per chunk with its index. Like
+/// [`prove_per_signer`](crate::aggregator::prove_per_signer), it should
+/// capture shared preprocessing. Proving stops at the first error.
+pub fn prove_chunks<F>(
+    chunks: &[AggregationBatch],
+    mut prove: F,
+) -> Result<Vec<AggregationProof>, AggregationError>
+where
+    F: FnMut(usize, &AggregationBatch) -> Result<AggregationProof, AggregationError>,
+{
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| prove(index, chunk))
+        .collect()
+}
+
+/// Verifies `proofs[i]` against `chunks[i]` for every chunk in parallel,
+/// returning the total number of verified signatures.
+///
+/// `verify` checks one proof against its chunk and returns its verified
+/// count, e.g. `prover::verify_aggregation_proof` with the verifier
+/// preprocessing captured.
+///
+/// # Errors
+///
+/// * `LengthMismatch` - `proofs` and `chunks` differ in length
+/// * `ChunkUnproven` - the lowest-indexed chunk whose proof failed, with the
+///   batch items it covers and the error `verify` returned
+pub fn verify_chunks<F>(
+    chunks: &[AggregationBatch],
+    proofs: &[AggregationProof],
+    verify: F,
+) -> Result<u32, AggregationError>
+where
+    F: Fn(&AggregationProof, &AggregationBatch) -> Result<u32, AggregationError> + Sync,
+{
+    if chunks.len() != proofs.len() {
+        return Err(AggregationError::LengthMismatch {
+            what: "chunk proofs".to_string(),
+            expected: chunks.len(),
+            actual: proofs.len(),
+        });
+    }
+
+    let next = AtomicUsize::new(0);
+    let first_failure = AtomicUsize::new(usize::MAX);
+    let results = Mutex::new(vec![None; chunks.len()]);
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(chunks.len());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= chunks.len() || index > first_failure.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let result = verify(&proofs[index], &chunks[index]);
+                    if result.is_err() {
+                        first_failure.fetch_min(index, Ordering::Relaxed);
+                    }
+                    results.lock().unwrap_or_else(PoisonError::into_inner)[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    let results = results.into_inner().unwrap_or_else(PoisonError::into_inner);
+    let mut start = 0;
+    let mut verified: u32 = 0;
+    for (chunk, (batch, result)) in chunks.iter().zip(results).enumerate() {
+        let end = start + batch.items.len();
+        match result {
+            Some(Ok(count)) => verified = verified.saturating_add(count),
+            Some(Err(source)) => {
+                return Err(AggregationError::ChunkUnproven {
+                    chunk,
+                    start,
+                    end,
+                    source: Box::new(source),
+                });
+            }
+            // Only chunks after a failure are skipped
+            None => unreachable!("chunk {} skipped before any failure", chunk),
+        }
+        start = end;
+    }
+    Ok(verified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_batch;
+
+    /// Stand-in proof recording the first epoch of its chunk.
+    fn stub_proof(chunk: &AggregationBatch) -> AggregationProof {
+        AggregationProof {
+            proof: chunk.items[0].epoch.to_le_bytes().to_vec(),
+            verified_count: chunk.items.len() as u32,
+            metadata: crate::types::ProofMetadata {
+                timestamp: 0,
+                batch_size: chunk.items.len(),
+                memory_size: 0,
+                trace_length: 0,
+                max_trace_length: 0,
+                proof_size: 0,
+                guest_hash: [0u8; 32],
+                urs_digest: [0u8; 32],
+                commitment_hash: crate::commitment::CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: None,
+                profile: crate::profile::ComplianceProfile::Standard,
+            },
+            failed: vec![],
+            verified_root: [0u8; 32],
+        }
+    }
+
+    fn stub_verify(
+        proof: &AggregationProof,
+        chunk: &AggregationBatch,
+    ) -> Result<u32, AggregationError> {
+        if proof.proof == chunk.items[0].epoch.to_le_bytes() {
+            Ok(proof.verified_count)
+        } else {
+            Err(AggregationError::InvalidProof)
+        }
+    }
+
+    #[test]
+    fn test_split_keeps_item_order() {
+        let chunks = split_into_chunks(test_batch(7), 3);
+        let sizes: Vec<usize> = chunks.iter().map(|chunk| chunk.items.len()).collect();
+        assert_eq!(sizes, vec![3, 3, 1]);
+        assert_eq!(chunks[1].items[0].epoch, 3);
+        assert!(split_into_chunks(AggregationBatch { items: vec![] }, 3).is_empty());
+    }
+
+    #[test]
+    fn test_reports_the_lowest_unproven_chunk() {
+        let chunks = split_into_chunks(test_batch(10), 2);
+        let mut proofs = prove_chunks(&chunks, |_, chunk| Ok(stub_proof(chunk))).unwrap();
+        assert_eq!(verify_chunks(&chunks, &proofs, stub_verify), Ok(10));
+
+        proofs.swap(2, 4);
+        assert_eq!(
+            verify_chunks(&chunks, &proofs, stub_verify),
+            Err(AggregationError::ChunkUnproven {
+                chunk: 2,
+                start: 4,
+                end: 6,
+                source: Box::new(AggregationError::InvalidProof),
+            })
+        );
+
+        proofs.pop();
+        assert_eq!(
+            verify_chunks(&chunks, &proofs, stub_verify),
+            Err(AggregationError::LengthMismatch {
+                what: "chunk proofs".to_string(),
+                expected: 5,
+                actual: 4
+            })
+        );
+    }
+}
@@ -0,0 +1,488 @@
+//! Message canonicalization before signing.
+//!
+//! XMSS signs a fixed [`MESSAGE_LENGTH`]-byte array. Applications sharing one
+//! aggregator rarely have payloads of that exact shape, and two applications
+//! hashing "the same" payload differently would sign different messages for
+//! it. A [`MessageCodec`] turns an application payload into the message array
+//! deterministically, and its [`MessageCodecId`] travels with the item in a
+//! [`CodedMessage`] so verifiers know how the message was derived.
+//!
+//! | Codec | Payload | Message |
+//! |---|---|---|
+//! | [`IdentityCodec`] | exactly `MESSAGE_LENGTH` bytes | the payload |
+//! | [`CanonicalJsonCodec`] | UTF-8 JSON | SHA-256 of the canonical form |
+//! | [`SszCodec`] | SSZ-serialized object | SHA-256 of the payload |
+//! | [`RlpCodec`] | one canonical RLP item | SHA-256 of the payload |
+//! | [`DocumentCodec`] | arbitrary bytes, e.g. a file | SHA-256 of the payload |
+//!
+//! Hashing codecs prefix a per-codec domain tag, so a payload can never yield
+//! the same message under two codecs. Canonical JSON sorts object keys by
+//! their UTF-8 bytes and drops insignificant whitespace. SSZ is canonical by
+//! construction for a fixed schema, so its payload is hashed as given. RLP
+//! payloads must use minimal length prefixes and are rejected otherwise.
+//!
+//! Codec IDs are bound into the batch commitment with
+//! [`batch_commitment_with_codecs`](crate::commitment::batch_commitment_with_codecs).
+//!
+//! # Pre-hashing
+//!
+//! Applications that only need to sign arbitrary byte strings can skip the
+//! codecs and use [`hash_to_message`], which hashes the bytes under an
+//! application-chosen domain. [`MessageInput`] accepts either a ready message
+//! or such a byte string. The guest crate exports the same `hash_to_message`,
+//! so a guest program handed the preimage derives exactly the message the
+//! host signed.
+//!
+//! # Examples
+//!
+//! ```
+//! use sig_agg::codec::{CanonicalJsonCodec, MessageCodec};
+//!
+//! let a = CanonicalJsonCodec.encode(br#"{"slot": 7, "root": "0xab"}"#).unwrap();
+//! let b = CanonicalJsonCodec.encode(br#"{ "root":"0xab","slot":7 }"#).unwrap();
+//! assert_eq!(a, b);
+//! ```
+
+use hashsig::MESSAGE_LENGTH;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::AggregationError;
+
+// Hashing codecs fill the message with a SHA-256 digest.
+const _: () = assert!(MESSAGE_LENGTH == 32);
+
+/// Fixed-size message array signed by XMSS.
+pub type Message = [u8; MESSAGE_LENGTH];
+
+/// Domain tag of [`hash_to_message`], shared with the guest program.
+const PREHASH_DOMAIN: &[u8] = b"sig-agg/message/prehash/v1";
+
+/// Hashes an arbitrary-length `data` into a message under an application
+/// `domain`.
+///
+/// The message is SHA-256 over a library tag, the length of `domain`
+/// (`u64` little-endian), `domain` and `data`. The length prefix keeps two
+/// applications from producing the same message by splitting one byte string
+/// differently between domain and data.
+#[must_use]
+pub fn hash_to_message(domain: &[u8], data: &[u8]) -> Message {
+    let mut hasher = Sha256::new();
+    hasher.update(PREHASH_DOMAIN);
+    hasher.update((domain.len() as u64).to_le_bytes());
+    hasher.update(domain);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// Message content as supplied by an application.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageInput {
+    /// A `MESSAGE_LENGTH`-byte message, signed as is
+    Fixed(Message),
+    /// A byte string of any length, pre-hashed with [`hash_to_message`]
+    Bytes(Vec<u8>),
+}
+
+impl MessageInput {
+    /// Message to sign and place in
+    /// [`VerificationItem::message`](crate::VerificationItem::message).
+    ///
+    /// `domain` only applies to [`MessageInput::Bytes`].
+    #[must_use]
+    pub fn to_message(&self, domain: &[u8]) -> Message {
+        match self {
+            Self::Fixed(message) => *message,
+            Self::Bytes(data) => hash_to_message(domain, data),
+        }
+    }
+}
+
+impl From<Message> for MessageInput {
+    fn from(message: Message) -> Self {
+        Self::Fixed(message)
+    }
+}
+
+impl From<Vec<u8>> for MessageInput {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Bytes(data)
+    }
+}
+
+impl From<&[u8]> for MessageInput {
+    fn from(data: &[u8]) -> Self {
+        Self::Bytes(data.to_vec())
+    }
+}
+
+/// Identifier of a message codec, recorded per item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MessageCodecId {
+    /// Payload is the message itself
+    #[default]
+    Identity,
+    /// SHA-256 of canonical JSON
+    CanonicalJson,
+    /// SHA-256 of an SSZ serialization
+    Ssz,
+    /// SHA-256 of a canonical RLP item
+    Rlp,
+    /// SHA-256 of an arbitrary document
+    Document,
+}
+
+impl MessageCodecId {
+    /// One-byte code absorbed into commitments.
+    #[must_use]
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::Identity => 0,
+            Self::CanonicalJson => 1,
+            Self::Ssz => 2,
+            Self::Rlp => 3,
+            Self::Document => 4,
+        }
+    }
+}
+
+/// Deterministic mapping from an application payload to a message array.
+pub trait MessageCodec {
+    /// Identifier recorded alongside messages produced by this codec.
+    fn id(&self) -> MessageCodecId;
+
+    /// Derives the message for `payload`.
+    ///
+    /// Fails with [`AggregationError::InvalidMessage`] if the payload is not
+    /// well-formed for this codec.
+    fn encode(&self, payload: &[u8]) -> Result<Message, AggregationError>;
+
+    /// Derives the message for `payload`, tagged with this codec's ID.
+    fn encode_coded(&self, payload: &[u8]) -> Result<CodedMessage, AggregationError> {
+        Ok(CodedMessage {
+            codec: self.id(),
+            message: self.encode(payload)?,
+        })
+    }
+}
+
+/// A message together with the codec that produced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CodedMessage {
+    /// Codec the message was derived with
+    pub codec: MessageCodecId,
+    /// Message to sign and place in [`VerificationItem::message`](crate::VerificationItem::message)
+    pub message: Message,
+}
+
+/// Uses a `MESSAGE_LENGTH`-byte payload as the message unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdentityCodec;
+
+impl MessageCodec for IdentityCodec {
+    fn id(&self) -> MessageCodecId {
+        MessageCodecId::Identity
+    }
+
+    fn encode(&self, payload: &[u8]) -> Result<Message, AggregationError> {
+        payload.try_into().map_err(|_| {
+            invalid_message(&format!(
+                "identity payload must be {} bytes, got {}",
+                MESSAGE_LENGTH,
+                payload.len()
+            ))
+        })
+    }
+}
+
+/// Hashes the canonical form of a JSON document.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CanonicalJsonCodec;
+
+impl MessageCodec for CanonicalJsonCodec {
+    fn id(&self) -> MessageCodecId {
+        MessageCodecId::CanonicalJson
+    }
+
+    fn encode(&self, payload: &[u8]) -> Result<Message, AggregationError> {
+        let value: serde_json::Value = serde_json::from_slice(payload)
+            .map_err(|e| invalid_message(&format!("invalid JSON: {}", e)))?;
+        let mut canonical = Vec::with_capacity(payload.len());
+        write_canonical_json(&value, &mut canonical)?;
+        Ok(tagged_digest(b"sig-agg/message/json/v1", &canonical))
+    }
+}
+
+/// Hashes an SSZ-serialized object.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SszCodec;
+
+impl MessageCodec for SszCodec {
+    fn id(&self) -> MessageCodecId {
+        MessageCodecId::Ssz
+    }
+
+    fn encode(&self, payload: &[u8]) -> Result<Message, AggregationError> {
+        Ok(tagged_digest(b"sig-agg/message/ssz/v1", payload))
+    }
+}
+
+/// Hashes a single canonically encoded RLP item.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RlpCodec;
+
+impl MessageCodec for RlpCodec {
+    fn id(&self) -> MessageCodecId {
+        MessageCodecId::Rlp
+    }
+
+    fn encode(&self, payload: &[u8]) -> Result<Message, AggregationError> {
+        let len = rlp_item_len(payload)?;
+        if len != payload.len() {
+            return Err(invalid_message("trailing bytes after RLP item"));
+        }
+        Ok(tagged_digest(b"sig-agg/message/rlp/v1", payload))
+    }
+}
+
+/// Hashes an arbitrary document, such as the contents of a file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DocumentCodec;
+
+impl MessageCodec for DocumentCodec {
+    fn id(&self) -> MessageCodecId {
+        MessageCodecId::Document
+    }
+
+    fn encode(&self, payload: &[u8]) -> Result<Message, AggregationError> {
+        Ok(tagged_digest(b"sig-agg/message/document/v1", payload))
+    }
+}
+
+fn tagged_digest(domain: &[u8], data: &[u8]) -> Message {
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+fn invalid_message(reason: &str) -> AggregationError {
+    AggregationError::InvalidMessage {
+        reason: reason.to_string(),
+    }
+}
+
+fn write_canonical_json(
+    value: &serde_json::Value,
+    out: &mut Vec<u8>,
+) -> Result<(), AggregationError> {
+    use serde_json::Value;
+
+    match value {
+        Value::Array(values) => {
+            out.push(b'[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical_json(value, out)?;
+            }
+            out.push(b']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+
+            out.push(b'{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical_json(&Value::String(key.clone()), out)?;
+                out.push(b':');
+                write_canonical_json(value, out)?;
+            }
+            out.push(b'}');
+        }
+        scalar => serde_json::to_writer(&mut *out, scalar).map_err(|e| {
+            AggregationError::SerializationError {
+                message: format!("Failed to write canonical JSON: {}", e),
+            }
+        })?,
+    }
+    Ok(())
+}
+
+/// Returns the encoded length of the RLP item at the start of `data`,
+/// rejecting non-minimal encodings.
+fn rlp_item_len(data: &[u8]) -> Result<usize, AggregationError> {
+    let Some(&prefix) = data.first() else {
+        return Err(invalid_message("empty RLP payload"));
+    };
+
+    let (header_len, payload_len, is_list) = match prefix {
+        0x00..=0x7f => return Ok(1),
+        0x80..=0xb7 => {
+            let len = usize::from(prefix - 0x80);
+            if len == 1 && data.get(1).is_some_and(|&byte| byte < 0x80) {
+                return Err(invalid_message(
+                    "single byte below 0x80 must not be wrapped",
+                ));
+            }
+            (1, len, false)
+        }
+        0xb8..=0xbf => (
+            1 + usize::from(prefix - 0xb7),
+            rlp_long_len(data, prefix - 0xb7)?,
+            false,
+        ),
+        0xc0..=0xf7 => (1, usize::from(prefix - 0xc0), true),
+        0xf8..=0xff => (
+            1 + usize::from(prefix - 0xf7),
+            rlp_long_len(data, prefix - 0xf7)?,
+            true,
+        ),
+    };
+
+    let total = header_len
+        .checked_add(payload_len)
+        .filter(|&total| total <= data.len())
+        .ok_or_else(|| invalid_message("RLP item is truncated"))?;
+
+    if is_list {
+        let mut rest = &data[header_len..total];
+        while !rest.is_empty() {
+            rest = &rest[rlp_item_len(rest)?..];
+        }
+    }
+    Ok(total)
+}
+
+fn rlp_long_len(data: &[u8], len_of_len: u8) -> Result<usize, AggregationError> {
+    let bytes = data
+        .get(1..=usize::from(len_of_len))
+        .ok_or_else(|| invalid_message("RLP length is truncated"))?;
+    if bytes[0] == 0 {
+        return Err(invalid_message("RLP length has leading zeros"));
+    }
+    if bytes.len() > size_of::<usize>() {
+        return Err(invalid_message("RLP length overflows"));
+    }
+
+    let len = bytes
+        .iter()
+        .fold(0usize, |len, &byte| (len << 8) | usize::from(byte));
+    if len < 56 {
+        return Err(invalid_message("RLP long form used for short payload"));
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_codec() {
+        assert_eq!(IdentityCodec.encode(&[7u8; MESSAGE_LENGTH]), Ok([7u8; 32]));
+        assert!(matches!(
+            IdentityCodec.encode(&[7u8; 31]),
+            Err(AggregationError::InvalidMessage { .. })
+        ));
+    }
+
+    #[test]
+    fn test_canonical_json_ignores_key_order_and_whitespace() {
+        let a = CanonicalJsonCodec
+            .encode(br#"{"b": [1, {"y": null, "x": true}], "a": "s"}"#)
+            .unwrap();
+        let b = CanonicalJsonCodec
+            .encode(br#"{"a":"s","b":[1,{"x":true,"y":null}]}"#)
+            .unwrap();
+        assert_eq!(a, b);
+
+        // Array order is significant
+        let c = CanonicalJsonCodec
+            .encode(br#"{"a":"s","b":[{"x":true,"y":null},1]}"#)
+            .unwrap();
+        assert_ne!(a, c);
+
+        assert!(CanonicalJsonCodec.encode(b"{not json").is_err());
+    }
+
+    #[test]
+    fn test_codecs_are_domain_separated() {
+        let payload = b"\x83dog";
+        assert_ne!(
+            SszCodec.encode(payload).unwrap(),
+            RlpCodec.encode(payload).unwrap()
+        );
+        assert_ne!(
+            SszCodec.encode(payload).unwrap(),
+            DocumentCodec.encode(payload).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rlp_canonical_encodings() {
+        // "dog", ["cat", "dog"], empty string, empty list, single byte
+        for payload in [
+            &b"\x83dog"[..],
+            b"\xc8\x83cat\x83dog",
+            b"\x80",
+            b"\xc0",
+            b"\x0f",
+        ] {
+            assert!(RlpCodec.encode(payload).is_ok(), "{:02x?}", payload);
+        }
+
+        let long_string = [&[0xb8, 56][..], &[b'a'; 56]].concat();
+        assert!(RlpCodec.encode(&long_string).is_ok());
+    }
+
+    #[test]
+    fn test_rlp_rejects_non_canonical_encodings() {
+        for payload in [
+            &b""[..],
+            b"\x81\x0f",     // wrapped single byte
+            b"\xb8\x03dog",  // long form for short string
+            b"\xb9\x00\x38", // leading zero in length
+            b"\x84dog",      // truncated
+            b"\x83dog\x00",  // trailing bytes
+            b"\xc4\x83do",   // truncated list element
+        ] {
+            assert!(
+                matches!(
+                    RlpCodec.encode(payload),
+                    Err(AggregationError::InvalidMessage { .. })
+                ),
+                "{:02x?}",
+                payload
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_to_message_is_domain_separated() {
+        let message = hash_to_message(b"app", b"payload");
+        assert_eq!(message, hash_to_message(b"app", b"payload"));
+        assert_ne!(message, hash_to_message(b"other", b"payload"));
+        // Moving bytes between domain and data changes the message
+        assert_ne!(message, hash_to_message(b"ap", b"ppayload"));
+        assert_ne!(message, DocumentCodec.encode(b"payload").unwrap());
+
+        assert_eq!(
+            MessageInput::from(&b"payload"[..]).to_message(b"app"),
+            message
+        );
+        assert_eq!(MessageInput::from([7u8; 32]).to_message(b"app"), [7u8; 32]);
+    }
+
+    #[test]
+    fn test_coded_message_records_codec() {
+        let coded = SszCodec.encode_coded(b"\x01\x02").unwrap();
+        assert_eq!(coded.codec, MessageCodecId::Ssz);
+        assert_eq!(coded.message, SszCodec.encode(b"\x01\x02").unwrap());
+    }
+}
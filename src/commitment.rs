@@ -0,0 +1,777 @@
+//! Batch commitments and public key fingerprints.
+//!
+//! A batch commitment is a 32-byte digest that identifies exactly which
+//! (message, epoch, public key) tuples a batch contains, in order. Verifiers
+//! can compare it against a proof instead of holding the multi-megabyte batch,
+//! and collectors can announce it before proving.
+//!
+//! When submissions are resolved through a [`KeyResolver`](crate::keys::KeyResolver),
+//! the commitment can additionally bind each item to the application-level
+//! signer ID its key was resolved from, via [`batch_commitment_with_signers`].
+//!
+//! # Hash backends
+//!
+//! Different consumers need different hashes: circuits prefer Poseidon, EVM
+//! contracts SHA-256, command-line tools Blake3. Commitments are generic over
+//! [`CommitmentHash`]; the `*_using` functions take the backend as a type
+//! parameter and the plain functions use [`Sha256Hash`]. Each backend reports
+//! a [`CommitmentAlgorithm`] that is recorded in
+//! [`ProofMetadata::commitment_hash`](crate::ProofMetadata::commitment_hash)
+//! so verifiers know how to recompute the commitment.
+//!
+//! | Backend | Algorithm | Feature |
+//! |---|---|---|
+//! | [`Sha256Hash`] | SHA-256 | always available |
+//! | `Blake3Hash` | BLAKE3 | `blake3` |
+//! | `Poseidon2Hash` | Poseidon2 over KoalaBear | `poseidon` |
+//! | `Keccak256Hash` | Keccak-256 (EVM `keccak256`) | `keccak` |
+//!
+//! # Encoding
+//!
+//! The batch commitment hashes a domain tag, the item count (u64) and then,
+//! for each item, the message, the epoch (u32) and the public key fingerprint
+//! (computed with the same backend). The signer-bound variant uses a
+//! different domain tag and appends the signer ID (u64 length followed by
+//! UTF-8 bytes) after each fingerprint, so the two commitments can never
+//! collide. The codec-bound variant ([`batch_commitment_with_codecs`])
+//! likewise uses its own domain tag and appends each item's
+//! [`MessageCodecId`] code byte.
+//!
+//! [`SignerBinding`] fingerprints identify keys across the whole system and
+//! are always SHA-256, whichever backend the commitment uses.
+//!
+//! # Policies
+//!
+//! A proof shows that signatures verified, not under which rules the batch
+//! was admitted. [`batch_commitment_with_policy`] additionally binds the
+//! [`PolicyMetadata`] in force at aggregation time: template, epoch window,
+//! signature threshold and allowlist root. After the items it absorbs, for
+//! each of these fields in that order, a presence byte (0 or 1) followed by
+//! the value if present: the template name (u64 length and UTF-8 bytes) and
+//! version (u32), the first and last epoch of the window (u32 each), the
+//! threshold (u32) and the 32-byte allowlist root. A
+//! verifier that recomputes the commitment under the policy it expects
+//! confirms the rules along with the batch.
+//!
+//! # Nullifiers
+//!
+//! A nullifier ([`nullifier_using`]) identifies a (public key, epoch) pair
+//! without revealing the signature. Since each XMSS epoch may be used only
+//! once per key, a verifier that records nullifiers can reject any later batch
+//! reusing the pair. It hashes a domain tag, the key fingerprint and the epoch
+//! (u32).
+//!
+//! # Integer encoding
+//!
+//! Integers are absorbed little-endian, except by `Keccak256Hash`, which
+//! absorbs them big-endian. Keccak-256 nullifiers and commitments are thus
+//! exactly `keccak256(abi.encodePacked(...))` of the fields above with
+//! `uint64` lengths and counts, `uint32` epochs, versions and thresholds,
+//! `uint8` presence and codec bytes, `bytes32` fingerprints and roots, and
+//! the domain tags, messages and names as raw bytes.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::commitment::{batch_commitment, batch_commitment_using, Sha256Hash};
+//! # let batch: sig_agg::AggregationBatch = unimplemented!();
+//!
+//! let commitment = batch_commitment(&batch).expect("Commitment failed");
+//! println!("Batch commitment: {}", hex::encode(commitment));
+//!
+//! // Equivalent, with the backend spelled out
+//! let same = batch_commitment_using::<Sha256Hash>(&batch).expect("Commitment failed");
+//! assert_eq!(commitment, same);
+//! ```
+
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+use crate::{
+    codec::MessageCodecId,
+    error::AggregationError,
+    keys::PublicKey,
+    template::TemplateRef,
+    types::{AggregationBatch, VerificationItem},
+};
+
+/// 32-byte digest committing to the contents of a batch.
+pub type BatchCommitment = [u8; 32];
+
+/// 32-byte digest identifying a public key.
+pub type KeyFingerprint = [u8; 32];
+
+const BATCH_DOMAIN: &[u8] = b"sig-agg/batch/v1";
+const SIGNER_BATCH_DOMAIN: &[u8] = b"sig-agg/batch-signers/v1";
+const CODEC_BATCH_DOMAIN: &[u8] = b"sig-agg/batch-codecs/v1";
+const POLICY_BATCH_DOMAIN: &[u8] = b"sig-agg/batch-policy/v1";
+const FINGERPRINT_DOMAIN: &[u8] = b"sig-agg/public-key/v1";
+const NULLIFIER_DOMAIN: &[u8] = b"sig-agg/nullifier/v1";
+
+/// Hash algorithm a commitment was computed with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CommitmentAlgorithm {
+    /// SHA-256
+    #[default]
+    Sha256,
+    /// BLAKE3 with 32-byte output
+    Blake3,
+    /// Poseidon2 sponge over the KoalaBear field
+    Poseidon2KoalaBear,
+    /// Keccak-256 as used by the EVM
+    Keccak256,
+}
+
+/// Incremental 32-byte hash used to build commitments.
+pub trait CommitmentHash: Default {
+    /// Identifier recorded alongside commitments built with this hash.
+    const ALGORITHM: CommitmentAlgorithm;
+
+    /// Absorbs `data`.
+    fn update(&mut self, data: &[u8]);
+
+    /// Absorbs `value` as 4 bytes, little-endian unless the backend
+    /// overrides it.
+    fn update_u32(&mut self, value: u32) {
+        self.update(&value.to_le_bytes());
+    }
+
+    /// Absorbs `value` as 8 bytes, little-endian unless the backend
+    /// overrides it.
+    fn update_u64(&mut self, value: u64) {
+        self.update(&value.to_le_bytes());
+    }
+
+    /// Returns the 32-byte digest of everything absorbed.
+    fn finalize(self) -> [u8; 32];
+}
+
+/// SHA-256 commitment backend (the default).
+#[derive(Debug, Clone, Default)]
+pub struct Sha256Hash(sha2::Sha256);
+
+impl CommitmentHash for Sha256Hash {
+    const ALGORITHM: CommitmentAlgorithm = CommitmentAlgorithm::Sha256;
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        self.0.finalize().into()
+    }
+}
+
+/// BLAKE3 commitment backend.
+#[cfg(feature = "blake3")]
+#[derive(Debug, Clone, Default)]
+pub struct Blake3Hash(blake3::Hasher);
+
+#[cfg(feature = "blake3")]
+impl CommitmentHash for Blake3Hash {
+    const ALGORITHM: CommitmentAlgorithm = CommitmentAlgorithm::Blake3;
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        self.0.finalize().into()
+    }
+}
+
+/// Keccak-256 commitment backend, matching Solidity's `keccak256`.
+///
+/// This is the original Keccak padding, not NIST SHA3-256. Integers are
+/// absorbed big-endian, as `abi.encodePacked` lays them out.
+#[cfg(feature = "keccak")]
+#[derive(Debug, Clone, Default)]
+pub struct Keccak256Hash(sha3::Keccak256);
+
+#[cfg(feature = "keccak")]
+impl CommitmentHash for Keccak256Hash {
+    const ALGORITHM: CommitmentAlgorithm = CommitmentAlgorithm::Keccak256;
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn update_u32(&mut self, value: u32) {
+        self.0.update(value.to_be_bytes());
+    }
+
+    fn update_u64(&mut self, value: u64) {
+        self.0.update(value.to_be_bytes());
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        self.0.finalize().into()
+    }
+}
+
+/// Poseidon2 commitment backend over the KoalaBear field.
+///
+/// Bytes are packed three per field element after appending a `0x01`
+/// terminator, then absorbed into a width-16 Poseidon2 sponge with rate 8. The
+/// digest is the first eight state elements, each encoded as a little-endian
+/// u32. Since KoalaBear elements are below 2^31, the digest carries slightly
+/// less than 256 bits of entropy; in exchange it is cheap to recompute inside
+/// a KoalaBear circuit.
+#[cfg(feature = "poseidon")]
+#[derive(Debug, Clone, Default)]
+pub struct Poseidon2Hash {
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "poseidon")]
+impl CommitmentHash for Poseidon2Hash {
+    const ALGORITHM: CommitmentAlgorithm = CommitmentAlgorithm::Poseidon2KoalaBear;
+
+    fn update(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    fn finalize(mut self) -> [u8; 32] {
+        use p3_field::{PrimeCharacteristicRing, PrimeField32};
+        use p3_koala_bear::{KoalaBear, default_koalabear_poseidon2_16};
+        use p3_symmetric::Permutation;
+
+        const WIDTH: usize = 16;
+        const RATE: usize = 8;
+
+        self.buffer.push(0x01);
+        let elements: Vec<KoalaBear> = self
+            .buffer
+            .chunks(3)
+            .map(|chunk| {
+                let mut word = [0u8; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                KoalaBear::from_u32(u32::from_le_bytes(word))
+            })
+            .collect();
+
+        let perm = default_koalabear_poseidon2_16();
+        let mut state = [KoalaBear::ZERO; WIDTH];
+        for block in elements.chunks(RATE) {
+            for (lane, element) in state.iter_mut().zip(block) {
+                *lane += *element;
+            }
+            perm.permute_mut(&mut state);
+        }
+
+        let mut digest = [0u8; 32];
+        for (out, lane) in digest.chunks_exact_mut(4).zip(&state[..RATE]) {
+            out.copy_from_slice(&lane.as_canonical_u32().to_le_bytes());
+        }
+        digest
+    }
+}
+
+/// Association between an application-level signer ID and the key it
+/// resolved to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SignerBinding {
+    /// Signer identifier from the key directory
+    pub signer_id: String,
+    /// Fingerprint of the public key resolved for this signer
+    pub fingerprint: KeyFingerprint,
+}
+
+/// Returns the SHA-256 fingerprint of a public key's bincode encoding.
+pub fn public_key_fingerprint(public_key: &PublicKey) -> Result<KeyFingerprint, AggregationError> {
+    public_key_fingerprint_using::<Sha256Hash>(public_key)
+}
+
+/// Returns the fingerprint of a public key's bincode encoding under `H`.
+pub fn public_key_fingerprint_using<H: CommitmentHash>(
+    public_key: &PublicKey,
+) -> Result<KeyFingerprint, AggregationError> {
+    let pk_bytes =
+        bincode::serialize(public_key).map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to serialize public key: {}", e),
+        })?;
+
+    let mut hasher = H::default();
+    hasher.update(FINGERPRINT_DOMAIN);
+    hasher.update(&pk_bytes);
+    Ok(hasher.finalize())
+}
+
+/// Returns the SHA-256 nullifier of an item's (public key, epoch) pair.
+pub fn nullifier(item: &VerificationItem) -> Result<[u8; 32], AggregationError> {
+    nullifier_using::<Sha256Hash>(item)
+}
+
+/// Returns the nullifier of an item's (public key, epoch) pair under `H`.
+///
+/// Two items share a nullifier exactly when they reuse the same one-time
+/// XMSS key slot, regardless of message or signature.
+pub fn nullifier_using<H: CommitmentHash>(
+    item: &VerificationItem,
+) -> Result<[u8; 32], AggregationError> {
+    let mut hasher = H::default();
+    hasher.update(NULLIFIER_DOMAIN);
+    hasher.update(&public_key_fingerprint_using::<H>(&item.public_key)?);
+    hasher.update_u32(item.epoch);
+    Ok(hasher.finalize())
+}
+
+/// Computes the SHA-256 commitment to a batch's (message, epoch, public key)
+/// tuples.
+///
+/// Signatures are deliberately excluded: the commitment identifies *what* was
+/// signed and by whom, independent of XMSS signing randomness.
+pub fn batch_commitment(batch: &AggregationBatch) -> Result<BatchCommitment, AggregationError> {
+    batch_commitment_using::<Sha256Hash>(batch)
+}
+
+/// Computes the batch commitment with hash backend `H`.
+pub fn batch_commitment_using<H: CommitmentHash>(
+    batch: &AggregationBatch,
+) -> Result<BatchCommitment, AggregationError> {
+    let mut hasher = H::default();
+    hasher.update(BATCH_DOMAIN);
+    hasher.update_u64(batch.items.len() as u64);
+
+    for item in &batch.items {
+        absorb_item(
+            &mut hasher,
+            item,
+            &public_key_fingerprint_using::<H>(&item.public_key)?,
+        );
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Computes a SHA-256 commitment that also binds each item to its signer ID.
+///
+/// `bindings[i]` must describe the signer of `batch.items[i]`; its fingerprint
+/// is checked against the item's public key so a binding cannot be attached to
+/// a different key.
+///
+/// # Errors
+///
+/// * `LengthMismatch` - `bindings` and `batch.items` differ in length
+/// * `SignerBindingMismatch` - a binding's fingerprint does not match its item
+pub fn batch_commitment_with_signers(
+    batch: &AggregationBatch,
+    bindings: &[SignerBinding],
+) -> Result<BatchCommitment, AggregationError> {
+    batch_commitment_with_signers_using::<Sha256Hash>(batch, bindings)
+}
+
+/// Computes the signer-bound batch commitment with hash backend `H`.
+///
+/// Bindings are still checked against SHA-256 fingerprints.
+pub fn batch_commitment_with_signers_using<H: CommitmentHash>(
+    batch: &AggregationBatch,
+    bindings: &[SignerBinding],
+) -> Result<BatchCommitment, AggregationError> {
+    if bindings.len() != batch.items.len() {
+        return Err(AggregationError::LengthMismatch {
+            what: "signer bindings".to_string(),
+            expected: batch.items.len(),
+            actual: bindings.len(),
+        });
+    }
+
+    let mut hasher = H::default();
+    hasher.update(SIGNER_BATCH_DOMAIN);
+    hasher.update_u64(batch.items.len() as u64);
+
+    for (index, (item, binding)) in batch.items.iter().zip(bindings).enumerate() {
+        if public_key_fingerprint(&item.public_key)? != binding.fingerprint {
+            return Err(AggregationError::SignerBindingMismatch { index });
+        }
+
+        let fingerprint = public_key_fingerprint_using::<H>(&item.public_key)?;
+        absorb_item(&mut hasher, item, &fingerprint);
+        hasher.update_u64(binding.signer_id.len() as u64);
+        hasher.update(binding.signer_id.as_bytes());
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Computes a SHA-256 commitment that also binds each item to the codec its
+/// message was derived with.
+///
+/// `codecs[i]` is the [`MessageCodecId`] of `batch.items[i]`, so a message
+/// cannot be reinterpreted under another codec after the batch is committed.
+///
+/// # Errors
+///
+/// * `LengthMismatch` - `codecs` and `batch.items` differ in length
+pub fn batch_commitment_with_codecs(
+    batch: &AggregationBatch,
+    codecs: &[MessageCodecId],
+) -> Result<BatchCommitment, AggregationError> {
+    batch_commitment_with_codecs_using::<Sha256Hash>(batch, codecs)
+}
+
+/// Computes the codec-bound batch commitment with hash backend `H`.
+pub fn batch_commitment_with_codecs_using<H: CommitmentHash>(
+    batch: &AggregationBatch,
+    codecs: &[MessageCodecId],
+) -> Result<BatchCommitment, AggregationError> {
+    if codecs.len() != batch.items.len() {
+        return Err(AggregationError::LengthMismatch {
+            what: "message codecs".to_string(),
+            expected: batch.items.len(),
+            actual: codecs.len(),
+        });
+    }
+
+    let mut hasher = H::default();
+    hasher.update(CODEC_BATCH_DOMAIN);
+    hasher.update_u64(batch.items.len() as u64);
+
+    for (item, codec) in batch.items.iter().zip(codecs) {
+        let fingerprint = public_key_fingerprint_using::<H>(&item.public_key)?;
+        absorb_item(&mut hasher, item, &fingerprint);
+        hasher.update(&[codec.code()]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Policy identifiers applied when a batch was aggregated.
+///
+/// Unset fields are committed as absent, so a policy without a threshold
+/// never matches one with a threshold of zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PolicyMetadata {
+    /// Template the batch was built with
+    #[serde(default)]
+    pub template: Option<TemplateRef>,
+    /// Epochs every item had to lie in, inclusive
+    #[serde(default)]
+    pub epoch_window: Option<RangeInclusive<u32>>,
+    /// Minimum number of signatures that had to verify
+    #[serde(default)]
+    pub threshold: Option<u32>,
+    /// Root of the allowlist signers were admitted from
+    #[serde(default)]
+    pub allowlist_root: Option<[u8; 32]>,
+}
+
+/// Computes a SHA-256 commitment that also binds the policies the batch was
+/// aggregated under.
+pub fn batch_commitment_with_policy(
+    batch: &AggregationBatch,
+    policy: &PolicyMetadata,
+) -> Result<BatchCommitment, AggregationError> {
+    batch_commitment_with_policy_using::<Sha256Hash>(batch, policy)
+}
+
+/// Computes the policy-bound batch commitment with hash backend `H`.
+pub fn batch_commitment_with_policy_using<H: CommitmentHash>(
+    batch: &AggregationBatch,
+    policy: &PolicyMetadata,
+) -> Result<BatchCommitment, AggregationError> {
+    let mut hasher = H::default();
+    hasher.update(POLICY_BATCH_DOMAIN);
+    hasher.update_u64(batch.items.len() as u64);
+
+    for item in &batch.items {
+        absorb_item(
+            &mut hasher,
+            item,
+            &public_key_fingerprint_using::<H>(&item.public_key)?,
+        );
+    }
+
+    hasher.update(&[u8::from(policy.template.is_some())]);
+    if let Some(template) = &policy.template {
+        hasher.update_u64(template.name.len() as u64);
+        hasher.update(template.name.as_bytes());
+        hasher.update_u32(template.version);
+    }
+    hasher.update(&[u8::from(policy.epoch_window.is_some())]);
+    if let Some(window) = &policy.epoch_window {
+        hasher.update_u32(*window.start());
+        hasher.update_u32(*window.end());
+    }
+    hasher.update(&[u8::from(policy.threshold.is_some())]);
+    if let Some(threshold) = policy.threshold {
+        hasher.update_u32(threshold);
+    }
+    hasher.update(&[u8::from(policy.allowlist_root.is_some())]);
+    if let Some(root) = &policy.allowlist_root {
+        hasher.update(root);
+    }
+
+    Ok(hasher.finalize())
+}
+
+fn absorb_item<H: CommitmentHash>(
+    hasher: &mut H,
+    item: &VerificationItem,
+    fingerprint: &KeyFingerprint,
+) {
+    hasher.update(&item.message);
+    hasher.update_u32(item.epoch);
+    hasher.update(fingerprint);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{public_key, signed_item};
+
+    fn create_test_batch(epochs: &[u32]) -> (AggregationBatch, Vec<SignerBinding>) {
+        let fingerprint = public_key_fingerprint(&public_key(0)).unwrap();
+        let items = epochs.iter().map(|&epoch| signed_item(0, epoch)).collect();
+        let bindings = epochs
+            .iter()
+            .map(|_| SignerBinding {
+                signer_id: "alice".to_string(),
+                fingerprint,
+            })
+            .collect();
+
+        (AggregationBatch { items }, bindings)
+    }
+
+    #[test]
+    fn test_commitment_is_deterministic() {
+        let (batch, _) = create_test_batch(&[0, 1, 2]);
+        assert_eq!(
+            batch_commitment(&batch).unwrap(),
+            batch_commitment(&batch).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_commitment_depends_on_order_and_content() {
+        let (mut batch, _) = create_test_batch(&[0, 1]);
+        let original = batch_commitment(&batch).unwrap();
+
+        batch.items.swap(0, 1);
+        assert_ne!(batch_commitment(&batch).unwrap(), original);
+
+        batch.items.swap(0, 1);
+        batch.items[0].message[0] ^= 1;
+        assert_ne!(batch_commitment(&batch).unwrap(), original);
+    }
+
+    #[test]
+    fn test_signer_binding_changes_commitment() {
+        let (batch, mut bindings) = create_test_batch(&[0, 1]);
+
+        let plain = batch_commitment(&batch).unwrap();
+        let bound = batch_commitment_with_signers(&batch, &bindings).unwrap();
+        assert_ne!(plain, bound);
+
+        bindings[1].signer_id = "mallory".to_string();
+        assert_ne!(
+            batch_commitment_with_signers(&batch, &bindings).unwrap(),
+            bound
+        );
+    }
+
+    #[test]
+    fn test_signer_binding_fingerprint_mismatch() {
+        let (batch, mut bindings) = create_test_batch(&[0, 1]);
+        bindings[1].fingerprint = [0u8; 32];
+
+        assert_eq!(
+            batch_commitment_with_signers(&batch, &bindings),
+            Err(AggregationError::SignerBindingMismatch { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_codec_binding_changes_commitment() {
+        let (batch, _) = create_test_batch(&[0, 1]);
+        let identity = [MessageCodecId::Identity; 2];
+        let mixed = [MessageCodecId::Identity, MessageCodecId::CanonicalJson];
+
+        let bound = batch_commitment_with_codecs(&batch, &identity).unwrap();
+        assert_ne!(bound, batch_commitment(&batch).unwrap());
+        assert_ne!(bound, batch_commitment_with_codecs(&batch, &mixed).unwrap());
+        assert!(matches!(
+            batch_commitment_with_codecs(&batch, &identity[..1]),
+            Err(AggregationError::LengthMismatch {
+                expected: 2,
+                actual: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_policy_binding_changes_commitment() {
+        let (batch, _) = create_test_batch(&[0, 1]);
+        let policy = PolicyMetadata {
+            template: Some(TemplateRef {
+                name: "checkpoints".to_string(),
+                version: 2,
+            }),
+            epoch_window: Some(0..=9),
+            threshold: Some(2),
+            allowlist_root: Some([7u8; 32]),
+        };
+        let bound = batch_commitment_with_policy(&batch, &policy).unwrap();
+        assert_eq!(
+            bound,
+            batch_commitment_with_policy(&batch, &policy).unwrap()
+        );
+        assert_ne!(
+            batch_commitment_with_policy(&batch, &PolicyMetadata::default()).unwrap(),
+            batch_commitment(&batch).unwrap()
+        );
+
+        let variants = [
+            PolicyMetadata {
+                template: Some(TemplateRef {
+                    name: "checkpoints".to_string(),
+                    version: 3,
+                }),
+                ..policy.clone()
+            },
+            PolicyMetadata {
+                epoch_window: Some(0..=10),
+                ..policy.clone()
+            },
+            PolicyMetadata {
+                threshold: None,
+                ..policy.clone()
+            },
+            PolicyMetadata {
+                allowlist_root: Some([8u8; 32]),
+                ..policy
+            },
+        ];
+        for variant in &variants {
+            assert_ne!(
+                batch_commitment_with_policy(&batch, variant).unwrap(),
+                bound
+            );
+        }
+    }
+
+    #[test]
+    fn test_sha256_backend_matches_sha2() {
+        let mut hasher = Sha256Hash::default();
+        hasher.update(b"abc");
+        assert_eq!(
+            hex::encode(hasher.finalize()),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(Sha256Hash::ALGORITHM, CommitmentAlgorithm::Sha256);
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_backends_produce_distinct_commitments() {
+        let (batch, bindings) = create_test_batch(&[0, 1]);
+
+        let sha = batch_commitment_using::<Sha256Hash>(&batch).unwrap();
+        let blake = batch_commitment_using::<Blake3Hash>(&batch).unwrap();
+        assert_ne!(sha, blake);
+        assert_eq!(blake, batch_commitment_using::<Blake3Hash>(&batch).unwrap());
+
+        // Bindings carry SHA-256 fingerprints but work with any backend
+        assert!(batch_commitment_with_signers_using::<Blake3Hash>(&batch, &bindings).is_ok());
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn test_keccak_backend_matches_evm() {
+        // keccak256("") as returned by the EVM
+        assert_eq!(
+            hex::encode(Keccak256Hash::default().finalize()),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+
+        let (batch, _) = create_test_batch(&[0]);
+        assert_ne!(
+            nullifier_using::<Keccak256Hash>(&batch.items[0]).unwrap(),
+            nullifier(&batch.items[0]).unwrap()
+        );
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn test_keccak_commitment_matches_abi_encode_packed() {
+        let empty = AggregationBatch { items: vec![] };
+        // keccak256(abi.encodePacked(bytes("sig-agg/batch/v1"), uint64(0)))
+        assert_eq!(
+            hex::encode(batch_commitment_using::<Keccak256Hash>(&empty).unwrap()),
+            "b56fc2c3fee1ccf6996529f486d9f6389b363ca9cb8a3ec2277c44f41cb43fcc"
+        );
+
+        let policy = PolicyMetadata {
+            template: Some(TemplateRef {
+                name: "checkpoints".to_string(),
+                version: 3,
+            }),
+            epoch_window: Some(10..=20),
+            threshold: Some(2),
+            allowlist_root: Some([0x11; 32]),
+        };
+        // keccak256(abi.encodePacked(
+        //     bytes("sig-agg/batch-policy/v1"), uint64(0),
+        //     uint8(1), uint64(11), bytes("checkpoints"), uint32(3),
+        //     uint8(1), uint32(10), uint32(20),
+        //     uint8(1), uint32(2),
+        //     uint8(1), bytes32(0x1111...11)
+        // ))
+        assert_eq!(
+            hex::encode(
+                batch_commitment_with_policy_using::<Keccak256Hash>(&empty, &policy).unwrap()
+            ),
+            "d65e8d81230d1668bfe7c6a641633eb8b6c2a31d93e57aa47cafe53bce4d5217"
+        );
+    }
+
+    #[test]
+    fn test_nullifier_identifies_key_epoch_pair() {
+        let (mut batch, _) = create_test_batch(&[3, 4]);
+        let first = nullifier(&batch.items[0]).unwrap();
+        assert_ne!(first, nullifier(&batch.items[1]).unwrap());
+
+        // Same key and epoch with a different message is the same slot
+        batch.items[0].message[0] ^= 1;
+        assert_eq!(nullifier(&batch.items[0]).unwrap(), first);
+    }
+
+    #[cfg(feature = "poseidon")]
+    #[test]
+    fn test_poseidon_backend() {
+        let digest = |data: &[u8]| {
+            let mut hasher = Poseidon2Hash::default();
+            hasher.update(data);
+            hasher.finalize()
+        };
+
+        assert_eq!(digest(b"abc"), digest(b"abc"));
+        // The terminator keeps trailing zero bytes significant
+        assert_ne!(digest(b"abc"), digest(b"abc\0"));
+        assert_ne!(digest(b""), digest(b"\0"));
+
+        let (batch, _) = create_test_batch(&[0, 1]);
+        assert_ne!(
+            batch_commitment_using::<Poseidon2Hash>(&batch).unwrap(),
+            batch_commitment(&batch).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_signer_binding_length_mismatch() {
+        let (batch, bindings) = create_test_batch(&[0, 1]);
+
+        assert!(matches!(
+            batch_commitment_with_signers(&batch, &bindings[..1]),
+            Err(AggregationError::LengthMismatch {
+                expected: 2,
+                actual: 1,
+                ..
+            })
+        ));
+    }
+}
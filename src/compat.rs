@@ -0,0 +1,237 @@
+//! Compatibility matrix of this build.
+//!
+//! Aggregators and verifiers are upgraded independently. Before exchanging
+//! batches or proofs, orchestration tooling can fetch each peer's
+//! [`Compatibility`] (it serializes to JSON) and compare it with its own
+//! using [`Compatibility::incompatibilities`].
+//!
+//! [`compatibility`] describes what this build supports; guest programs are
+//! deployment state rather than build state, so their hashes are added from
+//! a [`GuestRegistry`] with [`Compatibility::with_guests`].
+//!
+//! # Examples
+//!
+//! ```
+//! use sig_agg::compat::{Compatibility, compatibility};
+//! # let peer_json = serde_json::to_string(&compatibility()).unwrap();
+//!
+//! let peer: Compatibility = serde_json::from_str(&peer_json).expect("Malformed peer report");
+//! let problems = compatibility().incompatibilities(&peer);
+//! assert!(problems.is_empty(), "Peer is incompatible: {:?}", problems);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commitment::CommitmentAlgorithm,
+    envelope::ENVELOPE_VERSION,
+    guest::{GuestHash, GuestRegistry},
+    profile::ComplianceProfile,
+    proof_file::PROOF_FILE_VERSION,
+    types::SIGNATURE_SCHEME,
+    wire::WIRE_VERSION,
+};
+
+/// Source revision of the `hash-sig` dependency, as locked in `Cargo.lock`.
+pub const HASHSIG_REVISION: &str = "dea4d24b4d5652e245382abc23e92391787d3c6b";
+
+/// Source revision of the `jolt-sdk` dependency, as locked in `Cargo.lock`.
+pub const JOLT_REVISION: &str = "8bb365daca83dc310a90711f698104187c5c6d0d";
+
+/// Versions and identifiers a build supports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Compatibility {
+    /// Version of the `sig-agg` crate
+    pub crate_version: String,
+    /// Proof file versions [`AggregationProof::read_from`](crate::AggregationProof::read_from)
+    /// accepts, ascending; the last one is written
+    pub proof_file_versions: Vec<u16>,
+    /// Envelope versions the envelope reader accepts, ascending; the last
+    /// one is written
+    pub envelope_versions: Vec<u16>,
+    /// Guest wire format version
+    pub wire_version: u8,
+    /// Signature scheme identifiers, see [`SIGNATURE_SCHEME`]
+    pub signature_schemes: Vec<String>,
+    /// Commitment hash backends compiled in and allowed by `profile`
+    pub commitment_hashes: Vec<CommitmentAlgorithm>,
+    /// Compliance profile of the build
+    pub profile: ComplianceProfile,
+    /// Hashes of the guest programs this peer proves or verifies, sorted;
+    /// empty if unknown
+    #[serde(
+        serialize_with = "crate::serde_hex::serialize_list",
+        deserialize_with = "crate::serde_hex::deserialize_list"
+    )]
+    pub guest_hashes: Vec<GuestHash>,
+    /// Cryptographic backends and their source revisions
+    pub backends: Vec<Backend>,
+}
+
+/// A backend dependency and the revision the build uses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Backend {
+    /// Backend name, e.g. `"jolt"`
+    pub name: String,
+    /// Source revision
+    pub revision: String,
+}
+
+/// Describes this build, without guest hashes.
+#[must_use]
+pub fn compatibility() -> Compatibility {
+    let mut backends = vec![Backend {
+        name: "hash-sig".to_string(),
+        revision: HASHSIG_REVISION.to_string(),
+    }];
+    if cfg!(feature = "jolt") {
+        backends.push(Backend {
+            name: "jolt".to_string(),
+            revision: JOLT_REVISION.to_string(),
+        });
+    }
+
+    Compatibility {
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        proof_file_versions: vec![PROOF_FILE_VERSION],
+        envelope_versions: vec![ENVELOPE_VERSION],
+        wire_version: WIRE_VERSION,
+        signature_schemes: vec![SIGNATURE_SCHEME.to_string()],
+        commitment_hashes: compiled_commitment_hashes()
+            .into_iter()
+            .filter(|&algorithm| ComplianceProfile::ACTIVE.allows_commitment(algorithm))
+            .collect(),
+        profile: ComplianceProfile::ACTIVE,
+        guest_hashes: Vec::new(),
+        backends,
+    }
+}
+
+fn compiled_commitment_hashes() -> Vec<CommitmentAlgorithm> {
+    let mut algorithms = vec![CommitmentAlgorithm::Sha256];
+    if cfg!(feature = "blake3") {
+        algorithms.push(CommitmentAlgorithm::Blake3);
+    }
+    if cfg!(feature = "poseidon") {
+        algorithms.push(CommitmentAlgorithm::Poseidon2KoalaBear);
+    }
+    if cfg!(feature = "keccak") {
+        algorithms.push(CommitmentAlgorithm::Keccak256);
+    }
+    algorithms
+}
+
+impl Compatibility {
+    /// Adds the guests registered in `registry` to
+    /// [`Compatibility::guest_hashes`].
+    #[must_use]
+    pub fn with_guests<V>(mut self, registry: &GuestRegistry<V>) -> Self {
+        self.guest_hashes.extend(registry.guest_hashes().copied());
+        self.guest_hashes.sort_unstable();
+        self.guest_hashes.dedup();
+        self
+    }
+
+    /// Reasons proofs and batches cannot be exchanged with `peer` in both
+    /// directions; empty if they can.
+    ///
+    /// Guest hashes are only compared if both sides list some.
+    #[must_use]
+    pub fn incompatibilities(&self, peer: &Self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (format, ours, theirs) in [
+            (
+                "proof file",
+                &self.proof_file_versions,
+                &peer.proof_file_versions,
+            ),
+            ("envelope", &self.envelope_versions, &peer.envelope_versions),
+        ] {
+            if !writes_readable(ours, theirs) || !writes_readable(theirs, ours) {
+                problems.push(format!(
+                    "{} versions {:?} and {:?} cannot read each other's output",
+                    format, ours, theirs
+                ));
+            }
+        }
+        if self.wire_version != peer.wire_version {
+            problems.push(format!(
+                "wire versions differ: {} and {}",
+                self.wire_version, peer.wire_version
+            ));
+        }
+        if !intersects(&self.signature_schemes, &peer.signature_schemes) {
+            problems.push("no common signature scheme".to_string());
+        }
+        if !intersects(&self.commitment_hashes, &peer.commitment_hashes) {
+            problems.push("no common commitment hash".to_string());
+        }
+        if self.profile != peer.profile {
+            problems.push(format!(
+                "profiles differ: {} and {}",
+                self.profile.id(),
+                peer.profile.id()
+            ));
+        }
+        if !self.guest_hashes.is_empty()
+            && !peer.guest_hashes.is_empty()
+            && !intersects(&self.guest_hashes, &peer.guest_hashes)
+        {
+            problems.push("no common guest program".to_string());
+        }
+        problems
+    }
+}
+
+/// Returns `true` if the version `writer` writes (its last) is in `reader`.
+fn writes_readable(writer: &[u16], reader: &[u16]) -> bool {
+    writer
+        .last()
+        .is_some_and(|version| reader.contains(version))
+}
+
+fn intersects<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+    a.iter().any(|item| b.contains(item))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describes_this_build() {
+        let ours = compatibility();
+        assert_eq!(ours.proof_file_versions.last(), Some(&PROOF_FILE_VERSION));
+        assert_eq!(ours.signature_schemes, vec![SIGNATURE_SCHEME]);
+        assert!(
+            ours.commitment_hashes
+                .contains(&CommitmentAlgorithm::Sha256)
+        );
+        assert!(ours.incompatibilities(&ours).is_empty());
+
+        let json = serde_json::to_string(&ours).unwrap();
+        assert_eq!(serde_json::from_str::<Compatibility>(&json).unwrap(), ours);
+    }
+
+    #[test]
+    fn test_reports_incompatible_peers() {
+        let mut registry = GuestRegistry::new();
+        registry.register([2u8; 32], ());
+        registry.register([1u8; 32], ());
+        let ours = compatibility().with_guests(&registry);
+        assert_eq!(ours.guest_hashes, vec![[1u8; 32], [2u8; 32]]);
+
+        // A newer peer whose proof files we cannot read
+        let mut peer = compatibility();
+        peer.proof_file_versions = vec![PROOF_FILE_VERSION + 1];
+        let problems = ours.incompatibilities(&peer);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("proof file"));
+
+        let mut peer = compatibility().with_guests(&GuestRegistry::<()>::new());
+        peer.wire_version += 1;
+        peer.guest_hashes = vec![[3u8; 32]];
+        peer.signature_schemes = vec!["other".to_string()];
+        assert_eq!(ours.incompatibilities(&peer).len(), 3);
+    }
+}
@@ -0,0 +1,307 @@
+//! Append-only proof sets and deltas for batches that grow across rounds.
+//!
+//! A logical set of signatures that gains items every round would otherwise
+//! be re-shipped as a full proof set each time. A [`ProofSet`] holds one
+//! [chunk](crate::chunked) proof per chunk, in order, and never rewrites a
+//! chunk once proven: items added in a later round form new chunks, even if
+//! the last existing chunk is not full. A round's update is therefore a
+//! [`ProofDelta`] holding only the new chunk proofs and the updated set root.
+//!
+//! # Set root
+//!
+//! The root takes the place of a top-level proof over the whole set; the
+//! library has no recursive proving. It is a hash chain starting from all
+//! zeros, where each chunk proof extends the previous root:
+//!
+//! `root_i = SHA-256(domain tag || root_(i-1) || SHA-256(proof_i))`
+//!
+//! with `proof_i` the bincode encoding of the [`AggregationProof`]. Extending
+//! the set only needs the previous root, and a verifier holding the base set
+//! checks that a delta was built on exactly that set before
+//! [applying](ProofSet::apply) it. The new chunk proofs still have to be
+//! verified against their chunks, e.g. with
+//! [`verify_chunks`](crate::chunked::verify_chunks).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::chunked::{prove_chunks, split_into_chunks, verify_chunks};
+//! use sig_agg::delta::ProofSet;
+//! # let (mut producer, mut verifier) = (ProofSet::new(), ProofSet::new());
+//! # let new_items: sig_agg::AggregationBatch = unimplemented!();
+//! # let prove = |_: &sig_agg::AggregationBatch| -> Result<sig_agg::AggregationProof, sig_agg::AggregationError> { unimplemented!() };
+//! # let verify = |_: &sig_agg::AggregationProof, _: &sig_agg::AggregationBatch| -> Result<u32, sig_agg::AggregationError> { unimplemented!() };
+//!
+//! // Producer: prove this round's items and ship only the delta
+//! let chunks = split_into_chunks(new_items, 256);
+//! let proofs = prove_chunks(&chunks, |_index, chunk| prove(chunk)).expect("Proving failed");
+//! let bytes = producer.append(proofs).to_bytes().expect("Encoding failed");
+//!
+//! // Verifier: check the new chunks, then extend its copy of the set
+//! let delta = sig_agg::delta::ProofDelta::from_bytes(&bytes).expect("Invalid delta");
+//! verify_chunks(&chunks, delta.proofs(), verify).expect("Chunk unproven");
+//! verifier.apply(delta).expect("Delta does not extend this set");
+//! assert_eq!(verifier.root(), producer.root());
+//! ```
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{error::AggregationError, types::AggregationProof};
+
+/// Version of the [`ProofDelta`] encoding.
+pub const DELTA_VERSION: u8 = 1;
+
+/// Domain tag of the set root chain.
+const SET_ROOT_DOMAIN: &[u8] = b"sig-agg/proof-set/v1";
+
+/// Root of a [`ProofSet`], committing to its chunk proofs in order.
+pub type SetRoot = [u8; 32];
+
+/// Chunk proofs of a growing logical set, with their [root](self#set-root).
+#[derive(Debug, Clone, Default)]
+pub struct ProofSet {
+    proofs: Vec<AggregationProof>,
+    root: SetRoot,
+}
+
+impl ProofSet {
+    /// Creates an empty set, whose root is all zeros.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a set from chunk proofs in chunk order.
+    #[must_use]
+    pub fn from_proofs(proofs: Vec<AggregationProof>) -> Self {
+        let mut set = Self::new();
+        set.append(proofs);
+        set
+    }
+
+    /// Appends the proofs of newly proven chunks, returning the delta that
+    /// brings a copy of the set as it was before up to date.
+    pub fn append(&mut self, proofs: Vec<AggregationProof>) -> ProofDelta {
+        let base_len = self.proofs.len();
+        let base_root = self.root;
+        self.root = extend_root(self.root, &proofs);
+        self.proofs.extend(proofs.iter().cloned());
+        ProofDelta {
+            version: DELTA_VERSION,
+            base_len,
+            base_root,
+            proofs,
+            root: self.root,
+        }
+    }
+
+    /// Applies a delta produced by [`ProofSet::append`] on a set equal to
+    /// this one.
+    ///
+    /// The set is unchanged if the delta is rejected.
+    ///
+    /// # Errors
+    ///
+    /// * `DeltaRejected` - the delta has an unsupported version, its base
+    ///   length or root differs from this set's, or its proofs do not
+    ///   produce its claimed root
+    pub fn apply(&mut self, delta: ProofDelta) -> Result<(), AggregationError> {
+        if delta.version != DELTA_VERSION {
+            return Err(rejected(format!("unsupported version {}", delta.version)));
+        }
+        if delta.base_len != self.proofs.len() {
+            return Err(rejected(format!(
+                "built on {} chunks, set has {}",
+                delta.base_len,
+                self.proofs.len()
+            )));
+        }
+        if delta.base_root != self.root {
+            return Err(rejected(
+                "base root differs from the set's root".to_string(),
+            ));
+        }
+        let root = extend_root(self.root, &delta.proofs);
+        if root != delta.root {
+            return Err(rejected(
+                "proofs do not produce the claimed root".to_string(),
+            ));
+        }
+        self.root = root;
+        self.proofs.extend(delta.proofs);
+        Ok(())
+    }
+
+    /// Chunk proofs in chunk order.
+    #[must_use]
+    pub fn proofs(&self) -> &[AggregationProof] {
+        &self.proofs
+    }
+
+    /// Current set root.
+    #[must_use]
+    pub const fn root(&self) -> SetRoot {
+        self.root
+    }
+
+    /// Number of chunk proofs in the set.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.proofs.len()
+    }
+
+    /// Returns `true` if the set has no chunk proofs.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.proofs.is_empty()
+    }
+
+    /// Total number of signatures verified across the set.
+    #[must_use]
+    pub fn verified_count(&self) -> u32 {
+        self.proofs
+            .iter()
+            .fold(0, |total, proof| total.saturating_add(proof.verified_count))
+    }
+}
+
+/// New chunk proofs and the updated root of a [`ProofSet`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofDelta {
+    version: u8,
+    base_len: usize,
+    base_root: SetRoot,
+    proofs: Vec<AggregationProof>,
+    root: SetRoot,
+}
+
+impl ProofDelta {
+    /// Number of chunk proofs in the set the delta was built on.
+    #[must_use]
+    pub const fn base_len(&self) -> usize {
+        self.base_len
+    }
+
+    /// Proofs of the new chunks, in chunk order.
+    #[must_use]
+    pub fn proofs(&self) -> &[AggregationProof] {
+        &self.proofs
+    }
+
+    /// Root of the set after applying the delta.
+    #[must_use]
+    pub const fn root(&self) -> SetRoot {
+        self.root
+    }
+
+    /// Encodes the delta for shipping to verifiers.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, AggregationError> {
+        bincode::serialize(self).map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to serialize proof set delta: {}", e),
+        })
+    }
+
+    /// Decodes a delta written by [`ProofDelta::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AggregationError> {
+        bincode::deserialize(bytes).map_err(|e| AggregationError::SerializationError {
+            message: format!("Invalid proof set delta: {}", e),
+        })
+    }
+}
+
+fn extend_root(mut root: SetRoot, proofs: &[AggregationProof]) -> SetRoot {
+    for proof in proofs {
+        // Serializing plain data into memory cannot fail
+        let encoded = bincode::serialize(proof).expect("proof is serializable");
+        let mut hasher = Sha256::new();
+        hasher.update(SET_ROOT_DOMAIN);
+        hasher.update(root);
+        hasher.update(Sha256::digest(&encoded));
+        root = hasher.finalize().into();
+    }
+    root
+}
+
+const fn rejected(reason: String) -> AggregationError {
+    AggregationError::DeltaRejected { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stand-in proof of a chunk of `count` signatures.
+    fn stub_proof(count: u32) -> AggregationProof {
+        AggregationProof {
+            proof: count.to_le_bytes().to_vec(),
+            verified_count: count,
+            metadata: crate::types::ProofMetadata {
+                timestamp: 0,
+                batch_size: count as usize,
+                memory_size: 0,
+                trace_length: 0,
+                max_trace_length: 0,
+                proof_size: 0,
+                guest_hash: [0u8; 32],
+                urs_digest: [0u8; 32],
+                commitment_hash: crate::commitment::CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: None,
+                profile: crate::profile::ComplianceProfile::Standard,
+            },
+            failed: vec![],
+            verified_root: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_applied_deltas_match_full_set() {
+        let mut producer = ProofSet::new();
+        let mut verifier = ProofSet::new();
+        assert_eq!(verifier.root(), [0u8; 32]);
+
+        for round in [vec![4, 4], vec![3], vec![4, 4, 1]] {
+            let bytes = producer
+                .append(round.into_iter().map(stub_proof).collect())
+                .to_bytes()
+                .unwrap();
+            verifier
+                .apply(ProofDelta::from_bytes(&bytes).unwrap())
+                .unwrap();
+            assert_eq!(verifier.root(), producer.root());
+        }
+
+        let full = ProofSet::from_proofs([4, 4, 3, 4, 4, 1].map(stub_proof).to_vec());
+        assert_eq!(verifier.root(), full.root());
+        assert_eq!(verifier.len(), 6);
+        assert_eq!(verifier.verified_count(), 20);
+    }
+
+    #[test]
+    fn test_rejects_deltas_for_other_sets() {
+        let mut producer = ProofSet::from_proofs(vec![stub_proof(4)]);
+        let delta = producer.append(vec![stub_proof(2)]);
+
+        // Verifier missed the first round
+        let mut stale = ProofSet::new();
+        assert!(matches!(
+            stale.apply(delta.clone()),
+            Err(AggregationError::DeltaRejected { .. })
+        ));
+        assert!(stale.is_empty());
+
+        // Same length, different chunk proof
+        let mut diverged = ProofSet::from_proofs(vec![stub_proof(5)]);
+        assert!(diverged.apply(delta.clone()).is_err());
+
+        let mut tampered = delta.clone();
+        tampered.proofs[0].verified_count = 3;
+        let mut verifier = ProofSet::from_proofs(vec![stub_proof(4)]);
+        assert!(verifier.apply(tampered).is_err());
+        assert_eq!(verifier.len(), 1);
+
+        verifier.apply(delta).unwrap();
+        assert_eq!(verifier.root(), producer.root());
+    }
+}
@@ -0,0 +1,210 @@
+//! Reproducible evidence that a rejected item was invalid.
+//!
+//! An [admission audit log](crate::audit) shows that an item was rejected as
+//! [`InvalidSignature`](AdmissionRule::InvalidSignature), but not why. A
+//! submitter could still claim the signature was valid and the aggregator
+//! censored it. An [`InvalidityTranscript`] carries the exact bytes the
+//! aggregator verified, so anyone can re-run native XMSS verification on them
+//! with [`InvalidityTranscript::check`] and see it fail, then match the
+//! transcript to its audit entry by digest.
+//!
+//! Transcripts are only produced for items whose signature actually fails to
+//! verify; there is no evidence of invalidity for a valid signature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::dispute::InvalidityTranscript;
+//! # let item: sig_agg::VerificationItem = unimplemented!();
+//! # let entry: sig_agg::audit::AuditEntry = unimplemented!();
+//!
+//! if let Some(transcript) = InvalidityTranscript::new(&item).expect("Serialization failed") {
+//!     // Third party: re-run verification and tie it to the audit log
+//!     transcript.check().expect("Transcript does not demonstrate invalidity");
+//!     assert!(transcript.matches(&entry));
+//! }
+//! ```
+
+use hashsig::signature::SignatureScheme;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    audit::{AdmissionDecision, AdmissionRule, AuditEntry},
+    error::AggregationError,
+    types::{SIGNATURE_SCHEME, VerificationItem, XMSSSignature},
+};
+
+/// Self-contained record of a failed native signature verification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidityTranscript {
+    /// Scheme the item was verified under (see [`SIGNATURE_SCHEME`])
+    pub scheme: String,
+    /// Epoch of the rejected item
+    pub epoch: u32,
+    /// Hex-encoded bincode encoding of the rejected item
+    pub item_hex: String,
+    /// Hex-encoded SHA-256 of the item encoding, as recorded in the audit log
+    pub item_digest: String,
+}
+
+impl InvalidityTranscript {
+    /// Verifies `item` natively and returns a transcript if it is invalid.
+    ///
+    /// Returns `Ok(None)` if the signature verifies.
+    pub fn new(item: &VerificationItem) -> Result<Option<Self>, AggregationError> {
+        if XMSSSignature::verify(&item.public_key, item.epoch, &item.message, &item.signature) {
+            return Ok(None);
+        }
+
+        let bytes = bincode::serialize(item).map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to serialize item: {}", e),
+        })?;
+        Ok(Some(Self {
+            scheme: SIGNATURE_SCHEME.to_string(),
+            epoch: item.epoch,
+            item_digest: hex::encode(Sha256::digest(&bytes)),
+            item_hex: hex::encode(bytes),
+        }))
+    }
+
+    /// Decodes the item the transcript was produced for.
+    pub fn item(&self) -> Result<VerificationItem, AggregationError> {
+        let bytes = self.item_bytes()?;
+        bincode::deserialize(&bytes).map_err(|e| AggregationError::SerializationError {
+            message: format!("Transcript does not contain a valid item: {}", e),
+        })
+    }
+
+    /// Re-runs native verification and checks that the transcript holds.
+    ///
+    /// Fails with [`AggregationError::ProofVerificationError`] if the
+    /// transcript names another scheme, its digest or epoch does not match the
+    /// encoded item, or the item's signature actually verifies.
+    pub fn check(&self) -> Result<(), AggregationError> {
+        if self.scheme != SIGNATURE_SCHEME {
+            return Err(transcript_error(&format!(
+                "unsupported scheme {}",
+                self.scheme
+            )));
+        }
+        if hex::encode(Sha256::digest(self.item_bytes()?)) != self.item_digest {
+            return Err(transcript_error("item digest does not match item"));
+        }
+
+        let item = self.item()?;
+        if item.epoch != self.epoch {
+            return Err(transcript_error("epoch does not match item"));
+        }
+        if XMSSSignature::verify(&item.public_key, item.epoch, &item.message, &item.signature) {
+            return Err(transcript_error("signature verifies"));
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if `entry` records this item's rejection as an invalid
+    /// signature.
+    #[must_use]
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        hex::encode(entry.item_digest) == self.item_digest
+            && entry.decision
+                == AdmissionDecision::Reject {
+                    rule: AdmissionRule::InvalidSignature,
+                }
+    }
+
+    fn item_bytes(&self) -> Result<Vec<u8>, AggregationError> {
+        hex::decode(&self.item_hex).map_err(|e| AggregationError::SerializationError {
+            message: format!("Transcript item is not valid hex: {}", e),
+        })
+    }
+}
+
+fn transcript_error(reason: &str) -> AggregationError {
+    AggregationError::ProofVerificationError {
+        message: format!("Invalidity transcript rejected: {}", reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditLog, item_digest};
+    use hashsig::MESSAGE_LENGTH;
+
+    fn create_test_item(epoch: u32) -> VerificationItem {
+        let mut rng = rand::rng();
+        let (pk, sk) = XMSSSignature::key_gen(&mut rng, 0, 10);
+        let message = [epoch as u8; MESSAGE_LENGTH];
+
+        VerificationItem {
+            message,
+            epoch,
+            signature: XMSSSignature::sign(&sk, epoch, &message).expect("Signing should succeed"),
+            public_key: pk,
+        }
+    }
+
+    #[test]
+    fn test_no_transcript_for_valid_item() {
+        assert_eq!(
+            InvalidityTranscript::new(&create_test_item(1)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_transcript_checks_and_matches_audit_entry() {
+        let mut item = create_test_item(2);
+        item.message[0] ^= 1;
+
+        let transcript = InvalidityTranscript::new(&item).unwrap().unwrap();
+        assert!(transcript.check().is_ok());
+        assert_eq!(transcript.epoch, 2);
+
+        let mut log = AuditLog::new();
+        let digest = item_digest(&item).unwrap();
+        log.append(digest, AdmissionDecision::Admit);
+        log.append(
+            digest,
+            AdmissionDecision::Reject {
+                rule: AdmissionRule::InvalidSignature,
+            },
+        );
+        assert!(!transcript.matches(&log.entries()[0]));
+        assert!(transcript.matches(&log.entries()[1]));
+
+        // Survives a JSON roundtrip
+        let json = serde_json::to_string(&transcript).unwrap();
+        let decoded: InvalidityTranscript = serde_json::from_str(&json).unwrap();
+        assert!(decoded.check().is_ok());
+    }
+
+    #[test]
+    fn test_forged_transcript_is_rejected() {
+        let valid = create_test_item(3);
+        let mut item =
+            bincode::deserialize::<VerificationItem>(&bincode::serialize(&valid).unwrap()).unwrap();
+        item.message[0] ^= 1;
+        let transcript = InvalidityTranscript::new(&item).unwrap().unwrap();
+
+        // Swapping in the valid item must not pass as evidence of invalidity
+        let valid_bytes = bincode::serialize(&valid).unwrap();
+        let forged = InvalidityTranscript {
+            item_digest: hex::encode(Sha256::digest(&valid_bytes)),
+            item_hex: hex::encode(valid_bytes),
+            ..transcript.clone()
+        };
+        assert!(matches!(
+            forged.check(),
+            Err(AggregationError::ProofVerificationError { .. })
+        ));
+
+        // Editing the item without updating the digest is caught too
+        let tampered = InvalidityTranscript {
+            item_digest: hex::encode([0u8; 32]),
+            ..transcript
+        };
+        assert!(tampered.check().is_err());
+    }
+}
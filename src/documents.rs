@@ -0,0 +1,224 @@
+//! Aggregating signatures over sets of documents.
+//!
+//! The common "sign many files" workflow hashes each document into a message,
+//! signs it, and later needs to know which batch item covers which document.
+//! [`aggregate_documents`] derives every document's message with a
+//! [`MessageCodec`], checks it against the signature, builds the batch and
+//! returns a [`DocumentManifest`] mapping document IDs to item indices.
+//!
+//! Signers derive the message to sign with the same codec, usually
+//! [`DocumentCodec`](crate::codec::DocumentCodec) for raw file contents.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::codec::DocumentCodec;
+//! use sig_agg::documents::{SignedDocument, aggregate_documents};
+//! # let documents: Vec<SignedDocument> = vec![];
+//!
+//! let (batch, manifest) =
+//!     aggregate_documents(&DocumentCodec, documents).expect("Aggregation failed");
+//! let index = manifest.index_of("release-notes.pdf").expect("Document not in batch");
+//! assert_eq!(batch.items[index].message, manifest.entries[index].message);
+//! ```
+
+use std::collections::HashSet;
+
+use hashsig::signature::SignatureScheme;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aggregator::aggregate,
+    codec::{Message, MessageCodec, MessageCodecId},
+    commitment::{KeyFingerprint, public_key_fingerprint},
+    error::AggregationError,
+    types::{AggregationBatch, VerificationItem, XMSSSignature},
+};
+
+/// A document together with its signer's signature over the derived message.
+pub struct SignedDocument {
+    /// Caller-chosen document identifier, unique within the set
+    pub id: String,
+    /// Document contents, encoded into a message by the codec
+    pub content: Vec<u8>,
+    /// Epoch the signer signed at
+    pub epoch: u32,
+    /// Signature over the derived message
+    pub signature: <XMSSSignature as SignatureScheme>::Signature,
+    /// Signer's public key
+    pub public_key: <XMSSSignature as SignatureScheme>::PublicKey,
+}
+
+impl std::fmt::Debug for SignedDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignedDocument")
+            .field("id", &self.id)
+            .field("content_len", &self.content.len())
+            .field("epoch", &self.epoch)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Batch item covering one document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Document identifier
+    pub document_id: String,
+    /// Index of the document's item in the batch
+    pub index: usize,
+    /// Fingerprint of the signer's public key
+    #[serde(with = "crate::serde_hex")]
+    pub signer: KeyFingerprint,
+    /// Epoch the document was signed at
+    pub epoch: u32,
+    /// Message derived from the document
+    #[serde(with = "crate::serde_hex")]
+    pub message: Message,
+}
+
+/// Mapping from document IDs to batch items.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentManifest {
+    /// Codec every message was derived with
+    pub codec: MessageCodecId,
+    /// One entry per document, in batch order
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl DocumentManifest {
+    /// Batch index of the document `document_id`.
+    #[must_use]
+    pub fn index_of(&self, document_id: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .find(|entry| entry.document_id == document_id)
+            .map(|entry| entry.index)
+    }
+
+    /// Renders the manifest as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, AggregationError> {
+        serde_json::to_string_pretty(self).map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to serialize document manifest: {}", e),
+        })
+    }
+}
+
+/// Builds a batch over `documents`, in the given order, and its manifest.
+///
+/// # Errors
+///
+/// * `InvalidMessage` - a document ID repeats, or the codec rejects a
+///   document
+/// * `InvalidSignature` - a signature does not cover its document's message
+/// * any error of [`aggregate`](crate::aggregate())
+pub fn aggregate_documents<C: MessageCodec + ?Sized>(
+    codec: &C,
+    documents: Vec<SignedDocument>,
+) -> Result<(AggregationBatch, DocumentManifest), AggregationError> {
+    let mut seen = HashSet::with_capacity(documents.len());
+    let mut items = Vec::with_capacity(documents.len());
+    let mut entries = Vec::with_capacity(documents.len());
+
+    for (index, document) in documents.into_iter().enumerate() {
+        if !seen.insert(document.id.clone()) {
+            return Err(AggregationError::InvalidMessage {
+                reason: format!("duplicate document ID '{}'", document.id),
+            });
+        }
+
+        let message = codec.encode(&document.content)?;
+        if !XMSSSignature::verify(
+            &document.public_key,
+            document.epoch,
+            &message,
+            &document.signature,
+        ) {
+            return Err(AggregationError::InvalidSignature { index });
+        }
+
+        entries.push(ManifestEntry {
+            document_id: document.id,
+            index,
+            signer: public_key_fingerprint(&document.public_key)?,
+            epoch: document.epoch,
+            message,
+        });
+        items.push(VerificationItem {
+            message,
+            epoch: document.epoch,
+            signature: document.signature,
+            public_key: document.public_key,
+        });
+    }
+
+    let manifest = DocumentManifest {
+        codec: codec.id(),
+        entries,
+    };
+    Ok((aggregate(items)?, manifest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::DocumentCodec;
+
+    fn signed_documents(contents: &[&str]) -> Vec<SignedDocument> {
+        let mut rng = rand::rng();
+        let (pk, sk) = XMSSSignature::key_gen(&mut rng, 0, 10);
+        let pk_bytes = bincode::serialize(&pk).unwrap();
+
+        contents
+            .iter()
+            .enumerate()
+            .map(|(epoch, content)| {
+                let epoch = epoch as u32;
+                let message = DocumentCodec.encode(content.as_bytes()).unwrap();
+                SignedDocument {
+                    id: format!("{}.txt", content),
+                    content: content.as_bytes().to_vec(),
+                    epoch,
+                    signature: XMSSSignature::sign(&sk, epoch, &message)
+                        .expect("Signing should succeed"),
+                    public_key: bincode::deserialize(&pk_bytes).unwrap(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_manifest_maps_documents_to_items() {
+        let documents = signed_documents(&["alpha", "beta", "gamma"]);
+        let (batch, manifest) = aggregate_documents(&DocumentCodec, documents).unwrap();
+
+        assert_eq!(batch.items.len(), 3);
+        assert_eq!(manifest.codec, MessageCodecId::Document);
+        let index = manifest.index_of("beta.txt").unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(
+            batch.items[index].message,
+            DocumentCodec.encode(b"beta").unwrap()
+        );
+        assert_eq!(manifest.index_of("delta.txt"), None);
+
+        let decoded: DocumentManifest = serde_json::from_str(&manifest.to_json().unwrap()).unwrap();
+        assert_eq!(decoded, manifest);
+    }
+
+    #[test]
+    fn test_rejects_tampered_and_duplicate_documents() {
+        let mut documents = signed_documents(&["alpha", "beta"]);
+        documents[1].content = b"beta, edited".to_vec();
+        assert_eq!(
+            aggregate_documents(&DocumentCodec, documents).unwrap_err(),
+            AggregationError::InvalidSignature { index: 1 }
+        );
+
+        let mut documents = signed_documents(&["alpha", "beta"]);
+        documents[1].id = "alpha.txt".to_string();
+        assert!(matches!(
+            aggregate_documents(&DocumentCodec, documents),
+            Err(AggregationError::InvalidMessage { .. })
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+//! Encryption at rest for staged items and cached artifacts.
+//!
+//! Messages staged for aggregation may be sensitive until the batch is proven
+//! and published. With the `encryption` feature enabled, spooled items
+//! ([`SpoolConfig::encryption_key`](crate::spool::SpoolConfig::encryption_key))
+//! and arbitrary cached artifacts ([`write_encrypted`] / [`read_encrypted`])
+//! are sealed with AES-256-GCM before they touch the disk. Applications that
+//! cache batches write them with [`write_encrypted`]; the benchmark binary's
+//! batch cache holds only generated test signatures and stays in the clear.
+//!
+//! Each sealed blob is `nonce (12 bytes) || ciphertext || tag (16 bytes)` with
+//! a fresh random nonce. A caller-supplied context string is authenticated as
+//! associated data, so a blob sealed for one purpose (e.g. a spool record)
+//! cannot be substituted for another (e.g. a cached batch).
+//!
+//! Keys come from configuration: raw bytes (e.g. unwrapped by a KMS), a hex
+//! string, or an environment variable.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::encryption::{ArtifactKey, read_encrypted, write_encrypted};
+//!
+//! let key = ArtifactKey::from_env("SIG_AGG_ARTIFACT_KEY").expect("Missing key");
+//! write_encrypted("./tmp/batch.bin.enc", b"batch bytes", &key, "cached-batch")
+//!     .expect("Write failed");
+//! let bytes = read_encrypted("./tmp/batch.bin.enc", &key, "cached-batch")
+//!     .expect("Read failed");
+//! assert_eq!(bytes, b"batch bytes");
+//! ```
+
+use std::{env, fmt, fs, path::Path};
+
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    rand::{SecureRandom, SystemRandom},
+};
+
+use crate::error::AggregationError;
+
+/// Length in bytes of an [`ArtifactKey`].
+pub const KEY_LEN: usize = 32;
+
+/// AES-256-GCM key used to seal artifacts at rest.
+///
+/// The key bytes are never printed by `Debug`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ArtifactKey([u8; KEY_LEN]);
+
+impl ArtifactKey {
+    /// Wraps raw key bytes, e.g. a data key unwrapped by a KMS.
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Parses a key from 64 hex characters.
+    pub fn from_hex(hex_key: &str) -> Result<Self, AggregationError> {
+        let mut bytes = [0u8; KEY_LEN];
+        hex::decode_to_slice(hex_key.trim(), &mut bytes).map_err(|e| {
+            AggregationError::SerializationError {
+                message: format!("Invalid artifact key: {}", e),
+            }
+        })?;
+        Ok(Self(bytes))
+    }
+
+    /// Reads a hex-encoded key from the environment variable `var`.
+    pub fn from_env(var: &str) -> Result<Self, AggregationError> {
+        let value = env::var(var).map_err(|e| AggregationError::IoError {
+            message: format!("Artifact key variable {} unavailable: {}", var, e),
+        })?;
+        Self::from_hex(&value)
+    }
+
+    /// Encrypts `plaintext`, authenticating `context` as associated data.
+    pub fn seal(&self, plaintext: &[u8], context: &str) -> Result<Vec<u8>, AggregationError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| AggregationError::IoError {
+                message: "System randomness unavailable for artifact nonce".to_string(),
+            })?;
+
+        let mut sealed = Vec::with_capacity(NONCE_LEN + plaintext.len() + AES_256_GCM.tag_len());
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(plaintext);
+
+        let mut in_out = sealed.split_off(NONCE_LEN);
+        self.aead_key()
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(context.as_bytes()),
+                &mut in_out,
+            )
+            .map_err(|_| AggregationError::DecryptionFailed)?;
+        sealed.extend_from_slice(&in_out);
+        Ok(sealed)
+    }
+
+    /// Decrypts a blob produced by [`seal`](Self::seal) with the same `context`.
+    ///
+    /// Returns [`AggregationError::DecryptionFailed`] if the blob was
+    /// truncated, tampered with, sealed under another key, or sealed with a
+    /// different context.
+    pub fn open(&self, sealed: &[u8], context: &str) -> Result<Vec<u8>, AggregationError> {
+        if sealed.len() < NONCE_LEN + AES_256_GCM.tag_len() {
+            return Err(AggregationError::DecryptionFailed);
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| AggregationError::DecryptionFailed)?;
+        let mut in_out = ciphertext.to_vec();
+        let plaintext_len = self
+            .aead_key()
+            .open_in_place(nonce, Aad::from(context.as_bytes()), &mut in_out)
+            .map_err(|_| AggregationError::DecryptionFailed)?
+            .len();
+        in_out.truncate(plaintext_len);
+        Ok(in_out)
+    }
+
+    fn aead_key(&self) -> LessSafeKey {
+        LessSafeKey::new(
+            UnboundKey::new(&AES_256_GCM, &self.0).expect("32-byte key is valid for AES-256-GCM"),
+        )
+    }
+}
+
+impl fmt::Debug for ArtifactKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ArtifactKey(<redacted>)")
+    }
+}
+
+/// Seals `plaintext` and writes it to `path`.
+///
+/// The file is written to a temporary sibling first and renamed into place so
+/// readers never observe a partially written artifact.
+pub fn write_encrypted(
+    path: impl AsRef<Path>,
+    plaintext: &[u8],
+    key: &ArtifactKey,
+    context: &str,
+) -> Result<(), AggregationError> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, key.seal(plaintext, context)?)?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
+/// Reads and opens an artifact written by [`write_encrypted`].
+pub fn read_encrypted(
+    path: impl AsRef<Path>,
+    key: &ArtifactKey,
+    context: &str,
+) -> Result<Vec<u8>, AggregationError> {
+    key.open(&fs::read(path)?, context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key(byte: u8) -> ArtifactKey {
+        ArtifactKey::from_bytes([byte; KEY_LEN])
+    }
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let key = test_key(1);
+        let sealed = key.seal(b"staged message", "spool").unwrap();
+
+        assert_ne!(&sealed[NONCE_LEN..], b"staged message");
+        assert_eq!(key.open(&sealed, "spool").unwrap(), b"staged message");
+    }
+
+    #[test]
+    fn test_nonces_are_fresh() {
+        let key = test_key(1);
+        let a = key.seal(b"same", "ctx").unwrap();
+        let b = key.seal(b"same", "ctx").unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_open_rejects_wrong_key_context_and_tampering() {
+        let key = test_key(1);
+        let mut sealed = key.seal(b"payload", "ctx").unwrap();
+
+        assert_eq!(
+            test_key(2).open(&sealed, "ctx"),
+            Err(AggregationError::DecryptionFailed)
+        );
+        assert_eq!(
+            key.open(&sealed, "other"),
+            Err(AggregationError::DecryptionFailed)
+        );
+        assert_eq!(
+            key.open(&sealed[..10], "ctx"),
+            Err(AggregationError::DecryptionFailed)
+        );
+
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert_eq!(
+            key.open(&sealed, "ctx"),
+            Err(AggregationError::DecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn test_key_from_hex() {
+        let key = ArtifactKey::from_hex(&"ab".repeat(KEY_LEN)).unwrap();
+        assert_eq!(key, ArtifactKey::from_bytes([0xab; KEY_LEN]));
+
+        assert!(ArtifactKey::from_hex("abcd").is_err());
+        assert!(ArtifactKey::from_hex(&"zz".repeat(KEY_LEN)).is_err());
+    }
+
+    #[test]
+    fn test_debug_redacts_key() {
+        let debug = format!("{:?}", test_key(7));
+        assert_eq!(debug, "ArtifactKey(<redacted>)");
+    }
+
+    #[test]
+    fn test_file_roundtrip() {
+        let path = env::temp_dir()
+            .join("sig-agg-encryption-tests")
+            .join("artifact.bin");
+        let key = test_key(3);
+
+        write_encrypted(&path, b"cached batch", &key, "batch").unwrap();
+        assert_ne!(fs::read(&path).unwrap(), b"cached batch");
+        assert_eq!(
+            read_encrypted(&path, &key, "batch").unwrap(),
+            b"cached batch"
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! Size-bounded proof envelopes for constrained verifiers.
+//!
+//! A bincode-encoded [`AggregationProof`] starts with the proof body, so a
+//! verifier has to buffer the whole (potentially hostile, multi-gigabyte)
+//! file before it can even look at the metadata. The envelope format puts a
+//! small header first:
+//!
+//! | Field | Encoding |
+//! |---|---|
+//! | [artifact header](crate::artifact) | magic `b"SAGP"`, version [`ENVELOPE_VERSION`], scheme and crate version |
+//! | header length | u32 little-endian |
+//! | header | bincode `(verified_count, ProofMetadata, failed, verified_root)` |
+//! | body length | u64 little-endian |
+//! | body | proof bytes |
+//!
+//! [`EnvelopeReader::open`] reads and checks everything up to the body length
+//! against [`EnvelopeLimits`], and only [`EnvelopeReader::read_proof`] reads
+//! the body. Unknown versions, other schemes, oversized sections and
+//! unexpected guest or URS digests are rejected before the body is touched.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::{fs::File, io::BufReader};
+//!
+//! use sig_agg::envelope::{EnvelopeLimits, EnvelopeReader};
+//! # let verifier_urs = [0u8; 32];
+//!
+//! let limits = EnvelopeLimits {
+//!     expected_urs_digest: Some(verifier_urs),
+//!     ..EnvelopeLimits::default()
+//! };
+//! let file = BufReader::new(File::open("proof.sagp").expect("Missing proof"));
+//! let envelope = EnvelopeReader::open(file, &limits).expect("Envelope rejected");
+//! println!("Proof for {} signatures", envelope.metadata().batch_size);
+//!
+//! let proof = envelope.read_proof().expect("Truncated proof");
+//! ```
+
+use std::io::{Read, Write};
+
+use crate::{
+    artifact::{self, DEFAULT_SCHEME},
+    error::AggregationError,
+    guest::GuestHash,
+    inclusion::VerifiedRoot,
+    types::{AggregationProof, ProofMetadata},
+    urs::{UrsDigest, check_urs_digest},
+};
+
+/// Leading bytes of every proof envelope.
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"SAGP";
+
+/// Envelope format version written by [`write_envelope`].
+pub const ENVELOPE_VERSION: u16 = 1;
+
+/// Limits and expectations checked before the proof body is read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvelopeLimits {
+    /// Maximum header size in bytes
+    pub max_header_bytes: u32,
+    /// Maximum proof body size in bytes
+    pub max_body_bytes: u64,
+    /// Accepted guest programs; empty accepts any guest
+    pub expected_guest_hashes: Vec<GuestHash>,
+    /// URS digest the proof must have been generated with
+    pub expected_urs_digest: Option<UrsDigest>,
+}
+
+impl Default for EnvelopeLimits {
+    fn default() -> Self {
+        Self {
+            max_header_bytes: 64 * 1024,
+            max_body_bytes: 256 * 1024 * 1024,
+            expected_guest_hashes: Vec::new(),
+            expected_urs_digest: None,
+        }
+    }
+}
+
+/// Writes `proof` as an envelope.
+pub fn write_envelope<W: Write>(
+    mut writer: W,
+    proof: &AggregationProof,
+) -> Result<(), AggregationError> {
+    let header = bincode::serialize(&(
+        proof.verified_count,
+        &proof.metadata,
+        &proof.failed,
+        proof.verified_root,
+    ))
+    .map_err(|e| AggregationError::SerializationError {
+        message: format!("Failed to serialize envelope header: {}", e),
+    })?;
+    let header_len =
+        u32::try_from(header.len()).map_err(|_| envelope_rejected("header too large"))?;
+
+    artifact::write_header(&mut writer, &artifact::ENVELOPE, DEFAULT_SCHEME)?;
+    writer.write_all(&header_len.to_le_bytes())?;
+    writer.write_all(&header)?;
+    writer.write_all(&(proof.proof.len() as u64).to_le_bytes())?;
+    writer.write_all(&proof.proof)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Envelope whose header has been read and checked, with the body pending.
+#[derive(Debug)]
+pub struct EnvelopeReader<R> {
+    reader: R,
+    verified_count: u32,
+    metadata: ProofMetadata,
+    failed: Vec<u8>,
+    verified_root: VerifiedRoot,
+    body_len: u64,
+}
+
+impl<R: Read> EnvelopeReader<R> {
+    /// Reads the envelope header and checks it against `limits`.
+    ///
+    /// # Errors
+    ///
+    /// * `UnexpectedArtifact` - the input is not a proof envelope
+    /// * `UnsupportedFormatVersion` - the envelope has another version
+    /// * `SchemeMismatch` - the proof covers signatures of another scheme
+    /// * `SerializationError` - truncated or malformed header
+    /// * `EnvelopeRejected` - a section exceeds its limit
+    /// * `UnknownGuest` - the guest hash is not in `expected_guest_hashes`
+    /// * `ProofVerificationError` - the URS digest differs from the expected one
+    pub fn open(mut reader: R, limits: &EnvelopeLimits) -> Result<Self, AggregationError> {
+        artifact::read_header(&mut reader, &artifact::ENVELOPE, DEFAULT_SCHEME)?;
+
+        let mut header_len = [0u8; 4];
+        read_header_bytes(&mut reader, &mut header_len)?;
+        let header_len = u32::from_le_bytes(header_len);
+        if header_len > limits.max_header_bytes {
+            return Err(envelope_rejected(&format!(
+                "header of {} bytes exceeds limit {}",
+                header_len, limits.max_header_bytes
+            )));
+        }
+
+        let mut header = vec![0u8; header_len as usize];
+        read_header_bytes(&mut reader, &mut header)?;
+        let (verified_count, metadata, failed, verified_root): (
+            u32,
+            ProofMetadata,
+            Vec<u8>,
+            VerifiedRoot,
+        ) = bincode::deserialize(&header).map_err(|e| AggregationError::SerializationError {
+            message: format!("Invalid envelope header: {}", e),
+        })?;
+
+        if !limits.expected_guest_hashes.is_empty()
+            && !limits.expected_guest_hashes.contains(&metadata.guest_hash)
+        {
+            return Err(AggregationError::UnknownGuest {
+                guest_hash: hex::encode(metadata.guest_hash),
+            });
+        }
+        if let Some(expected) = &limits.expected_urs_digest {
+            check_urs_digest(&metadata, expected)?;
+        }
+
+        let mut body_len = [0u8; 8];
+        read_header_bytes(&mut reader, &mut body_len)?;
+        let body_len = u64::from_le_bytes(body_len);
+        if body_len > limits.max_body_bytes {
+            return Err(envelope_rejected(&format!(
+                "proof body of {} bytes exceeds limit {}",
+                body_len, limits.max_body_bytes
+            )));
+        }
+
+        Ok(Self {
+            reader,
+            verified_count,
+            metadata,
+            failed,
+            verified_root,
+            body_len,
+        })
+    }
+
+    /// Number of signatures the proof claims to verify.
+    #[must_use]
+    pub const fn verified_count(&self) -> u32 {
+        self.verified_count
+    }
+
+    /// Proof metadata from the header.
+    #[must_use]
+    pub const fn metadata(&self) -> &ProofMetadata {
+        &self.metadata
+    }
+
+    /// Failure bitmap from the header, see [`AggregationProof::failed`].
+    #[must_use]
+    pub fn failed(&self) -> &[u8] {
+        &self.failed
+    }
+
+    /// Verified-item root from the header, see
+    /// [`AggregationProof::verified_root`].
+    #[must_use]
+    pub const fn verified_root(&self) -> &VerifiedRoot {
+        &self.verified_root
+    }
+
+    /// Declared size of the proof body in bytes.
+    #[must_use]
+    pub const fn body_len(&self) -> u64 {
+        self.body_len
+    }
+
+    /// Reads the proof body and assembles the proof.
+    ///
+    /// Never reads more than the declared body length, which `open` already
+    /// checked against the limits.
+    pub fn read_proof(self) -> Result<AggregationProof, AggregationError> {
+        let mut proof = Vec::new();
+        self.reader.take(self.body_len).read_to_end(&mut proof)?;
+        if proof.len() as u64 != self.body_len {
+            return Err(AggregationError::SerializationError {
+                message: format!(
+                    "Truncated proof body: expected {} bytes, found {}",
+                    self.body_len,
+                    proof.len()
+                ),
+            });
+        }
+
+        Ok(AggregationProof {
+            proof,
+            verified_count: self.verified_count,
+            metadata: self.metadata,
+            failed: self.failed,
+            verified_root: self.verified_root,
+        })
+    }
+}
+
+fn read_header_bytes<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), AggregationError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        std::io::ErrorKind::UnexpectedEof => AggregationError::SerializationError {
+            message: "Truncated envelope header".to_string(),
+        },
+        _ => e.into(),
+    })
+}
+
+fn envelope_rejected(reason: &str) -> AggregationError {
+    AggregationError::EnvelopeRejected {
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commitment::CommitmentAlgorithm, profile::ComplianceProfile};
+
+    fn test_proof() -> AggregationProof {
+        AggregationProof {
+            proof: vec![0xab; 1000],
+            verified_count: 4,
+            metadata: ProofMetadata {
+                timestamp: 0,
+                batch_size: 4,
+                memory_size: 0,
+                trace_length: 0,
+                max_trace_length: 0,
+                proof_size: 0,
+                guest_hash: [1u8; 32],
+                urs_digest: [2u8; 32],
+                commitment_hash: CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: None,
+                profile: ComplianceProfile::Standard,
+            },
+            failed: vec![],
+            verified_root: [0u8; 32],
+        }
+    }
+
+    fn envelope_bytes() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_envelope(&mut bytes, &test_proof()).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let limits = EnvelopeLimits {
+            expected_guest_hashes: vec![[1u8; 32]],
+            expected_urs_digest: Some([2u8; 32]),
+            ..EnvelopeLimits::default()
+        };
+        let bytes = envelope_bytes();
+        let envelope = EnvelopeReader::open(&bytes[..], &limits).unwrap();
+        assert_eq!(envelope.verified_count(), 4);
+        assert_eq!(envelope.metadata().batch_size, 4);
+        assert_eq!(envelope.body_len(), 1000);
+
+        let proof = envelope.read_proof().unwrap();
+        assert_eq!(proof.proof, test_proof().proof);
+    }
+
+    #[test]
+    fn test_rejects_before_reading_body() {
+        // Only the header is available, so any attempt to read the body would fail
+        let bytes = envelope_bytes();
+        let header_end = bytes.len() - 1000;
+        let open = |limits: &EnvelopeLimits| {
+            EnvelopeReader::open(&bytes[..header_end], limits).map(|envelope| envelope.body_len())
+        };
+
+        assert_eq!(open(&EnvelopeLimits::default()), Ok(1000));
+        assert!(matches!(
+            open(&EnvelopeLimits {
+                max_body_bytes: 999,
+                ..EnvelopeLimits::default()
+            }),
+            Err(AggregationError::EnvelopeRejected { .. })
+        ));
+        assert!(matches!(
+            open(&EnvelopeLimits {
+                expected_guest_hashes: vec![[9u8; 32]],
+                ..EnvelopeLimits::default()
+            }),
+            Err(AggregationError::UnknownGuest { .. })
+        ));
+        assert!(matches!(
+            open(&EnvelopeLimits {
+                expected_urs_digest: Some([9u8; 32]),
+                ..EnvelopeLimits::default()
+            }),
+            Err(AggregationError::ProofVerificationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_malformed_envelopes() {
+        let limits = EnvelopeLimits::default();
+
+        let mut bad_magic = envelope_bytes();
+        bad_magic[0] = b'X';
+        assert!(matches!(
+            EnvelopeReader::open(&bad_magic[..], &limits),
+            Err(AggregationError::UnexpectedArtifact { .. })
+        ));
+
+        let mut future_version = envelope_bytes();
+        future_version[4] = 2;
+        assert!(matches!(
+            EnvelopeReader::open(&future_version[..], &limits),
+            Err(AggregationError::UnsupportedFormatVersion { version: 2, .. })
+        ));
+
+        // Header length far beyond the limit is rejected without allocating it
+        let mut artifact_header = Vec::new();
+        artifact::write_header(&mut artifact_header, &artifact::ENVELOPE, DEFAULT_SCHEME).unwrap();
+        let offset = artifact_header.len();
+        let mut huge_header = envelope_bytes();
+        huge_header[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            EnvelopeReader::open(&huge_header[..], &limits),
+            Err(AggregationError::EnvelopeRejected { .. })
+        ));
+
+        let bytes = envelope_bytes();
+        let truncated = EnvelopeReader::open(&bytes[..bytes.len() - 1], &limits).unwrap();
+        assert!(matches!(
+            truncated.read_proof(),
+            Err(AggregationError::SerializationError { .. })
+        ));
+    }
+}
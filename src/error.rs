@@ -11,6 +11,20 @@
 //! - [`EmptyBatch`](AggregationError::EmptyBatch) - No signatures provided
 //! - [`DuplicateKeyEpochPair`](AggregationError::DuplicateKeyEpochPair) - Same (key, epoch) pair
 //! - [`BatchTooLarge`](AggregationError::BatchTooLarge) - Batch exceeds zkVM memory limits
+//! - [`LengthMismatch`](AggregationError::LengthMismatch) - Per-item inputs do not line up with the batch
+//! - [`UnknownSigner`](AggregationError::UnknownSigner) - Signer ID not found in the key directory
+//! - [`InvalidSignerId`](AggregationError::InvalidSignerId) - Signer ID contains characters outside the allowed set
+//! - [`SignerBindingMismatch`](AggregationError::SignerBindingMismatch) - Signer binding does not match the item's key
+//! - [`MixedPublicKeys`](AggregationError::MixedPublicKeys) - Single-key batch contains another signer's key
+//! - [`DuplicateEpoch`](AggregationError::DuplicateEpoch) - Same epoch used twice in a [`SingleKey`](crate::AggregationMode::SingleKey) batch
+//! - [`MissingPublicKey`](AggregationError::MissingPublicKey) - Public key required by the aggregation mode is absent
+//! - [`UnexpectedPublicKey`](AggregationError::UnexpectedPublicKey) - Shared public key supplied in `MultiKey` mode
+//! - [`UnknownKeyIndex`](AggregationError::UnknownKeyIndex) - Key-table item refers to a key outside the table
+//! - [`EpochOutOfRange`](AggregationError::EpochOutOfRange) - Epoch is beyond the instantiation's lifetime
+//! - [`InvalidMessage`](AggregationError::InvalidMessage) - Payload is malformed for its message codec
+//! - [`UnknownTemplate`](AggregationError::UnknownTemplate) - No batch template registered under the name
+//! - [`TemplateRejected`](AggregationError::TemplateRejected) - Template is invalid, expired or its policies are violated
+//! - [`WithdrawalRejected`](AggregationError::WithdrawalRejected) - Item is not staged or the withdrawal token does not match
 //!
 //! ## Cryptographic Errors
 //!
@@ -18,6 +32,12 @@
 //! - [`InvalidSignature`](AggregationError::InvalidSignature) - Signature verification failed
 //! - [`VerificationMismatch`](AggregationError::VerificationMismatch) - Wrong number of valid signatures
 //! - [`InvalidProof`](AggregationError::InvalidProof) - zkVM proof is invalid
+//! - [`ChunkUnproven`](AggregationError::ChunkUnproven) - Proof of one chunk of a chunked batch failed
+//! - [`UnknownGuest`](AggregationError::UnknownGuest) - No verifier registered for the proof's guest program
+//! - [`DecryptionFailed`](AggregationError::DecryptionFailed) - Encrypted artifact failed authentication
+//! - [`AuditChainBroken`](AggregationError::AuditChainBroken) - Admission audit log was altered
+//! - [`ProofReplayed`](AggregationError::ProofReplayed) - Proof was already consumed by this verifier
+//! - [`ReplayWindowFull`](AggregationError::ReplayWindowFull) - Replay window cannot remember another proof
 //!
 //! ## System Errors
 //!
@@ -27,6 +47,19 @@
 //! - [`ProofGenerationError`](AggregationError::ProofGenerationError) - zkVM proof generation failed
 //! - [`ProofVerificationError`](AggregationError::ProofVerificationError) - zkVM proof verification failed
 //! - [`MemoryExhausted`](AggregationError::MemoryExhausted) - Out of memory during zkVM execution
+//! - [`IoError`](AggregationError::IoError) - Filesystem or storage operation failed
+//! - [`SpoolPoisoned`](AggregationError::SpoolPoisoned) - Earlier failed spool write may have lost items
+//! - [`EnvelopeRejected`](AggregationError::EnvelopeRejected) - Proof envelope exceeds size limits
+//! - [`DeltaRejected`](AggregationError::DeltaRejected) - Proof set delta does not extend the verifier's proof set
+//! - [`UnexpectedArtifact`](AggregationError::UnexpectedArtifact) - Serialized artifact is of another kind
+//! - [`UnsupportedFormatVersion`](AggregationError::UnsupportedFormatVersion) - Serialized artifact has an unsupported format version
+//! - [`SchemeMismatch`](AggregationError::SchemeMismatch) - Serialized artifact holds signatures of another instantiation
+//!
+//! ## Retryability
+//!
+//! [`AggregationError::is_retryable`] separates transient failures (IO, proving
+//! resource exhaustion) from permanent ones (invalid input, invalid proofs). The
+//! [`retry`](crate::retry) module only retries the former.
 //!
 //! # Examples
 //!
@@ -62,6 +95,8 @@
 
 use std::fmt;
 
+use crate::types::AggregationMode;
+
 /// Comprehensive error type for aggregation operations.
 ///
 /// All aggregation functions return `Result<T, AggregationError>` to provide
@@ -78,6 +113,46 @@ pub enum AggregationError {
     DuplicateKeyEpochPair { public_key: String, epoch: u32 },
     /// Batch size exceeds zkVM memory limits
     BatchTooLarge { size: usize, max: usize },
+    /// Input meant to line up with the batch has a different length
+    LengthMismatch {
+        what: String,
+        expected: usize,
+        actual: usize,
+    },
+    /// Signer ID could not be resolved to a public key
+    UnknownSigner { signer_id: String },
+    /// Signer ID is empty or not restricted to ASCII alphanumerics, `-`, `_`
+    InvalidSignerId { signer_id: String },
+    /// Signer binding fingerprint does not match the item's public key
+    SignerBindingMismatch { index: usize },
+    /// Item's public key differs from the shared key of a single-key batch
+    MixedPublicKeys { index: usize },
+    /// Epoch used twice in a `SingleKey` batch
+    DuplicateEpoch { epoch: u32 },
+    /// Public key required by the aggregation mode is absent: the shared key
+    /// (`index` is `None`) or an item's key
+    MissingPublicKey {
+        mode: AggregationMode,
+        index: Option<usize>,
+    },
+    /// Shared public key supplied in a mode whose items carry their own
+    UnexpectedPublicKey { mode: AggregationMode },
+    /// Item of a key-table batch refers to a key outside the table
+    UnknownKeyIndex { index: usize, key_index: u32 },
+    /// Item's epoch is not below the lifetime of its XMSS instantiation
+    EpochOutOfRange {
+        index: usize,
+        epoch: u32,
+        lifetime: u64,
+    },
+    /// Application payload is malformed for its message codec
+    InvalidMessage { reason: String },
+    /// No batch template is registered under the requested name
+    UnknownTemplate { name: String },
+    /// Batch template is invalid, expired or one of its policies is violated
+    TemplateRejected { name: String, reason: String },
+    /// Staged item cannot be withdrawn by this request
+    WithdrawalRejected { reason: String },
 
     // Cryptographic errors
     /// One or more signatures failed verification
@@ -86,6 +161,16 @@ pub enum AggregationError {
     VerificationMismatch { expected: usize, actual: usize },
     /// zkVM proof is cryptographically invalid
     InvalidProof,
+    /// Proof was produced by a guest program with no registered verifier
+    UnknownGuest { guest_hash: String },
+    /// Encrypted artifact is corrupt, truncated or sealed under another key
+    DecryptionFailed,
+    /// Admission audit log hash chain does not verify
+    AuditChainBroken { sequence: u64 },
+    /// Proof was already consumed within the verifier's replay window
+    ProofReplayed { proof_id: String },
+    /// Replay window is at capacity and its policy forbids evicting live IDs
+    ReplayWindowFull { capacity: usize },
 
     // System errors
     /// Serialization failed
@@ -98,9 +183,69 @@ pub enum AggregationError {
     ProofVerificationError { message: String },
     /// Memory limit exceeded during execution
     MemoryExhausted { used: usize, limit: usize },
+    /// Filesystem or storage operation failed
+    IoError { message: String },
+    /// A write to the spool failed earlier, so the spooled batch may be
+    /// incomplete
+    SpoolPoisoned,
+    /// Proof envelope section exceeds its size limit
+    EnvelopeRejected { reason: String },
+    /// Proof of one chunk of a chunked batch failed, covering items
+    /// `start..end`
+    ChunkUnproven {
+        chunk: usize,
+        start: usize,
+        end: usize,
+        source: Box<Self>,
+    },
+    /// Proof set delta has an unsupported version, was built on another
+    /// proof set, or does not produce its claimed root
+    DeltaRejected { reason: String },
+    /// [Artifact header](crate::artifact) has the magic of another kind
+    UnexpectedArtifact { expected: String, found: String },
+    /// Artifact header declares a format version this build cannot read
+    UnsupportedFormatVersion {
+        artifact: String,
+        version: u16,
+        supported: u16,
+    },
+    /// Artifact holds signatures of another XMSS instantiation
+    SchemeMismatch {
+        artifact: String,
+        expected: String,
+        found: String,
+        crate_version: String,
+    },
+}
+
+impl AggregationError {
+    /// Returns `true` if the failure is transient and the operation may succeed
+    /// when retried unchanged.
+    ///
+    /// Validation and cryptographic errors are permanent: retrying the same
+    /// batch or proof yields the same result. Proof generation failures, memory
+    /// exhaustion and IO errors usually stem from the environment and are
+    /// considered retryable.
+    #[must_use]
+    pub const fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::ProofGenerationError { .. } | Self::MemoryExhausted { .. } | Self::IoError { .. }
+        )
+    }
+}
+
+impl From<std::io::Error> for AggregationError {
+    fn from(error: std::io::Error) -> Self {
+        Self::IoError {
+            message: error.to_string(),
+        }
+    }
 }
 
 impl fmt::Display for AggregationError {
+    // One arm per variant
+    #[allow(clippy::too_many_lines)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::EmptyBatch => write!(f, "Empty batch: at least one signature required"),
@@ -118,6 +263,90 @@ impl fmt::Display for AggregationError {
                     size, max
                 )
             }
+            Self::LengthMismatch {
+                what,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "Length mismatch: expected {} {}, found {}",
+                    expected, what, actual
+                )
+            }
+            Self::UnknownSigner { signer_id } => {
+                write!(
+                    f,
+                    "Unknown signer: no public key registered for '{}'",
+                    signer_id
+                )
+            }
+            Self::InvalidSignerId { signer_id } => {
+                write!(
+                    f,
+                    "Invalid signer ID '{}': expected ASCII alphanumerics, '-' or '_'",
+                    signer_id
+                )
+            }
+            Self::SignerBindingMismatch { index } => {
+                write!(
+                    f,
+                    "Signer binding at index {} does not match the item's public key",
+                    index
+                )
+            }
+            Self::MixedPublicKeys { index } => {
+                write!(
+                    f,
+                    "Public key at index {} differs from the batch's shared key",
+                    index
+                )
+            }
+            Self::DuplicateEpoch { epoch } => {
+                write!(f, "Duplicate epoch {} in single-key batch", epoch)
+            }
+            Self::MissingPublicKey { mode, index: None } => {
+                write!(f, "Missing shared public key required in {} mode", mode)
+            }
+            Self::MissingPublicKey {
+                mode,
+                index: Some(index),
+            } => {
+                write!(
+                    f,
+                    "Missing public key at index {} required in {} mode",
+                    index, mode
+                )
+            }
+            Self::UnexpectedPublicKey { mode } => {
+                write!(f, "Shared public key not allowed in {} mode", mode)
+            }
+            Self::UnknownKeyIndex { index, key_index } => {
+                write!(
+                    f,
+                    "Item at index {} refers to key {} outside the key table",
+                    index, key_index
+                )
+            }
+            Self::EpochOutOfRange {
+                index,
+                epoch,
+                lifetime,
+            } => {
+                write!(
+                    f,
+                    "Item at index {} uses epoch {}, beyond the lifetime of {} epochs",
+                    index, epoch, lifetime
+                )
+            }
+            Self::InvalidMessage { reason } => write!(f, "Invalid message payload: {}", reason),
+            Self::UnknownTemplate { name } => {
+                write!(f, "Unknown batch template '{}'", name)
+            }
+            Self::TemplateRejected { name, reason } => {
+                write!(f, "Batch template '{}' rejected: {}", name, reason)
+            }
+            Self::WithdrawalRejected { reason } => write!(f, "Withdrawal rejected: {}", reason),
             Self::InvalidSignature { index } => {
                 write!(f, "Invalid signature at index {}", index)
             }
@@ -129,6 +358,32 @@ impl fmt::Display for AggregationError {
                 )
             }
             Self::InvalidProof => write!(f, "zkVM proof is cryptographically invalid"),
+            Self::UnknownGuest { guest_hash } => {
+                write!(
+                    f,
+                    "Unknown guest program: no verifier registered for guest hash {}",
+                    guest_hash
+                )
+            }
+            Self::DecryptionFailed => {
+                write!(
+                    f,
+                    "Decryption failed: artifact is corrupt or sealed under another key"
+                )
+            }
+            Self::AuditChainBroken { sequence } => {
+                write!(f, "Audit log hash chain is broken at entry {}", sequence)
+            }
+            Self::ProofReplayed { proof_id } => {
+                write!(f, "Proof replayed: {} was already consumed", proof_id)
+            }
+            Self::ReplayWindowFull { capacity } => {
+                write!(
+                    f,
+                    "Replay window full: {} proof IDs remembered, none expired",
+                    capacity
+                )
+            }
             Self::SerializationError { message } => {
                 write!(f, "Serialization error: {}", message)
             }
@@ -148,11 +403,78 @@ impl fmt::Display for AggregationError {
                     used, limit
                 )
             }
+            Self::IoError { message } => write!(f, "IO error: {}", message),
+            Self::SpoolPoisoned => {
+                write!(
+                    f,
+                    "Spool poisoned: an earlier write failed and items may be lost"
+                )
+            }
+            Self::EnvelopeRejected { reason } => {
+                write!(f, "Proof envelope rejected: {}", reason)
+            }
+            Self::ChunkUnproven {
+                chunk,
+                start,
+                end,
+                source,
+            } => {
+                write!(
+                    f,
+                    "Chunk {} (items {}..{}) is unproven: {}",
+                    chunk, start, end, source
+                )
+            }
+            Self::DeltaRejected { reason } => {
+                write!(f, "Proof set delta rejected: {}", reason)
+            }
+            Self::UnexpectedArtifact { expected, found } => {
+                write!(f, "Not a {}: found {}", expected, found)
+            }
+            Self::UnsupportedFormatVersion {
+                artifact,
+                version,
+                supported,
+            } => {
+                write!(
+                    f,
+                    "Unsupported {} format version {} (this build reads version {})",
+                    artifact, version, supported
+                )
+            }
+            Self::SchemeMismatch {
+                artifact,
+                expected,
+                found,
+                crate_version,
+            } => {
+                write!(
+                    f,
+                    "The {} holds {} signatures (written by sig-agg {}), expected {}",
+                    artifact, found, crate_version, expected
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for AggregationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ChunkUnproven { source, .. } => Some(source.as_ref()),
+            _ => None,
         }
     }
 }
 
-impl std::error::Error for AggregationError {}
+/// Message of a panic caught while compiling, preprocessing or proving.
+pub(crate) fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
 
 #[cfg(test)]
 mod tests {
@@ -191,6 +513,142 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_length_mismatch_error() {
+        let error = AggregationError::LengthMismatch {
+            what: "signer bindings".to_string(),
+            expected: 3,
+            actual: 2,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Length mismatch: expected 3 signer bindings, found 2"
+        );
+    }
+
+    #[test]
+    fn test_unknown_signer_error() {
+        let error = AggregationError::UnknownSigner {
+            signer_id: "alice".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Unknown signer: no public key registered for 'alice'"
+        );
+
+        let error = AggregationError::InvalidSignerId {
+            signer_id: "../alice".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Invalid signer ID '../alice': expected ASCII alphanumerics, '-' or '_'"
+        );
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_signer_binding_mismatch_error() {
+        let error = AggregationError::SignerBindingMismatch { index: 3 };
+        assert_eq!(
+            error.to_string(),
+            "Signer binding at index 3 does not match the item's public key"
+        );
+    }
+
+    #[test]
+    fn test_mixed_public_keys_error() {
+        let error = AggregationError::MixedPublicKeys { index: 2 };
+        assert_eq!(
+            error.to_string(),
+            "Public key at index 2 differs from the batch's shared key"
+        );
+    }
+
+    #[test]
+    fn test_mode_specific_errors() {
+        let error = AggregationError::DuplicateEpoch { epoch: 7 };
+        assert_eq!(error.to_string(), "Duplicate epoch 7 in single-key batch");
+
+        let error = AggregationError::UnexpectedPublicKey {
+            mode: AggregationMode::MultiKey,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Shared public key not allowed in multi-key mode"
+        );
+
+        let error = AggregationError::MissingPublicKey {
+            mode: AggregationMode::SingleKey,
+            index: None,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Missing shared public key required in single-key mode"
+        );
+        let error = AggregationError::MissingPublicKey {
+            mode: AggregationMode::MultiKey,
+            index: Some(4),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Missing public key at index 4 required in multi-key mode"
+        );
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_unknown_key_index_error() {
+        let error = AggregationError::UnknownKeyIndex {
+            index: 4,
+            key_index: 7,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Item at index 4 refers to key 7 outside the key table"
+        );
+    }
+
+    #[test]
+    fn test_epoch_out_of_range_error() {
+        let error = AggregationError::EpochOutOfRange {
+            index: 2,
+            epoch: 300_000,
+            lifetime: 262_144,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Item at index 2 uses epoch 300000, beyond the lifetime of 262144 epochs"
+        );
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_invalid_message_error() {
+        let error = AggregationError::InvalidMessage {
+            reason: "invalid JSON".to_string(),
+        };
+        assert_eq!(error.to_string(), "Invalid message payload: invalid JSON");
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let error = AggregationError::UnknownTemplate {
+            name: "checkpoints".to_string(),
+        };
+        assert_eq!(error.to_string(), "Unknown batch template 'checkpoints'");
+
+        let error = AggregationError::TemplateRejected {
+            name: "checkpoints".to_string(),
+            reason: "expired at 1000".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Batch template 'checkpoints' rejected: expired at 1000"
+        );
+        assert!(!error.is_retryable());
+    }
+
     #[test]
     fn test_invalid_signature_error() {
         let error = AggregationError::InvalidSignature { index: 42 };
@@ -268,6 +726,153 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_io_error() {
+        let error = AggregationError::IoError {
+            message: "permission denied".to_string(),
+        };
+        assert_eq!(error.to_string(), "IO error: permission denied");
+
+        let converted: AggregationError =
+            std::io::Error::new(std::io::ErrorKind::NotFound, "missing cache").into();
+        assert_eq!(
+            converted,
+            AggregationError::IoError {
+                message: "missing cache".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_envelope_rejected_error() {
+        let error = AggregationError::EnvelopeRejected {
+            reason: "unsupported version 2".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Proof envelope rejected: unsupported version 2"
+        );
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_withdrawal_rejected_error() {
+        let error = AggregationError::WithdrawalRejected {
+            reason: "item is not staged".to_string(),
+        };
+        assert_eq!(error.to_string(), "Withdrawal rejected: item is not staged");
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_chunk_unproven_error() {
+        let error = AggregationError::ChunkUnproven {
+            chunk: 2,
+            start: 512,
+            end: 768,
+            source: Box::new(AggregationError::InvalidProof),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Chunk 2 (items 512..768) is unproven: zkVM proof is cryptographically invalid"
+        );
+        assert!(!error.is_retryable());
+
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(
+            source.to_string(),
+            "zkVM proof is cryptographically invalid"
+        );
+        assert!(std::error::Error::source(&AggregationError::InvalidProof).is_none());
+    }
+
+    #[test]
+    fn test_delta_rejected_error() {
+        let error = AggregationError::DeltaRejected {
+            reason: "base root differs".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Proof set delta rejected: base root differs"
+        );
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_artifact_header_errors() {
+        let error = AggregationError::UnexpectedArtifact {
+            expected: "batch".to_string(),
+            found: "proof envelope".to_string(),
+        };
+        assert_eq!(error.to_string(), "Not a batch: found proof envelope");
+
+        let error = AggregationError::UnsupportedFormatVersion {
+            artifact: "proof envelope".to_string(),
+            version: 2,
+            supported: 1,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Unsupported proof envelope format version 2 (this build reads version 1)"
+        );
+
+        let error = AggregationError::SchemeMismatch {
+            artifact: "batch".to_string(),
+            expected: "SIGWinternitzLifetime18W1".to_string(),
+            found: "SIGWinternitzLifetime18W2".to_string(),
+            crate_version: "0.1.0".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "The batch holds SIGWinternitzLifetime18W2 signatures (written by sig-agg 0.1.0), \
+             expected SIGWinternitzLifetime18W1"
+        );
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_unknown_guest_error() {
+        let error = AggregationError::UnknownGuest {
+            guest_hash: "abcd".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Unknown guest program: no verifier registered for guest hash abcd"
+        );
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_decryption_failed_error() {
+        let error = AggregationError::DecryptionFailed;
+        assert_eq!(
+            error.to_string(),
+            "Decryption failed: artifact is corrupt or sealed under another key"
+        );
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_retryable_classification() {
+        assert!(
+            AggregationError::ProofGenerationError {
+                message: "killed".to_string()
+            }
+            .is_retryable()
+        );
+        assert!(AggregationError::MemoryExhausted { used: 2, limit: 1 }.is_retryable());
+        assert!(
+            AggregationError::IoError {
+                message: "busy".to_string()
+            }
+            .is_retryable()
+        );
+
+        assert!(!AggregationError::EmptyBatch.is_retryable());
+        assert!(!AggregationError::InvalidProof.is_retryable());
+        assert!(!AggregationError::InvalidSignature { index: 0 }.is_retryable());
+    }
+
     #[test]
     fn test_error_equality() {
         let error1 = AggregationError::EmptyBatch;
@@ -329,4 +934,32 @@ mod tests {
             _ => panic!("Wrong error variant"),
         }
     }
+
+    #[test]
+    fn test_proof_replayed_error() {
+        let error = AggregationError::ProofReplayed {
+            proof_id: "abcd".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Proof replayed: abcd was already consumed"
+        );
+        assert!(!error.is_retryable());
+
+        let error = AggregationError::ReplayWindowFull { capacity: 2 };
+        assert_eq!(
+            error.to_string(),
+            "Replay window full: 2 proof IDs remembered, none expired"
+        );
+    }
+
+    #[test]
+    fn test_audit_chain_broken_error() {
+        let error = AggregationError::AuditChainBroken { sequence: 7 };
+        assert_eq!(
+            error.to_string(),
+            "Audit log hash chain is broken at entry 7"
+        );
+        assert!(!error.is_retryable());
+    }
 }
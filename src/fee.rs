@@ -0,0 +1,143 @@
+//! Fee estimation for signature submissions.
+//!
+//! Services that accept signatures for aggregation typically charge per
+//! signature, per byte of zkVM input, or a combination of both. The [`FeeModel`]
+//! trait lets such services price an item or a whole batch before accepting it,
+//! using the encoded sizes reported by
+//! [`VerificationItem::encoded_size`] and [`AggregationBatch::encoded_size`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::fee::{FeeModel, LinearFee};
+//! use sig_agg::VerificationItem;
+//! # let item: VerificationItem = unimplemented!();
+//!
+//! let model = LinearFee {
+//!     base: 0,
+//!     per_signature: 10,
+//!     per_byte: 1,
+//! };
+//!
+//! let cost = model.item_fee(&item).expect("Size accounting failed");
+//! println!("Submitting this signature costs {} units", cost);
+//! ```
+
+use crate::{
+    error::AggregationError,
+    types::{AggregationBatch, VerificationItem},
+};
+
+/// Pricing rule for aggregated signatures.
+///
+/// Implementors only provide [`fee`](FeeModel::fee); item and batch pricing are
+/// derived from it.
+pub trait FeeModel {
+    /// Fee for `signatures` signatures occupying `bytes` bytes of zkVM input.
+    fn fee(&self, signatures: usize, bytes: usize) -> u64;
+
+    /// Fee for submitting a single item.
+    fn item_fee(&self, item: &VerificationItem) -> Result<u64, AggregationError> {
+        Ok(self.fee(1, item.encoded_size()?))
+    }
+
+    /// Fee for a whole batch.
+    fn batch_fee(&self, batch: &AggregationBatch) -> Result<u64, AggregationError> {
+        Ok(self.fee(batch.items.len(), batch.encoded_size()?))
+    }
+}
+
+/// Flat fee per signature, independent of size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerSignatureFee {
+    /// Fee charged for each signature
+    pub per_signature: u64,
+}
+
+impl FeeModel for PerSignatureFee {
+    fn fee(&self, signatures: usize, _bytes: usize) -> u64 {
+        self.per_signature.saturating_mul(signatures as u64)
+    }
+}
+
+/// Fee proportional to the encoded size of the submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerByteFee {
+    /// Fee charged for each encoded byte
+    pub per_byte: u64,
+}
+
+impl FeeModel for PerByteFee {
+    fn fee(&self, _signatures: usize, bytes: usize) -> u64 {
+        self.per_byte.saturating_mul(bytes as u64)
+    }
+}
+
+/// Base fee plus per-signature and per-byte components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinearFee {
+    /// Fixed fee per priced submission
+    pub base: u64,
+    /// Fee charged for each signature
+    pub per_signature: u64,
+    /// Fee charged for each encoded byte
+    pub per_byte: u64,
+}
+
+impl FeeModel for LinearFee {
+    fn fee(&self, signatures: usize, bytes: usize) -> u64 {
+        self.base
+            .saturating_add(self.per_signature.saturating_mul(signatures as u64))
+            .saturating_add(self.per_byte.saturating_mul(bytes as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_batch;
+
+    #[test]
+    fn test_per_signature_fee() {
+        let batch = test_batch(3);
+        let model = PerSignatureFee { per_signature: 5 };
+
+        assert_eq!(model.item_fee(&batch.items[0]).unwrap(), 5);
+        assert_eq!(model.batch_fee(&batch).unwrap(), 15);
+    }
+
+    #[test]
+    fn test_per_byte_fee() {
+        let batch = test_batch(2);
+        let model = PerByteFee { per_byte: 2 };
+
+        let item_bytes = batch.items[0].encoded_size().unwrap() as u64;
+        let batch_bytes = batch.encoded_size().unwrap() as u64;
+
+        assert_eq!(model.item_fee(&batch.items[0]).unwrap(), 2 * item_bytes);
+        assert_eq!(model.batch_fee(&batch).unwrap(), 2 * batch_bytes);
+    }
+
+    #[test]
+    fn test_linear_fee() {
+        let model = LinearFee {
+            base: 100,
+            per_signature: 10,
+            per_byte: 1,
+        };
+
+        assert_eq!(model.fee(0, 0), 100);
+        assert_eq!(model.fee(3, 50), 100 + 30 + 50);
+    }
+
+    #[test]
+    fn test_fee_saturates() {
+        let model = LinearFee {
+            base: u64::MAX,
+            per_signature: u64::MAX,
+            per_byte: u64::MAX,
+        };
+
+        assert_eq!(model.fee(usize::MAX, usize::MAX), u64::MAX);
+    }
+}
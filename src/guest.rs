@@ -0,0 +1,259 @@
+//! Registry of deployed guest program versions.
+//!
+//! Every change to the guest program changes its hash and therefore the
+//! verifier preprocessing needed to check its proofs. A verifier that only
+//! knows the latest guest rejects every proof produced before an upgrade.
+//!
+//! [`GuestRegistry`] maps guest hashes to verifier keys and picks the right
+//! one from the hash recorded in a proof's
+//! [`ProofMetadata`](crate::ProofMetadata), so proofs from older aggregator
+//! versions stay verifiable as long as their guest is registered. The registry
+//! is generic over the verifier key type so it does not tie the library to a
+//! particular zkVM.
+//!
+//! [`input_digest`] computes the digest the guest echoes in its output over
+//! the batch it executed on. Comparing it with the digest of the batch the
+//! host meant to prove detects any divergence between the two.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::guest::GuestRegistry;
+//! use sig_agg::AggregationProof;
+//! # struct VerifierKey;
+//! # let (v1_hash, v1_key, v2_hash, v2_key) = ([1u8; 32], VerifierKey, [2u8; 32], VerifierKey);
+//! # let proof: AggregationProof = unimplemented!();
+//!
+//! let mut registry = GuestRegistry::new();
+//! registry.register(v1_hash, v1_key);
+//! registry.register(v2_hash, v2_key);
+//!
+//! let verifier_key = registry.select(&proof).expect("Proof from unknown guest");
+//! ```
+
+use std::collections::HashMap;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::AggregationError,
+    key_table::KeyTableBatch,
+    scheme::{Instantiation, SchemeBatch},
+    types::{AggregationBatch, AggregationProof},
+    wire::{encode_batch, encode_key_table_batch, encode_single_key_batch},
+};
+
+/// Domain tag of input digests, shared with the guest program.
+const INPUT_DIGEST_DOMAIN: &[u8] = b"sig-agg/guest-input/v1";
+
+/// Domain tag of single-key input digests, shared with the guest program.
+const SINGLE_KEY_INPUT_DIGEST_DOMAIN: &[u8] = b"sig-agg/guest-input-single-key/v1";
+
+/// Domain tag of key-table input digests, shared with the guest program.
+const KEY_TABLE_INPUT_DIGEST_DOMAIN: &[u8] = b"sig-agg/guest-input-key-table/v1";
+
+/// Domain tag of input digests of other instantiations, shared with the guest
+/// program.
+const SCHEME_INPUT_DIGEST_DOMAIN: &[u8] = b"sig-agg/guest-input-scheme/v1";
+
+/// SHA-256 hash identifying a guest program build.
+pub type GuestHash = [u8; 32];
+
+/// Digest of the serialized batch a guest run executed on.
+pub type InputDigest = [u8; 32];
+
+/// Digest the guest outputs for `batch`: SHA-256 over a domain tag and the
+/// batch's [wire encoding](crate::wire), exactly as the guest computes it.
+pub fn input_digest(batch: &AggregationBatch) -> Result<InputDigest, AggregationError> {
+    Ok(tagged_digest(
+        INPUT_DIGEST_DOMAIN,
+        &[],
+        &encode_batch(batch)?,
+    ))
+}
+
+/// Digest the single-key guest entry point outputs for `batch`: SHA-256 over
+/// a domain tag and the batch's
+/// [single-key encoding](crate::wire::encode_single_key_batch).
+pub fn single_key_input_digest(batch: &AggregationBatch) -> Result<InputDigest, AggregationError> {
+    Ok(tagged_digest(
+        SINGLE_KEY_INPUT_DIGEST_DOMAIN,
+        &[],
+        &encode_single_key_batch(batch)?,
+    ))
+}
+
+/// Digest the key-table guest entry point outputs for `batch`: SHA-256 over
+/// a domain tag and the batch's
+/// [key-table encoding](crate::wire::encode_key_table_batch).
+pub fn key_table_input_digest(batch: &KeyTableBatch) -> Result<InputDigest, AggregationError> {
+    Ok(tagged_digest(
+        KEY_TABLE_INPUT_DIGEST_DOMAIN,
+        &[],
+        &encode_key_table_batch(batch)?,
+    ))
+}
+
+/// Digest an instantiation's guest entry point outputs for `batch`.
+///
+/// SHA-256 over a domain tag, the scheme's [`Instantiation::NAME`]
+/// (length-prefixed by one byte) and the batch's
+/// [wire encoding](crate::wire::encode_batch).
+pub fn scheme_input_digest<S: Instantiation>(
+    batch: &SchemeBatch<S>,
+) -> Result<InputDigest, AggregationError> {
+    let name_len =
+        u8::try_from(S::NAME.len()).map_err(|_| AggregationError::SerializationError {
+            message: format!("Scheme name {} exceeds 255 bytes", S::NAME),
+        })?;
+    let mut name = vec![name_len];
+    name.extend_from_slice(S::NAME.as_bytes());
+    Ok(tagged_digest(
+        SCHEME_INPUT_DIGEST_DOMAIN,
+        &name,
+        &encode_batch(batch)?,
+    ))
+}
+
+/// SHA-256 over `domain`, `prefix` and an `encoded` batch.
+fn tagged_digest(domain: &[u8], prefix: &[u8], encoded: &[u8]) -> InputDigest {
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    hasher.update(prefix);
+    hasher.update(encoded);
+    hasher.finalize().into()
+}
+
+/// Verifier keys indexed by the guest program they belong to.
+#[derive(Debug, Clone)]
+pub struct GuestRegistry<V> {
+    verifiers: HashMap<GuestHash, V>,
+}
+
+impl<V> GuestRegistry<V> {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            verifiers: HashMap::new(),
+        }
+    }
+
+    /// Registers the verifier key for a guest, returning the key it replaced.
+    pub fn register(&mut self, guest_hash: GuestHash, verifier: V) -> Option<V> {
+        self.verifiers.insert(guest_hash, verifier)
+    }
+
+    /// Removes a retired guest. Its proofs are no longer verifiable afterwards.
+    pub fn retire(&mut self, guest_hash: &GuestHash) -> Option<V> {
+        self.verifiers.remove(guest_hash)
+    }
+
+    /// Returns the verifier key for `guest_hash`.
+    pub fn get(&self, guest_hash: &GuestHash) -> Result<&V, AggregationError> {
+        self.verifiers
+            .get(guest_hash)
+            .ok_or_else(|| AggregationError::UnknownGuest {
+                guest_hash: hex::encode(guest_hash),
+            })
+    }
+
+    /// Returns the verifier key for the guest that produced `proof`.
+    pub fn select(&self, proof: &AggregationProof) -> Result<&V, AggregationError> {
+        self.get(&proof.metadata.guest_hash)
+    }
+
+    /// Returns `true` if a verifier is registered for `guest_hash`.
+    #[must_use]
+    pub fn contains(&self, guest_hash: &GuestHash) -> bool {
+        self.verifiers.contains_key(guest_hash)
+    }
+
+    /// Hashes of all registered guests, in no particular order.
+    pub fn guest_hashes(&self) -> impl Iterator<Item = &GuestHash> {
+        self.verifiers.keys()
+    }
+
+    /// Number of registered guests.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.verifiers.len()
+    }
+
+    /// Returns `true` if no guest is registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.verifiers.is_empty()
+    }
+}
+
+impl<V> Default for GuestRegistry<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commitment::CommitmentAlgorithm, profile::ComplianceProfile, types::ProofMetadata,
+    };
+
+    fn proof_from(guest_hash: GuestHash) -> AggregationProof {
+        AggregationProof {
+            proof: vec![],
+            verified_count: 1,
+            metadata: ProofMetadata {
+                timestamp: 0,
+                batch_size: 1,
+                memory_size: 0,
+                trace_length: 0,
+                max_trace_length: 0,
+                proof_size: 0,
+                guest_hash,
+                urs_digest: [0u8; 32],
+                commitment_hash: CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: None,
+                profile: ComplianceProfile::Standard,
+            },
+            failed: vec![],
+            verified_root: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_selects_verifier_by_guest_hash() {
+        let mut registry = GuestRegistry::new();
+        registry.register([1u8; 32], "v1");
+        registry.register([2u8; 32], "v2");
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.select(&proof_from([1u8; 32])), Ok(&"v1"));
+        assert_eq!(registry.select(&proof_from([2u8; 32])), Ok(&"v2"));
+    }
+
+    #[test]
+    fn test_unknown_guest() {
+        let registry: GuestRegistry<()> = GuestRegistry::new();
+
+        assert_eq!(
+            registry.select(&proof_from([0xab; 32])),
+            Err(AggregationError::UnknownGuest {
+                guest_hash: "ab".repeat(32)
+            })
+        );
+    }
+
+    #[test]
+    fn test_register_replaces_and_retire_removes() {
+        let mut registry = GuestRegistry::new();
+        assert_eq!(registry.register([1u8; 32], 1), None);
+        assert_eq!(registry.register([1u8; 32], 2), Some(1));
+
+        assert_eq!(registry.retire(&[1u8; 32]), Some(2));
+        assert!(!registry.contains(&[1u8; 32]));
+        assert!(registry.is_empty());
+    }
+}
@@ -0,0 +1,304 @@
+//! Per-item inclusion proofs against an aggregation proof.
+//!
+//! Besides the verified count, the guest outputs the root of a binary Merkle
+//! tree with one leaf per verified item, recorded as
+//! [`AggregationProof::verified_root`]. Whoever holds the batch can hand a
+//! single signer an [`InclusionProof`] for their item, and the signer can
+//! check it against the aggregation proof without the rest of the batch.
+//!
+//! # Tree
+//!
+//! Leaves are taken from the verified items only, in batch order. A leaf is
+//! SHA-256 over a domain tag, the message, the epoch (u32 little-endian) and
+//! the signer's [`public_key_fingerprint`]. Inner nodes are SHA-256 over
+//! another domain tag and both children. A node without a sibling moves up a
+//! level unchanged, and an empty tree has the all-zero root. The guest
+//! computes exactly this; a mismatch makes the proof fail to verify.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::inclusion::InclusionProof;
+//! # let (batch, proof): (sig_agg::AggregationBatch, sig_agg::AggregationProof) = unimplemented!();
+//!
+//! // Collector: prove that item 3 was among the verified items
+//! let inclusion = InclusionProof::for_item(&batch, &proof.failed, 3)
+//!     .expect("Encoding failed")
+//!     .expect("Item 3 did not verify");
+//!
+//! // Signer: check their own item against the aggregation proof
+//! assert!(proof.verify_inclusion(&batch.items[3], &inclusion).expect("Encoding failed"));
+//! ```
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    commitment::public_key_fingerprint,
+    error::AggregationError,
+    types::{AggregationBatch, AggregationProof, VerificationItem},
+};
+
+/// Domain tag of leaves, shared with the guest program.
+const LEAF_DOMAIN: &[u8] = b"sig-agg/verified-leaf/v1";
+
+/// Domain tag of inner nodes, shared with the guest program.
+const NODE_DOMAIN: &[u8] = b"sig-agg/verified-node/v1";
+
+/// Root of the tree over a batch's verified items.
+pub type VerifiedRoot = [u8; 32];
+
+/// Leaf of the verified-item tree for `item`.
+pub fn verified_leaf(item: &VerificationItem) -> Result<[u8; 32], AggregationError> {
+    let fingerprint = public_key_fingerprint(&item.public_key)?;
+    let mut hasher = Sha256::new();
+    hasher.update(LEAF_DOMAIN);
+    hasher.update(item.message);
+    hasher.update(item.epoch.to_le_bytes());
+    hasher.update(fingerprint);
+    Ok(hasher.finalize().into())
+}
+
+/// Leaves of the items of `batch` not marked in the `failed` bitmap, in
+/// batch order.
+pub fn verified_leaves(
+    batch: &AggregationBatch,
+    failed: &[u8],
+) -> Result<Vec<[u8; 32]>, AggregationError> {
+    batch
+        .items
+        .iter()
+        .enumerate()
+        .filter(|&(index, _)| !is_failed(failed, index))
+        .map(|(_, item)| verified_leaf(item))
+        .collect()
+}
+
+/// Root the guest outputs for `batch` given its `failed` bitmap.
+pub fn verified_root(
+    batch: &AggregationBatch,
+    failed: &[u8],
+) -> Result<VerifiedRoot, AggregationError> {
+    Ok(merkle_root(&verified_leaves(batch, failed)?))
+}
+
+/// Root of the tree over `leaves`.
+#[must_use]
+pub fn merkle_root(leaves: &[[u8; 32]]) -> VerifiedRoot {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Path from one leaf to the root of the verified-item tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    /// Position of the leaf among the verified items
+    pub leaf_index: usize,
+    /// Number of verified items, which fixes the shape of the tree
+    pub leaf_count: usize,
+    /// Sibling hashes from the leaf upwards, skipping levels where the node
+    /// has no sibling
+    #[serde(
+        serialize_with = "crate::serde_hex::serialize_list",
+        deserialize_with = "crate::serde_hex::deserialize_list"
+    )]
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl InclusionProof {
+    /// Proves the leaf at `leaf_index`, or returns `None` if it is out of
+    /// range.
+    #[must_use]
+    pub fn new(leaves: &[[u8; 32]], leaf_index: usize) -> Option<Self> {
+        if leaf_index >= leaves.len() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut level = leaves.to_vec();
+        let mut index = leaf_index;
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(index ^ 1) {
+                siblings.push(*sibling);
+            }
+            level = next_level(&level);
+            index /= 2;
+        }
+        Some(Self {
+            leaf_index,
+            leaf_count: leaves.len(),
+            siblings,
+        })
+    }
+
+    /// Proves item `item_index` of `batch`, or returns `Ok(None)` if the
+    /// item is out of range or marked in the `failed` bitmap.
+    pub fn for_item(
+        batch: &AggregationBatch,
+        failed: &[u8],
+        item_index: usize,
+    ) -> Result<Option<Self>, AggregationError> {
+        if item_index >= batch.items.len() || is_failed(failed, item_index) {
+            return Ok(None);
+        }
+        let leaf_index = (0..item_index)
+            .filter(|&index| !is_failed(failed, index))
+            .count();
+        Ok(Self::new(&verified_leaves(batch, failed)?, leaf_index))
+    }
+
+    /// Root obtained by hashing `leaf` up the path, or `None` if the proof
+    /// does not fit a tree of `leaf_count` leaves.
+    #[must_use]
+    pub fn root(&self, leaf: &[u8; 32]) -> Option<VerifiedRoot> {
+        if self.leaf_index >= self.leaf_count {
+            return None;
+        }
+        let mut siblings = self.siblings.iter();
+        let mut node = *leaf;
+        let mut index = self.leaf_index;
+        let mut width = self.leaf_count;
+        while width > 1 {
+            if index % 2 == 1 {
+                node = merkle_node(siblings.next()?, &node);
+            } else if index + 1 < width {
+                node = merkle_node(&node, siblings.next()?);
+            }
+            index /= 2;
+            width = width.div_ceil(2);
+        }
+        siblings.next().is_none().then_some(node)
+    }
+
+    /// Returns `true` if `item` is a leaf of the tree with root `root`.
+    pub fn verify(
+        &self,
+        item: &VerificationItem,
+        root: &VerifiedRoot,
+    ) -> Result<bool, AggregationError> {
+        Ok(self.root(&verified_leaf(item)?).as_ref() == Some(root))
+    }
+}
+
+impl AggregationProof {
+    /// Returns `true` if `inclusion` shows that `item` was among the items
+    /// this proof verified.
+    ///
+    /// Only meaningful once the aggregation proof itself has been verified,
+    /// which is what binds [`AggregationProof::verified_root`] to the guest
+    /// run.
+    pub fn verify_inclusion(
+        &self,
+        item: &VerificationItem,
+        inclusion: &InclusionProof,
+    ) -> Result<bool, AggregationError> {
+        inclusion.verify(item, &self.verified_root)
+    }
+}
+
+fn is_failed(failed: &[u8], index: usize) -> bool {
+    failed
+        .get(index / 8)
+        .is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => merkle_node(left, right),
+            _ => pair[0],
+        })
+        .collect()
+}
+
+fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(NODE_DOMAIN);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_batch;
+
+    #[test]
+    fn test_every_leaf_proves_against_the_root() {
+        for count in 1..=9u8 {
+            let leaves: Vec<[u8; 32]> = (0..count).map(|byte| [byte; 32]).collect();
+            let root = merkle_root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = InclusionProof::new(&leaves, index).unwrap();
+                assert_eq!(proof.root(leaf), Some(root), "leaf {} of {}", index, count);
+                assert_ne!(proof.root(&[0xff; 32]), Some(root));
+            }
+            assert_eq!(InclusionProof::new(&leaves, leaves.len()), None);
+        }
+        assert_eq!(merkle_root(&[]), [0u8; 32]);
+    }
+
+    #[test]
+    fn test_rejects_malformed_proofs() {
+        let leaves: Vec<[u8; 32]> = (0..5u8).map(|byte| [byte; 32]).collect();
+        let proof = InclusionProof::new(&leaves, 2).unwrap();
+
+        let mut extra_sibling = proof.clone();
+        extra_sibling.siblings.push([0u8; 32]);
+        assert_eq!(extra_sibling.root(&leaves[2]), None);
+
+        let mut missing_sibling = proof.clone();
+        missing_sibling.siblings.pop();
+        assert_eq!(missing_sibling.root(&leaves[2]), None);
+
+        let mut out_of_range = proof;
+        out_of_range.leaf_index = 5;
+        assert_eq!(out_of_range.root(&leaves[2]), None);
+    }
+
+    #[test]
+    fn test_failed_items_are_not_leaves() {
+        let batch = test_batch(4);
+        let failed = [0b0000_0010];
+        let mut proof = AggregationProof {
+            proof: vec![],
+            verified_count: 3,
+            metadata: crate::types::ProofMetadata {
+                timestamp: 0,
+                batch_size: 4,
+                memory_size: 0,
+                trace_length: 0,
+                max_trace_length: 0,
+                proof_size: 0,
+                guest_hash: [0u8; 32],
+                urs_digest: [0u8; 32],
+                commitment_hash: crate::commitment::CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: None,
+                profile: crate::profile::ComplianceProfile::Standard,
+            },
+            verified_root: verified_root(&batch, &failed).unwrap(),
+            failed: failed.to_vec(),
+        };
+
+        assert_eq!(InclusionProof::for_item(&batch, &failed, 1).unwrap(), None);
+        assert_eq!(InclusionProof::for_item(&batch, &failed, 4).unwrap(), None);
+        let inclusion = InclusionProof::for_item(&batch, &failed, 2)
+            .unwrap()
+            .unwrap();
+        assert_eq!(inclusion.leaf_index, 1);
+        assert_eq!(inclusion.leaf_count, 3);
+        assert!(proof.verify_inclusion(&batch.items[2], &inclusion).unwrap());
+        assert!(!proof.verify_inclusion(&batch.items[3], &inclusion).unwrap());
+
+        proof.verified_root = verified_root(&batch, &[]).unwrap();
+        assert!(!proof.verify_inclusion(&batch.items[2], &inclusion).unwrap());
+    }
+}
@@ -0,0 +1,156 @@
+//! Extracts the resource limits of the guest's `#[jolt::provable]` entry
+//! points, and the item capacities of its tiered entry points, from its
+//! source.
+//!
+//! Not part of the guest crate: the build scripts of `sig-agg` and the
+//! benchmark include this file with `#[path]` and generate constants from
+//! `guest/src/lib.rs`, so host code never repeats the limits by hand.
+
+// `unreachable_pub` asks for `pub(crate)`, which this nursery lint flags.
+#![allow(clippy::redundant_pub_crate)]
+
+use std::fmt::{self, Write};
+
+/// Attribute keys every entry point must declare, in generated field order.
+const LIMIT_KEYS: [&str; 4] = [
+    "stack_size",
+    "memory_size",
+    "max_input_size",
+    "max_trace_length",
+];
+
+/// An entry point and its limits, in [`LIMIT_KEYS`] order.
+pub(crate) struct EntryPointLimits {
+    pub(crate) name: String,
+    pub(crate) limits: [u64; 4],
+}
+
+/// Finds every `#[jolt::provable(...)]` function in `source`.
+///
+/// Fails if an attribute is malformed or omits one of [`LIMIT_KEYS`]; Jolt's
+/// defaults are deliberately not assumed.
+pub(crate) fn parse_entry_points(source: &str) -> Result<Vec<EntryPointLimits>, String> {
+    const ATTRIBUTE: &str = "#[jolt::provable(";
+
+    let mut entry_points = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find(ATTRIBUTE) {
+        rest = &rest[start + ATTRIBUTE.len()..];
+        let end = rest
+            .find(")]")
+            .ok_or("unterminated #[jolt::provable] attribute")?;
+        let arguments = &rest[..end];
+        rest = &rest[end..];
+
+        let fn_start = rest
+            .find("fn ")
+            .ok_or("#[jolt::provable] attribute without a function")?;
+        let name: String = rest[fn_start + 3..]
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+
+        let mut limits = [0u64; 4];
+        for (slot, key) in limits.iter_mut().zip(LIMIT_KEYS) {
+            *slot = argument(arguments, key)
+                .ok_or_else(|| format!("entry point `{}` does not declare `{}`", name, key))?;
+        }
+        entry_points.push(EntryPointLimits { name, limits });
+    }
+    Ok(entry_points)
+}
+
+/// Values of the guest's `TIER_CAPACITIES` array.
+pub(crate) fn parse_tier_capacities(source: &str) -> Result<Vec<u64>, String> {
+    const DECLARATION: &str = "pub const TIER_CAPACITIES: [usize;";
+
+    let start = source
+        .find(DECLARATION)
+        .ok_or("guest does not declare TIER_CAPACITIES")?;
+    let rest = &source[start..];
+    let values = rest
+        .find("= [")
+        .and_then(|open| {
+            let values = &rest[open + 3..];
+            values.find("];").map(|close| &values[..close])
+        })
+        .ok_or("malformed TIER_CAPACITIES declaration")?;
+
+    values
+        .split(',')
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| {
+            value
+                .trim()
+                .replace('_', "")
+                .parse()
+                .map_err(|_| format!("invalid tier capacity `{}`", value.trim()))
+        })
+        .collect()
+}
+
+/// Value of `key = <integer>` in a comma-separated argument list.
+fn argument(arguments: &str, key: &str) -> Option<u64> {
+    arguments.split(',').find_map(|argument| {
+        let (name, value) = argument.split_once('=')?;
+        if name.trim() != key {
+            return None;
+        }
+        value.trim().replace('_', "").parse().ok()
+    })
+}
+
+/// Rust source defining one `GuestLimits` constant per entry point, named
+/// after the function in upper case, `ENTRY_POINTS` listing them all and
+/// `TIER_CAPACITIES` copied from the guest.
+///
+/// The including module must define `GuestLimits` with `usize` fields named
+/// like [`LIMIT_KEYS`].
+pub(crate) fn generate(entry_points: &[EntryPointLimits], tier_capacities: &[u64]) -> String {
+    generate_into(entry_points, tier_capacities, String::new())
+        .expect("writing to a String cannot fail")
+}
+
+fn generate_into(
+    entry_points: &[EntryPointLimits],
+    tier_capacities: &[u64],
+    mut code: String,
+) -> Result<String, fmt::Error> {
+    for entry_point in entry_points {
+        let constant = entry_point.name.to_uppercase();
+        writeln!(
+            code,
+            "/// Limits of the `{}` entry point.",
+            entry_point.name
+        )?;
+        writeln!(code, "pub const {}: GuestLimits = GuestLimits {{", constant)?;
+        for (key, value) in LIMIT_KEYS.iter().zip(entry_point.limits) {
+            writeln!(code, "    {}: {},", key, value)?;
+        }
+        writeln!(code, "}};\n")?;
+    }
+
+    writeln!(
+        code,
+        "/// Every provable entry point and its limits, in source order."
+    )?;
+    writeln!(code, "pub const ENTRY_POINTS: &[(&str, GuestLimits)] = &[")?;
+    for entry_point in entry_points {
+        let constant = entry_point.name.to_uppercase();
+        writeln!(code, "    (\"{}\", {}),", entry_point.name, constant)?;
+    }
+    writeln!(code, "];\n")?;
+
+    writeln!(
+        code,
+        "/// Item capacities of the tiered entry points, smallest first."
+    )?;
+    let capacities: Vec<String> = tier_capacities.iter().map(u64::to_string).collect();
+    writeln!(
+        code,
+        "pub const TIER_CAPACITIES: [usize; {}] = [{}];",
+        capacities.len(),
+        capacities.join(", ")
+    )?;
+    Ok(code)
+}
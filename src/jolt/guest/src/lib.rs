@@ -1,3 +1,16 @@
+use bincode::Options;
+#[cfg(feature = "lifetime32")]
+use hashsig::signature::generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_32::hashing_optimized::SIGTopLevelTargetSumLifetime32Dim64Base8;
+#[cfg(feature = "sha3")]
+use hashsig::signature::generalized_xmss::instantiations_sha::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W1 as SIGSha3WinternitzLifetime18W1;
+#[cfg(feature = "w1-lifetime20")]
+use hashsig::signature::generalized_xmss::instantiations_poseidon::lifetime_2_to_the_20::winternitz::SIGWinternitzLifetime20W1;
+#[cfg(feature = "w2-lifetime18")]
+use hashsig::signature::generalized_xmss::instantiations_poseidon::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W2;
+#[cfg(feature = "w4-lifetime18")]
+use hashsig::signature::generalized_xmss::instantiations_poseidon::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W4;
+#[cfg(feature = "w8-lifetime18")]
+use hashsig::signature::generalized_xmss::instantiations_poseidon::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W8;
 use hashsig::{
     signature::{
         generalized_xmss::instantiations_poseidon::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W1,
@@ -5,31 +18,915 @@ use hashsig::{
     },
     MESSAGE_LENGTH,
 };
-use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, Write},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 // The signature scheme we are going to benchmark.
 type XMSSSignature = SIGWinternitzLifetime18W1;
 
+/// Winternitz parameter 2 instantiation, verified by [`verify_aggregation_w2`].
+#[cfg(feature = "w2-lifetime18")]
+pub type XMSSSignatureW2 = SIGWinternitzLifetime18W2;
+
+/// Winternitz parameter 4 instantiation, verified by [`verify_aggregation_w4`].
+#[cfg(feature = "w4-lifetime18")]
+pub type XMSSSignatureW4 = SIGWinternitzLifetime18W4;
+
+/// Winternitz parameter 8 instantiation, verified by [`verify_aggregation_w8`].
+#[cfg(feature = "w8-lifetime18")]
+pub type XMSSSignatureW8 = SIGWinternitzLifetime18W8;
+
+/// Lifetime 2^20 instantiation, verified by [`verify_aggregation_lifetime_20`].
+#[cfg(feature = "w1-lifetime20")]
+pub type XMSSSignatureLifetime20 = SIGWinternitzLifetime20W1;
+
+/// Lifetime 2^32 instantiation, verified by [`verify_aggregation_lifetime_32`].
+#[cfg(feature = "lifetime32")]
+pub type XMSSSignatureLifetime32 = SIGTopLevelTargetSumLifetime32Dim64Base8;
+
+/// SHA-3 instantiation, verified by [`verify_aggregation_sha3`].
+#[cfg(feature = "sha3")]
+pub type XMSSSignatureSha3 = SIGSha3WinternitzLifetime18W1;
+
+/// Domain tag of [`input_digest`]; must match the host library's.
+const INPUT_DIGEST_DOMAIN: &[u8] = b"sig-agg/guest-input/v1";
+
+/// Domain tag of [`single_key_input_digest`]; must match the host library's.
+const SINGLE_KEY_INPUT_DIGEST_DOMAIN: &[u8] = b"sig-agg/guest-input-single-key/v1";
+
+/// Domain tag of [`key_table_input_digest`]; must match the host library's.
+const KEY_TABLE_INPUT_DIGEST_DOMAIN: &[u8] = b"sig-agg/guest-input-key-table/v1";
+
+/// Domain tag of [`scheme_input_digest`]; must match the host library's.
+const SCHEME_INPUT_DIGEST_DOMAIN: &[u8] = b"sig-agg/guest-input-scheme/v1";
+
+/// Domain tag of [`hash_to_message`]; must match the host library's.
+const PREHASH_DOMAIN: &[u8] = b"sig-agg/message/prehash/v1";
+
+/// Domain tag of [`key_fingerprint`]; must match the host library's
+/// `commitment::public_key_fingerprint`.
+const KEY_FINGERPRINT_DOMAIN: &[u8] = b"sig-agg/public-key/v1";
+
+/// Domain tag of [`verified_leaf`]; must match the host library's.
+const VERIFIED_LEAF_DOMAIN: &[u8] = b"sig-agg/verified-leaf/v1";
+
+/// Domain tag of inner nodes of the verified-item tree; must match the host
+/// library's.
+const VERIFIED_NODE_DOMAIN: &[u8] = b"sig-agg/verified-node/v1";
+
+/// Version byte of [`encode_batch`]; must match the host library's.
+pub const WIRE_VERSION: u8 = 3;
+
+/// Item capacities of the tiered entry points
+/// ([`verify_aggregation_small`], [`verify_aggregation_medium`] and
+/// [`verify_aggregation_large`]). The host library's `limits::GuestTier`
+/// is generated from this declaration, so keep it a literal array.
+pub const TIER_CAPACITIES: [usize; 3] = [16, 256, 4096];
+
 /// A single XMSS verification item.
 ///
 /// Each item contains its own public key, supporting multi-key aggregation.
+pub type VerificationItem = SchemeItem<XMSSSignature>;
+
+/// The aggregation batch for zkVM verification
+///
+/// Each item in the batch includes its own public key, allowing
+/// signatures from different keys to be aggregated together.
+pub type AggregationBatch = SchemeBatch<XMSSSignature>;
+
+/// A verification item whose public key is the batch's shared key.
 #[derive(Serialize, Deserialize)]
-pub struct VerificationItem {
+pub struct SingleKeyItem {
     pub message: [u8; MESSAGE_LENGTH],
     pub epoch: u32,
     pub signature: <XMSSSignature as SignatureScheme>::Signature,
-    /// Public key for this signature (required)
+}
+
+/// A batch of signatures from a single signer.
+///
+/// The public key is carried once rather than per item, which keeps the
+/// guest input of large single-signer batches small.
+#[derive(Serialize, Deserialize)]
+pub struct SingleKeyBatch {
+    /// Public key shared by every item
     pub public_key: <XMSSSignature as SignatureScheme>::PublicKey,
+    pub items: Vec<SingleKeyItem>,
 }
 
-/// The aggregation batch for zkVM verification
+/// A verification item whose public key is an entry of the batch's table.
+#[derive(Serialize, Deserialize)]
+pub struct KeyTableItem {
+    pub message: [u8; MESSAGE_LENGTH],
+    pub epoch: u32,
+    pub signature: <XMSSSignature as SignatureScheme>::Signature,
+    /// Index into [`KeyTableBatch::keys`]
+    pub key_index: u32,
+}
+
+/// A batch in which each distinct public key is carried once.
 ///
-/// Each item in the batch includes its own public key, allowing
-/// signatures from different keys to be aggregated together.
+/// Items refer to their signer's key by index, which keeps the guest input
+/// of batches with many signatures per signer small.
+#[derive(Serialize, Deserialize)]
+pub struct KeyTableBatch {
+    pub keys: Vec<<XMSSSignature as SignatureScheme>::PublicKey>,
+    pub items: Vec<KeyTableItem>,
+}
+
+/// An XMSS instantiation other than the default one, verified by its own
+/// entry point.
+pub trait Instantiation: SignatureScheme {
+    /// Scheme name bound into [`scheme_input_digest`]; must match the host
+    /// library's `scheme::Instantiation::NAME`.
+    const NAME: &'static str;
+}
+
+#[cfg(feature = "w2-lifetime18")]
+impl Instantiation for XMSSSignatureW2 {
+    const NAME: &'static str = "SIGWinternitzLifetime18W2";
+}
+
+#[cfg(feature = "w4-lifetime18")]
+impl Instantiation for XMSSSignatureW4 {
+    const NAME: &'static str = "SIGWinternitzLifetime18W4";
+}
+
+#[cfg(feature = "w8-lifetime18")]
+impl Instantiation for XMSSSignatureW8 {
+    const NAME: &'static str = "SIGWinternitzLifetime18W8";
+}
+
+#[cfg(feature = "w1-lifetime20")]
+impl Instantiation for XMSSSignatureLifetime20 {
+    const NAME: &'static str = "SIGWinternitzLifetime20W1";
+}
+
+#[cfg(feature = "lifetime32")]
+impl Instantiation for XMSSSignatureLifetime32 {
+    const NAME: &'static str = "SIGTopLevelTargetSumLifetime32Dim64Base8";
+}
+
+#[cfg(feature = "sha3")]
+impl Instantiation for XMSSSignatureSha3 {
+    const NAME: &'static str = "instantiations_sha::SIGWinternitzLifetime18W1";
+}
+
+/// A verification item under instantiation `S`.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SchemeItem<S: SignatureScheme> {
+    pub message: [u8; MESSAGE_LENGTH],
+    pub epoch: u32,
+    pub signature: S::Signature,
+    /// Public key for this signature (required)
+    pub public_key: S::PublicKey,
+}
+
+/// A batch of items under instantiation `S`; [`AggregationBatch`] is the
+/// batch of the default instantiation.
 #[derive(Serialize, Deserialize)]
-pub struct AggregationBatch {
-    /// Collection of verification items (each with its own public key)
-    pub items: Vec<VerificationItem>,
+#[serde(bound = "")]
+pub struct SchemeBatch<S: SignatureScheme> {
+    pub items: Vec<SchemeItem<S>>,
+}
+
+/// Public result of verifying a batch.
+///
+/// Produced by [`verify_batch`], which is the single implementation of the
+/// verification loop shared by the zkVM entry point and native host builds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuestOutput {
+    /// Number of signatures that verified successfully
+    pub verified_count: u32,
+    /// Digest of the batch the guest actually executed on
+    pub input_digest: [u8; 32],
+    /// Merkle root over the [`verified_leaf`] of every verified item, in
+    /// batch order; see [`merkle_root`]
+    pub verified_root: [u8; 32],
+    /// Packed bitmap of failed items: bit `i % 8` of byte `i / 8` is set if
+    /// item `i` did not verify
+    pub failed: Vec<u8>,
+}
+
+/// Public result of [`verify_aggregation_threshold`].
+///
+/// Reveals only whether the threshold was met, not how many or which
+/// signatures failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdOutput {
+    /// `true` if at least `min_valid` signatures verified
+    pub met: bool,
+    /// Threshold the batch was checked against
+    pub min_valid: u32,
+    /// Digest of the batch the guest actually executed on
+    pub input_digest: [u8; 32],
+}
+
+/// Number of verified signatures of one signer in a [`TallyOutput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerTally {
+    /// [`key_fingerprint`] of the signer's public key
+    pub fingerprint: [u8; 32],
+    /// Number of the signer's items that verified
+    pub count: u32,
+}
+
+/// Public result of [`verify_aggregation_tally`].
+///
+/// Attests how many signatures each signer contributed, so applications
+/// weighting signers need not trust the host's bookkeeping.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TallyOutput {
+    /// Number of signatures that verified
+    pub verified_count: u32,
+    /// Digest of the batch the guest actually executed on
+    pub input_digest: [u8; 32],
+    /// One entry per signer with at least one verified item, ascending by
+    /// fingerprint
+    pub tally: Vec<SignerTally>,
+}
+
+impl GuestOutput {
+    /// Fixed layout: verified count (`u32`), the digest, the verified root,
+    /// then the bitmap length (`u32`) and bitmap, little-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + 32 + 32 + 4 + self.failed.len());
+        bytes.extend_from_slice(&self.verified_count.to_le_bytes());
+        bytes.extend_from_slice(&self.input_digest);
+        bytes.extend_from_slice(&self.verified_root);
+        bytes.extend_from_slice(&(self.failed.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.failed);
+        bytes
+    }
+
+    /// Inverse of [`GuestOutput::to_bytes`].
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, String> {
+        let verified_count = read_u32(&mut bytes)?;
+        let mut input_digest = [0u8; 32];
+        input_digest.copy_from_slice(take(&mut bytes, 32)?);
+        let mut verified_root = [0u8; 32];
+        verified_root.copy_from_slice(take(&mut bytes, 32)?);
+        let len = read_u32(&mut bytes)? as usize;
+        let failed = take(&mut bytes, len)?.to_vec();
+        if !bytes.is_empty() {
+            return Err(format!("{} trailing bytes", bytes.len()));
+        }
+        Ok(Self {
+            verified_count,
+            input_digest,
+            verified_root,
+            failed,
+        })
+    }
+}
+
+/// Bincode configuration for signatures and public keys, pinned explicitly.
+fn blob_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+        .reject_trailing_bytes()
+}
+
+/// Writes the `u32` length prefix and pinned encoding of `value` into
+/// `sink`.
+///
+/// `value` is encoded once, into `scratch`, which callers reuse across
+/// blobs so that it is allocated only as large as the largest blob.
+fn write_blob<T: Serialize>(sink: &mut impl Write, scratch: &mut Vec<u8>, value: &T) {
+    scratch.clear();
+    blob_options()
+        .serialize_into(&mut *scratch, value)
+        .expect("key material is always serializable");
+    write_all(sink, &(scratch.len() as u32).to_le_bytes());
+    write_all(sink, scratch);
+}
+
+fn write_all(sink: &mut impl Write, bytes: &[u8]) {
+    sink.write_all(bytes).expect("sinks are in memory");
+}
+
+/// SHA-256 state usable as a [`Write`] sink, so encodings are hashed as
+/// they are produced instead of being buffered in full first.
+struct HashSink(Sha256);
+
+impl HashSink {
+    fn new(domain: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(domain);
+        Self(hasher)
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        self.0.finalize().into()
+    }
+}
+
+impl Write for HashSink {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.update(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_batch<S: SignatureScheme>(sink: &mut impl Write, batch: &SchemeBatch<S>) {
+    let mut scratch = Vec::new();
+    write_all(sink, &[WIRE_VERSION]);
+    write_all(sink, &(batch.items.len() as u32).to_le_bytes());
+    for item in &batch.items {
+        write_all(sink, &item.message);
+        write_all(sink, &item.epoch.to_le_bytes());
+        write_blob(sink, &mut scratch, &item.signature);
+        write_blob(sink, &mut scratch, &item.public_key);
+    }
+}
+
+fn write_single_key_batch(sink: &mut impl Write, batch: &SingleKeyBatch) {
+    let mut scratch = Vec::new();
+    write_all(sink, &[WIRE_VERSION]);
+    write_blob(sink, &mut scratch, &batch.public_key);
+    write_all(sink, &(batch.items.len() as u32).to_le_bytes());
+    for item in &batch.items {
+        write_all(sink, &item.message);
+        write_all(sink, &item.epoch.to_le_bytes());
+        write_blob(sink, &mut scratch, &item.signature);
+    }
+}
+
+fn write_key_table_batch(sink: &mut impl Write, batch: &KeyTableBatch) {
+    let mut scratch = Vec::new();
+    write_all(sink, &[WIRE_VERSION]);
+    write_all(sink, &(batch.keys.len() as u32).to_le_bytes());
+    for public_key in &batch.keys {
+        write_blob(sink, &mut scratch, public_key);
+    }
+    write_all(sink, &(batch.items.len() as u32).to_le_bytes());
+    for item in &batch.items {
+        write_all(sink, &item.message);
+        write_all(sink, &item.epoch.to_le_bytes());
+        write_all(sink, &item.key_index.to_le_bytes());
+        write_blob(sink, &mut scratch, &item.signature);
+    }
+}
+
+/// Encodes `batch` in the host library's fixed wire layout (`sig_agg::wire`):
+/// a version byte and `u32` item count, then per item the message, `u32`
+/// epoch and length-prefixed signature and public key, all little-endian.
+/// Batches of every instantiation share the layout.
+pub fn encode_batch<S: SignatureScheme>(batch: &SchemeBatch<S>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_batch(&mut bytes, batch);
+    bytes
+}
+
+/// Encodes `batch` in the host library's single-key layout
+/// (`sig_agg::wire::encode_single_key_batch`): a version byte, the
+/// length-prefixed public key and `u32` item count, then per item the
+/// message, `u32` epoch and length-prefixed signature.
+pub fn encode_single_key_batch(batch: &SingleKeyBatch) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_single_key_batch(&mut bytes, batch);
+    bytes
+}
+
+/// Encodes `batch` in the host library's key-table layout
+/// (`sig_agg::wire::encode_key_table_batch`): a version byte and `u32` key
+/// count, the length-prefixed keys, a `u32` item count, then per item the
+/// message, `u32` epoch, `u32` key index and length-prefixed signature.
+pub fn encode_key_table_batch(batch: &KeyTableBatch) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_key_table_batch(&mut bytes, batch);
+    bytes
+}
+
+/// Splits `len` bytes off the front of `bytes`.
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if bytes.len() < len {
+        return Err("unexpected end of input".to_string());
+    }
+    let (head, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(head)
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<u32, String> {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(take(bytes, 4)?);
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_blob<T: DeserializeOwned>(bytes: &mut &[u8]) -> Result<T, String> {
+    let len = read_u32(bytes)? as usize;
+    blob_options()
+        .deserialize(take(bytes, len)?)
+        .map_err(|e| e.to_string())
+}
+
+/// Decodes a batch encoded with [`encode_batch`].
+pub fn decode_batch<S: SignatureScheme>(mut bytes: &[u8]) -> Result<SchemeBatch<S>, String> {
+    let version = take(&mut bytes, 1)?[0];
+    if version != WIRE_VERSION {
+        return Err(format!("unsupported wire version {}", version));
+    }
+
+    let count = read_u32(&mut bytes)? as usize;
+    let mut items = Vec::with_capacity(count.min(bytes.len() / (MESSAGE_LENGTH + 12)));
+    for _ in 0..count {
+        let mut message = [0u8; MESSAGE_LENGTH];
+        message.copy_from_slice(take(&mut bytes, MESSAGE_LENGTH)?);
+        items.push(SchemeItem {
+            message,
+            epoch: read_u32(&mut bytes)?,
+            signature: read_blob(&mut bytes)?,
+            public_key: read_blob(&mut bytes)?,
+        });
+    }
+    if !bytes.is_empty() {
+        return Err(format!("{} trailing bytes", bytes.len()));
+    }
+    Ok(SchemeBatch { items })
+}
+
+/// Decodes a batch encoded with [`encode_single_key_batch`].
+pub fn decode_single_key_batch(mut bytes: &[u8]) -> Result<SingleKeyBatch, String> {
+    let version = take(&mut bytes, 1)?[0];
+    if version != WIRE_VERSION {
+        return Err(format!("unsupported wire version {}", version));
+    }
+
+    let public_key = read_blob(&mut bytes)?;
+    let count = read_u32(&mut bytes)? as usize;
+    let mut items = Vec::with_capacity(count.min(bytes.len() / (MESSAGE_LENGTH + 8)));
+    for _ in 0..count {
+        let mut message = [0u8; MESSAGE_LENGTH];
+        message.copy_from_slice(take(&mut bytes, MESSAGE_LENGTH)?);
+        items.push(SingleKeyItem {
+            message,
+            epoch: read_u32(&mut bytes)?,
+            signature: read_blob(&mut bytes)?,
+        });
+    }
+    if !bytes.is_empty() {
+        return Err(format!("{} trailing bytes", bytes.len()));
+    }
+    Ok(SingleKeyBatch { public_key, items })
+}
+
+/// Decodes a batch encoded with [`encode_key_table_batch`].
+///
+/// Key indices are not checked here; [`first_unknown_key`] finds the first
+/// one out of range.
+pub fn decode_key_table_batch(mut bytes: &[u8]) -> Result<KeyTableBatch, String> {
+    let version = take(&mut bytes, 1)?[0];
+    if version != WIRE_VERSION {
+        return Err(format!("unsupported wire version {}", version));
+    }
+
+    let key_count = read_u32(&mut bytes)? as usize;
+    let mut keys = Vec::with_capacity(key_count.min(bytes.len() / 4));
+    for _ in 0..key_count {
+        keys.push(read_blob(&mut bytes)?);
+    }
+    let count = read_u32(&mut bytes)? as usize;
+    let mut items = Vec::with_capacity(count.min(bytes.len() / (MESSAGE_LENGTH + 12)));
+    for _ in 0..count {
+        let mut message = [0u8; MESSAGE_LENGTH];
+        message.copy_from_slice(take(&mut bytes, MESSAGE_LENGTH)?);
+        items.push(KeyTableItem {
+            message,
+            epoch: read_u32(&mut bytes)?,
+            key_index: read_u32(&mut bytes)?,
+            signature: read_blob(&mut bytes)?,
+        });
+    }
+    if !bytes.is_empty() {
+        return Err(format!("{} trailing bytes", bytes.len()));
+    }
+    Ok(KeyTableBatch { keys, items })
+}
+
+/// SHA-256 over a domain tag and the [`encode_batch`] encoding of `batch`.
+///
+/// The guest echoes this digest in its output. The host computes it over the
+/// batch it meant to prove, so a proof over different bytes than the
+/// verifier expects is caught by comparing the two.
+pub fn input_digest(batch: &AggregationBatch) -> [u8; 32] {
+    let mut sink = HashSink::new(INPUT_DIGEST_DOMAIN);
+    write_batch(&mut sink, batch);
+    sink.finalize()
+}
+
+/// SHA-256 over a domain tag and the [`encode_single_key_batch`] encoding of
+/// `batch`; the single-key counterpart of [`input_digest`].
+pub fn single_key_input_digest(batch: &SingleKeyBatch) -> [u8; 32] {
+    let mut sink = HashSink::new(SINGLE_KEY_INPUT_DIGEST_DOMAIN);
+    write_single_key_batch(&mut sink, batch);
+    sink.finalize()
+}
+
+/// SHA-256 over a domain tag and the [`encode_key_table_batch`] encoding of
+/// `batch`; the key-table counterpart of [`input_digest`].
+pub fn key_table_input_digest(batch: &KeyTableBatch) -> [u8; 32] {
+    let mut sink = HashSink::new(KEY_TABLE_INPUT_DIGEST_DOMAIN);
+    write_key_table_batch(&mut sink, batch);
+    sink.finalize()
+}
+
+/// Hashes an arbitrary-length `data` into a message under an application
+/// `domain`, exactly like the host library's `codec::hash_to_message`.
+///
+/// Lets a guest program handed a preimage derive the message that was
+/// signed instead of trusting the host's.
+pub fn hash_to_message(domain: &[u8], data: &[u8]) -> [u8; MESSAGE_LENGTH] {
+    let mut hasher = Sha256::new();
+    hasher.update(PREHASH_DOMAIN);
+    hasher.update((domain.len() as u64).to_le_bytes());
+    hasher.update(domain);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// SHA-256 over a domain tag, the scheme name (length-prefixed by one byte)
+/// and the [`encode_batch`] encoding of `batch`.
+///
+/// Binding the name keeps a batch from being passed off as one of another
+/// instantiation whose key and signature encodings happen to coincide.
+pub fn scheme_input_digest<S: Instantiation>(batch: &SchemeBatch<S>) -> [u8; 32] {
+    let mut sink = HashSink::new(SCHEME_INPUT_DIGEST_DOMAIN);
+    write_all(&mut sink, &[S::NAME.len() as u8]);
+    write_all(&mut sink, S::NAME.as_bytes());
+    write_batch(&mut sink, batch);
+    sink.finalize()
+}
+
+/// SHA-256 over a domain tag and the pinned encoding of `public_key`; the
+/// same fingerprint the host library computes for the key.
+pub fn key_fingerprint<K: Serialize>(public_key: &K) -> [u8; 32] {
+    let mut sink = HashSink::new(KEY_FINGERPRINT_DOMAIN);
+    blob_options()
+        .serialize_into(&mut sink, public_key)
+        .expect("key material is always serializable");
+    sink.finalize()
+}
+
+/// Leaf of the verified-item tree: SHA-256 over a domain tag, the message,
+/// the `u32` little-endian epoch and the signer's [`key_fingerprint`].
+pub fn verified_leaf(
+    message: &[u8; MESSAGE_LENGTH],
+    epoch: u32,
+    key_fingerprint: &[u8; 32],
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(VERIFIED_LEAF_DOMAIN);
+    hasher.update(message);
+    hasher.update(epoch.to_le_bytes());
+    hasher.update(key_fingerprint);
+    hasher.finalize().into()
+}
+
+/// [`verified_leaf`] of `item`, for when it verifies.
+pub fn item_leaf<S: SignatureScheme>(item: &SchemeItem<S>) -> [u8; 32] {
+    verified_leaf(
+        &item.message,
+        item.epoch,
+        &key_fingerprint(&item.public_key),
+    )
+}
+
+/// Root of the binary Merkle tree over `leaves`.
+///
+/// Inner nodes are SHA-256 over a domain tag and both children. A node
+/// without a sibling moves up a level unchanged, and an empty tree has the
+/// all-zero root.
+pub fn merkle_root(leaves: Vec<[u8; 32]>) -> [u8; 32] {
+    let mut frontier = MerkleFrontier::default();
+    for leaf in leaves {
+        frontier.push(leaf);
+    }
+    frontier.root()
+}
+
+/// Builds the [`merkle_root`] tree one leaf at a time.
+///
+/// Holds at most one pending node per level, so verification loops keep
+/// O(log n) hashes instead of every leaf. Left subtrees are always complete,
+/// so a pending node is merged with its right sibling as soon as that
+/// appears, and whatever remains at the end is promoted past missing
+/// siblings exactly as in [`merkle_root`].
+#[derive(Debug, Default)]
+pub struct MerkleFrontier {
+    /// Pending node of each level, lowest first
+    levels: Vec<Option<[u8; 32]>>,
+}
+
+impl MerkleFrontier {
+    /// Appends the next leaf.
+    pub fn push(&mut self, leaf: [u8; 32]) {
+        let mut node = leaf;
+        for pending in &mut self.levels {
+            match pending.take() {
+                Some(left) => node = merkle_node(&left, &node),
+                None => {
+                    *pending = Some(node);
+                    return;
+                }
+            }
+        }
+        self.levels.push(Some(node));
+    }
+
+    /// Root over the leaves pushed so far.
+    pub fn root(&self) -> [u8; 32] {
+        let mut carry: Option<[u8; 32]> = None;
+        for pending in &self.levels {
+            carry = match (pending, carry) {
+                (Some(left), Some(right)) => Some(merkle_node(left, &right)),
+                (Some(node), None) => Some(*node),
+                (None, carry) => carry,
+            };
+        }
+        carry.unwrap_or([0u8; 32])
+    }
+}
+
+/// Inner node of the verified-item tree.
+pub fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(VERIFIED_NODE_DOMAIN);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Verifies every signature in the batch.
+///
+/// Does not check that (public key, epoch) pairs are unique: inside the zkVM,
+/// programs call [`verify_aggregation_checked`] instead. Host code can call
+/// this and [`first_duplicate`] natively to pre-verify a batch with no risk of
+/// behavioral drift between host and guest.
+///
+/// # Memory
+///
+/// The guest deserializes the whole batch before verifying it, so its
+/// footprint grows with the batch size. The zkVM's allocator never reuses
+/// freed memory, so the loop avoids temporaries beyond the failure bitmap
+/// (one bit per item) and a [`MerkleFrontier`] of O(log n) nodes:
+/// fingerprints and the input digest are hashed from the pinned encoding
+/// through a [`Write`] sink, reusing one encoding buffer for every item.
+pub fn verify_batch(batch: &AggregationBatch) -> GuestOutput {
+    verify_items(batch, input_digest(batch))
+}
+
+/// Panics if a (public key, epoch) pair is reused, then verifies every
+/// signature in the batch.
+///
+/// The body of [`verify_aggregation`], shared with the SP1 program and the
+/// RISC Zero guest so every proving system attests the same [`GuestOutput`].
+pub fn verify_aggregation_checked(batch: &AggregationBatch) -> GuestOutput {
+    assert_unique(batch);
+    verify_batch(batch)
+}
+
+/// Verification loop of [`verify_batch`] and [`verify_scheme_batch`], which
+/// differ only in the input digest they report.
+fn verify_items<S: SignatureScheme>(batch: &SchemeBatch<S>, input_digest: [u8; 32]) -> GuestOutput {
+    let mut verified_count: u32 = 0;
+    let mut failed = vec![0u8; batch.items.len().div_ceil(8)];
+    let mut leaves = MerkleFrontier::default();
+
+    for (index, item) in batch.items.iter().enumerate() {
+        // Each item has its own public key
+        if S::verify(&item.public_key, item.epoch, &item.message, &item.signature) {
+            verified_count += 1;
+            leaves.push(item_leaf(item));
+        } else {
+            failed[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    GuestOutput {
+        verified_count,
+        input_digest,
+        verified_root: leaves.root(),
+        failed,
+    }
+}
+
+/// Verifies every signature in a single-key batch against its shared key.
+///
+/// This is exactly what [`verify_aggregation_single_key`] executes inside the
+/// zkVM.
+pub fn verify_single_key_batch(batch: &SingleKeyBatch) -> GuestOutput {
+    let mut verified_count: u32 = 0;
+    let mut failed = vec![0u8; batch.items.len().div_ceil(8)];
+    let mut leaves = MerkleFrontier::default();
+    let fingerprint = key_fingerprint(&batch.public_key);
+
+    for (index, item) in batch.items.iter().enumerate() {
+        if XMSSSignature::verify(
+            &batch.public_key,
+            item.epoch,
+            &item.message,
+            &item.signature,
+        ) {
+            verified_count += 1;
+            leaves.push(verified_leaf(&item.message, item.epoch, &fingerprint));
+        } else {
+            failed[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    GuestOutput {
+        verified_count,
+        input_digest: single_key_input_digest(batch),
+        verified_root: leaves.root(),
+        failed,
+    }
+}
+
+/// Verifies every signature in a batch of instantiation `S`.
+///
+/// This is exactly what the instantiation's entry point (e.g.
+/// [`verify_aggregation_w2`]) executes inside the zkVM. The output has the
+/// same shape as [`verify_batch`]'s, with [`scheme_input_digest`] as the
+/// input digest.
+pub fn verify_scheme_batch<S: Instantiation>(batch: &SchemeBatch<S>) -> GuestOutput {
+    verify_items(batch, scheme_input_digest(batch))
+}
+
+/// Verifies every signature in a key-table batch against its table key.
+///
+/// This is exactly what [`verify_aggregation_key_table`] executes inside the
+/// zkVM. Leaves use the table key's fingerprint, so the verified root equals
+/// that of the same batch with one key per item. Panics if a key index is
+/// out of range.
+pub fn verify_key_table_batch(batch: &KeyTableBatch) -> GuestOutput {
+    let mut verified_count: u32 = 0;
+    let mut failed = vec![0u8; batch.items.len().div_ceil(8)];
+    let mut leaves = MerkleFrontier::default();
+    let fingerprints: Vec<[u8; 32]> = batch.keys.iter().map(key_fingerprint).collect();
+
+    for (index, item) in batch.items.iter().enumerate() {
+        let key_index = item.key_index as usize;
+        if XMSSSignature::verify(
+            &batch.keys[key_index],
+            item.epoch,
+            &item.message,
+            &item.signature,
+        ) {
+            verified_count += 1;
+            leaves.push(verified_leaf(
+                &item.message,
+                item.epoch,
+                &fingerprints[key_index],
+            ));
+        } else {
+            failed[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    GuestOutput {
+        verified_count,
+        input_digest: key_table_input_digest(batch),
+        verified_root: leaves.root(),
+        failed,
+    }
+}
+
+/// Index of the first item of a key-table batch whose key index is out of
+/// range, if any.
+pub fn first_unknown_key(batch: &KeyTableBatch) -> Option<usize> {
+    batch
+        .items
+        .iter()
+        .position(|item| item.key_index as usize >= batch.keys.len())
+}
+
+/// Index of the first item of a key-table batch whose (public key, epoch)
+/// pair already occurred earlier, if any.
+///
+/// Keys are compared by their encoding rather than their index, so a key
+/// listed twice in the table cannot be used to reuse an epoch. Items with an
+/// out-of-range key index are skipped; see [`first_unknown_key`].
+pub fn first_duplicate_key_table_pair(batch: &KeyTableBatch) -> Option<usize> {
+    let fingerprints: Vec<[u8; 32]> = batch.keys.iter().map(key_fingerprint).collect();
+    let mut seen = BTreeSet::new();
+    batch.items.iter().position(|item| {
+        fingerprints
+            .get(item.key_index as usize)
+            .is_some_and(|fingerprint| !seen.insert((*fingerprint, item.epoch)))
+    })
+}
+
+/// Returns `true` if at least `min_valid` signatures in the batch verify.
+///
+/// Stops as soon as the outcome is decided: once `min_valid` signatures
+/// verified, or once too few items remain to reach it.
+pub fn meets_threshold(batch: &AggregationBatch, min_valid: u32) -> bool {
+    let mut valid: usize = 0;
+    let min_valid = min_valid as usize;
+    for (index, item) in batch.items.iter().enumerate() {
+        if valid >= min_valid {
+            return true;
+        }
+        if valid + (batch.items.len() - index) < min_valid {
+            return false;
+        }
+        if XMSSSignature::verify(&item.public_key, item.epoch, &item.message, &item.signature) {
+            valid += 1;
+        }
+    }
+    valid >= min_valid
+}
+
+/// Checks the batch against `min_valid`.
+///
+/// This is exactly what [`verify_aggregation_threshold`] executes inside the
+/// zkVM.
+pub fn verify_threshold_batch(batch: &AggregationBatch, min_valid: u32) -> ThresholdOutput {
+    ThresholdOutput {
+        met: meets_threshold(batch, min_valid),
+        min_valid,
+        input_digest: input_digest(batch),
+    }
+}
+
+/// Counts the verified items of each signer.
+///
+/// This is exactly what [`verify_aggregation_tally`] executes inside the
+/// zkVM.
+pub fn verify_tally_batch(batch: &AggregationBatch) -> TallyOutput {
+    let mut verified_count: u32 = 0;
+    let mut counts = BTreeMap::new();
+    for item in &batch.items {
+        if XMSSSignature::verify(&item.public_key, item.epoch, &item.message, &item.signature) {
+            verified_count += 1;
+            *counts.entry(key_fingerprint(&item.public_key)).or_insert(0) += 1;
+        }
+    }
+
+    TallyOutput {
+        verified_count,
+        input_digest: input_digest(batch),
+        tally: counts
+            .into_iter()
+            .map(|(fingerprint, count)| SignerTally { fingerprint, count })
+            .collect(),
+    }
+}
+
+/// Index of the first item whose epoch already occurred earlier in a
+/// single-key batch, if any.
+pub fn first_duplicate_epoch(batch: &SingleKeyBatch) -> Option<usize> {
+    let mut seen = BTreeSet::new();
+    batch.items.iter().position(|item| !seen.insert(item.epoch))
+}
+
+/// Index of the first item whose signature does not verify, if any.
+///
+/// This is the check [`verify_aggregation_strict`] executes inside the zkVM.
+pub fn first_invalid(batch: &AggregationBatch) -> Option<usize> {
+    batch.items.iter().position(|item| {
+        !XMSSSignature::verify(&item.public_key, item.epoch, &item.message, &item.signature)
+    })
+}
+
+/// Index of the first item whose (public key, epoch) pair already occurred
+/// earlier in a batch of any instantiation, if any.
+///
+/// Public keys are compared by the [`key_fingerprint`] of their pinned
+/// encoding, so the result does not depend on how the host serialized them,
+/// and each seen pair is stored in 36 bytes rather than as a copy of the key.
+pub fn first_duplicate<S: SignatureScheme>(batch: &SchemeBatch<S>) -> Option<usize> {
+    let mut seen = BTreeSet::new();
+    batch
+        .items
+        .iter()
+        .position(|item| !seen.insert((key_fingerprint(&item.public_key), item.epoch)))
+}
+
+/// Panics if a (public key, epoch) pair occurs twice.
+///
+/// Reusing an XMSS epoch breaks the scheme's security, and the host's
+/// validation is not part of the proof, so every entry point enforces
+/// uniqueness itself: a proof of a non-panicking run attests that no pair
+/// was reused.
+fn assert_unique<S: SignatureScheme>(batch: &SchemeBatch<S>) {
+    if let Some(index) = first_duplicate(batch) {
+        panic!(
+            "item {} reuses the (public key, epoch) pair of an earlier item",
+            index
+        );
+    }
 }
 
 /// Verify aggregated signature batch in zkVM
@@ -38,7 +935,10 @@ pub struct AggregationBatch {
 /// includes its own public key. This enables multi-key aggregation where
 /// signatures from different keys can be batched together.
 ///
-/// Returns the count of successfully verified signatures
+/// Returns the count of successfully verified signatures, the digest of the
+/// input batch, the Merkle root of the verified items and a bitmap of the
+/// items that failed. Panics if a (public key, epoch) pair is reused.
+///
 // Resource hints stay power-of-two sized but far tighter than the previous defaults.
 // Keeping memory_size down prevents Dory from allocating multi-GB prover polynomials.
 #[jolt::provable(
@@ -47,22 +947,252 @@ pub struct AggregationBatch {
     max_input_size = 4_194_304,
     max_trace_length = 33_554_432
 )]
-fn verify_aggregation(batch: AggregationBatch) -> u32 {
-    let mut verified_count: u32 = 0;
+fn verify_aggregation(batch: AggregationBatch) -> GuestOutput {
+    verify_aggregation_checked(&batch)
+}
 
-    for item in batch.items {
-        // Each item has its own public key
-        let is_valid = SIGWinternitzLifetime18W1::verify(
-            &item.public_key,
-            item.epoch,
-            &item.message,
-            &item.signature,
-        );
+/// Verify that every signature in the batch is valid, in zkVM
+///
+/// All-or-nothing variant of [`verify_aggregation`]: the guest panics at the
+/// first invalid signature or reused (public key, epoch) pair, so a proof of
+/// a non-panicking run attests that the whole batch is valid. Returns the
+/// digest of the input batch.
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 8_388_608,
+    max_input_size = 4_194_304,
+    max_trace_length = 33_554_432
+)]
+fn verify_aggregation_strict(batch: AggregationBatch) -> [u8; 32] {
+    assert_unique(&batch);
+    if let Some(index) = first_invalid(&batch) {
+        panic!("signature of item {} is invalid", index);
+    }
+    input_digest(&batch)
+}
 
-        if is_valid {
-            verified_count += 1;
-        }
+/// Verify a single-signer batch in zkVM
+///
+/// Same as [`verify_aggregation`], but the batch carries its public key once
+/// instead of per item. Panics if an epoch is reused. Returns the same output
+/// as [`verify_aggregation`]
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 8_388_608,
+    max_input_size = 4_194_304,
+    max_trace_length = 33_554_432
+)]
+fn verify_aggregation_single_key(batch: SingleKeyBatch) -> GuestOutput {
+    if let Some(index) = first_duplicate_epoch(&batch) {
+        panic!("item {} reuses the epoch of an earlier item", index);
     }
+    verify_single_key_batch(&batch)
+}
 
-    verified_count
+/// Verify that at least `min_valid` signatures in the batch are valid, in zkVM
+///
+/// Threshold variant of [`verify_aggregation`] for committee and quorum
+/// attestations: the output says whether the threshold was met, without the
+/// verified count or failure bitmap. Panics if a (public key, epoch) pair is
+/// reused.
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 8_388_608,
+    max_input_size = 4_194_304,
+    max_trace_length = 33_554_432
+)]
+fn verify_aggregation_threshold(batch: AggregationBatch, min_valid: u32) -> ThresholdOutput {
+    assert_unique(&batch);
+    verify_threshold_batch(&batch, min_valid)
+}
+
+/// Verify aggregated signature batch in zkVM, counting per signer
+///
+/// Outputs the number of verified signatures of each public key alongside
+/// the total. Panics if a (public key, epoch) pair is reused.
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 8_388_608,
+    max_input_size = 4_194_304,
+    max_trace_length = 33_554_432
+)]
+fn verify_aggregation_tally(batch: AggregationBatch) -> TallyOutput {
+    assert_unique(&batch);
+    verify_tally_batch(&batch)
+}
+
+/// Verify a batch of W2 signatures in zkVM
+///
+/// Same as [`verify_aggregation`] for the Winternitz parameter 2
+/// instantiation, whose signatures are smaller than W1's at a higher
+/// verification cost. Panics if a (public key, epoch) pair is reused.
+#[cfg(feature = "w2-lifetime18")]
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 8_388_608,
+    max_input_size = 4_194_304,
+    max_trace_length = 33_554_432
+)]
+fn verify_aggregation_w2(batch: SchemeBatch<XMSSSignatureW2>) -> GuestOutput {
+    assert_unique(&batch);
+    verify_scheme_batch(&batch)
+}
+
+/// Verify a batch of W4 signatures in zkVM
+///
+/// Same as [`verify_aggregation_w2`] for the Winternitz parameter 4
+/// instantiation.
+#[cfg(feature = "w4-lifetime18")]
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 8_388_608,
+    max_input_size = 4_194_304,
+    max_trace_length = 33_554_432
+)]
+fn verify_aggregation_w4(batch: SchemeBatch<XMSSSignatureW4>) -> GuestOutput {
+    assert_unique(&batch);
+    verify_scheme_batch(&batch)
+}
+
+/// Verify a batch of W8 signatures in zkVM
+///
+/// Same as [`verify_aggregation_w2`] for the Winternitz parameter 8
+/// instantiation, which has the smallest signatures and the most hashing
+/// per verification, so the fewest signatures fit one trace.
+#[cfg(feature = "w8-lifetime18")]
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 8_388_608,
+    max_input_size = 4_194_304,
+    max_trace_length = 33_554_432
+)]
+fn verify_aggregation_w8(batch: SchemeBatch<XMSSSignatureW8>) -> GuestOutput {
+    assert_unique(&batch);
+    verify_scheme_batch(&batch)
+}
+
+/// Verify a batch of lifetime 2^20 signatures in zkVM
+///
+/// Same as [`verify_aggregation`] for keys with a lifetime of 2^20 epochs;
+/// the two extra Merkle levels cost a few hashes per signature.
+#[cfg(feature = "w1-lifetime20")]
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 8_388_608,
+    max_input_size = 4_194_304,
+    max_trace_length = 33_554_432
+)]
+fn verify_aggregation_lifetime_20(batch: SchemeBatch<XMSSSignatureLifetime20>) -> GuestOutput {
+    assert_unique(&batch);
+    verify_scheme_batch(&batch)
+}
+
+/// Verify a batch of lifetime 2^32 signatures in zkVM
+///
+/// Same as [`verify_aggregation`] for long-lived validator keys. Their
+/// signatures carry a 32-level authentication path and 64 chains, so memory,
+/// input and trace limits are doubled over the lifetime 2^18 entry points.
+#[cfg(feature = "lifetime32")]
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 16_777_216,
+    max_input_size = 8_388_608,
+    max_trace_length = 67_108_864
+)]
+fn verify_aggregation_lifetime_32(batch: SchemeBatch<XMSSSignatureLifetime32>) -> GuestOutput {
+    assert_unique(&batch);
+    verify_scheme_batch(&batch)
+}
+
+/// Verify a batch of SHA-3 signatures in zkVM
+///
+/// Same as [`verify_aggregation`] for signers that must use standard hashes.
+/// SHA-3 is not native to the zkVM and every permutation is executed in
+/// software, so the trace limit is doubled.
+#[cfg(feature = "sha3")]
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 8_388_608,
+    max_input_size = 4_194_304,
+    max_trace_length = 67_108_864
+)]
+fn verify_aggregation_sha3(batch: SchemeBatch<XMSSSignatureSha3>) -> GuestOutput {
+    assert_unique(&batch);
+    verify_scheme_batch(&batch)
+}
+
+/// Verify a batch with a deduplicated key table in zkVM
+///
+/// Same as [`verify_aggregation`], but each distinct public key is carried
+/// once and items refer to it by index. Panics if a key index is out of range
+/// or a (public key, epoch) pair is reused. The verified root matches that of
+/// [`verify_aggregation`] on the expanded batch.
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 8_388_608,
+    max_input_size = 4_194_304,
+    max_trace_length = 33_554_432
+)]
+fn verify_aggregation_key_table(batch: KeyTableBatch) -> GuestOutput {
+    if let Some(index) = first_unknown_key(&batch) {
+        panic!("item {} refers to a key outside the table", index);
+    }
+    if let Some(index) = first_duplicate_key_table_pair(&batch) {
+        panic!(
+            "item {} reuses the (public key, epoch) pair of an earlier item",
+            index
+        );
+    }
+    verify_key_table_batch(&batch)
+}
+
+/// Panics unless `batch` fits a tier of `capacity` items, then runs
+/// [`verify_aggregation`]'s checks.
+fn verify_tier(batch: &AggregationBatch, capacity: usize) -> GuestOutput {
+    assert!(
+        batch.items.len() <= capacity,
+        "batch of {} items exceeds the tier capacity of {}",
+        batch.items.len(),
+        capacity
+    );
+    verify_aggregation_checked(batch)
+}
+
+// The tiered entry points below budget 2^17 cycles and 8 KiB of input per
+// item, with memory at twice the input size (at least 1 MiB); the small tier
+// doubles its trace for fixed overhead. Memory grows with the tier because
+// the whole batch is deserialized before the entry point runs; only the
+// verification loop's own allocations are independent of the batch size.
+
+/// [`verify_aggregation`] for batches of at most 16 items
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 1_048_576,
+    max_input_size = 131_072,
+    max_trace_length = 4_194_304
+)]
+fn verify_aggregation_small(batch: AggregationBatch) -> GuestOutput {
+    verify_tier(&batch, TIER_CAPACITIES[0])
+}
+
+/// [`verify_aggregation`] for batches of at most 256 items
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 4_194_304,
+    max_input_size = 2_097_152,
+    max_trace_length = 33_554_432
+)]
+fn verify_aggregation_medium(batch: AggregationBatch) -> GuestOutput {
+    verify_tier(&batch, TIER_CAPACITIES[1])
+}
+
+/// [`verify_aggregation`] for batches of at most 4096 items
+#[jolt::provable(
+    stack_size = 32_768,
+    memory_size = 67_108_864,
+    max_input_size = 33_554_432,
+    max_trace_length = 536_870_912
+)]
+fn verify_aggregation_large(batch: AggregationBatch) -> GuestOutput {
+    verify_tier(&batch, TIER_CAPACITIES[2])
 }
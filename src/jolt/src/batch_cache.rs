@@ -0,0 +1,46 @@
+//! Header of cached benchmark batches.
+//!
+//! Cached batches used to be bare bincode, so a cache left behind by an
+//! older build, or by a run of another scheme, could decode into a batch the
+//! guest then rejected for no visible reason. Cache files are now `sig-agg`
+//! batch artifacts, written and checked with the library's
+//! `artifact::write_header` and `artifact::read_header`. A cache whose header
+//! does not match is reported and regenerated.
+
+use guest::AggregationBatch;
+use sig_agg::artifact::{self, BATCH, DEFAULT_SCHEME};
+
+/// Encodes `batch` with the cache header.
+pub(crate) fn encode(batch: &AggregationBatch) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    artifact::write_header(&mut bytes, &BATCH, DEFAULT_SCHEME).map_err(|e| e.to_string())?;
+    bincode::serialize_into(&mut bytes, batch).map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// Decodes a batch written by [`encode`], explaining why a cache is stale.
+pub(crate) fn decode(mut bytes: &[u8]) -> Result<AggregationBatch, String> {
+    artifact::read_header(&mut bytes, &BATCH, DEFAULT_SCHEME).map_err(|e| e.to_string())?;
+    bincode::deserialize(bytes).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_stale_caches() {
+        let empty = AggregationBatch { items: vec![] };
+        let bytes = encode(&empty).unwrap();
+        assert!(decode(&bytes).unwrap().items.is_empty());
+
+        // Bare bincode, as written before the header existed
+        let legacy = bincode::serialize(&empty).unwrap();
+        assert!(decode(&legacy).is_err());
+
+        let mut other_scheme = bytes.clone();
+        other_scheme[7 + DEFAULT_SCHEME.len() - 1] = b'2';
+        assert!(matches!(decode(&other_scheme), Err(e) if e.contains("W2")));
+        assert!(decode(&bytes[..8]).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Guest build settings for the benchmark.
+//!
+//! Shared CI machines run several benchmarks at once, and a guest build on
+//! every core with network access can starve the others. The target
+//! directory, cargo job count and offline mode can be set from the command
+//! line or the environment:
+//!
+//! | Flag | Variable | Default |
+//! |---|---|---|
+//! | `--target-dir <dir>` | `GUEST_TARGET_DIR` | `/tmp/jolt-guest-targets` |
+//! | `--jobs <n>` | `GUEST_BUILD_JOBS` | one per CPU |
+//! | `--offline` | `GUEST_BUILD_OFFLINE=1` | online |
+//!
+//! Flags take precedence over variables. Jolt builds the guest with a child
+//! `cargo` process, so jobs and offline mode reach it as `CARGO_BUILD_JOBS`
+//! and `CARGO_NET_OFFLINE`.
+
+use std::env;
+
+const DEFAULT_TARGET_DIR: &str = "/tmp/jolt-guest-targets";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct GuestBuildConfig {
+    pub(crate) target_dir: String,
+    pub(crate) jobs: Option<usize>,
+    pub(crate) offline: bool,
+}
+
+impl GuestBuildConfig {
+    /// Reads the configuration from the process arguments and environment.
+    pub(crate) fn from_args_and_env() -> Self {
+        let args: Vec<String> = env::args().skip(1).collect();
+        Self::parse(&args, |name| env::var(name).ok())
+    }
+
+    fn parse(args: &[String], var: impl Fn(&str) -> Option<String>) -> Self {
+        let flag_value = |flag: &str| {
+            args.windows(2)
+                .find(|pair| pair[0] == flag)
+                .map(|pair| pair[1].clone())
+        };
+
+        let target_dir = flag_value("--target-dir")
+            .or_else(|| var("GUEST_TARGET_DIR"))
+            .unwrap_or_else(|| DEFAULT_TARGET_DIR.to_string());
+
+        let jobs = flag_value("--jobs")
+            .or_else(|| var("GUEST_BUILD_JOBS"))
+            .and_then(|raw| match raw.trim().parse::<usize>() {
+                Ok(jobs) if jobs > 0 => Some(jobs),
+                _ => {
+                    println!(
+                        "Guest build jobs must be a positive integer (got '{}'); using cargo's default",
+                        raw
+                    );
+                    None
+                }
+            });
+
+        let offline = args.iter().any(|arg| arg == "--offline")
+            || matches!(
+                var("GUEST_BUILD_OFFLINE").as_deref().map(str::trim),
+                Some("1" | "true" | "TRUE" | "True")
+            );
+
+        Self {
+            target_dir,
+            jobs,
+            offline,
+        }
+    }
+
+    /// Exports the job count and offline mode for the guest's cargo build.
+    ///
+    /// Changes the process environment, so call it before other threads run.
+    pub(crate) fn apply_to_env(&self) {
+        if let Some(jobs) = self.jobs {
+            env::set_var("CARGO_BUILD_JOBS", jobs.to_string());
+        }
+        if self.offline {
+            env::set_var("CARGO_NET_OFFLINE", "true");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn parse(args: &[&str], vars: &[(&str, &str)]) -> GuestBuildConfig {
+        let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        GuestBuildConfig::parse(&args, |name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn defaults_without_flags_or_variables() {
+        assert_eq!(
+            parse(&[], &[]),
+            GuestBuildConfig {
+                target_dir: DEFAULT_TARGET_DIR.to_string(),
+                jobs: None,
+                offline: false,
+            }
+        );
+    }
+
+    #[test]
+    fn flags_take_precedence_over_variables() {
+        let config = parse(
+            &["--jobs", "2", "--target-dir", "/ci/guest", "--offline"],
+            &[
+                ("GUEST_BUILD_JOBS", "8"),
+                ("GUEST_TARGET_DIR", "/tmp/other"),
+            ],
+        );
+        assert_eq!(config.jobs, Some(2));
+        assert_eq!(config.target_dir, "/ci/guest");
+        assert!(config.offline);
+
+        let config = parse(
+            &[],
+            &[("GUEST_BUILD_JOBS", "8"), ("GUEST_BUILD_OFFLINE", "1")],
+        );
+        assert_eq!(config.jobs, Some(8));
+        assert!(config.offline);
+    }
+
+    #[test]
+    fn ignores_invalid_job_counts() {
+        assert_eq!(parse(&["--jobs", "0"], &[]).jobs, None);
+        assert_eq!(parse(&[], &[("GUEST_BUILD_JOBS", "many")]).jobs, None);
+    }
+}
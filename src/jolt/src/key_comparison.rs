@@ -0,0 +1,126 @@
+//! Side-by-side proving of real and phony key material.
+//!
+//! Phony keys make large benchmark batches cheap to generate, but capacity
+//! planning on phony numbers is only sound if they match what real keys
+//! cost. `--compare-keys` proves a real and a phony batch of the same size
+//! back to back with the same preprocessing and reports how far apart their
+//! proving time and trace length are.
+
+use std::time::Duration;
+
+/// Relative difference, in percent, above which phony results are flagged as
+/// unrepresentative.
+///
+/// Applies to both proving time and trace length. Phony traces should be
+/// nearly identical to real ones, so most of this budget is timing noise.
+pub(crate) const REPRESENTATIVE_TOLERANCE_PERCENT: f64 = 5.0;
+
+/// Measurements of one proven batch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct StrategyRun {
+    pub(crate) prove_time: Duration,
+    pub(crate) trace_length: usize,
+}
+
+/// Real and phony runs over batches of the same size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct KeyComparison {
+    pub(crate) real: StrategyRun,
+    pub(crate) phony: StrategyRun,
+}
+
+impl KeyComparison {
+    /// Phony proving time relative to real, in percent (negative if faster).
+    pub(crate) fn prove_time_delta_percent(&self) -> f64 {
+        delta_percent(
+            self.real.prove_time.as_secs_f64(),
+            self.phony.prove_time.as_secs_f64(),
+        )
+    }
+
+    /// Phony trace length relative to real, in percent.
+    pub(crate) fn trace_length_delta_percent(&self) -> f64 {
+        delta_percent(
+            self.real.trace_length as f64,
+            self.phony.trace_length as f64,
+        )
+    }
+
+    /// Returns `true` if both deltas are within `tolerance_percent`.
+    pub(crate) fn is_representative(&self, tolerance_percent: f64) -> bool {
+        self.prove_time_delta_percent().abs() <= tolerance_percent
+            && self.trace_length_delta_percent().abs() <= tolerance_percent
+    }
+
+    pub(crate) fn print(&self, num_signatures: usize) {
+        println!("Key material comparison ({} signatures):", num_signatures);
+        println!("                      real            phony           delta");
+        println!(
+            "  • Proof Generation:  {:<15} {:<15} {:+.1}%",
+            format!("{:.2?}", self.real.prove_time),
+            format!("{:.2?}", self.phony.prove_time),
+            self.prove_time_delta_percent()
+        );
+        println!(
+            "  • Trace Length:      {:<15} {:<15} {:+.1}%",
+            self.real.trace_length,
+            self.phony.trace_length,
+            self.trace_length_delta_percent()
+        );
+        if self.is_representative(REPRESENTATIVE_TOLERANCE_PERCENT) {
+            println!(
+                "✓ Phony keys are within {:.0}% of real keys",
+                REPRESENTATIVE_TOLERANCE_PERCENT
+            );
+        } else {
+            println!(
+                "⚠ Phony keys differ from real keys by more than {:.0}%; do not use phony results for capacity planning",
+                REPRESENTATIVE_TOLERANCE_PERCENT
+            );
+        }
+    }
+}
+
+fn delta_percent(baseline: f64, value: f64) -> f64 {
+    if baseline == 0.0 {
+        return 0.0;
+    }
+    (value - baseline) / baseline * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comparison(real: (u64, usize), phony: (u64, usize)) -> KeyComparison {
+        KeyComparison {
+            real: StrategyRun {
+                prove_time: Duration::from_millis(real.0),
+                trace_length: real.1,
+            },
+            phony: StrategyRun {
+                prove_time: Duration::from_millis(phony.0),
+                trace_length: phony.1,
+            },
+        }
+    }
+
+    #[test]
+    fn deltas_are_relative_to_real_keys() {
+        let result = comparison((10_000, 1_000_000), (9_000, 1_010_000));
+        assert!((result.prove_time_delta_percent() + 10.0).abs() < 1e-9);
+        assert!((result.trace_length_delta_percent() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn flags_unrepresentative_phony_runs() {
+        assert!(comparison((10_000, 1_000), (10_200, 1_010)).is_representative(5.0));
+        assert!(!comparison((10_000, 1_000), (10_000, 800)).is_representative(5.0));
+        assert!(!comparison((10_000, 1_000), (20_000, 1_000)).is_representative(5.0));
+    }
+
+    #[test]
+    fn empty_baseline_has_no_delta() {
+        assert_eq!(delta_percent(0.0, 5.0), 0.0);
+    }
+}
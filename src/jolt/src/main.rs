@@ -2,10 +2,15 @@ use std::{
     env, fs, io,
     io::Read,
     path::{Path, PathBuf},
-    time::{Duration, Instant, UNIX_EPOCH},
+    time::Instant,
 };
 
+mod batch_cache;
+mod guest_build;
+mod key_comparison;
 mod phony_xmss;
+mod scheme_bench;
+mod trace_calibration;
 
 use hashsig::{
     signature::{
@@ -14,6 +19,7 @@ use hashsig::{
     },
     MESSAGE_LENGTH,
 };
+use rand::{rngs::StdRng, SeedableRng};
 use rayon::{iter::IntoParallelIterator, prelude::*};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -54,6 +60,42 @@ fn benchmark_batch_size() -> usize {
     }
 }
 
+/// Reads `PHONY_TRACE_TARGET_PERCENT`, the share of the guest's
+/// `max_trace_length` the phony batch should be calibrated to.
+fn benchmark_trace_target() -> Option<u32> {
+    let raw = env::var("PHONY_TRACE_TARGET_PERCENT").ok()?;
+    match raw.trim().parse::<u32>() {
+        Ok(percent) if percent > 0 => Some(percent),
+        _ => {
+            println!(
+                "PHONY_TRACE_TARGET_PERCENT must be a positive integer (got '{}'); ignoring",
+                raw
+            );
+            None
+        }
+    }
+}
+
+/// Picks the phony batch size whose trace is closest to, without exceeding,
+/// `percent` of the guest's trace limit.
+fn calibrated_batch_size(percent: u32) -> usize {
+    let target = target_trace_length(percent, GUEST_MAX_TRACE_LENGTH);
+    println!(
+        "Calibrating phony batch to {}% of max trace length ({} cycles)...",
+        percent, target
+    );
+    let start = Instant::now();
+    let size = calibrate_batch_size(target, |n| {
+        guest::analyze_verify_aggregation(generate_phony_batch(n)).trace_len()
+    });
+    println!(
+        "Calibrated batch size: {} signatures (in {:?})",
+        size,
+        start.elapsed()
+    );
+    size
+}
+
 fn benchmark_key_strategy() -> KeyMaterialStrategy {
     let cli_requests_phony = env::args().skip(1).any(|arg| arg == "--phony-keys");
     if cli_requests_phony {
@@ -68,6 +110,73 @@ fn benchmark_key_strategy() -> KeyMaterialStrategy {
     }
 }
 
+/// Key generation RNG: seeded from `BENCHMARK_SEED` for reproducible real
+/// keys, otherwise from OS entropy.
+fn benchmark_rng() -> StdRng {
+    let Ok(raw) = env::var("BENCHMARK_SEED") else {
+        return StdRng::from_os_rng();
+    };
+    match raw.trim().parse::<u64>() {
+        Ok(seed) => {
+            println!("Using BENCHMARK_SEED={} for key generation", seed);
+            StdRng::seed_from_u64(seed)
+        }
+        Err(_) => {
+            println!(
+                "BENCHMARK_SEED must be an unsigned integer (got '{}'); using OS entropy",
+                raw
+            );
+            StdRng::from_os_rng()
+        }
+    }
+}
+
+/// `--cross-check` (or `CROSS_CHECK=1`) re-runs the guest logic natively after
+/// proving and aborts if the public outputs differ.
+fn cross_check_requested() -> bool {
+    if env::args().skip(1).any(|arg| arg == "--cross-check") {
+        return true;
+    }
+
+    matches!(
+        env::var("CROSS_CHECK").as_deref().map(str::trim),
+        Ok("1" | "true" | "TRUE" | "True")
+    )
+}
+
+/// `--compare-keys` (or `COMPARE_KEYS=1`) proves a real and a phony batch of
+/// the same size back to back and reports the difference instead of running
+/// the full benchmark.
+fn compare_keys_requested() -> bool {
+    if env::args().skip(1).any(|arg| arg == "--compare-keys") {
+        return true;
+    }
+
+    matches!(
+        env::var("COMPARE_KEYS").as_deref().map(str::trim),
+        Ok("1" | "true" | "TRUE" | "True")
+    )
+}
+
+/// Proves `batch` and records the proving time and trace length.
+fn measure_strategy(
+    prove: &impl Fn(AggregationBatch) -> (GuestOutput, jolt_sdk::RV64IMACProof, jolt_sdk::JoltDevice),
+    batch: AggregationBatch,
+) -> StrategyRun {
+    let start = Instant::now();
+    let (_, proof, _) = prove(batch);
+    StrategyRun {
+        prove_time: start.elapsed(),
+        trace_length: proof.trace_length,
+    }
+}
+
+/// Native backend for cross-checking: the guest's own verification loop,
+/// executed on the host without proving.
+fn native_output(batch: &AggregationBatch) -> GuestOutput {
+    guest::verify_batch(batch)
+}
+
 fn cache_file_path(num_signatures: usize, strategy: KeyMaterialStrategy) -> String {
     let cache_dir = "./tmp";
     let label = strategy_label(strategy);
@@ -92,7 +201,7 @@ fn deterministic_message(index: usize) -> [u8; MESSAGE_LENGTH] {
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 struct PcsCacheMetadata {
     guest_hash: [u8; 32],
-    urs_timestamp: u64,
+    urs_digest: [u8; 32],
     strategy: KeyMaterialStrategy,
 }
 
@@ -110,8 +219,149 @@ struct PcsCachePlan {
 }
 
 // Use the guest types directly to avoid duplication
-use guest::{AggregationBatch, VerificationItem};
+use guest::{AggregationBatch, GuestOutput, VerificationItem};
+use guest_build::GuestBuildConfig;
+use key_comparison::{KeyComparison, StrategyRun};
 use phony_xmss::generate_phony_item;
+use scheme_bench::{BenchScheme, SchemeRun};
+use trace_calibration::{calibrate_batch_size, target_trace_length, GUEST_MAX_TRACE_LENGTH};
+
+/// Batch of `num_signatures` items signed under `S` with one real key.
+fn generate_scheme_batch<S: SignatureScheme>(num_signatures: usize) -> guest::SchemeBatch<S> {
+    let mut rng = benchmark_rng();
+    let (pk, sk) = S::key_gen(&mut rng, 0, num_signatures);
+    let pk_bytes = bincode::serialize(&pk).expect("Failed to serialize public key");
+    let items = (0..num_signatures)
+        .map(|i| {
+            let epoch = i as u32;
+            let message = deterministic_message(i);
+            guest::SchemeItem {
+                message,
+                epoch,
+                signature: S::sign(&sk, epoch, &message).expect("Signing failed"),
+                public_key: bincode::deserialize(&pk_bytes)
+                    .expect("Failed to deserialize public key"),
+            }
+        })
+        .collect();
+    guest::SchemeBatch { items }
+}
+
+/// Signs a batch under `$signature`, then compiles, proves and verifies it
+/// with the scheme's entry point. Jolt generates separate functions per entry
+/// point, hence a macro rather than a generic function.
+macro_rules! run_scheme_benchmark {
+    (
+        $scheme:expr,
+        $signature:ty,
+        $num_signatures:expr,
+        $target_dir:expr,
+        $compile:ident,
+        $preprocess:ident,
+        $verifier_preprocessing:ident,
+        $build_prover:ident,
+        $build_verifier:ident
+    ) => {{
+        println!(
+            "Signing {} items under {}...",
+            $num_signatures,
+            $scheme.name()
+        );
+        let batch: guest::SchemeBatch<$signature> = generate_scheme_batch($num_signatures);
+        let signature_bytes = bincode::serialize(&batch.items[0].signature)
+            .expect("Failed to serialize signature")
+            .len();
+        let batch_for_verify: guest::SchemeBatch<$signature> =
+            guest::decode_batch(&guest::encode_batch(&batch))
+                .expect("failed to decode batch for verifier");
+
+        println!("Compiling and preprocessing {}...", $scheme.entry_point());
+        let mut program = guest::$compile($target_dir);
+        let prover_preprocessing = guest::$preprocess(&mut program);
+        let verifier_preprocessing = guest::$verifier_preprocessing(&prover_preprocessing);
+        let prove = guest::$build_prover(program, prover_preprocessing);
+        let verify = guest::$build_verifier(verifier_preprocessing);
+
+        println!("Proof generation in progress...");
+        let start_prove = Instant::now();
+        let (output, proof, program_io) = prove(batch);
+        let prove_time = start_prove.elapsed();
+        let trace_length = proof.trace_length;
+        let verified_count = output.verified_count;
+
+        let start_verify = Instant::now();
+        let valid = verify(batch_for_verify, output, program_io.panic, proof);
+        SchemeRun {
+            scheme: $scheme,
+            num_signatures: $num_signatures,
+            verified_count,
+            signature_bytes,
+            prove_time,
+            verify_time: start_verify.elapsed(),
+            trace_length,
+            valid,
+        }
+    }};
+}
+
+/// Proves a batch of a non-default scheme and prints its measurements.
+fn run_scheme_benchmark(
+    scheme: BenchScheme,
+    num_signatures: usize,
+    build_config: &GuestBuildConfig,
+) {
+    let target_dir = &build_config.target_dir;
+    let run = match scheme {
+        BenchScheme::W1 => unreachable!("W1 runs the full benchmark"),
+        BenchScheme::W2 => run_scheme_benchmark!(
+            scheme,
+            guest::XMSSSignatureW2,
+            num_signatures,
+            target_dir,
+            compile_verify_aggregation_w2,
+            preprocess_prover_verify_aggregation_w2,
+            verifier_preprocessing_from_prover_verify_aggregation_w2,
+            build_prover_verify_aggregation_w2,
+            build_verifier_verify_aggregation_w2
+        ),
+        BenchScheme::W4 => run_scheme_benchmark!(
+            scheme,
+            guest::XMSSSignatureW4,
+            num_signatures,
+            target_dir,
+            compile_verify_aggregation_w4,
+            preprocess_prover_verify_aggregation_w4,
+            verifier_preprocessing_from_prover_verify_aggregation_w4,
+            build_prover_verify_aggregation_w4,
+            build_verifier_verify_aggregation_w4
+        ),
+        BenchScheme::W8 => run_scheme_benchmark!(
+            scheme,
+            guest::XMSSSignatureW8,
+            num_signatures,
+            target_dir,
+            compile_verify_aggregation_w8,
+            preprocess_prover_verify_aggregation_w8,
+            verifier_preprocessing_from_prover_verify_aggregation_w8,
+            build_prover_verify_aggregation_w8,
+            build_verifier_verify_aggregation_w8
+        ),
+    };
+    println!();
+    run.print();
+}
+
+/// Deterministic phony batch: item `i` uses epoch `i` and seed `i`.
+fn generate_phony_batch(num_signatures: usize) -> AggregationBatch {
+    let items = (0..num_signatures)
+        .into_par_iter()
+        .map(|i| {
+            let mut rng = StdRng::seed_from_u64(i as u64);
+            generate_phony_item(i as u32, deterministic_message(i), &mut rng)
+        })
+        .collect();
+    AggregationBatch { items }
+}
 
 /// Generates or loads cached public key and 100 signatures to be verified.
 fn setup_benchmark_data(num_signatures: usize, strategy: KeyMaterialStrategy) -> AggregationBatch {
@@ -127,7 +377,7 @@ fn setup_benchmark_data(num_signatures: usize, strategy: KeyMaterialStrategy) ->
         match fs::read(&cache_file) {
             Ok(cached_data) => {
                 let payload_len = cached_data.len();
-                match bincode::deserialize::<AggregationBatch>(&cached_data) {
+                match batch_cache::decode(&cached_data) {
                     Ok(data) => {
                         let cached_items = data.items.len();
                         if cached_items == num_signatures {
@@ -149,7 +399,7 @@ fn setup_benchmark_data(num_signatures: usize, strategy: KeyMaterialStrategy) ->
                         }
                     }
                     Err(e) => {
-                        println!("Failed to deserialize cached data: {}, regenerating...", e);
+                        println!("Discarding cached data: {}, regenerating...", e);
                     }
                 }
             }
@@ -165,13 +415,13 @@ fn setup_benchmark_data(num_signatures: usize, strategy: KeyMaterialStrategy) ->
     );
     let start = Instant::now();
 
-    let items: Vec<VerificationItem> = match strategy {
+    let aggregation_batch = match strategy {
         KeyMaterialStrategy::Real => {
-            let mut rng = rand::rng();
+            let mut rng = benchmark_rng();
             let (pk, sk) = SIGWinternitzLifetime18W1::key_gen(&mut rng, 0, num_signatures);
             let pk_bytes = bincode::serialize(&pk).expect("Failed to serialize public key");
 
-            (0..num_signatures)
+            let items = (0..num_signatures)
                 .into_par_iter()
                 .map(|i| {
                     let epoch = i as u32;
@@ -189,18 +439,14 @@ fn setup_benchmark_data(num_signatures: usize, strategy: KeyMaterialStrategy) ->
                         public_key: pk_clone,
                     }
                 })
-                .collect()
+                .collect();
+            AggregationBatch { items }
         }
-        KeyMaterialStrategy::Phony => (0..num_signatures)
-            .into_par_iter()
-            .map(|i| generate_phony_item(i as u32, deterministic_message(i), i as u64))
-            .collect(),
+        KeyMaterialStrategy::Phony => generate_phony_batch(num_signatures),
     };
 
-    let aggregation_batch = AggregationBatch { items };
-
     // Cache the generated data
-    match bincode::serialize(&aggregation_batch) {
+    match batch_cache::encode(&aggregation_batch) {
         Ok(serialized_data) => {
             let payload_len = serialized_data.len();
             println!(
@@ -229,7 +475,7 @@ fn setup_benchmark_data(num_signatures: usize, strategy: KeyMaterialStrategy) ->
 fn build_pcs_cache_plan(strategy: KeyMaterialStrategy) -> io::Result<PcsCachePlan> {
     let metadata = PcsCacheMetadata {
         guest_hash: compute_guest_source_hash()?,
-        urs_timestamp: read_urs_timestamp()?,
+        urs_digest: compute_urs_digest()?,
         strategy,
     };
 
@@ -334,6 +580,10 @@ fn hash_guest_sources(path: &Path, hasher: &mut Sha256) -> io::Result<()> {
     Ok(())
 }
 
+fn hex_digest(digest: &[u8; 32]) -> String {
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
 fn hash_guest_file(path: &Path, hasher: &mut Sha256) -> io::Result<()> {
     let mut file = fs::File::open(path)?;
     let mut buffer = Vec::new();
@@ -342,18 +592,58 @@ fn hash_guest_file(path: &Path, hasher: &mut Sha256) -> io::Result<()> {
     Ok(())
 }
 
-fn read_urs_timestamp() -> io::Result<u64> {
-    let metadata = fs::metadata(URS_FILENAME)?;
-    let modified = metadata.modified()?;
-    let duration = modified
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0));
-    Ok(duration.as_secs())
+/// SHA-256 of the URS file, matching `sig_agg::urs::urs_file_digest`.
+///
+/// Keying the PCS cache on the content digest rather than the modification
+/// time catches a replaced URS even if its timestamp was preserved.
+fn compute_urs_digest() -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    hash_guest_file(Path::new(URS_FILENAME), &mut hasher)?;
+    Ok(hasher.finalize().into())
 }
 
 pub fn main() {
-    let num_signatures = benchmark_batch_size();
-    let key_strategy = benchmark_key_strategy();
+    // Exported before rayon starts worker threads that may read the environment
+    let build_config = GuestBuildConfig::from_args_and_env();
+    build_config.apply_to_env();
+    let trace_target = benchmark_trace_target();
+    let requested_strategy = benchmark_key_strategy();
+    let compare_keys = compare_keys_requested();
+    if cfg!(feature = "fips")
+        && (trace_target.is_some()
+            || compare_keys
+            || requested_strategy == KeyMaterialStrategy::Phony)
+    {
+        eprintln!("Phony keys are not approved under the fips profile; use real keys");
+        std::process::exit(1);
+    }
+    let scheme = BenchScheme::from_args_and_env();
+    if scheme != BenchScheme::W1 {
+        if trace_target.is_some()
+            || compare_keys
+            || requested_strategy == KeyMaterialStrategy::Phony
+        {
+            eprintln!(
+                "Phony keys only exist for W1; {} runs sign with real keys",
+                scheme.name()
+            );
+            std::process::exit(1);
+        }
+        run_scheme_benchmark(scheme, benchmark_batch_size(), &build_config);
+        return;
+    }
+    let cross_check = cross_check_requested();
+    let (num_signatures, key_strategy) = match trace_target {
+        // Calibration relies on deterministic items, so it always uses phony keys
+        Some(percent) => (calibrated_batch_size(percent), KeyMaterialStrategy::Phony),
+        None => (benchmark_batch_size(), requested_strategy),
+    };
+    // A comparison proves the real batch first and then a phony one
+    let key_strategy = if compare_keys {
+        KeyMaterialStrategy::Real
+    } else {
+        key_strategy
+    };
     let use_small_pcs_cache = num_signatures == SMALL_PCS_CACHE_BATCH_SIZE;
     let mut pcs_cache_plan: Option<PcsCachePlan> = None;
     let mut cached_preprocessing: Option<(
@@ -364,6 +654,11 @@ pub fn main() {
     if use_small_pcs_cache {
         match build_pcs_cache_plan(key_strategy) {
             Ok(plan) => {
+                println!(
+                    "URS digest ({}): {}",
+                    URS_FILENAME,
+                    hex_digest(&plan.metadata.urs_digest)
+                );
                 match load_pcs_cache(&plan) {
                     Ok(Some(preprocessing)) => {
                         println!(
@@ -428,10 +723,11 @@ pub fn main() {
         num_signatures - 1
     );
     let verification_data = setup_benchmark_data(num_signatures, key_strategy);
-    let verification_bytes =
-        bincode::serialize(&verification_data).expect("failed to encode batch for prover");
-    let verification_data_for_verify: AggregationBatch =
-        bincode::deserialize(&verification_bytes).expect("failed to decode batch for verifier");
+    let verification_bytes = guest::encode_batch(&verification_data);
+    let verification_data_for_verify =
+        guest::decode_batch(&verification_bytes).expect("failed to decode batch for verifier");
+    let host_input_digest = guest::input_digest(&verification_data_for_verify);
+    println!("Input digest: {}", hex_digest(&host_input_digest));
     println!();
 
     // 2. Jolt Compilation and Preprocessing
@@ -440,8 +736,19 @@ pub fn main() {
     println!("Compiling the guest program (verify_aggregation) to zkVM bytecode...");
     println!("This step is slow on first run but cached for subsequent runs.");
     let start_preprocess = Instant::now();
-    let target_dir = "/tmp/jolt-guest-targets";
-    let mut program = guest::compile_verify_aggregation(target_dir);
+    println!(
+        "Guest build: target dir {}, jobs {}, {}",
+        build_config.target_dir,
+        build_config
+            .jobs
+            .map_or_else(|| "default".to_string(), |jobs| jobs.to_string()),
+        if build_config.offline {
+            "offline"
+        } else {
+            "online"
+        }
+    );
+    let mut program = guest::compile_verify_aggregation(&build_config.target_dir);
 
     let (prover_preprocessing, verifier_preprocessing) =
         if let Some((prover, verifier)) = cached_preprocessing {
@@ -484,6 +791,21 @@ pub fn main() {
         guest::build_verifier_verify_aggregation(verifier_preprocessing);
     println!();
 
+    if compare_keys {
+        println!("Key Material Comparison (real vs phony)");
+        println!("---------------------------------------");
+        println!("Proving the real batch...");
+        let real = measure_strategy(&prove_verify_aggregation, verification_data);
+        println!("Proving a phony batch of the same size...");
+        let phony = measure_strategy(
+            &prove_verify_aggregation,
+            setup_benchmark_data(num_signatures, KeyMaterialStrategy::Phony),
+        );
+        println!();
+        KeyComparison { real, phony }.print(num_signatures);
+        return;
+    }
+
     // 3. Proving Phase
     println!("Phase 3: Proof Generation (Aggregated Verification)");
     println!("----------------------------------------------------");
@@ -494,25 +816,76 @@ pub fn main() {
     println!("The guest program:");
     println!("  1. Receives the aggregation batch as input");
     println!("  2. Verifies each XMSS signature individually");
-    println!("  3. Returns the count of successfully verified signatures");
+    println!("  3. Returns the count of successfully verified signatures, the");
+    println!("     digest of the batch it executed on, a Merkle root of the");
+    println!("     verified items and a bitmap of failures");
     println!("  4. zkVM generates a succinct proof of this computation");
     println!();
     println!("Proof generation in progress (this may take 30-60 seconds)...");
     let start_prove = Instant::now();
-    let (verified_count, proof, program_io) = prove_verify_aggregation(verification_data);
+    let (output, proof, program_io) = prove_verify_aggregation(verification_data);
+    let verified_count = output.verified_count;
     let prove_time = start_prove.elapsed();
     println!();
     println!("✓ zkVM proof generated in {:?}", prove_time);
+    if output.input_digest != host_input_digest {
+        eprintln!(
+            "✗ Guest executed on input {} but the host prepared {}; refusing to continue",
+            hex_digest(&output.input_digest),
+            hex_digest(&host_input_digest)
+        );
+        std::process::exit(1);
+    }
+    println!("✓ Guest input digest matches the host batch");
     println!(
         "✓ Guest program verified {} signatures successfully",
         verified_count
     );
+    let failed_indices: Vec<usize> = (0..num_signatures)
+        .filter(|&index| output.failed[index / 8] & (1 << (index % 8)) != 0)
+        .collect();
+    if !failed_indices.is_empty() {
+        println!("✗ Failed items: {:?}", failed_indices);
+    }
     println!(
         "✓ Proving throughput: {:.2} signatures/second",
         num_signatures as f64 / prove_time.as_secs_f64()
     );
+    // Record the cycles actually used rather than the configured maximum
+    let trace_length = proof.trace_length;
+    println!(
+        "✓ Execution trace: {} cycles ({:.1}% of max_trace_length {})",
+        trace_length,
+        trace_length as f64 / GUEST_MAX_TRACE_LENGTH as f64 * 100.0,
+        GUEST_MAX_TRACE_LENGTH
+    );
     println!();
 
+    if cross_check {
+        println!("Phase 3.1: Cross-check (Jolt vs native)");
+        println!("----------------------------------------");
+        println!("Re-running the guest verification logic natively on the host...");
+        let start_native = Instant::now();
+        let native = native_output(&verification_data_for_verify);
+        println!(
+            "  • Jolt output:   {} (panicked: {})",
+            verified_count, program_io.panic
+        );
+        println!(
+            "  • Native output: {} (in {:?})",
+            native.verified_count,
+            start_native.elapsed()
+        );
+        if program_io.panic || native != output {
+            eprintln!(
+                "✗ Cross-check failed: backends disagree on the public output; refusing to continue"
+            );
+            std::process::exit(1);
+        }
+        println!("✓ Backends agree on the public output");
+        println!();
+    }
+
     // 3.5. Proof Size Measurement
     println!("Phase 3.5: Proof Size Analysis");
     println!("-------------------------------");
@@ -564,9 +937,17 @@ pub fn main() {
     println!("without re-executing the guest program.");
     println!();
     let start_verify = Instant::now();
+    // The verifier checks the proof against the host's digest, so a proof
+    // over any other input is rejected
+    let expected_output = GuestOutput {
+        verified_count,
+        input_digest: host_input_digest,
+        verified_root: output.verified_root,
+        failed: output.failed.clone(),
+    };
     let is_valid = verify_verify_aggregation(
         verification_data_for_verify,
-        verified_count,
+        expected_output,
         program_io.panic,
         proof,
     );
@@ -583,6 +964,10 @@ pub fn main() {
     println!("Batch Configuration:");
     println!("  • Batch Size:        {} signatures", num_signatures);
     println!("  • Verified Count:    {} signatures", verified_count);
+    println!(
+        "  • Trace Length:      {} / {} cycles",
+        trace_length, GUEST_MAX_TRACE_LENGTH
+    );
     println!();
     println!("Performance Metrics:");
     println!("  • Proof Generation:  {:?}", prove_time);
@@ -17,7 +17,7 @@ use p3_koala_bear::{
     default_koalabear_poseidon2_16, default_koalabear_poseidon2_24, KoalaBear, Poseidon2KoalaBear,
 };
 use p3_symmetric::Permutation;
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 const PARAMETER_LEN: usize = 5;
@@ -33,21 +33,21 @@ const NUM_CHAINS: usize = NUM_CHUNKS_W1 + NUM_CHUNKS_CHECKSUM_W1;
 const CHAIN_LENGTH: usize = BASE_W1;
 const LOG_LIFETIME: usize = 32;
 
-pub(crate) fn generate_phony_item(
+/// Builds a phony item whose random parameter, randomness, chain starts and
+/// Merkle siblings are all drawn from `rng`.
+pub(crate) fn generate_phony_item<R: Rng + ?Sized>(
     epoch: u32,
     message: [u8; MESSAGE_LENGTH],
-    seed: u64,
+    rng: &mut R,
 ) -> VerificationItem {
-    let mut rng = StdRng::seed_from_u64(seed);
-
     let parameter: [KoalaBear; PARAMETER_LEN] = rng.random();
     let rho: [KoalaBear; RAND_LEN] = rng.random();
 
     let encoding = winternitz_encode(&parameter, epoch, &rho, &message);
 
-    let (hashes, chain_ends) = build_wots_hashes(&mut rng, &parameter, epoch, &encoding);
+    let (hashes, chain_ends) = build_wots_hashes(rng, &parameter, epoch, &encoding);
 
-    let (co_path, root) = build_merkle_path(&mut rng, &parameter, epoch, &chain_ends);
+    let (co_path, root) = build_merkle_path(rng, &parameter, epoch, &chain_ends);
 
     let public_key = deserialize_public_key(RawPublicKey { root, parameter });
     let signature = deserialize_signature(RawSignature {
@@ -202,8 +202,8 @@ fn decode_to_chunks<const DIMENSION: usize, const BASE: usize, const HASH_LEN: u
     })
 }
 
-fn build_wots_hashes(
-    rng: &mut StdRng,
+fn build_wots_hashes<R: Rng + ?Sized>(
+    rng: &mut R,
     parameter: &[KoalaBear; PARAMETER_LEN],
     epoch: u32,
     encoding: &[u8],
@@ -255,8 +255,8 @@ fn poseidon_chain_hash(
     poseidon_compress::<_, 16, HASH_LEN_FE>(&perm, &combined_input)
 }
 
-fn build_merkle_path(
-    rng: &mut StdRng,
+fn build_merkle_path<R: Rng + ?Sized>(
+    rng: &mut R,
     parameter: &[KoalaBear; PARAMETER_LEN],
     epoch: u32,
     leaf: &[Digest],
@@ -450,6 +450,7 @@ fn poseidon2_24() -> Poseidon2KoalaBear<24> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
 
     fn test_message(tag: u8) -> [u8; MESSAGE_LENGTH] {
         std::array::from_fn(|i| tag.wrapping_add(i as u8))
@@ -457,15 +458,15 @@ mod tests {
 
     #[test]
     fn phony_signature_has_full_path() {
-        let item = generate_phony_item(5, test_message(1), 42);
+        let item = generate_phony_item(5, test_message(1), &mut StdRng::seed_from_u64(42));
         let raw_sig = serialize_signature(&item.signature);
         assert_eq!(raw_sig.path.co_path.len(), LOG_LIFETIME);
     }
 
     #[test]
     fn phony_signature_is_deterministic_per_seed() {
-        let a = generate_phony_item(10, test_message(2), 999);
-        let b = generate_phony_item(10, test_message(2), 999);
+        let a = generate_phony_item(10, test_message(2), &mut StdRng::seed_from_u64(999));
+        let b = generate_phony_item(10, test_message(2), &mut StdRng::seed_from_u64(999));
         assert_eq!(
             bincode::serialize(&a.signature).unwrap(),
             bincode::serialize(&b.signature).unwrap()
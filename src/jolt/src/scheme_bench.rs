@@ -0,0 +1,137 @@
+//! Benchmark selection of the Winternitz instantiation.
+//!
+//! The default run proves `verify_aggregation` over W1 signatures. A larger
+//! Winternitz parameter gives smaller signatures at a higher verification
+//! cost, and deployments choosing one need both sides of that trade.
+//! `--scheme <w1|w2|w4|w8>` (or `BENCHMARK_SCHEME`) proves the matching
+//! entry point instead and reports the signature size next to the proving
+//! cost. Phony key material only exists for W1, so other schemes always sign
+//! with real keys.
+
+use std::{env, time::Duration};
+
+/// Winternitz instantiation the benchmark proves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BenchScheme {
+    W1,
+    W2,
+    W4,
+    W8,
+}
+
+impl BenchScheme {
+    /// Reads the scheme from the process arguments and environment.
+    pub(crate) fn from_args_and_env() -> Self {
+        let args: Vec<String> = env::args().skip(1).collect();
+        Self::parse(&args, |name| env::var(name).ok())
+    }
+
+    fn parse(args: &[String], var: impl Fn(&str) -> Option<String>) -> Self {
+        let Some(raw) = args
+            .windows(2)
+            .find(|pair| pair[0] == "--scheme")
+            .map(|pair| pair[1].clone())
+            .or_else(|| var("BENCHMARK_SCHEME"))
+        else {
+            return Self::W1;
+        };
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "w1" => Self::W1,
+            "w2" => Self::W2,
+            "w4" => Self::W4,
+            "w8" => Self::W8,
+            _ => {
+                println!(
+                    "Scheme must be one of w1, w2, w4 or w8 (got '{}'); using w1",
+                    raw
+                );
+                Self::W1
+            }
+        }
+    }
+
+    /// Name of the `hashsig` instantiation.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::W1 => "SIGWinternitzLifetime18W1",
+            Self::W2 => "SIGWinternitzLifetime18W2",
+            Self::W4 => "SIGWinternitzLifetime18W4",
+            Self::W8 => "SIGWinternitzLifetime18W8",
+        }
+    }
+
+    /// Guest entry point proving batches of this scheme.
+    pub(crate) fn entry_point(self) -> &'static str {
+        match self {
+            Self::W1 => "verify_aggregation",
+            Self::W2 => "verify_aggregation_w2",
+            Self::W4 => "verify_aggregation_w4",
+            Self::W8 => "verify_aggregation_w8",
+        }
+    }
+}
+
+/// Measurements of one proven batch of a non-default scheme.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct SchemeRun {
+    pub(crate) scheme: BenchScheme,
+    pub(crate) num_signatures: usize,
+    pub(crate) verified_count: u32,
+    pub(crate) signature_bytes: usize,
+    pub(crate) prove_time: Duration,
+    pub(crate) verify_time: Duration,
+    pub(crate) trace_length: usize,
+    pub(crate) valid: bool,
+}
+
+impl SchemeRun {
+    pub(crate) fn print(&self) {
+        println!(
+            "Scheme benchmark: {} via {} ({} signatures)",
+            self.scheme.name(),
+            self.scheme.entry_point(),
+            self.num_signatures
+        );
+        println!("  • Verified Count:     {} signatures", self.verified_count);
+        println!("  • Signature Size:     {} bytes", self.signature_bytes);
+        println!("  • Trace Length:       {} cycles", self.trace_length);
+        println!(
+            "  • Cycles / Signature: {}",
+            self.trace_length / self.num_signatures.max(1)
+        );
+        println!("  • Proof Generation:   {:?}", self.prove_time);
+        println!("  • Proof Verification: {:?}", self.verify_time);
+        println!("  • Proof is valid:     {}", self.valid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn parse(args: &[&str], vars: &[(&str, &str)]) -> BenchScheme {
+        let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        BenchScheme::parse(&args, |name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn defaults_to_w1() {
+        assert_eq!(parse(&[], &[]), BenchScheme::W1);
+        assert_eq!(parse(&["--scheme", "w3"], &[]), BenchScheme::W1);
+    }
+
+    #[test]
+    fn flag_takes_precedence_over_variable() {
+        assert_eq!(
+            parse(&["--scheme", "W4"], &[("BENCHMARK_SCHEME", "w8")]),
+            BenchScheme::W4
+        );
+        assert_eq!(parse(&[], &[("BENCHMARK_SCHEME", "w8")]), BenchScheme::W8);
+        assert_eq!(BenchScheme::W2.entry_point(), "verify_aggregation_w2");
+    }
+}
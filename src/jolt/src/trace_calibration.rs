@@ -0,0 +1,130 @@
+//! Phony batches sized to hit a target fraction of the guest trace limit.
+//!
+//! Resource estimation and overflow handling are only interesting near the
+//! boundary, so the benchmark can pick its batch size from a trace-length
+//! target (e.g. 50%, 90% or 101% of `max_trace_length`) instead of a signature
+//! count. Phony items are deterministic per index, so the same target always
+//! yields the same batch.
+
+use std::collections::HashMap;
+
+/// Guest limits generated by `build.rs` from the `#[jolt::provable]`
+/// attributes in `guest/src/lib.rs`.
+#[allow(dead_code)]
+mod guest_limits {
+    pub(crate) struct GuestLimits {
+        pub(crate) stack_size: usize,
+        pub(crate) memory_size: usize,
+        pub(crate) max_input_size: usize,
+        pub(crate) max_trace_length: usize,
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/guest_limits.rs"));
+}
+
+/// `max_trace_length` of the `verify_aggregation` guest.
+pub(crate) const GUEST_MAX_TRACE_LENGTH: usize = guest_limits::VERIFY_AGGREGATION.max_trace_length;
+
+/// Absolute trace length corresponding to `percent` of `max_trace_length`.
+pub(crate) fn target_trace_length(percent: u32, max_trace_length: usize) -> usize {
+    (max_trace_length as u128 * u128::from(percent) / 100) as usize
+}
+
+/// Finds the largest batch size whose trace length does not exceed `target`.
+///
+/// `measure(n)` must return the trace length of the deterministic `n`-item
+/// batch. A first estimate is derived from the 1- and 2-item traces and then
+/// refined one item at a time, so only a handful of batches are traced when
+/// the cost per item is close to constant. At least one item is always
+/// returned, even if a single item already exceeds the target.
+pub(crate) fn calibrate_batch_size(target: usize, measure: impl FnMut(usize) -> usize) -> usize {
+    let mut traces = MeasuredTraces {
+        measure,
+        cache: HashMap::new(),
+    };
+
+    let single = traces.get(1);
+    let per_item = traces.get(2).saturating_sub(single).max(1);
+    let overhead = single.saturating_sub(per_item);
+    let mut size = (target.saturating_sub(overhead) / per_item).max(1);
+
+    while size > 1 && traces.get(size) > target {
+        size -= 1;
+    }
+    while traces.get(size + 1) <= target {
+        size += 1;
+    }
+
+    size
+}
+
+struct MeasuredTraces<F> {
+    measure: F,
+    cache: HashMap<usize, usize>,
+}
+
+impl<F: FnMut(usize) -> usize> MeasuredTraces<F> {
+    fn get(&mut self, size: usize) -> usize {
+        if let Some(&trace) = self.cache.get(&size) {
+            return trace;
+        }
+        let trace = (self.measure)(size);
+        println!("  traced {size}-item phony batch: {trace} cycles");
+        self.cache.insert(size, trace);
+        trace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linear(overhead: usize, per_item: usize) -> impl FnMut(usize) -> usize {
+        move |n| overhead + n * per_item
+    }
+
+    #[test]
+    fn target_is_percentage_of_limit() {
+        assert_eq!(target_trace_length(50, 1000), 500);
+        assert_eq!(target_trace_length(101, 1000), 1010);
+        assert_eq!(
+            target_trace_length(100, GUEST_MAX_TRACE_LENGTH),
+            GUEST_MAX_TRACE_LENGTH
+        );
+    }
+
+    #[test]
+    fn picks_largest_batch_within_target() {
+        // overhead 100, 30 per item: 10 items = 400, 11 items = 430
+        assert_eq!(calibrate_batch_size(400, linear(100, 30)), 10);
+        assert_eq!(calibrate_batch_size(429, linear(100, 30)), 10);
+        assert_eq!(calibrate_batch_size(430, linear(100, 30)), 11);
+    }
+
+    #[test]
+    fn refines_when_cost_is_not_linear() {
+        // Items get more expensive as the batch grows
+        let measure = |n: usize| 100 + n * 30 + n * n;
+        let size = calibrate_batch_size(1_000, measure);
+        assert!(measure(size) <= 1_000);
+        assert!(measure(size + 1) > 1_000);
+    }
+
+    #[test]
+    fn returns_one_item_when_target_is_tiny() {
+        assert_eq!(calibrate_batch_size(10, linear(100, 30)), 1);
+    }
+
+    #[test]
+    fn measures_each_size_once() {
+        let mut calls = Vec::new();
+        calibrate_batch_size(400, |n| {
+            calls.push(n);
+            100 + n * 30
+        });
+        let mut unique = calls.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), calls.len());
+    }
+}
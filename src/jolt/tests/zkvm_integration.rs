@@ -2,6 +2,11 @@
 //!
 //! These tests verify guest program compilation, proof generation,
 //! and proof verification functionality.
+//!
+//! Real-key proving tests are slow and ignored by default; a single-item
+//! phony batch keeps the full prove/verify path in every test run.
+
+use std::{sync::mpsc, thread, time::Duration};
 
 use hashsig::{
     signature::{
@@ -14,6 +19,22 @@ use hashsig::{
 type XMSSSignature = SIGWinternitzLifetime18W1;
 
 use guest::{AggregationBatch, VerificationItem};
+use rand::{rngs::StdRng, SeedableRng};
+
+#[path = "../src/phony_xmss.rs"]
+mod phony_xmss;
+
+/// Upper bound on preprocessing, proving and verifying the single-item
+/// phony batch, so a regression fails the run instead of stalling it. Guest
+/// compilation is excluded; it is cached in the target directory.
+const PHONY_E2E_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Deterministic one-item phony batch.
+fn single_item_phony_batch() -> AggregationBatch {
+    let item =
+        phony_xmss::generate_phony_item(0, [7u8; MESSAGE_LENGTH], &mut StdRng::seed_from_u64(0));
+    AggregationBatch { items: vec![item] }
+}
 
 /// Test: Guest program compilation succeeds
 #[test]
@@ -31,6 +52,43 @@ fn test_guest_compilation() {
     println!("✓ Guest program compiled successfully");
 }
 
+/// Test: End-to-end proving and verification of a single phony item
+#[test]
+fn test_single_phony_item_prove_and_verify() {
+    println!("Testing prove/verify with a 1-item phony batch...");
+
+    let target_dir = "/tmp/jolt-test-phony-e2e";
+    let mut program = guest::compile_verify_aggregation(target_dir);
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let prover_preprocessing = guest::preprocess_prover_verify_aggregation(&mut program);
+        let verifier_preprocessing =
+            guest::verifier_preprocessing_from_prover_verify_aggregation(&prover_preprocessing);
+        let prove_fn = guest::build_prover_verify_aggregation(program, prover_preprocessing);
+        let verify_fn = guest::build_verifier_verify_aggregation(verifier_preprocessing);
+
+        let batch = single_item_phony_batch();
+        let expected_digest = guest::input_digest(&batch);
+        let (output, proof, io) = prove_fn(batch);
+        let is_valid = verify_fn(single_item_phony_batch(), output.clone(), io.panic, proof);
+        sender
+            .send((output, expected_digest, io.panic, is_valid))
+            .expect("test thread is waiting");
+    });
+
+    let (output, expected_digest, panicked, is_valid) = receiver
+        .recv_timeout(PHONY_E2E_TIMEOUT)
+        .expect("Prove/verify failed or exceeded the time bound");
+    assert!(!panicked);
+    assert_eq!(output.verified_count, 1);
+    assert_eq!(output.input_digest, expected_digest);
+    assert_eq!(output.failed, vec![0u8]);
+    assert!(is_valid);
+
+    println!("✓ Phony proof generated and verified");
+}
+
 /// Test: Proof generation for small batch (10 signatures)
 #[test]
 #[ignore] // Slow test (~10-15 seconds)
@@ -70,9 +128,15 @@ fn test_proof_generation() {
     let prover_preprocessing = guest::preprocess_prover_verify_aggregation(&mut program);
     let prove_fn = guest::build_prover_verify_aggregation(program, prover_preprocessing);
 
-    let (verified_count, _proof, _io) = prove_fn(batch);
+    let expected_digest = guest::input_digest(&batch);
+    let expected_root = guest::merkle_root(batch.items.iter().map(guest::item_leaf).collect());
+    let (output, _proof, _io) = prove_fn(batch);
+    let verified_count = output.verified_count;
 
     assert_eq!(verified_count, 10);
+    assert_eq!(output.input_digest, expected_digest);
+    assert_eq!(output.verified_root, expected_root);
+    assert_eq!(output.failed, vec![0u8; 2]);
     println!("✓ Generated proof for {} signatures", verified_count);
 }
 
@@ -122,12 +186,12 @@ fn test_proof_verification() {
     let prove_fn = guest::build_prover_verify_aggregation(program, prover_preprocessing);
     let verify_fn = guest::build_verifier_verify_aggregation(verifier_preprocessing);
 
-    let (verified_count, proof, io) = prove_fn(batch);
-    assert_eq!(verified_count, 10);
+    let (output, proof, io) = prove_fn(batch);
+    assert_eq!(output.verified_count, 10);
 
     let batch_verify = AggregationBatch { items: gen_items() };
 
-    let is_valid = verify_fn(batch_verify, verified_count, io.panic, proof);
+    let is_valid = verify_fn(batch_verify, output, io.panic, proof);
     assert!(is_valid);
 
     println!("✓ Proof verified successfully");
@@ -0,0 +1,203 @@
+//! Batches with a deduplicated public key table.
+//!
+//! Every [`VerificationItem`] carries its own public key, so a signer with
+//! dozens of signatures in a batch has its key serialized into the guest
+//! input dozens of times. A [`KeyTableBatch`] stores each distinct key once
+//! and has items refer to it by index. [`KeyTableBatch::from_batch`] and
+//! [`KeyTableBatch::into_batch`] convert between the two representations;
+//! the [key table encoding](crate::wire::encode_key_table_batch) is the input
+//! of the `verify_aggregation_key_table` guest entry point.
+//!
+//! Keys are compared by their serialized bytes, so two items share a table
+//! entry exactly when the guest would see the same key.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::key_table::KeyTableBatch;
+//! # let batch: sig_agg::AggregationBatch = unimplemented!();
+//!
+//! let table = KeyTableBatch::from_batch(batch).expect("Encoding failed");
+//! println!("{} items from {} signers", table.items.len(), table.keys.len());
+//! let batch = table.into_batch().expect("Invalid key index");
+//! ```
+
+use std::collections::HashMap;
+
+use hashsig::signature::SignatureScheme;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codec::Message,
+    error::AggregationError,
+    keys::PublicKey,
+    types::{AggregationBatch, VerificationItem, XMSSSignature},
+};
+
+/// A verification item whose public key is an entry of the batch's table.
+#[derive(Serialize, Deserialize)]
+pub struct KeyTableItem {
+    /// Message that was signed
+    pub message: Message,
+    /// Epoch the signature was created in
+    pub epoch: u32,
+    /// XMSS signature data
+    pub signature: <XMSSSignature as SignatureScheme>::Signature,
+    /// Index of the signer's key in [`KeyTableBatch::keys`]
+    pub key_index: u32,
+}
+
+impl std::fmt::Debug for KeyTableItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyTableItem")
+            .field("epoch", &self.epoch)
+            .field("key_index", &self.key_index)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Batch in which each distinct public key is stored once.
+#[derive(Serialize, Deserialize)]
+pub struct KeyTableBatch {
+    /// Distinct public keys, in order of first use
+    pub keys: Vec<PublicKey>,
+    /// Items in batch order
+    pub items: Vec<KeyTableItem>,
+}
+
+impl std::fmt::Debug for KeyTableBatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyTableBatch")
+            .field("keys", &self.keys.len())
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl KeyTableBatch {
+    /// Moves each distinct public key of `batch` into the table, in order of
+    /// first use.
+    pub fn from_batch(batch: AggregationBatch) -> Result<Self, AggregationError> {
+        let mut indices: HashMap<Vec<u8>, u32> = HashMap::new();
+        let mut keys = Vec::new();
+        let mut items = Vec::with_capacity(batch.items.len());
+
+        for item in batch.items {
+            let encoded = encode_key(&item.public_key)?;
+            let key_index = if let Some(&key_index) = indices.get(&encoded) {
+                key_index
+            } else {
+                let key_index = u32::try_from(keys.len()).map_err(|_| {
+                    AggregationError::SerializationError {
+                        message: "Key table exceeds u32 entries".to_string(),
+                    }
+                })?;
+                indices.insert(encoded, key_index);
+                keys.push(item.public_key);
+                key_index
+            };
+            items.push(KeyTableItem {
+                message: item.message,
+                epoch: item.epoch,
+                signature: item.signature,
+                key_index,
+            });
+        }
+
+        Ok(Self { keys, items })
+    }
+
+    /// Expands the table back into one public key per item.
+    ///
+    /// Fails with [`AggregationError::UnknownKeyIndex`] if an item refers to
+    /// a key outside the table.
+    pub fn into_batch(self) -> Result<AggregationBatch, AggregationError> {
+        let encoded = self
+            .keys
+            .iter()
+            .map(encode_key)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let items = self
+            .items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let key = encoded.get(item.key_index as usize).ok_or(
+                    AggregationError::UnknownKeyIndex {
+                        index,
+                        key_index: item.key_index,
+                    },
+                )?;
+                Ok(VerificationItem {
+                    message: item.message,
+                    epoch: item.epoch,
+                    signature: item.signature,
+                    public_key: bincode::deserialize(key).map_err(|e| {
+                        AggregationError::SerializationError {
+                            message: format!("Failed to copy public key: {}", e),
+                        }
+                    })?,
+                })
+            })
+            .collect::<Result<_, AggregationError>>()?;
+
+        Ok(AggregationBatch { items })
+    }
+
+    /// Public key of item `index`, if both the item and its key exist.
+    #[must_use]
+    pub fn public_key(&self, index: usize) -> Option<&PublicKey> {
+        let item = self.items.get(index)?;
+        self.keys.get(item.key_index as usize)
+    }
+}
+
+fn encode_key(public_key: &PublicKey) -> Result<Vec<u8>, AggregationError> {
+    bincode::serialize(public_key).map_err(|e| AggregationError::SerializationError {
+        message: format!("Failed to serialize public key: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::signed_item;
+
+    fn create_test_batch(signers: usize, per_signer: u32) -> AggregationBatch {
+        AggregationBatch {
+            items: (0..signers)
+                .flat_map(|signer| (0..per_signer).map(move |epoch| signed_item(signer, epoch)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_deduplicates_keys() {
+        let batch = create_test_batch(2, 3);
+        let original = bincode::serialize(&batch).unwrap();
+
+        let table = KeyTableBatch::from_batch(batch).unwrap();
+        assert_eq!(table.keys.len(), 2);
+        let indices: Vec<u32> = table.items.iter().map(|item| item.key_index).collect();
+        assert_eq!(indices, vec![0, 0, 0, 1, 1, 1]);
+        assert!(table.public_key(4).is_some());
+        assert!(table.public_key(6).is_none());
+
+        let restored = table.into_batch().unwrap();
+        assert_eq!(bincode::serialize(&restored).unwrap(), original);
+    }
+
+    #[test]
+    fn test_rejects_unknown_key_index() {
+        let mut table = KeyTableBatch::from_batch(create_test_batch(1, 2)).unwrap();
+        table.items[1].key_index = 1;
+        assert_eq!(
+            table.into_batch().map(|_| ()),
+            Err(AggregationError::UnknownKeyIndex {
+                index: 1,
+                key_index: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,452 @@
+//! Public key resolution by signer ID.
+//!
+//! Shipping a full XMSS public key with every submission inflates payloads and
+//! lets a submitter pair a signature with any key it likes. With a
+//! [`KeyResolver`], submitters reference a signer ID instead and the collector
+//! attaches the canonical public key from a key directory before aggregation.
+//!
+//! Two resolvers are provided:
+//!
+//! - [`FileKeyResolver`] reads `<signer_id>.pk` files (bincode-encoded public
+//!   keys) from a directory.
+//! - `HttpKeyResolver` (behind the `http` feature) fetches the same encoding
+//!   from `<base_url>/<signer_id>`.
+//!
+//! Keys are generated with [`generate_keypair`], which draws all randomness
+//! from a caller-supplied RNG. Pass a seeded `StdRng` for reproducible tests,
+//! or any [`RngCore`] implementation backed by an HSM or a compliant DRBG.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::keys::{resolve_submissions, FileKeyResolver, SignerSubmission};
+//! use sig_agg::aggregate;
+//! # let submissions: Vec<SignerSubmission> = vec![];
+//!
+//! let resolver = FileKeyResolver::new("./keys");
+//! let items = resolve_submissions(&resolver, submissions)
+//!     .expect("Unknown signer");
+//! let batch = aggregate(items).expect("Aggregation failed");
+//! ```
+
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use hashsig::{MESSAGE_LENGTH, signature::SignatureScheme};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commitment::{SignerBinding, public_key_fingerprint},
+    error::AggregationError,
+    types::{VerificationItem, XMSSSignature},
+};
+
+/// Public key type resolved by a [`KeyResolver`].
+pub type PublicKey = <XMSSSignature as SignatureScheme>::PublicKey;
+
+/// Secret key type produced by [`generate_keypair`].
+pub type SecretKey = <XMSSSignature as SignatureScheme>::SecretKey;
+
+/// Upper bound on the encoded size of a public key accepted from a key
+/// directory; the bincode encoding of an XMSS public key is far smaller.
+pub const MAX_PUBLIC_KEY_BYTES: usize = 4096;
+
+/// Generates a key pair valid for `num_active_epochs` epochs starting at
+/// `activation_epoch`, drawing randomness only from `rng`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rand::{SeedableRng, rngs::StdRng};
+/// use sig_agg::keys::generate_keypair;
+///
+/// // Same seed, same keys: suitable for tests, never for production keys
+/// let mut rng = StdRng::seed_from_u64(7);
+/// let (public_key, secret_key) = generate_keypair(&mut rng, 0, 1 << 10);
+/// ```
+pub fn generate_keypair<R: RngCore>(
+    rng: &mut R,
+    activation_epoch: usize,
+    num_active_epochs: usize,
+) -> (PublicKey, SecretKey) {
+    XMSSSignature::key_gen(rng, activation_epoch, num_active_epochs)
+}
+
+/// Looks up the canonical public key of a signer.
+pub trait KeyResolver {
+    /// Returns the public key registered for `signer_id`.
+    ///
+    /// Returns [`AggregationError::UnknownSigner`] if the directory has no
+    /// key for this signer.
+    fn resolve(&self, signer_id: &str) -> Result<PublicKey, AggregationError>;
+}
+
+/// A signature submitted by signer ID rather than by public key.
+///
+/// Converted into a [`VerificationItem`] by [`resolve_submission`].
+#[derive(Serialize, Deserialize)]
+pub struct SignerSubmission {
+    /// Signer identifier understood by the key directory
+    pub signer_id: String,
+    /// Message that was signed (fixed-length byte array)
+    pub message: [u8; MESSAGE_LENGTH],
+    /// Epoch (XMSS one-time signature index) when signature was created
+    pub epoch: u32,
+    /// XMSS signature data
+    pub signature: <XMSSSignature as SignatureScheme>::Signature,
+}
+
+impl std::fmt::Debug for SignerSubmission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignerSubmission")
+            .field("signer_id", &self.signer_id)
+            .field("epoch", &self.epoch)
+            .field("signature", &"<XMSS Signature>")
+            .finish_non_exhaustive()
+    }
+}
+
+/// Attaches the canonical public key to a submission.
+pub fn resolve_submission<R: KeyResolver + ?Sized>(
+    resolver: &R,
+    submission: SignerSubmission,
+) -> Result<VerificationItem, AggregationError> {
+    let public_key = resolver.resolve(&submission.signer_id)?;
+    Ok(VerificationItem {
+        message: submission.message,
+        epoch: submission.epoch,
+        signature: submission.signature,
+        public_key,
+    })
+}
+
+/// Resolves every submission, failing on the first unknown signer.
+pub fn resolve_submissions<R: KeyResolver + ?Sized>(
+    resolver: &R,
+    submissions: Vec<SignerSubmission>,
+) -> Result<Vec<VerificationItem>, AggregationError> {
+    submissions
+        .into_iter()
+        .map(|submission| resolve_submission(resolver, submission))
+        .collect()
+}
+
+/// Resolves every submission and records which signer each item came from.
+///
+/// The returned bindings are index-aligned with the items and can be passed to
+/// [`batch_commitment_with_signers`](crate::commitment::batch_commitment_with_signers)
+/// so the batch commitment binds signer IDs as well as keys.
+pub fn resolve_submissions_with_bindings<R: KeyResolver + ?Sized>(
+    resolver: &R,
+    submissions: Vec<SignerSubmission>,
+) -> Result<(Vec<VerificationItem>, Vec<SignerBinding>), AggregationError> {
+    let mut items = Vec::with_capacity(submissions.len());
+    let mut bindings = Vec::with_capacity(submissions.len());
+
+    for submission in submissions {
+        let signer_id = submission.signer_id.clone();
+        let item = resolve_submission(resolver, submission)?;
+        bindings.push(SignerBinding {
+            signer_id,
+            fingerprint: public_key_fingerprint(&item.public_key)?,
+        });
+        items.push(item);
+    }
+
+    Ok((items, bindings))
+}
+
+/// Key directory backed by one file per signer.
+///
+/// The key for signer `alice` is read from `<dir>/alice.pk` and must contain
+/// the bincode encoding of the public key. Signer IDs are restricted to ASCII
+/// alphanumerics, `-` and `_` so they cannot escape the directory; other IDs
+/// fail with [`AggregationError::InvalidSignerId`]. Files longer than
+/// [`MAX_PUBLIC_KEY_BYTES`] are rejected without being read in full.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileKeyResolver {
+    dir: PathBuf,
+}
+
+impl FileKeyResolver {
+    /// Creates a resolver reading keys from `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Path at which the key for `signer_id` is expected.
+    pub fn key_path(&self, signer_id: &str) -> Result<PathBuf, AggregationError> {
+        check_signer_id(signer_id)?;
+        Ok(self.dir.join(format!("{}.pk", signer_id)))
+    }
+
+    /// Writes `public_key` as the key for `signer_id`, creating the directory
+    /// if needed.
+    pub fn register(
+        &self,
+        signer_id: &str,
+        public_key: &PublicKey,
+    ) -> Result<(), AggregationError> {
+        let path = self.key_path(signer_id)?;
+        fs::create_dir_all(&self.dir)?;
+        fs::write(path, encode_public_key(public_key)?)?;
+        Ok(())
+    }
+
+    /// Directory the resolver reads from.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl KeyResolver for FileKeyResolver {
+    fn resolve(&self, signer_id: &str) -> Result<PublicKey, AggregationError> {
+        let path = self.key_path(signer_id)?;
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(AggregationError::UnknownSigner {
+                    signer_id: signer_id.to_string(),
+                });
+            }
+            Err(err) => return Err(err.into()),
+        };
+        read_public_key(file, &path.display().to_string())
+    }
+}
+
+/// Key directory served over HTTP.
+///
+/// `GET <base_url>/<signer_id>` must return the bincode encoding of the public
+/// key; a 404 response maps to [`AggregationError::UnknownSigner`]. Bodies
+/// longer than [`MAX_PUBLIC_KEY_BYTES`] are rejected without being read in
+/// full.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone)]
+pub struct HttpKeyResolver {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "http")]
+impl HttpKeyResolver {
+    /// Creates a resolver for the directory at `base_url`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            agent: ureq::Agent::new(),
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl KeyResolver for HttpKeyResolver {
+    fn resolve(&self, signer_id: &str) -> Result<PublicKey, AggregationError> {
+        check_signer_id(signer_id)?;
+        let url = format!("{}/{}", self.base_url, signer_id);
+        let response = match self.agent.get(&url).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(404, _)) => {
+                return Err(AggregationError::UnknownSigner {
+                    signer_id: signer_id.to_string(),
+                });
+            }
+            Err(err) => {
+                return Err(AggregationError::IoError {
+                    message: format!("Key directory request to {} failed: {}", url, err),
+                });
+            }
+        };
+
+        read_public_key(response.into_reader(), &url)
+    }
+}
+
+/// Decodes a public key from `reader`, reading at most one byte past
+/// [`MAX_PUBLIC_KEY_BYTES`] so an oversized `source` is rejected early.
+fn read_public_key(reader: impl Read, source: &str) -> Result<PublicKey, AggregationError> {
+    let mut bytes = Vec::new();
+    reader
+        .take(MAX_PUBLIC_KEY_BYTES as u64 + 1)
+        .read_to_end(&mut bytes)?;
+    if bytes.len() > MAX_PUBLIC_KEY_BYTES {
+        return Err(AggregationError::SerializationError {
+            message: format!(
+                "Public key from {} exceeds {} bytes",
+                source, MAX_PUBLIC_KEY_BYTES
+            ),
+        });
+    }
+    decode_public_key(&bytes)
+}
+
+fn check_signer_id(signer_id: &str) -> Result<(), AggregationError> {
+    let valid = !signer_id.is_empty()
+        && signer_id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    if valid {
+        Ok(())
+    } else {
+        Err(AggregationError::InvalidSignerId {
+            signer_id: signer_id.to_string(),
+        })
+    }
+}
+
+fn encode_public_key(public_key: &PublicKey) -> Result<Vec<u8>, AggregationError> {
+    bincode::serialize(public_key).map_err(|e| AggregationError::SerializationError {
+        message: format!("Failed to serialize public key: {}", e),
+    })
+}
+
+fn decode_public_key(bytes: &[u8]) -> Result<PublicKey, AggregationError> {
+    bincode::deserialize(bytes).map_err(|e| AggregationError::SerializationError {
+        message: format!("Failed to deserialize public key: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn test_resolver(name: &str) -> FileKeyResolver {
+        FileKeyResolver::new(env::temp_dir().join("sig-agg-key-tests").join(name))
+    }
+
+    #[test]
+    fn test_register_and_resolve_submission() {
+        let mut rng = rand::rng();
+        let (pk, sk) = XMSSSignature::key_gen(&mut rng, 0, 10);
+        let resolver = test_resolver("roundtrip");
+        resolver.register("alice", &pk).unwrap();
+
+        let message = [3u8; MESSAGE_LENGTH];
+        let submission = SignerSubmission {
+            signer_id: "alice".to_string(),
+            message,
+            epoch: 3,
+            signature: XMSSSignature::sign(&sk, 3, &message).expect("Signing should succeed"),
+        };
+
+        let item = resolve_submission(&resolver, submission).unwrap();
+        assert_eq!(item.epoch, 3);
+        assert_eq!(
+            bincode::serialize(&item.public_key).unwrap(),
+            bincode::serialize(&pk).unwrap()
+        );
+        assert!(XMSSSignature::verify(
+            &item.public_key,
+            item.epoch,
+            &item.message,
+            &item.signature
+        ));
+    }
+
+    #[test]
+    fn test_generate_keypair_is_seed_deterministic() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let key = |seed| {
+            let (pk, _) = generate_keypair(&mut StdRng::seed_from_u64(seed), 0, 10);
+            bincode::serialize(&pk).unwrap()
+        };
+        assert_eq!(key(1), key(1));
+        assert_ne!(key(1), key(2));
+    }
+
+    #[test]
+    fn test_resolve_with_bindings() {
+        let mut rng = rand::rng();
+        let (pk, sk) = XMSSSignature::key_gen(&mut rng, 0, 10);
+        let resolver = test_resolver("bindings");
+        resolver.register("carol", &pk).unwrap();
+
+        let submissions = (0..2)
+            .map(|epoch| SignerSubmission {
+                signer_id: "carol".to_string(),
+                message: [epoch as u8; MESSAGE_LENGTH],
+                epoch,
+                signature: XMSSSignature::sign(&sk, epoch, &[epoch as u8; MESSAGE_LENGTH])
+                    .expect("Signing should succeed"),
+            })
+            .collect();
+
+        let (items, bindings) = resolve_submissions_with_bindings(&resolver, submissions).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(bindings.len(), 2);
+        assert!(bindings.iter().all(|b| b.signer_id == "carol"));
+        assert_eq!(
+            bindings[0].fingerprint,
+            public_key_fingerprint(&pk).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unknown_signer() {
+        let resolver = test_resolver("unknown");
+        let result = resolver.resolve("nobody");
+
+        assert_eq!(
+            result.err(),
+            Some(AggregationError::UnknownSigner {
+                signer_id: "nobody".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_path_traversal() {
+        let resolver = test_resolver("traversal");
+
+        for signer_id in ["../etc/passwd", "a/b", "", "."] {
+            assert!(matches!(
+                resolver.key_path(signer_id),
+                Err(AggregationError::InvalidSignerId { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_public_key_fits_size_bound() {
+        let encoded = encode_public_key(&crate::test_support::public_key(0)).unwrap();
+        assert!(encoded.len() <= MAX_PUBLIC_KEY_BYTES);
+    }
+
+    #[test]
+    fn test_corrupt_key_file() {
+        let resolver = test_resolver("corrupt");
+        fs::create_dir_all(resolver.dir()).unwrap();
+        fs::write(resolver.key_path("bob").unwrap(), [1u8, 2, 3]).unwrap();
+
+        assert!(matches!(
+            resolver.resolve("bob"),
+            Err(AggregationError::SerializationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_oversized_key_file() {
+        let resolver = test_resolver("oversized");
+        resolver
+            .register("dave", &crate::test_support::public_key(0))
+            .unwrap();
+        let path = resolver.key_path("dave").unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.resize(MAX_PUBLIC_KEY_BYTES + 1, 0);
+        fs::write(&path, bytes).unwrap();
+
+        match resolver.resolve("dave") {
+            Err(AggregationError::SerializationError { message }) => {
+                assert!(message.contains("exceeds"));
+            }
+            other => panic!("Expected an oversized key error, got {:?}", other.err()),
+        }
+    }
+}
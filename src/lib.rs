@@ -32,16 +32,137 @@
 //! # Features
 //!
 //! - Post-quantum signature aggregation (XMSS with Poseidon hashing)
-//! - Batch verification in zkVM (Jolt)
+//! - Batch verification in zkVM (Jolt), counting, all-or-nothing or k-of-n
+//!   threshold
+//! - Per-signer tallies of verified signatures attested by the guest
+//! - Single-key guest variant carrying a shared public key once per batch
+//! - Key-table batches carrying each distinct public key once
+//! - Items, batches and their validation generic over the `hashsig`
+//!   signature scheme, defaulting to Winternitz W1 with lifetime 2^18
+//! - Winternitz W2, W4 and W8 instantiations with their own guest entry
+//!   points, trading signature size for verification cost
+//! - Lifetime 2^20 instantiation for high-frequency signers and lifetime 2^32
+//!   for long-lived validator keys, with enlarged guest limits
+//! - SHA-3 instantiation with its own guest entry point, for ecosystems that
+//!   mandate standard hashes
+//! - Cargo features selecting the instantiations compiled into the library
+//!   and guest
+//! - (public_key, epoch) uniqueness enforced inside the guest, not only on the
+//!   host
 //! - O(N) aggregation complexity
+//! - Per-signer batch splitting for one proof per signer
+//! - Chunked proving with parallel verification that pinpoints unproven chunks
+//! - Append-only proof sets shipped to verifiers as deltas of new chunk proofs
+//! - Incremental batch building for streaming ingestion
 //! - Comprehensive error handling
+//! - Retry policy with exponential backoff for transient failures
+//! - Disk-spooled batch construction with a bounded memory footprint
+//! - Encoded-size accounting and pluggable fee models
+//! - Public key resolution by signer ID (file and HTTP key directories)
+//! - Batch commitments with pluggable hash backends, optionally binding items
+//!   to signer identities or the policies the batch was aggregated under
+//! - Optional encryption at rest for spooled items and cached artifacts
+//! - Verifier selection across deployed guest program versions
+//! - Cost reports comparing aggregated and naive verification, and per-proof
+//!   prover cost accounting
+//! - URS digest pinning to catch prover/verifier parameter mismatches
+//! - `SingleKey` and `MultiKey` aggregation modes with a batch-level shared
+//!   public key and mode-specific validation
+//! - Native execution of the exact guest verification logic
+//! - Seeded sample verification for cheap pre-proving spot checks
+//! - Parallel native verification of whole batches with per-item results,
+//!   for pre-flight checks or deployments that need no proof
+//! - Guest input digests binding proofs to the batch the host submitted
+//! - Per-item failure bitmaps identifying which signatures did not verify
+//! - Merkle roots of verified items for per-item inclusion proofs
+//! - Versioned fixed-layout encoding of guest input and output
+//! - Self-describing artifact headers (kind, format version, scheme and
+//!   crate version) on batches and envelopes, checked before decoding
+//! - Host-side Jolt proving and verification of aggregation proofs
+//! - SP1 and RISC Zero proving backends running the same guest verification
+//!   logic
+//! - `ZkVmBackend` trait (compile, preprocess, prove, verify) implemented by
+//!   every proving backend, with a backend-generic `prove_batch`
+//! - Stage-by-stage progress callbacks during proof generation
+//! - Per-item staging with a hash-chained audit log of admission decisions
+//! - Authenticated withdrawal of staged items until the batch is cut
+//! - Export and import of staging state for migrating a collector
+//! - Reproducible invalidity transcripts for disputed rejections
+//! - Pre-hashing of arbitrary-length messages, reproducible in the guest
+//! - Message codecs (identity, canonical JSON, SSZ, RLP, documents) bound into
+//!   commitments
+//! - Document-set aggregation with a manifest mapping documents to batch items
+//! - Named batch templates recorded in proof metadata
+//! - Size-bounded streaming proof envelopes for constrained verifiers
+//! - Versioned, checksummed proof files
+//! - Proof metadata measured from the actual Jolt run, including proof size
+//! - Verifier-side replay protection for proofs that must be consumed once
+//! - Batch announcements (log or webhook) published before proving
+//! - Markdown and JSON proof summaries for release notes and audit packages
+//! - `fips` build profile restricted to approved primitives, recorded in
+//!   proof metadata
+//! - Serializable compatibility matrix for checking peers before exchanging
+//!   batches or proofs
+//! - Guest resource limits generated from the guest's own attributes
+//! - Tiered guest entry points sized for small, medium and large batches
 //! - Serialization support for zkVM I/O
 
 pub mod aggregator;
+pub mod announce;
+pub mod artifact;
+pub mod audit;
+pub mod backend;
+pub mod batch_verify;
+pub mod chunked;
+pub mod codec;
+pub mod commitment;
+pub mod compat;
+pub mod delta;
+pub mod dispute;
+pub mod documents;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod envelope;
 pub mod error;
+pub mod fee;
+pub mod guest;
+pub mod inclusion;
+pub mod key_table;
+pub mod keys;
+pub mod limits;
+pub mod mode;
+#[cfg(feature = "native")]
+pub mod native;
+pub mod profile;
+pub mod progress;
+pub mod proof_file;
+#[cfg(feature = "jolt")]
+pub mod prover;
+pub mod replay;
+pub mod report;
+pub mod retry;
+#[cfg(feature = "risc0")]
+pub mod risc0;
+pub mod scheme;
+mod serde_hex;
+#[cfg(feature = "sp1")]
+pub mod sp1;
+pub mod spool;
+pub mod spot_check;
+pub mod staging;
+pub mod summary;
+pub mod template;
+#[cfg(test)]
+mod test_support;
 pub mod types;
+pub mod urs;
+pub mod wire;
 
 // Re-export commonly used types and functions for convenience
 pub use aggregator::{aggregate, validate};
+pub use batch_verify::{BatchVerificationResult, verify_batch_native};
 pub use error::AggregationError;
-pub use types::{AggregationBatch, AggregationProof, ProofMetadata, VerificationItem};
+pub use retry::{RetryMetrics, RetryPolicy};
+pub use types::{
+    AggregationBatch, AggregationMode, AggregationProof, ProofMetadata, VerificationItem,
+};
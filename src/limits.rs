@@ -0,0 +1,168 @@
+//! Resource limits of the guest program's entry points.
+//!
+//! Each `#[jolt::provable]` entry point declares its stack, memory, input
+//! and trace limits in its attribute. The build script reads them from the
+//! guest source and generates one [`GuestLimits`] constant per entry point
+//! (e.g. [`VERIFY_AGGREGATION`]), so host-side checks and error messages
+//! always match the guest that is actually deployed.
+//!
+//! Limits sized for the largest batch make every small batch pay for memory
+//! it never touches. The guest therefore also has tiered entry points for
+//! batches of up to 16, 256 and 4096 items, and [`GuestTier::for_batch_size`]
+//! picks the smallest one a batch fits.
+//!
+//! # Examples
+//!
+//! ```
+//! use sig_agg::limits::{self, VERIFY_AGGREGATION};
+//!
+//! let trace_length = 20_000_000;
+//! if trace_length > VERIFY_AGGREGATION.max_trace_length {
+//!     eprintln!(
+//!         "Trace of {} cycles exceeds the guest limit of {}",
+//!         trace_length, VERIFY_AGGREGATION.max_trace_length
+//!     );
+//! }
+//! assert_eq!(limits::entry_point("verify_aggregation"), Some(VERIFY_AGGREGATION));
+//!
+//! let tier = limits::GuestTier::for_batch_size(100).expect("Batch too large for any tier");
+//! assert_eq!(tier.entry_point(), "verify_aggregation_medium");
+//! ```
+
+/// Resource limits declared in a `#[jolt::provable]` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GuestLimits {
+    /// Guest stack size, in bytes
+    pub stack_size: usize,
+    /// Guest memory size, in bytes
+    pub memory_size: usize,
+    /// Maximum serialized input size, in bytes
+    pub max_input_size: usize,
+    /// Maximum execution trace length, in cycles
+    pub max_trace_length: usize,
+}
+
+include!(concat!(env!("OUT_DIR"), "/guest_limits.rs"));
+
+/// Size class of the tiered guest entry points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GuestTier {
+    /// `verify_aggregation_small`, up to 16 items
+    Small,
+    /// `verify_aggregation_medium`, up to 256 items
+    Medium,
+    /// `verify_aggregation_large`, up to 4096 items
+    Large,
+}
+
+impl GuestTier {
+    /// Every tier, smallest first.
+    pub const ALL: [Self; 3] = [Self::Small, Self::Medium, Self::Large];
+
+    /// Largest batch the tier's entry point accepts, as generated from the
+    /// guest's `TIER_CAPACITIES`; the guest panics on anything bigger.
+    #[must_use]
+    pub const fn capacity(self) -> usize {
+        match self {
+            Self::Small => TIER_CAPACITIES[0],
+            Self::Medium => TIER_CAPACITIES[1],
+            Self::Large => TIER_CAPACITIES[2],
+        }
+    }
+
+    /// Name of the tier's guest entry point.
+    #[must_use]
+    pub const fn entry_point(self) -> &'static str {
+        match self {
+            Self::Small => "verify_aggregation_small",
+            Self::Medium => "verify_aggregation_medium",
+            Self::Large => "verify_aggregation_large",
+        }
+    }
+
+    /// Resource limits of the tier's entry point.
+    #[must_use]
+    pub const fn limits(self) -> GuestLimits {
+        match self {
+            Self::Small => VERIFY_AGGREGATION_SMALL,
+            Self::Medium => VERIFY_AGGREGATION_MEDIUM,
+            Self::Large => VERIFY_AGGREGATION_LARGE,
+        }
+    }
+
+    /// Smallest tier that fits `batch_size` items, or `None` if the batch
+    /// exceeds every tier.
+    #[must_use]
+    pub fn for_batch_size(batch_size: usize) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|tier| batch_size <= tier.capacity())
+    }
+}
+
+/// Limits of the entry point named `name`, if the guest declares it.
+#[must_use]
+pub fn entry_point(name: &str) -> Option<GuestLimits> {
+    ENTRY_POINTS
+        .iter()
+        .find(|(entry_point, _)| *entry_point == name)
+        .map(|&(_, limits)| limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_from_guest_attributes() {
+        let names: Vec<&str> = ENTRY_POINTS.iter().map(|(name, _)| *name).collect();
+        assert!(names.contains(&"verify_aggregation"));
+        assert!(names.contains(&"verify_aggregation_strict"));
+        assert!(names.contains(&"verify_aggregation_single_key"));
+        assert!(names.contains(&"verify_aggregation_key_table"));
+        assert!(names.contains(&"verify_aggregation_threshold"));
+        assert!(names.contains(&"verify_aggregation_tally"));
+        assert!(names.contains(&"verify_aggregation_w2"));
+        assert!(names.contains(&"verify_aggregation_w4"));
+        assert!(names.contains(&"verify_aggregation_w8"));
+        assert!(names.contains(&"verify_aggregation_lifetime_20"));
+        assert!(names.contains(&"verify_aggregation_lifetime_32"));
+        assert!(names.contains(&"verify_aggregation_sha3"));
+        assert_eq!(entry_point("verify_aggregation"), Some(VERIFY_AGGREGATION));
+        assert_eq!(entry_point("missing"), None);
+        for tier in GuestTier::ALL {
+            assert_eq!(entry_point(tier.entry_point()), Some(tier.limits()));
+        }
+
+        // Jolt requires power-of-two sizes
+        for (_, limits) in ENTRY_POINTS {
+            assert!(limits.memory_size.is_power_of_two());
+            assert!(limits.max_trace_length.is_power_of_two());
+        }
+    }
+
+    #[test]
+    fn test_tiers_grow_with_batch_size() {
+        assert_eq!(GuestTier::for_batch_size(1), Some(GuestTier::Small));
+        assert_eq!(GuestTier::for_batch_size(16), Some(GuestTier::Small));
+        assert_eq!(GuestTier::for_batch_size(17), Some(GuestTier::Medium));
+        assert_eq!(GuestTier::for_batch_size(4096), Some(GuestTier::Large));
+        assert_eq!(GuestTier::for_batch_size(4097), None);
+
+        for pair in GuestTier::ALL.windows(2) {
+            let (smaller, larger) = (pair[0].limits(), pair[1].limits());
+            assert!(smaller.memory_size < larger.memory_size);
+            assert!(smaller.max_input_size < larger.max_input_size);
+            assert!(smaller.max_trace_length < larger.max_trace_length);
+        }
+    }
+
+    #[test]
+    fn test_lifetime_32_limits_are_enlarged() {
+        let enlarged = entry_point("verify_aggregation_lifetime_32").unwrap();
+        let standard = entry_point("verify_aggregation_lifetime_20").unwrap();
+        assert!(enlarged.memory_size > standard.memory_size);
+        assert!(enlarged.max_input_size > standard.max_input_size);
+        assert!(enlarged.max_trace_length > standard.max_trace_length);
+    }
+}
@@ -0,0 +1,342 @@
+//! Batches with an explicit aggregation mode.
+//!
+//! An [`AggregationBatch`] carries one public key per item, which is all the
+//! guest needs. Callers aggregating one signer's signatures would rather
+//! supply that key once, and need epochs, not (key, epoch) pairs, to be
+//! unique. A [`ModeBatch`] states its [`AggregationMode`]:
+//!
+//! | Mode | Shared `public_key` | Item `public_key` | Unique per batch |
+//! |---|---|---|---|
+//! | `SingleKey` | required | `None` or the shared key | epoch |
+//! | `MultiKey` | rejected | required | (public key, epoch) |
+//!
+//! [`aggregate_with_mode`] checks these rules and expands the batch into an
+//! [`AggregationBatch`] with the key copied into every item, ready for
+//! proving. A `SingleKey` batch can then be proven with the single-key guest
+//! entry point, which again carries the key once (see
+//! [`encode_single_key_batch`](crate::wire::encode_single_key_batch)) and
+//! binds the mode into the proof through its input digest.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::mode::{ModeBatch, ModeItem, aggregate_with_mode};
+//! use sig_agg::{AggregationError, AggregationMode};
+//! # let public_key = unimplemented!();
+//! # let items: Vec<ModeItem> = vec![];
+//!
+//! let batch = ModeBatch {
+//!     mode: AggregationMode::SingleKey,
+//!     public_key: Some(public_key),
+//!     items,
+//! };
+//! match aggregate_with_mode(batch) {
+//!     Ok(batch) => println!("{} signatures ready", batch.items.len()),
+//!     Err(AggregationError::DuplicateEpoch { epoch }) => {
+//!         eprintln!("Epoch {} signed twice", epoch);
+//!     }
+//!     Err(e) => eprintln!("Aggregation failed: {}", e),
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use hashsig::signature::SignatureScheme;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aggregator::validate,
+    codec::Message,
+    error::AggregationError,
+    keys::PublicKey,
+    types::{AggregationBatch, AggregationMode, VerificationItem, XMSSSignature},
+};
+
+/// A signature whose public key may be supplied by its batch.
+#[derive(Serialize, Deserialize)]
+pub struct ModeItem {
+    /// Message that was signed
+    pub message: Message,
+    /// Epoch the signature was created in
+    pub epoch: u32,
+    /// XMSS signature data
+    pub signature: <XMSSSignature as SignatureScheme>::Signature,
+    /// Signer's public key; required in `MultiKey` mode, `None` or the
+    /// shared key in `SingleKey` mode
+    pub public_key: Option<PublicKey>,
+}
+
+impl std::fmt::Debug for ModeItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModeItem")
+            .field("epoch", &self.epoch)
+            .field(
+                "public_key",
+                &self.public_key.as_ref().map(|_| "<XMSS PublicKey>"),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+/// Items aggregated under an explicit [`AggregationMode`].
+#[derive(Serialize, Deserialize)]
+pub struct ModeBatch {
+    /// Key supply and uniqueness rule of the batch
+    pub mode: AggregationMode,
+    /// Public key shared by every item; required in `SingleKey` mode and
+    /// rejected in `MultiKey` mode
+    pub public_key: Option<PublicKey>,
+    /// Items in batch order
+    pub items: Vec<ModeItem>,
+}
+
+impl std::fmt::Debug for ModeBatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModeBatch")
+            .field("mode", &self.mode)
+            .field(
+                "public_key",
+                &self.public_key.as_ref().map(|_| "<XMSS PublicKey>"),
+            )
+            .field("items", &format_args!("[{} items]", self.items.len()))
+            .finish()
+    }
+}
+
+/// Checks `batch` against the rules of its mode and expands it into an
+/// [`AggregationBatch`] with a public key in every item.
+///
+/// # Errors
+///
+/// * `EmptyBatch` - the batch has no items
+/// * `MissingPublicKey` - a `SingleKey` batch has no shared key, or an item
+///   of a `MultiKey` batch has no key
+/// * `UnexpectedPublicKey` - a `MultiKey` batch has a shared key
+/// * `MixedPublicKeys` - an item of a `SingleKey` batch carries a key other
+///   than the shared one
+/// * `DuplicateEpoch` - an epoch occurs twice in a `SingleKey` batch
+/// * `DuplicateKeyEpochPair` - a (public key, epoch) pair occurs twice in a
+///   `MultiKey` batch
+/// * `SerializationError` - a public key failed to serialize
+pub fn aggregate_with_mode(batch: ModeBatch) -> Result<AggregationBatch, AggregationError> {
+    if batch.items.is_empty() {
+        return Err(AggregationError::EmptyBatch);
+    }
+    let batch = match batch.mode {
+        AggregationMode::SingleKey => expand_single_key(batch)?,
+        AggregationMode::MultiKey => expand_multi_key(batch)?,
+    };
+    validate(&batch.items)?;
+    Ok(batch)
+}
+
+fn expand_single_key(batch: ModeBatch) -> Result<AggregationBatch, AggregationError> {
+    let shared = batch
+        .public_key
+        .as_ref()
+        .ok_or(AggregationError::MissingPublicKey {
+            mode: AggregationMode::SingleKey,
+            index: None,
+        })
+        .and_then(encode_key)?;
+
+    let mut epochs = HashSet::new();
+    let mut items = Vec::with_capacity(batch.items.len());
+    for (index, item) in batch.items.into_iter().enumerate() {
+        if let Some(public_key) = &item.public_key
+            && encode_key(public_key)? != shared
+        {
+            return Err(AggregationError::MixedPublicKeys { index });
+        }
+        if !epochs.insert(item.epoch) {
+            return Err(AggregationError::DuplicateEpoch { epoch: item.epoch });
+        }
+        items.push(VerificationItem {
+            message: item.message,
+            epoch: item.epoch,
+            signature: item.signature,
+            public_key: decode_key(&shared)?,
+        });
+    }
+    Ok(AggregationBatch { items })
+}
+
+fn expand_multi_key(batch: ModeBatch) -> Result<AggregationBatch, AggregationError> {
+    if batch.public_key.is_some() {
+        return Err(AggregationError::UnexpectedPublicKey {
+            mode: AggregationMode::MultiKey,
+        });
+    }
+    let items = batch
+        .items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let public_key = item.public_key.ok_or(AggregationError::MissingPublicKey {
+                mode: AggregationMode::MultiKey,
+                index: Some(index),
+            })?;
+            Ok(VerificationItem {
+                message: item.message,
+                epoch: item.epoch,
+                signature: item.signature,
+                public_key,
+            })
+        })
+        .collect::<Result<_, AggregationError>>()?;
+    Ok(AggregationBatch { items })
+}
+
+fn encode_key(public_key: &PublicKey) -> Result<Vec<u8>, AggregationError> {
+    bincode::serialize(public_key).map_err(|e| AggregationError::SerializationError {
+        message: format!("Failed to serialize public key: {}", e),
+    })
+}
+
+fn decode_key(bytes: &[u8]) -> Result<PublicKey, AggregationError> {
+    bincode::deserialize(bytes).map_err(|e| AggregationError::SerializationError {
+        message: format!("Failed to copy public key: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hashsig::MESSAGE_LENGTH;
+
+    fn signed_items(n: u32) -> (PublicKey, Vec<ModeItem>) {
+        let mut rng = rand::rng();
+        let (pk, sk) = XMSSSignature::key_gen(&mut rng, 0, 10);
+        let items = (0..n)
+            .map(|epoch| ModeItem {
+                message: [epoch as u8; MESSAGE_LENGTH],
+                epoch,
+                signature: XMSSSignature::sign(&sk, epoch, &[epoch as u8; MESSAGE_LENGTH])
+                    .expect("Signing should succeed"),
+                public_key: None,
+            })
+            .collect();
+        (pk, items)
+    }
+
+    fn copy_key(public_key: &PublicKey) -> PublicKey {
+        decode_key(&encode_key(public_key).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_single_key_batch_shares_its_key() {
+        let (pk, mut items) = signed_items(3);
+        items[1].public_key = Some(copy_key(&pk));
+        let expected = encode_key(&pk).unwrap();
+
+        let batch = aggregate_with_mode(ModeBatch {
+            mode: AggregationMode::SingleKey,
+            public_key: Some(pk),
+            items,
+        })
+        .unwrap();
+        assert_eq!(batch.items.len(), 3);
+        for item in &batch.items {
+            assert_eq!(encode_key(&item.public_key).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_single_key_rules() {
+        let (pk, items) = signed_items(2);
+        assert_eq!(
+            aggregate_with_mode(ModeBatch {
+                mode: AggregationMode::SingleKey,
+                public_key: None,
+                items,
+            })
+            .unwrap_err(),
+            AggregationError::MissingPublicKey {
+                mode: AggregationMode::SingleKey,
+                index: None,
+            }
+        );
+
+        let (other, mut items) = signed_items(2);
+        items[1].public_key = Some(other);
+        assert_eq!(
+            aggregate_with_mode(ModeBatch {
+                mode: AggregationMode::SingleKey,
+                public_key: Some(copy_key(&pk)),
+                items,
+            })
+            .unwrap_err(),
+            AggregationError::MixedPublicKeys { index: 1 }
+        );
+
+        let (_, mut items) = signed_items(3);
+        items[2].epoch = 0;
+        assert_eq!(
+            aggregate_with_mode(ModeBatch {
+                mode: AggregationMode::SingleKey,
+                public_key: Some(pk),
+                items,
+            })
+            .unwrap_err(),
+            AggregationError::DuplicateEpoch { epoch: 0 }
+        );
+    }
+
+    #[test]
+    fn test_multi_key_rules() {
+        let (pk, mut items) = signed_items(2);
+        for item in &mut items {
+            item.public_key = Some(copy_key(&pk));
+        }
+        assert_eq!(
+            aggregate_with_mode(ModeBatch {
+                mode: AggregationMode::MultiKey,
+                public_key: Some(copy_key(&pk)),
+                items,
+            })
+            .unwrap_err(),
+            AggregationError::UnexpectedPublicKey {
+                mode: AggregationMode::MultiKey,
+            }
+        );
+
+        let (_, mut items) = signed_items(2);
+        items[0].public_key = Some(copy_key(&pk));
+        assert_eq!(
+            aggregate_with_mode(ModeBatch {
+                mode: AggregationMode::MultiKey,
+                public_key: None,
+                items,
+            })
+            .unwrap_err(),
+            AggregationError::MissingPublicKey {
+                mode: AggregationMode::MultiKey,
+                index: Some(1),
+            }
+        );
+
+        let (_, mut items) = signed_items(2);
+        items[1].epoch = 0;
+        for item in &mut items {
+            item.public_key = Some(copy_key(&pk));
+        }
+        assert!(matches!(
+            aggregate_with_mode(ModeBatch {
+                mode: AggregationMode::MultiKey,
+                public_key: None,
+                items,
+            }),
+            Err(AggregationError::DuplicateKeyEpochPair { epoch: 0, .. })
+        ));
+
+        assert_eq!(
+            aggregate_with_mode(ModeBatch {
+                mode: AggregationMode::default(),
+                public_key: None,
+                items: vec![],
+            })
+            .unwrap_err(),
+            AggregationError::EmptyBatch
+        );
+    }
+}
@@ -0,0 +1,599 @@
+//! Native execution of the guest's verification logic.
+//!
+//! Pre-verifying a batch on the host is only meaningful if the host checks
+//! exactly what the guest will check. Rather than re-implementing the loop,
+//! this module (behind the `native` feature) links the guest crate natively
+//! and calls its [`guest::verify_batch`], the same function the zkVM entry
+//! point runs. Any change to the guest's verification logic is therefore
+//! picked up by host pre-verification automatically.
+//!
+//! [`verify_natively_strict`] likewise runs the check of the all-or-nothing
+//! `verify_aggregation_strict` entry point, and [`verify_natively_threshold`]
+//! that of the k-of-n `verify_aggregation_threshold` entry point.
+//! [`verify_natively_tally`] runs the per-signer counting of the
+//! `verify_aggregation_tally` entry point.
+//!
+//! [`verify_natively_single_key`] runs the `verify_aggregation_single_key`
+//! entry point's logic on a single-signer batch, and
+//! [`verify_natively_key_table`] the `verify_aggregation_key_table` entry
+//! point's logic on a [key-table batch](crate::key_table).
+//! [`verify_natively_w2`], [`verify_natively_w4`], [`verify_natively_w8`],
+//! [`verify_natively_lifetime_20`], [`verify_natively_lifetime_32`] and
+//! [`verify_natively_sha3`] run the logic of the `verify_aggregation_w2`,
+//! `_w4`, `_w8`, `_lifetime_20`, `_lifetime_32` and `_sha3` entry points on
+//! batches of [those instantiations](crate::scheme).
+//!
+//! The entry points panic on a reused (public key, epoch) pair; the native
+//! functions return [`AggregationError::DuplicateKeyEpochPair`] instead.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::native::verify_natively;
+//! # let batch: sig_agg::AggregationBatch = unimplemented!();
+//!
+//! let output = verify_natively(&batch).expect("Conversion failed");
+//! assert_eq!(output.verified_count as usize, batch.items.len());
+//! ```
+
+pub use guest::{GuestOutput, SignerTally, TallyOutput, ThresholdOutput};
+
+use crate::{
+    aggregator::duplicate_pair,
+    error::AggregationError,
+    guest::InputDigest,
+    key_table::KeyTableBatch,
+    scheme::{Instantiation, SchemeBatch},
+    types::AggregationBatch,
+};
+
+/// Runs the guest's verification logic natively on `batch`.
+///
+/// The batch is converted to the guest's input type through the fixed
+/// [wire encoding](crate::wire), mirroring how the prover hands the batch to
+/// the zkVM.
+pub fn verify_natively(batch: &AggregationBatch) -> Result<GuestOutput, AggregationError> {
+    let guest_batch = to_guest_batch(batch)?;
+    check_unique(batch, &guest_batch)?;
+    Ok(guest::verify_batch(&guest_batch))
+}
+
+/// Runs the strict guest's check natively on `batch`.
+///
+/// Returns the input digest the strict guest outputs if every signature
+/// verifies, and [`AggregationError::InvalidSignature`] with the first
+/// failing index otherwise, where the strict guest would panic.
+pub fn verify_natively_strict(batch: &AggregationBatch) -> Result<InputDigest, AggregationError> {
+    let guest_batch = to_guest_batch(batch)?;
+    check_unique(batch, &guest_batch)?;
+    if let Some(index) = guest::first_invalid(&guest_batch) {
+        return Err(AggregationError::InvalidSignature { index });
+    }
+    Ok(guest::input_digest(&guest_batch))
+}
+
+/// Runs the threshold guest's check natively on `batch`, reporting whether
+/// at least `min_valid` signatures verify.
+pub fn verify_natively_threshold(
+    batch: &AggregationBatch,
+    min_valid: u32,
+) -> Result<ThresholdOutput, AggregationError> {
+    let guest_batch = to_guest_batch(batch)?;
+    check_unique(batch, &guest_batch)?;
+    Ok(guest::verify_threshold_batch(&guest_batch, min_valid))
+}
+
+/// Runs the tally guest's verification logic natively on `batch`, counting
+/// the verified signatures of each signer.
+///
+/// Tally fingerprints equal
+/// [`public_key_fingerprint`](crate::commitment::public_key_fingerprint) of
+/// the signer's key.
+pub fn verify_natively_tally(batch: &AggregationBatch) -> Result<TallyOutput, AggregationError> {
+    let guest_batch = to_guest_batch(batch)?;
+    check_unique(batch, &guest_batch)?;
+    Ok(guest::verify_tally_batch(&guest_batch))
+}
+
+/// Runs the single-key guest's verification logic natively on `batch`.
+///
+/// Fails with [`AggregationError::MixedPublicKeys`] if the items do not all
+/// share one public key.
+pub fn verify_natively_single_key(
+    batch: &AggregationBatch,
+) -> Result<GuestOutput, AggregationError> {
+    let bytes = crate::wire::encode_single_key_batch(batch)?;
+    let guest_batch = guest::decode_single_key_batch(&bytes).map_err(|e| {
+        AggregationError::SerializationError {
+            message: format!("Batch is not a valid single-key guest input: {}", e),
+        }
+    })?;
+    if let Some(index) = guest::first_duplicate_epoch(&guest_batch) {
+        let pk_bytes = bincode::serialize(&batch.items[index].public_key).map_err(|e| {
+            AggregationError::SerializationError {
+                message: format!("Failed to serialize public key: {}", e),
+            }
+        })?;
+        return Err(duplicate_pair(&pk_bytes, batch.items[index].epoch));
+    }
+    Ok(guest::verify_single_key_batch(&guest_batch))
+}
+
+/// Runs the key-table guest's verification logic natively on `batch`.
+///
+/// Fails with [`AggregationError::UnknownKeyIndex`] if an item refers to a
+/// key outside the table.
+pub fn verify_natively_key_table(batch: &KeyTableBatch) -> Result<GuestOutput, AggregationError> {
+    let bytes = crate::wire::encode_key_table_batch(batch)?;
+    let guest_batch = guest::decode_key_table_batch(&bytes).map_err(|e| {
+        AggregationError::SerializationError {
+            message: format!("Batch is not a valid key-table guest input: {}", e),
+        }
+    })?;
+    if let Some(index) = guest::first_duplicate_key_table_pair(&guest_batch) {
+        let item = &batch.items[index];
+        let pk_bytes = bincode::serialize(&batch.keys[item.key_index as usize]).map_err(|e| {
+            AggregationError::SerializationError {
+                message: format!("Failed to serialize public key: {}", e),
+            }
+        })?;
+        return Err(duplicate_pair(&pk_bytes, item.epoch));
+    }
+    Ok(guest::verify_key_table_batch(&guest_batch))
+}
+
+/// Runs the W2 guest's verification logic natively on `batch`.
+#[cfg(feature = "w2-lifetime18")]
+pub fn verify_natively_w2(
+    batch: &SchemeBatch<crate::scheme::XMSSSignatureW2>,
+) -> Result<GuestOutput, AggregationError> {
+    verify_natively_scheme(batch)
+}
+
+/// Runs the W4 guest's verification logic natively on `batch`.
+#[cfg(feature = "w4-lifetime18")]
+pub fn verify_natively_w4(
+    batch: &SchemeBatch<crate::scheme::XMSSSignatureW4>,
+) -> Result<GuestOutput, AggregationError> {
+    verify_natively_scheme(batch)
+}
+
+/// Runs the W8 guest's verification logic natively on `batch`.
+#[cfg(feature = "w8-lifetime18")]
+pub fn verify_natively_w8(
+    batch: &SchemeBatch<crate::scheme::XMSSSignatureW8>,
+) -> Result<GuestOutput, AggregationError> {
+    verify_natively_scheme(batch)
+}
+
+/// Runs the lifetime 2^20 guest's verification logic natively on `batch`.
+#[cfg(feature = "w1-lifetime20")]
+pub fn verify_natively_lifetime_20(
+    batch: &SchemeBatch<crate::scheme::XMSSSignatureLifetime20>,
+) -> Result<GuestOutput, AggregationError> {
+    verify_natively_scheme(batch)
+}
+
+/// Runs the lifetime 2^32 guest's verification logic natively on `batch`.
+#[cfg(feature = "lifetime32")]
+pub fn verify_natively_lifetime_32(
+    batch: &SchemeBatch<crate::scheme::XMSSSignatureLifetime32>,
+) -> Result<GuestOutput, AggregationError> {
+    verify_natively_scheme(batch)
+}
+
+/// Runs the SHA-3 guest's verification logic natively on `batch`.
+#[cfg(feature = "sha3")]
+pub fn verify_natively_sha3(
+    batch: &SchemeBatch<crate::scheme::XMSSSignatureSha3>,
+) -> Result<GuestOutput, AggregationError> {
+    verify_natively_scheme(batch)
+}
+
+/// Shared body of the per-instantiation functions; the guest crate names
+/// the same `hashsig` types, so one type implements both `Instantiation`
+/// traits.
+fn verify_natively_scheme<S>(batch: &SchemeBatch<S>) -> Result<GuestOutput, AggregationError>
+where
+    S: Instantiation + guest::Instantiation,
+{
+    let bytes = crate::wire::encode_batch(batch)?;
+    let guest_batch: guest::SchemeBatch<S> =
+        guest::decode_batch(&bytes).map_err(|e| AggregationError::SerializationError {
+            message: format!(
+                "Batch is not a valid {} guest input: {}",
+                <S as Instantiation>::NAME,
+                e
+            ),
+        })?;
+    if let Some(index) = guest::first_duplicate(&guest_batch) {
+        let pk_bytes = bincode::serialize(&batch.items[index].public_key).map_err(|e| {
+            AggregationError::SerializationError {
+                message: format!("Failed to serialize public key: {}", e),
+            }
+        })?;
+        return Err(duplicate_pair(&pk_bytes, batch.items[index].epoch));
+    }
+    Ok(guest::verify_scheme_batch(&guest_batch))
+}
+
+/// The guest's uniqueness check, reported as an error rather than a panic.
+fn check_unique(
+    batch: &AggregationBatch,
+    guest_batch: &guest::AggregationBatch,
+) -> Result<(), AggregationError> {
+    let Some(index) = guest::first_duplicate(guest_batch) else {
+        return Ok(());
+    };
+    let item = &batch.items[index];
+    let pk_bytes =
+        bincode::serialize(&item.public_key).map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to serialize public key: {}", e),
+        })?;
+    Err(duplicate_pair(&pk_bytes, item.epoch))
+}
+
+pub(crate) fn to_guest_batch(
+    batch: &AggregationBatch,
+) -> Result<guest::AggregationBatch, AggregationError> {
+    let bytes = crate::wire::encode_batch(batch)?;
+    guest::decode_batch(&bytes).map_err(|e| AggregationError::SerializationError {
+        message: format!("Batch is not a valid guest input: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{public_key, signed_item, test_batch};
+    use hashsig::MESSAGE_LENGTH;
+    use hashsig::signature::SignatureScheme;
+
+    #[test]
+    fn test_native_output_counts_valid_signatures() {
+        let mut batch = test_batch(3);
+        assert_eq!(verify_natively(&batch).unwrap().verified_count, 3);
+
+        // Tamper with one message so its signature no longer verifies
+        batch.items[1].message[0] ^= 1;
+        let output = verify_natively(&batch).unwrap();
+        assert_eq!(output.verified_count, 2);
+        assert_eq!(output.failed, vec![0b0000_0010]);
+    }
+
+    #[test]
+    fn test_strict_check_rejects_any_invalid_signature() {
+        let mut batch = test_batch(3);
+        assert_eq!(
+            verify_natively_strict(&batch).unwrap(),
+            crate::guest::input_digest(&batch).unwrap()
+        );
+
+        batch.items[2].message[0] ^= 1;
+        assert_eq!(
+            verify_natively_strict(&batch),
+            Err(AggregationError::InvalidSignature { index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_rejects_reused_key_epoch_pairs_like_the_guest() {
+        let mut batch = test_batch(3);
+        batch.items[2].epoch = 0;
+
+        for result in [
+            verify_natively(&batch).map(|_| ()),
+            verify_natively_strict(&batch).map(|_| ()),
+        ] {
+            assert!(matches!(
+                result,
+                Err(AggregationError::DuplicateKeyEpochPair { epoch: 0, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_single_key_variant_matches_multi_key_results() {
+        let mut batch = test_batch(3);
+        batch.items[1].message[0] ^= 1;
+
+        let single_key = verify_natively_single_key(&batch).unwrap();
+        let multi_key = verify_natively(&batch).unwrap();
+        assert_eq!(single_key.verified_count, multi_key.verified_count);
+        assert_eq!(single_key.failed, multi_key.failed);
+        assert_eq!(
+            single_key.input_digest,
+            crate::guest::single_key_input_digest(&batch).unwrap()
+        );
+
+        batch.items[2].epoch = 0;
+        assert!(matches!(
+            verify_natively_single_key(&batch),
+            Err(AggregationError::DuplicateKeyEpochPair { epoch: 0, .. })
+        ));
+
+        batch.items[2].public_key = public_key(1);
+        assert_eq!(
+            verify_natively_single_key(&batch).map(|_| ()),
+            Err(AggregationError::MixedPublicKeys { index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_key_table_variant_matches_multi_key_results() {
+        let mut batch = test_batch(3);
+        batch.items[1].message[0] ^= 1;
+        batch.items.push(signed_item(1, 0));
+        let multi_key = verify_natively(&batch).unwrap();
+
+        let table = KeyTableBatch::from_batch(batch).unwrap();
+        assert_eq!(table.keys.len(), 2);
+        let key_table = verify_natively_key_table(&table).unwrap();
+        assert_eq!(key_table.verified_count, multi_key.verified_count);
+        assert_eq!(key_table.failed, multi_key.failed);
+        assert_eq!(key_table.verified_root, multi_key.verified_root);
+        assert_eq!(
+            key_table.input_digest,
+            crate::guest::key_table_input_digest(&table).unwrap()
+        );
+
+        // A key listed twice does not hide a reused epoch
+        let mut table = table;
+        let copy = bincode::serialize(&table.keys[0]).unwrap();
+        table.keys.push(bincode::deserialize(&copy).unwrap());
+        table.items[1].key_index = 2;
+        table.items[1].epoch = 0;
+        assert!(matches!(
+            verify_natively_key_table(&table),
+            Err(AggregationError::DuplicateKeyEpochPair { epoch: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_threshold_variant() {
+        let mut batch = test_batch(4);
+        batch.items[1].message[0] ^= 1;
+
+        for (min_valid, met) in [(0, true), (3, true), (4, false), (5, false)] {
+            let output = verify_natively_threshold(&batch, min_valid).unwrap();
+            assert_eq!(output.met, met, "min_valid {}", min_valid);
+            assert_eq!(output.min_valid, min_valid);
+            assert_eq!(
+                output.input_digest,
+                crate::guest::input_digest(&batch).unwrap()
+            );
+        }
+
+        batch.items[3].epoch = 0;
+        assert!(matches!(
+            verify_natively_threshold(&batch, 1),
+            Err(AggregationError::DuplicateKeyEpochPair { epoch: 0, .. })
+        ));
+    }
+
+    fn create_scheme_batch<S: SignatureScheme>(n: u32) -> SchemeBatch<S> {
+        let mut rng = rand::rng();
+        let (pk, sk) = S::key_gen(&mut rng, 0, 10);
+        let pk_bytes = bincode::serialize(&pk).unwrap();
+        let items = (0..n)
+            .map(|epoch| crate::scheme::SchemeItem {
+                message: [epoch as u8; MESSAGE_LENGTH],
+                epoch,
+                signature: S::sign(&sk, epoch, &[epoch as u8; MESSAGE_LENGTH])
+                    .expect("Signing should succeed"),
+                public_key: bincode::deserialize(&pk_bytes).unwrap(),
+            })
+            .collect();
+        crate::scheme::aggregate_scheme(items).unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "w2-lifetime18")]
+    fn test_w2_variant() {
+        let mut batch = create_scheme_batch::<crate::scheme::XMSSSignatureW2>(3);
+        batch.items[1].message[0] ^= 1;
+
+        let output = verify_natively_w2(&batch).unwrap();
+        assert_eq!(output.verified_count, 2);
+        assert_eq!(output.failed, vec![0b0000_0010]);
+        assert_eq!(
+            output.input_digest,
+            crate::guest::scheme_input_digest(&batch).unwrap()
+        );
+
+        batch.items[2].epoch = 0;
+        assert!(matches!(
+            verify_natively_w2(&batch),
+            Err(AggregationError::DuplicateKeyEpochPair { epoch: 0, .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "w4-lifetime18", feature = "w8-lifetime18"))]
+    fn test_w4_and_w8_variants() {
+        let w4 = create_scheme_batch::<crate::scheme::XMSSSignatureW4>(3);
+        let w8 = create_scheme_batch::<crate::scheme::XMSSSignatureW8>(3);
+        let w4_output = verify_natively_w4(&w4).unwrap();
+        let w8_output = verify_natively_w8(&w8).unwrap();
+        assert_eq!(w4_output.verified_count, 3);
+        assert_eq!(w8_output.verified_count, 3);
+        assert_eq!(
+            w4_output.input_digest,
+            crate::guest::scheme_input_digest(&w4).unwrap()
+        );
+        assert_eq!(
+            w8_output.input_digest,
+            crate::guest::scheme_input_digest(&w8).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "w1-lifetime20")]
+    fn test_lifetime_20_variant() {
+        let mut batch = create_scheme_batch::<crate::scheme::XMSSSignatureLifetime20>(2);
+        batch.items[0].message[0] ^= 1;
+        let output = verify_natively_lifetime_20(&batch).unwrap();
+        assert_eq!(output.verified_count, 1);
+        assert_eq!(output.failed, vec![0b0000_0001]);
+    }
+
+    #[test]
+    #[cfg(feature = "lifetime32")]
+    fn test_lifetime_32_variant() {
+        let mut batch = create_scheme_batch::<crate::scheme::XMSSSignatureLifetime32>(2);
+        batch.items[1].message[0] ^= 1;
+        let output = verify_natively_lifetime_32(&batch).unwrap();
+        assert_eq!(output.verified_count, 1);
+        assert_eq!(output.failed, vec![0b0000_0010]);
+        assert_eq!(
+            output.input_digest,
+            crate::guest::scheme_input_digest(&batch).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sha3")]
+    fn test_sha3_variant() {
+        let mut batch = create_scheme_batch::<crate::scheme::XMSSSignatureSha3>(3);
+        batch.items[2].message[0] ^= 1;
+        let output = verify_natively_sha3(&batch).unwrap();
+        assert_eq!(output.verified_count, 2);
+        assert_eq!(output.failed, vec![0b0000_0100]);
+
+        // Bound to the SHA-3 scheme, not the Poseidon one of the same name
+        assert_ne!(
+            crate::scheme::XMSSSignatureSha3::NAME,
+            crate::artifact::DEFAULT_SCHEME
+        );
+        assert_eq!(
+            output.input_digest,
+            crate::guest::scheme_input_digest(&batch).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tally_counts_verified_items_per_signer() {
+        let mut batch = test_batch(3);
+        batch.items[1].message[0] ^= 1;
+        batch.items.push(signed_item(1, 0));
+
+        let output = verify_natively_tally(&batch).unwrap();
+        assert_eq!(output.verified_count, 3);
+        assert_eq!(
+            output.input_digest,
+            crate::guest::input_digest(&batch).unwrap()
+        );
+
+        let first = crate::commitment::public_key_fingerprint(&batch.items[0].public_key).unwrap();
+        let other = crate::commitment::public_key_fingerprint(&batch.items[3].public_key).unwrap();
+        let mut expected = vec![
+            SignerTally {
+                fingerprint: first,
+                count: 2,
+            },
+            SignerTally {
+                fingerprint: other,
+                count: 1,
+            },
+        ];
+        expected.sort_by_key(|entry| entry.fingerprint);
+        assert_eq!(output.tally, expected);
+    }
+
+    #[test]
+    fn test_incremental_guest_root_matches_host_tree() {
+        for count in 0..=17u8 {
+            let leaves: Vec<[u8; 32]> = (0..count).map(|byte| [byte; 32]).collect();
+            assert_eq!(
+                guest::merkle_root(leaves.clone()),
+                crate::inclusion::merkle_root(&leaves),
+                "{} leaves",
+                count
+            );
+        }
+    }
+
+    #[test]
+    fn test_host_and_guest_prehashing_agree() {
+        for (domain, data) in [
+            (&b""[..], &b""[..]),
+            (b"app", b"payload"),
+            (b"app", &[0u8; 1000]),
+        ] {
+            assert_eq!(
+                guest::hash_to_message(domain, data),
+                crate::codec::hash_to_message(domain, data)
+            );
+        }
+    }
+
+    #[test]
+    fn test_host_and_guest_tier_capacities_agree() {
+        let host: Vec<usize> = crate::limits::GuestTier::ALL
+            .iter()
+            .map(|tier| tier.capacity())
+            .collect();
+        assert_eq!(host, guest::TIER_CAPACITIES);
+    }
+
+    #[test]
+    fn test_host_and_guest_wire_encodings_agree() {
+        let batch = test_batch(2);
+        let host_bytes = crate::wire::encode_batch(&batch).unwrap();
+        assert_eq!(
+            guest::encode_batch(&to_guest_batch(&batch).unwrap()),
+            host_bytes
+        );
+
+        let output = verify_natively(&batch).unwrap();
+        assert_eq!(
+            output.to_bytes(),
+            crate::wire::encode_output(
+                output.verified_count,
+                &output.input_digest,
+                &output.verified_root,
+                &output.failed
+            )
+            .unwrap()
+        );
+        assert_eq!(GuestOutput::from_bytes(&output.to_bytes()), Ok(output));
+    }
+
+    #[test]
+    fn test_host_and_guest_input_digests_agree() {
+        let mut batch = test_batch(2);
+        let output = verify_natively(&batch).unwrap();
+        assert_eq!(
+            output.input_digest,
+            crate::guest::input_digest(&batch).unwrap()
+        );
+
+        batch.items[0].message[0] ^= 1;
+        assert_ne!(
+            verify_natively(&batch).unwrap().input_digest,
+            output.input_digest
+        );
+    }
+
+    #[test]
+    fn test_host_and_guest_verified_roots_agree() {
+        let mut batch = test_batch(3);
+        let pk = &batch.items[0].public_key;
+        assert_eq!(
+            guest::key_fingerprint(&to_guest_batch(&batch).unwrap().items[0].public_key),
+            crate::commitment::public_key_fingerprint(pk).unwrap()
+        );
+
+        batch.items[1].message[0] ^= 1;
+        let output = verify_natively(&batch).unwrap();
+        assert_eq!(output.failed, vec![0b0000_0010]);
+        assert_eq!(
+            output.verified_root,
+            crate::inclusion::verified_root(&batch, &output.failed).unwrap()
+        );
+        assert_eq!(
+            verify_natively_single_key(&batch).unwrap().verified_root,
+            output.verified_root
+        );
+    }
+}
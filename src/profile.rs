@@ -0,0 +1,187 @@
+//! Compliance profiles restricting the cryptographic primitives in use.
+//!
+//! Building with the `fips` feature selects [`ComplianceProfile::Fips`]:
+//!
+//! - Non-approved commitment backends (`blake3`, `poseidon`, `keccak`) cannot
+//!   be enabled alongside it; the build fails at compile time.
+//! - Only SHA-256 batch commitments are accepted by [`check_profile`].
+//! - The benchmark's phony key material is disabled (see the bench's own
+//!   `fips` feature).
+//!
+//! The active profile is recorded in [`ProofMetadata::profile`] so compliance
+//! audits can tell which build produced a proof. A `fips` verifier rejects
+//! proofs from a standard build.
+//!
+//! The profile governs the composition around the signature scheme: batch
+//! commitments, message codecs and encryption at rest (AES-256-GCM). The
+//! Poseidon-based XMSS instantiation itself is not a NIST SP 800-208
+//! parameter set, so the profile does not by itself make a deployment
+//! FIPS 140 validated.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::profile::check_profile;
+//! # let proof: sig_agg::AggregationProof = unimplemented!();
+//!
+//! check_profile(&proof.metadata).expect("Proof violates the compliance profile");
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{commitment::CommitmentAlgorithm, error::AggregationError, types::ProofMetadata};
+
+#[cfg(all(
+    feature = "fips",
+    any(feature = "blake3", feature = "poseidon", feature = "keccak")
+))]
+compile_error!(
+    "the `fips` feature cannot be combined with the non-approved `blake3`, `poseidon` or `keccak` commitment backends"
+);
+
+/// Set of primitives a build is restricted to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComplianceProfile {
+    /// Every compiled-in primitive is allowed
+    #[default]
+    Standard,
+    /// NIST-approved hash and composition choices only
+    Fips,
+}
+
+impl ComplianceProfile {
+    /// Profile selected by this build's features.
+    pub const ACTIVE: Self = if cfg!(feature = "fips") {
+        Self::Fips
+    } else {
+        Self::Standard
+    };
+
+    /// Stable identifier for audit logs and reports.
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Fips => "fips",
+        }
+    }
+
+    /// Returns `true` if batch commitments may use `algorithm`.
+    #[must_use]
+    pub const fn allows_commitment(self, algorithm: CommitmentAlgorithm) -> bool {
+        match self {
+            Self::Standard => true,
+            Self::Fips => matches!(algorithm, CommitmentAlgorithm::Sha256),
+        }
+    }
+}
+
+/// Checks a proof's metadata against the [active](ComplianceProfile::ACTIVE)
+/// profile.
+///
+/// Under [`ComplianceProfile::Fips`] the proof must come from a `fips` build
+/// and use an approved commitment hash; otherwise this fails with
+/// [`AggregationError::ProofVerificationError`]. A standard build accepts
+/// any proof.
+pub fn check_profile(metadata: &ProofMetadata) -> Result<(), AggregationError> {
+    check_profile_against(metadata, ComplianceProfile::ACTIVE)
+}
+
+fn check_profile_against(
+    metadata: &ProofMetadata,
+    required: ComplianceProfile,
+) -> Result<(), AggregationError> {
+    if required == ComplianceProfile::Standard {
+        return Ok(());
+    }
+
+    if metadata.profile != required {
+        return Err(AggregationError::ProofVerificationError {
+            message: format!(
+                "Profile mismatch: proof was generated under profile {} but verifier requires {}",
+                metadata.profile.id(),
+                required.id()
+            ),
+        });
+    }
+    if !required.allows_commitment(metadata.commitment_hash) {
+        return Err(AggregationError::ProofVerificationError {
+            message: format!(
+                "Commitment hash {:?} is not approved under profile {}",
+                metadata.commitment_hash,
+                required.id()
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata_with(
+        profile: ComplianceProfile,
+        commitment_hash: CommitmentAlgorithm,
+    ) -> ProofMetadata {
+        ProofMetadata {
+            timestamp: 0,
+            batch_size: 1,
+            memory_size: 0,
+            trace_length: 0,
+            max_trace_length: 0,
+            proof_size: 0,
+            guest_hash: [0u8; 32],
+            urs_digest: [0u8; 32],
+            commitment_hash,
+            template: None,
+            cost: None,
+            profile,
+        }
+    }
+
+    #[test]
+    fn test_active_profile_follows_feature() {
+        assert_eq!(
+            ComplianceProfile::ACTIVE == ComplianceProfile::Fips,
+            cfg!(feature = "fips")
+        );
+    }
+
+    #[test]
+    fn test_standard_accepts_anything() {
+        let metadata = metadata_with(ComplianceProfile::Standard, CommitmentAlgorithm::Blake3);
+        assert!(check_profile_against(&metadata, ComplianceProfile::Standard).is_ok());
+    }
+
+    #[test]
+    fn test_fips_requires_profile_and_approved_hash() {
+        let fips = ComplianceProfile::Fips;
+
+        let ok = metadata_with(fips, CommitmentAlgorithm::Sha256);
+        assert!(check_profile_against(&ok, fips).is_ok());
+
+        let standard_build =
+            metadata_with(ComplianceProfile::Standard, CommitmentAlgorithm::Sha256);
+        assert!(matches!(
+            check_profile_against(&standard_build, fips),
+            Err(AggregationError::ProofVerificationError { .. })
+        ));
+
+        let keccak = metadata_with(fips, CommitmentAlgorithm::Keccak256);
+        assert!(matches!(
+            check_profile_against(&keccak, fips),
+            Err(AggregationError::ProofVerificationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_profile_id_serialization() {
+        assert_eq!(
+            serde_json::to_string(&ComplianceProfile::Fips).unwrap(),
+            "\"fips\""
+        );
+        assert_eq!(ComplianceProfile::Standard.id(), "standard");
+    }
+}
@@ -0,0 +1,180 @@
+//! Progress reporting for long-running proofs.
+//!
+//! Proving a large batch takes minutes. A [`ProgressObserver`] passed to the
+//! `jolt` feature's `prover::prove_batch_with_progress` or
+//! `Prover::prove_with_progress` is called as the prover enters each [`ProofStage`], with the time elapsed
+//! since the call started and a rough completion estimate, so GUIs and
+//! servers can show progress.
+//!
+//! Percentages are static estimates of each stage's share of a typical proof,
+//! not measurements; they only ever increase and reach 100 at
+//! [`ProofStage::Done`].
+//!
+//! # Examples
+//!
+//! ```
+//! use sig_agg::progress::{Progress, ProofStage, ProgressReporter};
+//!
+//! let log = |progress: &Progress| {
+//!     println!("{:?} {}% ({:?})", progress.stage, progress.percent, progress.elapsed);
+//! };
+//! let reporter = ProgressReporter::new(&log, false);
+//! reporter.report(ProofStage::Setup);
+//! reporter.report(ProofStage::Done);
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// Stage of proof generation, in the order the prover enters them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ProofStage {
+    /// Validating the batch and encoding the guest input
+    Setup,
+    /// Compiling the guest program
+    GuestCompile,
+    /// Prover and verifier preprocessing
+    Preprocessing,
+    /// Executing the guest and proving its trace, which Jolt performs as
+    /// one step
+    Proving,
+    /// Proof generated
+    Done,
+}
+
+impl ProofStage {
+    /// Estimated percent complete on entering this stage.
+    ///
+    /// `preprocessed` is `true` if compilation and preprocessing happened
+    /// before the call, as with a reused `prover::Prover`.
+    #[must_use]
+    pub const fn estimated_percent(self, preprocessed: bool) -> u8 {
+        match (self, preprocessed) {
+            (Self::Setup, _) => 0,
+            (Self::GuestCompile, _) => 1,
+            (Self::Preprocessing, _) => 15,
+            (Self::Proving, false) => 30,
+            (Self::Proving, true) => 2,
+            (Self::Done, _) => 100,
+        }
+    }
+}
+
+/// Snapshot passed to a [`ProgressObserver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Stage just entered
+    pub stage: ProofStage,
+    /// Time since proof generation started
+    pub elapsed: Duration,
+    /// Estimated percent complete, see [`ProofStage::estimated_percent`]
+    pub percent: u8,
+}
+
+/// Receives progress updates during proof generation.
+///
+/// Called on the proving thread; implementations should return quickly.
+pub trait ProgressObserver {
+    /// Called on entering each stage.
+    fn on_progress(&self, progress: &Progress);
+}
+
+impl<F: Fn(&Progress)> ProgressObserver for F {
+    fn on_progress(&self, progress: &Progress) {
+        self(progress);
+    }
+}
+
+/// Observer that ignores all updates.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressObserver for NoProgress {
+    fn on_progress(&self, _progress: &Progress) {}
+}
+
+/// Turns stage transitions into [`Progress`] updates for an observer.
+///
+/// Provers create one at the start of a call and report each stage as they
+/// enter it.
+pub struct ProgressReporter<'a> {
+    observer: &'a dyn ProgressObserver,
+    start: Instant,
+    preprocessed: bool,
+}
+
+impl std::fmt::Debug for ProgressReporter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("start", &self.start)
+            .field("preprocessed", &self.preprocessed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> ProgressReporter<'a> {
+    /// Starts the clock for a proof; see [`ProofStage::estimated_percent`]
+    /// for `preprocessed`.
+    #[must_use]
+    pub fn new(observer: &'a dyn ProgressObserver, preprocessed: bool) -> Self {
+        Self {
+            observer,
+            start: Instant::now(),
+            preprocessed,
+        }
+    }
+
+    /// Notifies the observer that `stage` was entered.
+    pub fn report(&self, stage: ProofStage) {
+        self.observer.on_progress(&Progress {
+            stage,
+            elapsed: self.start.elapsed(),
+            percent: stage.estimated_percent(self.preprocessed),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    const STAGES: [ProofStage; 5] = [
+        ProofStage::Setup,
+        ProofStage::GuestCompile,
+        ProofStage::Preprocessing,
+        ProofStage::Proving,
+        ProofStage::Done,
+    ];
+
+    #[test]
+    fn test_estimates_increase_to_100() {
+        for preprocessed in [false, true] {
+            let percents: Vec<u8> = STAGES
+                .iter()
+                .filter(|stage| {
+                    !preprocessed
+                        || !matches!(stage, ProofStage::GuestCompile | ProofStage::Preprocessing)
+                })
+                .map(|stage| stage.estimated_percent(preprocessed))
+                .collect();
+            assert!(percents.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(percents.last(), Some(&100));
+        }
+    }
+
+    #[test]
+    fn test_reporter_notifies_observer() {
+        let seen = RefCell::new(Vec::new());
+        let observer = |progress: &Progress| seen.borrow_mut().push(*progress);
+        let reporter = ProgressReporter::new(&observer, true);
+
+        reporter.report(ProofStage::Setup);
+        reporter.report(ProofStage::Done);
+
+        let seen = seen.into_inner();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0].stage, ProofStage::Setup);
+        assert_eq!(seen[1].percent, 100);
+        assert!(seen[1].elapsed >= seen[0].elapsed);
+    }
+}
@@ -0,0 +1,194 @@
+//! Versioned on-disk proof files.
+//!
+//! [`AggregationProof::write_to`] stores a proof with a header identifying
+//! the format and a checksum over the contents, and
+//! [`AggregationProof::read_from`] checks both before decoding:
+//!
+//! | Field | Encoding |
+//! |---|---|
+//! | magic | `b"SAGF"` |
+//! | version | u16 little-endian, currently [`PROOF_FILE_VERSION`] |
+//! | payload length | u64 little-endian |
+//! | payload | bincode [`AggregationProof`] |
+//! | checksum | SHA-256 of the payload |
+//!
+//! The version identifies the payload layout, and files of any other
+//! version are rejected.
+//!
+//! For untrusted input from the network, prefer the
+//! [`envelope`](crate::envelope) format, which bounds what is read before
+//! the proof body.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::AggregationProof;
+//! # let proof: AggregationProof = unimplemented!();
+//!
+//! proof.write_to("batch-42.proof").expect("Write failed");
+//! let stored = AggregationProof::read_from("batch-42.proof").expect("Corrupt proof file");
+//! assert_eq!(stored.verified_count, proof.verified_count);
+//! ```
+
+use std::{fs, path::Path};
+
+use sha2::{Digest, Sha256};
+
+use crate::{error::AggregationError, types::AggregationProof};
+
+/// Leading bytes of every proof file.
+pub const PROOF_FILE_MAGIC: [u8; 4] = *b"SAGF";
+
+/// Proof file format version written by [`AggregationProof::write_to`].
+pub const PROOF_FILE_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = 4 + 2 + 8;
+const CHECKSUM_LEN: usize = 32;
+
+impl AggregationProof {
+    /// Writes the proof to `path` in the proof file format.
+    ///
+    /// # Errors
+    ///
+    /// * `SerializationError` - the proof could not be encoded
+    /// * `IoError` - the file could not be written
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<(), AggregationError> {
+        fs::write(path, self.to_file_bytes()?)?;
+        Ok(())
+    }
+
+    /// Reads a proof written by [`AggregationProof::write_to`].
+    ///
+    /// # Errors
+    ///
+    /// * `IoError` - the file could not be read
+    /// * `SerializationError` - wrong magic, unsupported version, truncated
+    ///   file, checksum mismatch or undecodable payload
+    pub fn read_from<P: AsRef<Path>>(path: P) -> Result<Self, AggregationError> {
+        Self::from_file_bytes(&fs::read(path)?)
+    }
+
+    fn to_file_bytes(&self) -> Result<Vec<u8>, AggregationError> {
+        let payload =
+            bincode::serialize(self).map_err(|e| AggregationError::SerializationError {
+                message: format!("Failed to serialize proof: {}", e),
+            })?;
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len() + CHECKSUM_LEN);
+        bytes.extend_from_slice(&PROOF_FILE_MAGIC);
+        bytes.extend_from_slice(&PROOF_FILE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        bytes.extend_from_slice(&Sha256::digest(&payload));
+        Ok(bytes)
+    }
+
+    fn from_file_bytes(bytes: &[u8]) -> Result<Self, AggregationError> {
+        if bytes.len() < HEADER_LEN + CHECKSUM_LEN {
+            return Err(invalid("file is truncated"));
+        }
+        if bytes[..4] != PROOF_FILE_MAGIC {
+            return Err(invalid("not a proof file"));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != PROOF_FILE_VERSION {
+            return Err(invalid(&format!("unsupported version {}", version)));
+        }
+
+        let mut length = [0u8; 8];
+        length.copy_from_slice(&bytes[6..HEADER_LEN]);
+        let body = &bytes[HEADER_LEN..];
+        if u64::try_from(body.len() - CHECKSUM_LEN).ok() != Some(u64::from_le_bytes(length)) {
+            return Err(invalid("payload length does not match file size"));
+        }
+
+        let (payload, checksum) = body.split_at(body.len() - CHECKSUM_LEN);
+        if Sha256::digest(payload).as_slice() != checksum {
+            return Err(invalid("checksum mismatch"));
+        }
+        bincode::deserialize(payload).map_err(|e| invalid(&e.to_string()))
+    }
+}
+
+fn invalid(reason: &str) -> AggregationError {
+    AggregationError::SerializationError {
+        message: format!("Invalid proof file: {}", reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commitment::CommitmentAlgorithm, profile::ComplianceProfile, types::ProofMetadata,
+    };
+    use std::env;
+
+    fn test_proof() -> AggregationProof {
+        AggregationProof {
+            proof: vec![0xab; 1000],
+            verified_count: 4,
+            metadata: ProofMetadata {
+                timestamp: 0,
+                batch_size: 4,
+                memory_size: 0,
+                trace_length: 0,
+                max_trace_length: 0,
+                proof_size: 0,
+                guest_hash: [1u8; 32],
+                urs_digest: [2u8; 32],
+                commitment_hash: CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: None,
+                profile: ComplianceProfile::Standard,
+            },
+            failed: vec![0b0000_0010],
+            verified_root: [3u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_round_trip_through_file() {
+        let dir = env::temp_dir().join("sig-agg-proof-file-tests");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("round-trip.proof");
+
+        test_proof().write_to(&path).unwrap();
+        let read = AggregationProof::read_from(&path).unwrap();
+        assert_eq!(read.proof, test_proof().proof);
+        assert_eq!(read.verified_count, 4);
+        assert_eq!(read.metadata.guest_hash, [1u8; 32]);
+        assert_eq!(read.verified_root, [3u8; 32]);
+
+        assert!(matches!(
+            AggregationProof::read_from(dir.join("missing.proof")),
+            Err(AggregationError::IoError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_corrupt_files() {
+        let bytes = test_proof().to_file_bytes().unwrap();
+        assert_eq!(&bytes[..4], b"SAGF");
+
+        let mut flipped = bytes.clone();
+        flipped[HEADER_LEN + 10] ^= 1;
+        let mut future_version = bytes.clone();
+        future_version[4] = 2;
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'X';
+
+        for corrupt in [
+            &flipped[..],
+            &future_version[..],
+            &wrong_magic[..],
+            &bytes[..bytes.len() - 1],
+            &bytes[..HEADER_LEN],
+        ] {
+            assert!(matches!(
+                AggregationProof::from_file_bytes(corrupt),
+                Err(AggregationError::SerializationError { .. })
+            ));
+        }
+    }
+}
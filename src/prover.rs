@@ -0,0 +1,796 @@
+//! Host-side proving with the Jolt zkVM.
+//!
+//! This module (behind the `jolt` feature) wraps the steps the benchmark
+//! binary performs by hand: compiling the guest, preprocessing, proving, and
+//! packaging the result as an [`AggregationProof`] with real proof bytes and
+//! populated [`ProofMetadata`].
+//!
+//! Compilation and preprocessing dominate the cost of a single small proof.
+//! [`prove_batch`] redoes them on every call. A [`Prover`] does them once
+//! and can then prove any number of batches, e.g. with
+//! [`prove_per_signer`](crate::aggregator::prove_per_signer), which is what
+//! a service proving many batches should use.
+//!
+//! The `*_with_progress` variants report each [`ProofStage`] to a
+//! [`ProgressObserver`].
+//!
+//! Applications verify proofs with [`verify_aggregation_proof`], given the
+//! proven batch and the verifier preprocessing of the guest that produced the
+//! proof ([`Prover::verifier_preprocessing`] or
+//! [`load_verifier_preprocessing`]).
+//!
+//! In [`GuestMode::Strict`] the prover proves the all-or-nothing
+//! `verify_aggregation_strict` entry point instead: proving fails if any
+//! signature is invalid, and [`verify_strict_proof`] checks the result.
+//!
+//! [`GuestMode::Tier`] proves with one of the tiered entry points, whose
+//! memory and trace limits are sized for a batch size class (see
+//! [`GuestTier`]); [`verify_tier_proof`] checks the result. [`prove_batch`]
+//! picks the smallest tier a batch fits, so small batches are not proven
+//! with large-batch limits.
+//!
+//! [`JoltBackend`] exposes the same steps as a [`ZkVmBackend`], for code
+//! that is generic over the proving system.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::prover::{Prover, ProverConfig};
+//! # let batches: Vec<sig_agg::AggregationBatch> = vec![];
+//!
+//! let prover = Prover::new(&ProverConfig::default()).expect("Guest compilation failed");
+//! for batch in batches {
+//!     let proof = prover.prove(batch).expect("Proving failed");
+//!     println!("{} bytes", proof.proof.len());
+//! }
+//! ```
+
+use std::{
+    panic::{AssertUnwindSafe, catch_unwind},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use jolt_sdk::{JoltDevice, JoltVerifierPreprocessing, RV64IMACProof, Serializable};
+
+use crate::{
+    aggregator::validate,
+    backend::{ZkVmBackend, check_batch_size, check_input_digest, expected_output},
+    commitment::CommitmentAlgorithm,
+    error::{AggregationError, panic_message},
+    guest::{GuestHash, input_digest},
+    inclusion::verified_root,
+    limits::{self, GuestLimits, GuestTier},
+    native::{GuestOutput, to_guest_batch},
+    profile::ComplianceProfile,
+    progress::{NoProgress, ProgressObserver, ProgressReporter, ProofStage},
+    report::CostMeter,
+    retry::{RetryMetrics, RetryPolicy},
+    types::{AggregationBatch, AggregationProof, ProofMetadata},
+    urs::UrsDigest,
+};
+
+/// `memory_size` of the `verify_aggregation` guest, in bytes.
+pub const GUEST_MEMORY_SIZE: usize = limits::VERIFY_AGGREGATION.memory_size;
+
+/// `max_trace_length` of the `verify_aggregation` guest.
+pub const GUEST_MAX_TRACE_LENGTH: usize = limits::VERIFY_AGGREGATION.max_trace_length;
+
+/// Verifier preprocessing of the `verify_aggregation` guest.
+pub type VerifierPreprocessing = JoltVerifierPreprocessing<jolt_sdk::F, jolt_sdk::PCS>;
+
+/// Guest entry point to prove with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GuestMode {
+    /// `verify_aggregation`: proves how many signatures are valid and which
+    /// ones failed
+    #[default]
+    Counting,
+    /// `verify_aggregation_strict`: proves that every signature is valid;
+    /// proving fails if any is not
+    Strict,
+    /// The tier's entry point: same as `Counting`, with limits sized for the
+    /// tier; proving fails if the batch exceeds the tier's capacity
+    Tier(GuestTier),
+}
+
+impl GuestMode {
+    /// Resource limits of the mode's entry point.
+    #[must_use]
+    pub const fn limits(self) -> GuestLimits {
+        match self {
+            Self::Counting => limits::VERIFY_AGGREGATION,
+            Self::Strict => limits::VERIFY_AGGREGATION_STRICT,
+            Self::Tier(tier) => tier.limits(),
+        }
+    }
+
+    /// [`GuestMode::Tier`] with the smallest tier that fits `batch_size`
+    /// items, or [`GuestMode::Counting`] if none does.
+    #[must_use]
+    pub fn for_batch_size(batch_size: usize) -> Self {
+        GuestTier::for_batch_size(batch_size).map_or(Self::Counting, Self::Tier)
+    }
+}
+
+/// How the guest program is built.
+///
+/// Jolt builds the guest with a child `cargo` process, which inherits the
+/// process environment, so its job count and offline mode come from
+/// `CARGO_BUILD_JOBS` and `CARGO_NET_OFFLINE`. The library does not set
+/// them: changing the environment is unsound once other threads may read
+/// it. Binaries set them at startup instead, as the benchmark does for its
+/// `--jobs` and `--offline` flags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuestBuildConfig {
+    /// Cargo target directory for the guest build
+    pub target_dir: PathBuf,
+}
+
+impl Default for GuestBuildConfig {
+    fn default() -> Self {
+        Self {
+            target_dir: PathBuf::from("/tmp/jolt-guest-targets"),
+        }
+    }
+}
+
+impl GuestBuildConfig {
+    /// Runs a guest `compile` function (e.g.
+    /// `guest::compile_verify_aggregation`) with this configuration.
+    pub fn compile<T>(&self, compile: impl FnOnce(&str) -> T) -> T {
+        compile(&self.target_dir.to_string_lossy())
+    }
+}
+
+/// Where to build the guest and what to record in proof metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProverConfig {
+    /// Guest build settings
+    pub build: GuestBuildConfig,
+    /// Guest hash recorded in [`ProofMetadata::guest_hash`]
+    pub guest_hash: GuestHash,
+    /// URS digest recorded in [`ProofMetadata::urs_digest`], see
+    /// [`urs_file_digest`](crate::urs::urs_file_digest)
+    pub urs_digest: UrsDigest,
+    /// Guest entry point to prove with
+    pub mode: GuestMode,
+}
+
+impl Default for ProverConfig {
+    fn default() -> Self {
+        Self {
+            build: GuestBuildConfig::default(),
+            guest_hash: [0u8; 32],
+            urs_digest: [0u8; 32],
+            mode: GuestMode::Counting,
+        }
+    }
+}
+
+/// Measurements of one proven guest run.
+struct GuestRun {
+    output: GuestOutput,
+    trace_length: usize,
+    /// Guest memory size reported by Jolt, in bytes
+    memory_size: usize,
+    proof: Vec<u8>,
+}
+
+type ProveFn = dyn Fn(guest::AggregationBatch) -> Result<GuestRun, AggregationError> + Send + Sync;
+
+/// Any guest entry point's prover, with the strict output widened to a
+/// [`GuestOutput`].
+type StrictOrCountingFn =
+    dyn Fn(guest::AggregationBatch) -> (GuestOutput, RV64IMACProof, JoltDevice) + Send + Sync;
+
+/// Guest program compiled by Jolt, ready to be preprocessed.
+pub type Program = jolt_sdk::host::Program;
+
+/// Compiles the entry point of `config.mode`.
+fn compile_program(config: &ProverConfig) -> Program {
+    let compile = match config.mode {
+        GuestMode::Counting => guest::compile_verify_aggregation,
+        GuestMode::Strict => guest::compile_verify_aggregation_strict,
+        GuestMode::Tier(GuestTier::Small) => guest::compile_verify_aggregation_small,
+        GuestMode::Tier(GuestTier::Medium) => guest::compile_verify_aggregation_medium,
+        GuestMode::Tier(GuestTier::Large) => guest::compile_verify_aggregation_large,
+    };
+    config.build.compile(compile)
+}
+
+/// Preprocesses a compiled counting entry point (`verify_aggregation` or a
+/// tier) from the names of its generated guest functions, returning its
+/// prover and verifier preprocessing.
+macro_rules! counting_prover {
+    ($program:ident, $preprocess:ident, $verifier_from:ident, $build:ident) => {{
+        let preprocessing = guest::$preprocess(&mut $program);
+        let verifier_preprocessing = guest::$verifier_from(&preprocessing);
+        let prove_fn: Box<StrictOrCountingFn> = Box::new(guest::$build($program, preprocessing));
+        (prove_fn, verifier_preprocessing)
+    }};
+}
+
+/// Output of a strict guest run over `len` items: all verified, none failed.
+///
+/// The strict guest outputs no root; since every item verified, it is the
+/// root over all of them.
+fn strict_output(len: usize, input_digest: [u8; 32], verified_root: [u8; 32]) -> GuestOutput {
+    GuestOutput {
+        verified_count: u32::try_from(len).unwrap_or(u32::MAX),
+        input_digest,
+        verified_root,
+        failed: vec![0u8; len.div_ceil(8)],
+    }
+}
+
+/// Checks that a strict proof over `len` items claims what the strict guest
+/// attests: every item verified and none failed.
+fn check_strict_claims(proof: &AggregationProof, len: usize) -> Result<(), AggregationError> {
+    let expected = strict_output(len, [0u8; 32], proof.verified_root);
+    if proof.verified_count != expected.verified_count || proof.failed != expected.failed {
+        return Err(AggregationError::InvalidProof);
+    }
+    Ok(())
+}
+
+/// Compiled and preprocessed guest, ready to prove any number of batches.
+pub struct Prover {
+    config: ProverConfig,
+    prove: Box<ProveFn>,
+    verifier_preprocessing: VerifierPreprocessing,
+    retry: RetryPolicy,
+    retry_metrics: RetryMetrics,
+}
+
+impl std::fmt::Debug for Prover {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Prover")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Prover {
+    /// Compiles the guest and runs prover and verifier preprocessing.
+    ///
+    /// Fails with [`AggregationError::CompilationError`] if the guest does
+    /// not build or preprocessing panics.
+    pub fn new(config: &ProverConfig) -> Result<Self, AggregationError> {
+        Self::build(config, &ProgressReporter::new(&NoProgress, false))
+    }
+
+    /// Same as [`Prover::new`], reporting the compile and preprocessing
+    /// stages to `observer`.
+    pub fn new_with_progress(
+        config: &ProverConfig,
+        observer: &dyn ProgressObserver,
+    ) -> Result<Self, AggregationError> {
+        Self::build(config, &ProgressReporter::new(observer, false))
+    }
+
+    fn build(
+        config: &ProverConfig,
+        reporter: &ProgressReporter<'_>,
+    ) -> Result<Self, AggregationError> {
+        reporter.report(ProofStage::GuestCompile);
+        let program = JoltBackend::compile(config)?;
+        reporter.report(ProofStage::Preprocessing);
+        Self::from_program(config, program)
+    }
+
+    /// Preprocesses a `program` compiled for `config`.
+    fn from_program(config: &ProverConfig, mut program: Program) -> Result<Self, AggregationError> {
+        let (prove_fn, verifier_preprocessing) =
+            catch_unwind(AssertUnwindSafe(|| match config.mode {
+                GuestMode::Counting => counting_prover!(
+                    program,
+                    preprocess_prover_verify_aggregation,
+                    verifier_preprocessing_from_prover_verify_aggregation,
+                    build_prover_verify_aggregation
+                ),
+                GuestMode::Tier(GuestTier::Small) => counting_prover!(
+                    program,
+                    preprocess_prover_verify_aggregation_small,
+                    verifier_preprocessing_from_prover_verify_aggregation_small,
+                    build_prover_verify_aggregation_small
+                ),
+                GuestMode::Tier(GuestTier::Medium) => counting_prover!(
+                    program,
+                    preprocess_prover_verify_aggregation_medium,
+                    verifier_preprocessing_from_prover_verify_aggregation_medium,
+                    build_prover_verify_aggregation_medium
+                ),
+                GuestMode::Tier(GuestTier::Large) => counting_prover!(
+                    program,
+                    preprocess_prover_verify_aggregation_large,
+                    verifier_preprocessing_from_prover_verify_aggregation_large,
+                    build_prover_verify_aggregation_large
+                ),
+                GuestMode::Strict => {
+                    let preprocessing =
+                        guest::preprocess_prover_verify_aggregation_strict(&mut program);
+                    let verifier_preprocessing =
+                        guest::verifier_preprocessing_from_prover_verify_aggregation_strict(
+                            &preprocessing,
+                        );
+                    let prove_strict =
+                        guest::build_prover_verify_aggregation_strict(program, preprocessing);
+                    let prove_fn: Box<StrictOrCountingFn> =
+                        Box::new(move |batch: guest::AggregationBatch| {
+                            let len = batch.items.len();
+                            let root = guest::merkle_root(
+                                batch.items.iter().map(guest::item_leaf).collect(),
+                            );
+                            let (digest, proof, program_io) = prove_strict(batch);
+                            (strict_output(len, digest, root), proof, program_io)
+                        });
+                    (prove_fn, verifier_preprocessing)
+                }
+            }))
+            .map_err(|panic| AggregationError::CompilationError {
+                message: panic_message(&*panic),
+            })?;
+
+        let prove = move |batch: guest::AggregationBatch| -> Result<_, AggregationError> {
+            let (output, proof, program_io) = catch_unwind(AssertUnwindSafe(|| prove_fn(batch)))
+                .map_err(|panic| AggregationError::ProofGenerationError {
+                    message: panic_message(&*panic),
+                })?;
+            if program_io.panic {
+                return Err(AggregationError::ProofGenerationError {
+                    message: "Guest program panicked".to_string(),
+                });
+            }
+
+            let trace_length = proof.trace_length;
+            let memory_size = program_io.memory_layout.memory_size as usize;
+            let bytes =
+                proof
+                    .serialize_to_bytes()
+                    .map_err(|e| AggregationError::SerializationError {
+                        message: format!("Failed to serialize proof: {}", e),
+                    })?;
+            Ok(GuestRun {
+                output,
+                trace_length,
+                memory_size,
+                proof: bytes,
+            })
+        };
+
+        Ok(Self {
+            config: config.clone(),
+            prove: Box::new(prove),
+            verifier_preprocessing,
+            retry: RetryPolicy::no_retry(),
+            retry_metrics: RetryMetrics::new(),
+        })
+    }
+
+    /// Retries proving runs that fail with a retryable error according to
+    /// `policy`.
+    ///
+    /// Provers do not retry by default: a guest that panics on a batch
+    /// panics again on every attempt, so retries only pay off where runs
+    /// fail for environmental reasons, e.g. the prover being killed for
+    /// memory.
+    #[must_use]
+    pub const fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Retry activity of this prover's proving runs.
+    #[must_use]
+    pub const fn retry_metrics(&self) -> &RetryMetrics {
+        &self.retry_metrics
+    }
+
+    /// Verifier preprocessing matching this prover's guest, for
+    /// [`verify_aggregation_proof`].
+    #[must_use]
+    pub const fn verifier_preprocessing(&self) -> &VerifierPreprocessing {
+        &self.verifier_preprocessing
+    }
+
+    /// Serializes the verifier preprocessing for distribution to verifiers,
+    /// who load it with [`load_verifier_preprocessing`].
+    pub fn export_verifier_preprocessing(&self) -> Result<Vec<u8>, AggregationError> {
+        self.verifier_preprocessing
+            .serialize_to_bytes()
+            .map_err(|e| AggregationError::SerializationError {
+                message: format!("Failed to serialize verifier preprocessing: {}", e),
+            })
+    }
+
+    /// Verifies a proof produced by this prover's guest.
+    ///
+    /// Same as [`verify_aggregation_proof`], or [`verify_strict_proof`] in
+    /// [`GuestMode::Strict`] and [`verify_tier_proof`] in
+    /// [`GuestMode::Tier`], with this prover's preprocessing.
+    pub fn verify(
+        &self,
+        proof: &AggregationProof,
+        batch: &AggregationBatch,
+    ) -> Result<u32, AggregationError> {
+        verify_in_mode(self.config.mode, proof, batch, &self.verifier_preprocessing)
+    }
+
+    /// Validates and proves `batch`.
+    ///
+    /// # Errors
+    ///
+    /// * any error of [`validate`](crate::validate())
+    /// * `BatchTooLarge` - the batch exceeds the [`GuestMode::Tier`] capacity
+    /// * `SerializationError` - the batch or proof could not be encoded
+    /// * `ProofGenerationError` - proving failed or the guest panicked
+    pub fn prove(&self, batch: AggregationBatch) -> Result<AggregationProof, AggregationError> {
+        self.prove_with_progress(batch, &NoProgress)
+    }
+
+    /// Same as [`Prover::prove`], reporting each stage to `observer`.
+    pub fn prove_with_progress(
+        &self,
+        batch: AggregationBatch,
+        observer: &dyn ProgressObserver,
+    ) -> Result<AggregationProof, AggregationError> {
+        let reporter = ProgressReporter::new(observer, true);
+        reporter.report(ProofStage::Setup);
+        self.prove_reporting(batch, &reporter)
+    }
+
+    fn prove_reporting(
+        &self,
+        batch: AggregationBatch,
+        reporter: &ProgressReporter<'_>,
+    ) -> Result<AggregationProof, AggregationError> {
+        validate(&batch.items)?;
+        let batch_size = batch.items.len();
+        if let GuestMode::Tier(tier) = self.config.mode
+            && batch_size > tier.capacity()
+        {
+            return Err(AggregationError::BatchTooLarge {
+                size: batch_size,
+                max: tier.capacity(),
+            });
+        }
+        let meter = CostMeter::start();
+        let expected_digest = input_digest(&batch)?;
+        reporter.report(ProofStage::Proving);
+        let run = self.retry.run(&self.retry_metrics, || {
+            (self.prove)(to_guest_batch(&batch)?)
+        })?;
+        let output = run.output;
+        check_input_digest(&output, &expected_digest)?;
+        let cost = meter.finish(1);
+        reporter.report(ProofStage::Done);
+
+        Ok(AggregationProof {
+            verified_count: output.verified_count,
+            metadata: ProofMetadata {
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_secs()),
+                batch_size,
+                memory_size: run.memory_size / (1024 * 1024),
+                trace_length: run.trace_length,
+                max_trace_length: self.config.mode.limits().max_trace_length,
+                proof_size: run.proof.len(),
+                guest_hash: self.config.guest_hash,
+                urs_digest: self.config.urs_digest,
+                commitment_hash: CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: Some(cost),
+                profile: ComplianceProfile::ACTIVE,
+            },
+            proof: run.proof,
+            failed: output.failed,
+            verified_root: output.verified_root,
+        })
+    }
+}
+
+/// Verifier preprocessing together with the guest entry point it belongs to.
+#[derive(Clone)]
+pub struct JoltVerifierKey {
+    /// Entry point the preprocessing belongs to
+    pub mode: GuestMode,
+    /// Verifier preprocessing of the entry point
+    pub preprocessing: VerifierPreprocessing,
+}
+
+impl std::fmt::Debug for JoltVerifierKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JoltVerifierKey")
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The Jolt zkVM as a [`ZkVmBackend`].
+///
+/// Compiles the entry point of [`ProverConfig::mode`] and preprocesses it
+/// into a [`Prover`]; proofs are checked like [`Prover::verify`] does.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JoltBackend;
+
+impl ZkVmBackend for JoltBackend {
+    const NAME: &'static str = "jolt";
+
+    type Config = ProverConfig;
+    type Program = Program;
+    type Prover = Prover;
+    type VerifierKey = JoltVerifierKey;
+
+    /// Fails with [`AggregationError::CompilationError`] if the guest does
+    /// not build.
+    fn compile(config: &ProverConfig) -> Result<Program, AggregationError> {
+        catch_unwind(AssertUnwindSafe(|| compile_program(config))).map_err(|panic| {
+            AggregationError::CompilationError {
+                message: panic_message(&*panic),
+            }
+        })
+    }
+
+    fn preprocess(
+        config: &ProverConfig,
+        program: Program,
+    ) -> Result<(Prover, JoltVerifierKey), AggregationError> {
+        let prover = Prover::from_program(config, program)?;
+        let key = JoltVerifierKey {
+            mode: config.mode,
+            preprocessing: prover.verifier_preprocessing.clone(),
+        };
+        Ok((prover, key))
+    }
+
+    fn prove(
+        prover: &Prover,
+        batch: AggregationBatch,
+    ) -> Result<AggregationProof, AggregationError> {
+        prover.prove(batch)
+    }
+
+    fn verify(
+        key: &JoltVerifierKey,
+        proof: &AggregationProof,
+        batch: &AggregationBatch,
+    ) -> Result<u32, AggregationError> {
+        verify_in_mode(key.mode, proof, batch, &key.preprocessing)
+    }
+}
+
+/// Checks a proof of the entry point of `mode`.
+fn verify_in_mode(
+    mode: GuestMode,
+    proof: &AggregationProof,
+    batch: &AggregationBatch,
+    preprocessing: &VerifierPreprocessing,
+) -> Result<u32, AggregationError> {
+    match mode {
+        GuestMode::Counting => verify_aggregation_proof(proof, batch, preprocessing),
+        GuestMode::Strict => {
+            verify_strict_proof(proof, batch, preprocessing).map(|()| proof.verified_count)
+        }
+        GuestMode::Tier(tier) => verify_tier_proof(proof, batch, tier, preprocessing),
+    }
+}
+
+/// Compiles, preprocesses and proves `batch` with the default configuration,
+/// in the smallest [`GuestMode::Tier`] that fits the batch.
+///
+/// Convenient for one-off proofs; use a [`Prover`] to amortize compilation
+/// and preprocessing across batches.
+pub fn prove_batch(batch: AggregationBatch) -> Result<AggregationProof, AggregationError> {
+    prove_batch_with_progress(batch, &NoProgress)
+}
+
+/// Same as [`prove_batch`], reporting every stage from setup through guest
+/// compilation and preprocessing to proving to `observer`.
+pub fn prove_batch_with_progress(
+    batch: AggregationBatch,
+    observer: &dyn ProgressObserver,
+) -> Result<AggregationProof, AggregationError> {
+    let reporter = ProgressReporter::new(observer, false);
+    reporter.report(ProofStage::Setup);
+    let config = ProverConfig {
+        mode: GuestMode::for_batch_size(batch.items.len()),
+        ..ProverConfig::default()
+    };
+    Prover::build(&config, &reporter)?.prove_reporting(batch, &reporter)
+}
+
+/// Decodes verifier preprocessing serialized with Jolt's `Serializable`.
+pub fn load_verifier_preprocessing(
+    bytes: &[u8],
+) -> Result<VerifierPreprocessing, AggregationError> {
+    VerifierPreprocessing::deserialize_from_bytes(bytes).map_err(|e| {
+        AggregationError::SerializationError {
+            message: format!("Invalid verifier preprocessing: {}", e),
+        }
+    })
+}
+
+/// Verifies `proof` over `batch`, returning the number of valid signatures
+/// it attests.
+///
+/// The batch is the proof's public input, so the verifier needs the exact
+/// batch that was proven; `preprocessing` must belong to the guest recorded
+/// in the proof's metadata (see [`GuestRegistry`](crate::guest::GuestRegistry)).
+///
+/// # Errors
+///
+/// * `LengthMismatch` - the batch size differs from the proof metadata
+/// * `SerializationError` - the batch or proof bytes cannot be decoded
+/// * `InvalidProof` - the proof does not verify
+pub fn verify_aggregation_proof(
+    proof: &AggregationProof,
+    batch: &AggregationBatch,
+    preprocessing: &VerifierPreprocessing,
+) -> Result<u32, AggregationError> {
+    verify_counting_proof(
+        proof,
+        batch,
+        guest::build_verifier_verify_aggregation(preprocessing.clone()),
+    )
+}
+
+/// Verifies a proof from a [`GuestMode::Tier`] prover of `tier`.
+///
+/// `preprocessing` must belong to the tier's entry point.
+///
+/// # Errors
+///
+/// Same as [`verify_aggregation_proof`].
+pub fn verify_tier_proof(
+    proof: &AggregationProof,
+    batch: &AggregationBatch,
+    tier: GuestTier,
+    preprocessing: &VerifierPreprocessing,
+) -> Result<u32, AggregationError> {
+    let preprocessing = preprocessing.clone();
+    match tier {
+        GuestTier::Small => verify_counting_proof(
+            proof,
+            batch,
+            guest::build_verifier_verify_aggregation_small(preprocessing),
+        ),
+        GuestTier::Medium => verify_counting_proof(
+            proof,
+            batch,
+            guest::build_verifier_verify_aggregation_medium(preprocessing),
+        ),
+        GuestTier::Large => verify_counting_proof(
+            proof,
+            batch,
+            guest::build_verifier_verify_aggregation_large(preprocessing),
+        ),
+    }
+}
+
+/// Checks a proof of a counting entry point with that entry point's
+/// `verify` function.
+fn verify_counting_proof(
+    proof: &AggregationProof,
+    batch: &AggregationBatch,
+    verify: impl Fn(guest::AggregationBatch, GuestOutput, bool, RV64IMACProof) -> bool,
+) -> Result<u32, AggregationError> {
+    // The proof only verifies if the guest echoed the digest of this batch
+    let expected_output = expected_output(proof, batch)?;
+    let guest_batch = to_guest_batch(batch)?;
+    let jolt_proof = RV64IMACProof::deserialize_from_bytes(&proof.proof).map_err(|e| {
+        AggregationError::SerializationError {
+            message: format!("Invalid proof bytes: {}", e),
+        }
+    })?;
+
+    // A malformed proof may panic inside the verifier rather than return false
+    let valid = catch_unwind(AssertUnwindSafe(|| {
+        verify(guest_batch, expected_output, false, jolt_proof)
+    }))
+    .unwrap_or(false);
+
+    if !valid {
+        return Err(AggregationError::InvalidProof);
+    }
+    Ok(proof.verified_count)
+}
+
+/// Verifies a proof from a [`GuestMode::Strict`] prover, which attests that
+/// every signature in `batch` is valid.
+///
+/// `preprocessing` must belong to the strict guest.
+///
+/// # Errors
+///
+/// Same as [`verify_aggregation_proof`]; a proof claiming any count other
+/// than the batch size, or any failed item, is an `InvalidProof`.
+pub fn verify_strict_proof(
+    proof: &AggregationProof,
+    batch: &AggregationBatch,
+    preprocessing: &VerifierPreprocessing,
+) -> Result<(), AggregationError> {
+    check_batch_size(proof, batch)?;
+
+    // The strict guest outputs no root, count or bitmap, so they are checked
+    // against the batch
+    check_strict_claims(proof, batch.items.len())?;
+    if proof.verified_root != verified_root(batch, &[])? {
+        return Err(AggregationError::InvalidProof);
+    }
+
+    let expected_digest = input_digest(batch)?;
+    let guest_batch = to_guest_batch(batch)?;
+    let jolt_proof = RV64IMACProof::deserialize_from_bytes(&proof.proof).map_err(|e| {
+        AggregationError::SerializationError {
+            message: format!("Invalid proof bytes: {}", e),
+        }
+    })?;
+
+    let verify = guest::build_verifier_verify_aggregation_strict(preprocessing.clone());
+    let valid = catch_unwind(AssertUnwindSafe(|| {
+        verify(guest_batch, expected_digest, false, jolt_proof)
+    }))
+    .unwrap_or(false);
+
+    if !valid {
+        return Err(AggregationError::InvalidProof);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strict_proof(len: usize) -> AggregationProof {
+        let output = strict_output(len, [0u8; 32], [0u8; 32]);
+        AggregationProof {
+            proof: vec![],
+            verified_count: output.verified_count,
+            metadata: ProofMetadata {
+                timestamp: 0,
+                batch_size: len,
+                memory_size: GUEST_MEMORY_SIZE,
+                trace_length: 0,
+                max_trace_length: GUEST_MAX_TRACE_LENGTH,
+                proof_size: 0,
+                guest_hash: [0u8; 32],
+                urs_digest: [0u8; 32],
+                commitment_hash: CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: None,
+                profile: ComplianceProfile::Standard,
+            },
+            failed: output.failed,
+            verified_root: output.verified_root,
+        }
+    }
+
+    #[test]
+    fn test_strict_claims_reject_tampered_count() {
+        assert_eq!(check_strict_claims(&strict_proof(9), 9), Ok(()));
+
+        let mut proof = strict_proof(9);
+        proof.verified_count = 100;
+        assert_eq!(
+            check_strict_claims(&proof, 9),
+            Err(AggregationError::InvalidProof)
+        );
+
+        let mut proof = strict_proof(9);
+        proof.failed[1] = 1;
+        assert_eq!(
+            check_strict_claims(&proof, 9),
+            Err(AggregationError::InvalidProof)
+        );
+
+        let mut proof = strict_proof(9);
+        proof.failed.clear();
+        assert_eq!(
+            check_strict_claims(&proof, 9),
+            Err(AggregationError::InvalidProof)
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Verifier-side replay protection.
+//!
+//! A valid proof stays valid forever. When accepting a proof triggers an
+//! irreversible action, such as releasing funds, the same proof must not be
+//! accepted twice. [`ReplayGuard`] remembers the IDs of recently consumed
+//! proofs and rejects any ID it has already seen within its window.
+//!
+//! The ID is up to the caller. [`proof_id`] identifies the proof bytes and
+//! the public outputs they prove, ignoring metadata that anyone can rewrite.
+//! A [batch commitment](crate::commitment) identifies the batch, which also
+//! catches a second, differently randomized proof of the same batch.
+//!
+//! The window is bounded by [`ReplayPolicy::capacity`] and, optionally, by
+//! [`ReplayPolicy::ttl`]. IDs evicted from the window are accepted again, so
+//! the window must cover the period in which a replay would be harmful. By
+//! default a full window rejects new IDs with
+//! [`AggregationError::ReplayWindowFull`] until the TTL expires some; set
+//! [`ReplayPolicy::evict_when_full`] to evict the oldest ID instead. The
+//! cache lives in memory; verifiers that restart must persist their own
+//! consumed set or bind proofs to a nonce.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::replay::{ReplayGuard, ReplayPolicy, proof_id};
+//! # let proof: sig_agg::AggregationProof = unimplemented!();
+//!
+//! let mut guard = ReplayGuard::new(ReplayPolicy::default());
+//!
+//! // After the proof has been verified:
+//! guard.consume(proof_id(&proof))
+//!     .expect("Proof was already consumed");
+//! ```
+
+use std::{
+    collections::{HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{error::AggregationError, types::AggregationProof};
+
+/// 32-byte identifier of a consumed proof.
+pub type ProofId = [u8; 32];
+
+/// Domain tag of [`proof_id`].
+const PROOF_ID_DOMAIN: &[u8] = b"sig-agg/proof-id/v1";
+
+/// Size and age limits of the replay window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayPolicy {
+    /// Maximum number of remembered IDs
+    pub capacity: usize,
+    /// Forget IDs older than this, if set
+    pub ttl: Option<Duration>,
+    /// When full, evict the oldest ID rather than reject the new one
+    pub evict_when_full: bool,
+}
+
+impl Default for ReplayPolicy {
+    fn default() -> Self {
+        Self {
+            capacity: 100_000,
+            ttl: None,
+            evict_when_full: false,
+        }
+    }
+}
+
+/// Cache of recently consumed proof IDs.
+#[derive(Debug, Clone)]
+pub struct ReplayGuard {
+    policy: ReplayPolicy,
+    seen: HashSet<ProofId>,
+    order: VecDeque<(ProofId, Instant)>,
+}
+
+impl ReplayGuard {
+    /// Creates an empty guard.
+    #[must_use]
+    pub fn new(policy: ReplayPolicy) -> Self {
+        Self {
+            policy,
+            seen: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Records `id` as consumed, failing with
+    /// [`AggregationError::ProofReplayed`] if it was consumed before within
+    /// the window, or with [`AggregationError::ReplayWindowFull`] if the
+    /// window is full and the policy does not evict.
+    pub fn consume(&mut self, id: ProofId) -> Result<(), AggregationError> {
+        self.consume_at(id, Instant::now())
+    }
+
+    /// Like [`consume`](Self::consume), at the caller-supplied time `now`.
+    pub fn consume_at(&mut self, id: ProofId, now: Instant) -> Result<(), AggregationError> {
+        self.evict(now);
+        if self.seen.contains(&id) {
+            return Err(AggregationError::ProofReplayed {
+                proof_id: hex::encode(id),
+            });
+        }
+        if self.order.len() >= self.policy.capacity && !self.policy.evict_when_full {
+            return Err(AggregationError::ReplayWindowFull {
+                capacity: self.policy.capacity,
+            });
+        }
+        if self.policy.capacity == 0 {
+            return Ok(());
+        }
+
+        while self.order.len() >= self.policy.capacity {
+            self.pop_oldest();
+        }
+        self.seen.insert(id);
+        self.order.push_back((id, now));
+        Ok(())
+    }
+
+    /// Returns `true` if `id` is in the window.
+    #[must_use]
+    pub fn contains(&self, id: &ProofId) -> bool {
+        self.seen.contains(id)
+    }
+
+    /// Number of remembered IDs.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns `true` if no ID is remembered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    fn evict(&mut self, now: Instant) {
+        let Some(ttl) = self.policy.ttl else {
+            return;
+        };
+        while self
+            .order
+            .front()
+            .is_some_and(|&(_, seen_at)| now.saturating_duration_since(seen_at) >= ttl)
+        {
+            self.pop_oldest();
+        }
+    }
+
+    fn pop_oldest(&mut self) {
+        if let Some((id, _)) = self.order.pop_front() {
+            self.seen.remove(&id);
+        }
+    }
+}
+
+/// SHA-256 over the proof bytes and the public outputs they prove:
+/// `verified_count`, `failed` and `verified_root`.
+///
+/// [`ProofMetadata`](crate::ProofMetadata) is not bound by the proof, so it
+/// is left out: rewriting a timestamp must not turn a consumed proof into a
+/// fresh one.
+#[must_use]
+pub fn proof_id(proof: &AggregationProof) -> ProofId {
+    Sha256::new()
+        .chain_update(PROOF_ID_DOMAIN)
+        .chain_update((proof.proof.len() as u64).to_le_bytes())
+        .chain_update(&proof.proof)
+        .chain_update(proof.verified_count.to_le_bytes())
+        .chain_update((proof.failed.len() as u64).to_le_bytes())
+        .chain_update(&proof.failed)
+        .chain_update(proof.verified_root)
+        .finalize()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commitment::CommitmentAlgorithm, profile::ComplianceProfile, types::ProofMetadata,
+    };
+
+    fn test_proof() -> AggregationProof {
+        AggregationProof {
+            proof: vec![1, 2, 3],
+            verified_count: 3,
+            metadata: ProofMetadata {
+                timestamp: 1_700_000_000,
+                batch_size: 3,
+                memory_size: 0,
+                trace_length: 0,
+                max_trace_length: 0,
+                proof_size: 3,
+                guest_hash: [0u8; 32],
+                urs_digest: [0u8; 32],
+                commitment_hash: CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: None,
+                profile: ComplianceProfile::Standard,
+            },
+            failed: vec![0],
+            verified_root: [5u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_metadata_change_is_still_a_replay() {
+        let mut guard = ReplayGuard::new(ReplayPolicy::default());
+        let proof = test_proof();
+        guard.consume(proof_id(&proof)).unwrap();
+
+        let mut restamped = test_proof();
+        restamped.metadata.timestamp += 1;
+        restamped.metadata.template = Some(crate::template::TemplateRef {
+            name: "payouts".to_string(),
+            version: 2,
+        });
+        assert!(matches!(
+            guard.consume(proof_id(&restamped)),
+            Err(AggregationError::ProofReplayed { .. })
+        ));
+
+        let mut other = test_proof();
+        other.verified_root[0] ^= 1;
+        assert!(guard.consume(proof_id(&other)).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_replay() {
+        let mut guard = ReplayGuard::new(ReplayPolicy::default());
+
+        assert!(guard.consume([1u8; 32]).is_ok());
+        assert!(guard.consume([2u8; 32]).is_ok());
+        assert_eq!(
+            guard.consume([1u8; 32]),
+            Err(AggregationError::ProofReplayed {
+                proof_id: "01".repeat(32)
+            })
+        );
+        assert_eq!(guard.len(), 2);
+    }
+
+    #[test]
+    fn test_capacity_evicts_oldest() {
+        let mut guard = ReplayGuard::new(ReplayPolicy {
+            capacity: 2,
+            ttl: None,
+            evict_when_full: true,
+        });
+
+        for id in 1..=3 {
+            guard.consume([id; 32]).unwrap();
+        }
+        assert!(!guard.contains(&[1u8; 32]));
+        assert!(guard.contains(&[3u8; 32]));
+        assert!(guard.consume([1u8; 32]).is_ok());
+    }
+
+    #[test]
+    fn test_full_window_rejects_by_default() {
+        let mut guard = ReplayGuard::new(ReplayPolicy {
+            capacity: 2,
+            ttl: Some(Duration::from_secs(30)),
+            ..ReplayPolicy::default()
+        });
+        let start = Instant::now();
+
+        guard.consume_at([1u8; 32], start).unwrap();
+        guard.consume_at([2u8; 32], start).unwrap();
+        assert_eq!(
+            guard.consume_at([3u8; 32], start),
+            Err(AggregationError::ReplayWindowFull { capacity: 2 })
+        );
+        assert!(guard.contains(&[1u8; 32]));
+        assert!(
+            guard
+                .consume_at([3u8; 32], start + Duration::from_secs(30))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_ttl_expires_ids() {
+        let mut guard = ReplayGuard::new(ReplayPolicy {
+            capacity: 10,
+            ttl: Some(Duration::from_secs(30)),
+            ..ReplayPolicy::default()
+        });
+        let start = Instant::now();
+
+        guard.consume_at([1u8; 32], start).unwrap();
+        assert!(
+            guard
+                .consume_at([1u8; 32], start + Duration::from_secs(29))
+                .is_err()
+        );
+        assert!(
+            guard
+                .consume_at([1u8; 32], start + Duration::from_secs(30))
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,461 @@
+//! Cost comparison between aggregated and naive verification.
+//!
+//! Integrators usually want one question answered: what does checking a batch
+//! through one aggregation proof cost compared to checking every XMSS
+//! signature individually? [`ComparisonReport`] puts both sides next to each
+//! other in terms of bytes a verifier must receive, wall-clock verification
+//! time and estimated gas under a configurable [`GasCostModel`].
+//!
+//! The naive side assumes the verifier receives every [`VerificationItem`]
+//! (message, epoch, signature and public key). The aggregated side assumes it
+//! receives the serialized [`AggregationProof`] plus the 32-byte
+//! [batch commitment](crate::commitment) the proof is checked against.
+//!
+//! Reports render as a Markdown table ([`ComparisonReport::to_table`]) or as
+//! JSON ([`ComparisonReport::to_json`]).
+//!
+//! # Proving costs
+//!
+//! [`CostReport`] records what producing one proof cost the prover: wall
+//! time, CPU time, peak memory and chunk count. It is stored in
+//! [`ProofMetadata::cost`](crate::ProofMetadata::cost) so operators can
+//! attribute infrastructure costs to tenants and tune batch policies. Wrap
+//! proving in a [`CostMeter`] to collect it:
+//!
+//! ```no_run
+//! use sig_agg::report::CostMeter;
+//! # let mut metadata: sig_agg::ProofMetadata = unimplemented!();
+//!
+//! let meter = CostMeter::start();
+//! // ... prove ...
+//! metadata.cost = Some(meter.finish(1));
+//! ```
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use sig_agg::report::{ComparisonReport, GasCostModel, measure_native_verification};
+//! # let batch: sig_agg::AggregationBatch = unimplemented!();
+//! # let proof: sig_agg::AggregationProof = unimplemented!();
+//! # let proof_verify_time = Duration::from_millis(800);
+//!
+//! let model = GasCostModel::ethereum_calldata(2_000_000, 4_000_000);
+//! let naive_time = measure_native_verification(&batch);
+//! let report = ComparisonReport::new(&batch, &proof, naive_time, proof_verify_time, &model)
+//!     .expect("Report generation failed");
+//!
+//! println!("{}", report.to_table());
+//! ```
+
+use std::{
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use hashsig::signature::SignatureScheme;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commitment::BatchCommitment,
+    error::AggregationError,
+    types::{AggregationBatch, AggregationProof, VerificationItem, XMSSSignature},
+};
+
+/// Linear gas model for an on-chain verifier.
+///
+/// Gas for a verification is `base + per_byte * bytes` plus either
+/// `per_signature * N` (naive) or `proof_verification` (aggregated). The
+/// verification constants depend heavily on the target chain and verifier
+/// contract, so they must be supplied by the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasCostModel {
+    /// Fixed gas per verification transaction
+    pub base: u64,
+    /// Gas per byte of data sent to the verifier
+    pub per_byte: u64,
+    /// Gas to verify one XMSS signature directly
+    pub per_signature: u64,
+    /// Gas to verify one aggregation proof
+    pub proof_verification: u64,
+}
+
+impl GasCostModel {
+    /// Ethereum transaction base cost (21,000) and non-zero calldata pricing
+    /// (16 gas per byte) with caller-supplied verification costs.
+    #[must_use]
+    pub const fn ethereum_calldata(per_signature: u64, proof_verification: u64) -> Self {
+        Self {
+            base: 21_000,
+            per_byte: 16,
+            per_signature,
+            proof_verification,
+        }
+    }
+
+    /// Gas to verify `signatures` signatures individually from `bytes` bytes.
+    #[must_use]
+    pub const fn naive_gas(&self, signatures: usize, bytes: usize) -> u64 {
+        self.data_gas(bytes)
+            .saturating_add(self.per_signature.saturating_mul(signatures as u64))
+    }
+
+    /// Gas to verify one aggregation proof from `bytes` bytes.
+    #[must_use]
+    pub const fn aggregated_gas(&self, bytes: usize) -> u64 {
+        self.data_gas(bytes).saturating_add(self.proof_verification)
+    }
+
+    const fn data_gas(&self, bytes: usize) -> u64 {
+        self.base
+            .saturating_add(self.per_byte.saturating_mul(bytes as u64))
+    }
+}
+
+/// Side-by-side costs of naive and aggregated verification of one batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonReport {
+    /// Number of signatures in the batch
+    pub batch_size: usize,
+    /// Bytes a naive verifier receives (all items)
+    pub naive_bytes: usize,
+    /// Bytes an aggregated verifier receives (proof plus batch commitment)
+    pub aggregated_bytes: usize,
+    /// Time to verify every signature natively, in seconds
+    pub naive_verify_secs: f64,
+    /// Time to verify the aggregation proof, in seconds
+    pub aggregated_verify_secs: f64,
+    /// Estimated gas for naive verification
+    pub naive_gas: u64,
+    /// Estimated gas for aggregated verification
+    pub aggregated_gas: u64,
+    /// Gas model the estimates were computed with
+    pub model: GasCostModel,
+}
+
+impl ComparisonReport {
+    /// Builds a report from a batch, its proof and measured verification times.
+    pub fn new(
+        batch: &AggregationBatch,
+        proof: &AggregationProof,
+        naive_verify_time: Duration,
+        proof_verify_time: Duration,
+        model: &GasCostModel,
+    ) -> Result<Self, AggregationError> {
+        let batch_size = batch.items.len();
+        let naive_bytes = batch
+            .items
+            .iter()
+            .map(VerificationItem::encoded_size)
+            .sum::<Result<usize, _>>()?;
+        let proof_bytes =
+            bincode::serialized_size(proof).map_err(|e| AggregationError::SerializationError {
+                message: format!("Failed to compute proof size: {}", e),
+            })? as usize;
+        let aggregated_bytes = proof_bytes + size_of::<BatchCommitment>();
+
+        Ok(Self {
+            batch_size,
+            naive_bytes,
+            aggregated_bytes,
+            naive_verify_secs: naive_verify_time.as_secs_f64(),
+            aggregated_verify_secs: proof_verify_time.as_secs_f64(),
+            naive_gas: model.naive_gas(batch_size, naive_bytes),
+            aggregated_gas: model.aggregated_gas(aggregated_bytes),
+            model: *model,
+        })
+    }
+
+    /// Naive bytes divided by aggregated bytes.
+    #[must_use]
+    pub fn bytes_ratio(&self) -> f64 {
+        ratio(self.naive_bytes as f64, self.aggregated_bytes as f64)
+    }
+
+    /// Naive verification time divided by aggregated verification time.
+    #[must_use]
+    pub fn time_ratio(&self) -> f64 {
+        ratio(self.naive_verify_secs, self.aggregated_verify_secs)
+    }
+
+    /// Naive gas divided by aggregated gas.
+    #[must_use]
+    pub fn gas_ratio(&self) -> f64 {
+        ratio(self.naive_gas as f64, self.aggregated_gas as f64)
+    }
+
+    /// Renders the report as a Markdown table.
+    #[must_use]
+    pub fn to_table(&self) -> String {
+        let rows = [
+            (
+                "Bytes to verifier",
+                self.naive_bytes.to_string(),
+                self.aggregated_bytes.to_string(),
+                self.bytes_ratio(),
+            ),
+            (
+                "Verification time (s)",
+                format!("{:.4}", self.naive_verify_secs),
+                format!("{:.4}", self.aggregated_verify_secs),
+                self.time_ratio(),
+            ),
+            (
+                "Estimated gas",
+                self.naive_gas.to_string(),
+                self.aggregated_gas.to_string(),
+                self.gas_ratio(),
+            ),
+        ];
+
+        let mut table = format!(
+            "| Metric ({} signatures) | Naive | Aggregated | Naive / Aggregated |\n\
+             |---|---:|---:|---:|\n",
+            self.batch_size
+        );
+        for (metric, naive, aggregated, ratio) in rows {
+            let _ = writeln!(
+                table,
+                "| {} | {} | {} | {:.2}x |",
+                metric, naive, aggregated, ratio
+            );
+        }
+        table
+    }
+
+    /// Renders the report as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, AggregationError> {
+        serde_json::to_string_pretty(self).map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to serialize report: {}", e),
+        })
+    }
+}
+
+/// Resources spent producing one proof.
+///
+/// CPU time and peak memory are read from `/proc/self` and are `None` on
+/// platforms without it. Peak memory is the process high-water mark, so it
+/// covers everything the process did before the proof as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostReport {
+    /// Wall-clock proving time in milliseconds
+    pub wall_time_ms: u64,
+    /// User plus system CPU time of the process in milliseconds
+    pub cpu_time_ms: Option<u64>,
+    /// Peak resident memory of the process in bytes
+    pub peak_memory_bytes: Option<u64>,
+    /// Number of chunks the batch was proven in
+    pub chunk_count: u32,
+}
+
+impl CostReport {
+    /// CPU time divided by wall time, i.e. the average number of busy cores.
+    #[must_use]
+    pub fn cpu_utilization(&self) -> Option<f64> {
+        self.cpu_time_ms
+            .map(|cpu| ratio(cpu as f64, self.wall_time_ms as f64))
+    }
+}
+
+/// Measures the cost of a proving run from start to [`finish`](Self::finish).
+#[derive(Debug, Clone, Copy)]
+pub struct CostMeter {
+    started: Instant,
+    cpu_time_at_start: Option<Duration>,
+}
+
+impl CostMeter {
+    /// Starts measuring.
+    #[must_use]
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            cpu_time_at_start: process_cpu_time(),
+        }
+    }
+
+    /// Stops measuring a run that was proven in `chunk_count` chunks.
+    #[must_use]
+    pub fn finish(self, chunk_count: u32) -> CostReport {
+        let cpu_time = process_cpu_time()
+            .zip(self.cpu_time_at_start)
+            .map(|(end, start)| end.saturating_sub(start));
+
+        CostReport {
+            wall_time_ms: self.started.elapsed().as_millis() as u64,
+            cpu_time_ms: cpu_time.map(|cpu| cpu.as_millis() as u64),
+            peak_memory_bytes: peak_memory_bytes(),
+            chunk_count,
+        }
+    }
+}
+
+/// User plus system time from `/proc/self/stat`, assuming the standard
+/// 100 Hz clock tick.
+fn process_cpu_time() -> Option<Duration> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // Fields after the parenthesised command name start at field 3 (state)
+    let mut fields = stat.get(stat.rfind(')')? + 2..)?.split_whitespace();
+    let utime: u64 = fields.nth(11)?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(Duration::from_millis((utime + stime) * 10))
+}
+
+/// `VmHWM` from `/proc/self/status`.
+fn peak_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line
+        .trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Verifies every signature in `batch` natively and returns the elapsed time.
+///
+/// This is the baseline a verifier without aggregation would pay. Invalid
+/// signatures are verified (and rejected) like valid ones.
+#[must_use]
+pub fn measure_native_verification(batch: &AggregationBatch) -> Duration {
+    let start = Instant::now();
+    for item in &batch.items {
+        let _ = XMSSSignature::verify(&item.public_key, item.epoch, &item.message, &item.signature);
+    }
+    start.elapsed()
+}
+
+fn ratio(naive: f64, aggregated: f64) -> f64 {
+    if aggregated == 0.0 {
+        0.0
+    } else {
+        naive / aggregated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_batch;
+    use crate::{
+        commitment::CommitmentAlgorithm, profile::ComplianceProfile, types::ProofMetadata,
+    };
+
+    fn test_proof(batch_size: usize) -> AggregationProof {
+        AggregationProof {
+            proof: vec![0u8; 1000],
+            verified_count: batch_size as u32,
+            metadata: ProofMetadata {
+                timestamp: 0,
+                batch_size,
+                memory_size: 0,
+                trace_length: 0,
+                max_trace_length: 0,
+                proof_size: 0,
+                guest_hash: [0u8; 32],
+                urs_digest: [0u8; 32],
+                commitment_hash: CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: None,
+                profile: ComplianceProfile::Standard,
+            },
+            failed: vec![],
+            verified_root: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_gas_model() {
+        let model = GasCostModel::ethereum_calldata(1_000, 50_000);
+
+        assert_eq!(model.naive_gas(3, 100), 21_000 + 1_600 + 3_000);
+        assert_eq!(model.aggregated_gas(100), 21_000 + 1_600 + 50_000);
+    }
+
+    #[test]
+    fn test_report_sizes_and_gas() {
+        let batch = test_batch(4);
+        let proof = test_proof(4);
+        let model = GasCostModel::ethereum_calldata(1_000, 50_000);
+
+        let report = ComparisonReport::new(
+            &batch,
+            &proof,
+            Duration::from_millis(40),
+            Duration::from_millis(10),
+            &model,
+        )
+        .unwrap();
+
+        let item_bytes: usize = batch.items.iter().map(|i| i.encoded_size().unwrap()).sum();
+        let proof_bytes = bincode::serialized_size(&proof).unwrap() as usize;
+
+        assert_eq!(report.batch_size, 4);
+        assert_eq!(report.naive_bytes, item_bytes);
+        assert_eq!(report.aggregated_bytes, proof_bytes + 32);
+        assert_eq!(report.naive_gas, model.naive_gas(4, item_bytes));
+        assert_eq!(
+            report.aggregated_gas,
+            model.aggregated_gas(proof_bytes + 32)
+        );
+        assert!((report.time_ratio() - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_report_rendering() {
+        let batch = test_batch(2);
+        let report = ComparisonReport::new(
+            &batch,
+            &test_proof(2),
+            Duration::from_millis(20),
+            Duration::ZERO,
+            &GasCostModel::ethereum_calldata(1_000, 50_000),
+        )
+        .unwrap();
+
+        let table = report.to_table();
+        assert!(table.starts_with("| Metric (2 signatures) |"));
+        assert_eq!(table.lines().count(), 5);
+        // Zero aggregated time must not produce inf/NaN
+        assert!(table.contains("| 0.00x |"));
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["batch_size"], 2);
+        assert_eq!(json["model"]["per_byte"], 16);
+    }
+
+    #[test]
+    fn test_cost_meter() {
+        let meter = CostMeter::start();
+        let batch = test_batch(2);
+        let _ = measure_native_verification(&batch);
+        let cost = meter.finish(3);
+
+        assert_eq!(cost.chunk_count, 3);
+        if cfg!(target_os = "linux") {
+            assert!(cost.peak_memory_bytes.unwrap() > 0);
+            assert!(cost.cpu_time_ms.is_some());
+        }
+    }
+
+    #[test]
+    fn test_cpu_utilization() {
+        let cost = CostReport {
+            wall_time_ms: 1_000,
+            cpu_time_ms: Some(4_000),
+            peak_memory_bytes: None,
+            chunk_count: 1,
+        };
+        assert_eq!(cost.cpu_utilization(), Some(4.0));
+
+        let cost = CostReport {
+            cpu_time_ms: None,
+            ..cost
+        };
+        assert_eq!(cost.cpu_utilization(), None);
+    }
+}
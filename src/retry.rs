@@ -0,0 +1,378 @@
+//! Retry policy for transient aggregation failures.
+//!
+//! Proof generation, cache IO and artifact storage can fail for reasons that
+//! have nothing to do with the batch itself: the prover process is killed for
+//! memory, a cache file is briefly locked, a network volume hiccups. This module
+//! wraps such operations in a configurable retry loop with exponential backoff
+//! and jitter, retrying only errors that
+//! [`AggregationError::is_retryable`] classifies as transient.
+//!
+//! [`SpooledBatch`](crate::spool::SpooledBatch) retries opening and creating
+//! its spool file, and the Jolt prover (`prover::Prover::with_retry`) retries
+//! proving runs.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::Duration;
+//! use sig_agg::retry::{RetryMetrics, RetryPolicy};
+//! use sig_agg::AggregationError;
+//!
+//! let policy = RetryPolicy {
+//!     max_attempts: 3,
+//!     initial_backoff: Duration::ZERO,
+//!     ..RetryPolicy::default()
+//! };
+//! let metrics = RetryMetrics::default();
+//!
+//! let mut calls = 0;
+//! let result = policy.run(&metrics, || {
+//!     calls += 1;
+//!     if calls < 2 {
+//!         Err(AggregationError::IoError { message: "cache locked".to_string() })
+//!     } else {
+//!         Ok(calls)
+//!     }
+//! });
+//!
+//! assert_eq!(result, Ok(2));
+//! assert_eq!(metrics.snapshot().retries, 1);
+//! ```
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::error::AggregationError;
+
+/// Configuration for retrying transient failures.
+///
+/// The delay before retry `n` (1-based) is
+/// `initial_backoff * multiplier^(n - 1)`, capped at `max_backoff`, then
+/// reduced by a random fraction of up to `jitter` so that concurrent callers
+/// do not retry in lockstep.
+///
+/// # Fields
+///
+/// * `max_attempts` - Total attempts including the first one (minimum 1)
+/// * `initial_backoff` - Delay before the first retry
+/// * `max_backoff` - Upper bound on any single delay
+/// * `multiplier` - Backoff growth factor between consecutive retries
+/// * `jitter` - Fraction of the delay (0.0-1.0) that may be randomly removed;
+///   values outside that range are clamped and NaN counts as 0.0
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound on any single delay
+    pub max_backoff: Duration,
+    /// Backoff growth factor between consecutive retries
+    pub multiplier: u32,
+    /// Fraction of each delay (0.0-1.0) that may be randomly removed
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries; the operation runs exactly once.
+    #[must_use]
+    pub fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Sets `jitter`, clamped to `[0.0, 1.0]`; NaN disables jitter.
+    #[must_use]
+    pub const fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = unit_fraction(jitter);
+        self
+    }
+
+    /// Returns the un-jittered delay before retry number `retry` (1-based).
+    #[must_use]
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1)
+            .saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Returns the delay before retry number `retry` with jitter applied.
+    ///
+    /// `sample` is a uniform value in `[0.0, 1.0)`; the delay is reduced by
+    /// `sample * jitter` of its length. Both are clamped to `[0.0, 1.0]`, and
+    /// NaN counts as 0.0.
+    #[must_use]
+    pub fn delay(&self, retry: u32, sample: f64) -> Duration {
+        let jitter = unit_fraction(self.jitter) * unit_fraction(sample);
+        self.backoff(retry).mul_f64(1.0 - jitter)
+    }
+
+    /// Runs `operation`, retrying transient failures according to this policy.
+    ///
+    /// Errors for which [`AggregationError::is_retryable`] returns `false` are
+    /// returned immediately. When all attempts fail, the last error is returned.
+    pub fn run<T, F>(&self, metrics: &RetryMetrics, mut operation: F) -> Result<T, AggregationError>
+    where
+        F: FnMut() -> Result<T, AggregationError>,
+    {
+        let max_attempts = self.max_attempts.max(1);
+        let mut jitter_state = jitter_seed();
+        let mut attempt = 1;
+
+        loop {
+            metrics.attempts.fetch_add(1, Ordering::Relaxed);
+            match operation() {
+                Ok(value) => return Ok(value),
+                Err(error) if !error.is_retryable() => return Err(error),
+                Err(error) if attempt >= max_attempts => {
+                    metrics.exhausted.fetch_add(1, Ordering::Relaxed);
+                    return Err(error);
+                }
+                Err(_) => {
+                    metrics.retries.fetch_add(1, Ordering::Relaxed);
+                    let sample = next_unit_sample(&mut jitter_state);
+                    let delay = self.delay(attempt, sample);
+                    if !delay.is_zero() {
+                        thread::sleep(delay);
+                    }
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Counters describing retry activity.
+///
+/// A single `RetryMetrics` can be shared between threads and policies; all
+/// counters are updated atomically.
+#[derive(Debug, Default)]
+pub struct RetryMetrics {
+    attempts: AtomicU64,
+    retries: AtomicU64,
+    exhausted: AtomicU64,
+}
+
+/// Point-in-time copy of [`RetryMetrics`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetrySnapshot {
+    /// Operation invocations, including first attempts
+    pub attempts: u64,
+    /// Retries performed after a transient failure
+    pub retries: u64,
+    /// Operations that still failed after exhausting all attempts
+    pub exhausted: u64,
+}
+
+impl RetryMetrics {
+    /// Counters at zero.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            attempts: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+            exhausted: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the current counter values.
+    #[must_use]
+    pub fn snapshot(&self) -> RetrySnapshot {
+        RetrySnapshot {
+            attempts: self.attempts.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            exhausted: self.exhausted.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// `f64::clamp` passes NaN through, which `Duration::mul_f64` panics on
+const fn unit_fraction(value: f64) -> f64 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(0.0, 1.0)
+    }
+}
+
+fn jitter_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+// SplitMix64 step mapped to [0.0, 1.0); jitter only needs to de-synchronise
+// callers, not to be cryptographically random.
+fn next_unit_sample(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instant_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::ZERO,
+            ..RetryPolicy::default()
+        }
+    }
+
+    fn transient() -> AggregationError {
+        AggregationError::IoError {
+            message: "temporarily unavailable".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_backoff_grows_exponentially_and_caps() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+            multiplier: 2,
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(40), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_delay_applies_jitter() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            jitter: 0.5,
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.delay(1, 0.0), Duration::from_millis(100));
+        assert_eq!(policy.delay(1, 1.0), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_out_of_range_jitter_is_clamped() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            ..RetryPolicy::default()
+        };
+
+        for jitter in [f64::NAN, -1.0, f64::NEG_INFINITY] {
+            let policy = policy.clone().with_jitter(jitter);
+            assert_eq!(policy.delay(1, 1.0), Duration::from_millis(100));
+        }
+        let policy = policy.with_jitter(7.0);
+        assert_eq!(policy.delay(1, 1.0), Duration::ZERO);
+
+        // Fields set directly are clamped when used
+        let policy = RetryPolicy {
+            jitter: f64::NAN,
+            ..policy
+        };
+        assert_eq!(policy.delay(1, f64::NAN), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_retries_transient_errors_until_success() {
+        let metrics = RetryMetrics::default();
+        let mut calls = 0;
+
+        let result = instant_policy(5).run(&metrics, || {
+            calls += 1;
+            if calls < 3 {
+                Err(transient())
+            } else {
+                Ok(calls)
+            }
+        });
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(
+            metrics.snapshot(),
+            RetrySnapshot {
+                attempts: 3,
+                retries: 2,
+                exhausted: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_permanent_errors_are_not_retried() {
+        let metrics = RetryMetrics::default();
+        let mut calls = 0;
+
+        let result: Result<(), _> = instant_policy(5).run(&metrics, || {
+            calls += 1;
+            Err(AggregationError::EmptyBatch)
+        });
+
+        assert_eq!(result, Err(AggregationError::EmptyBatch));
+        assert_eq!(calls, 1);
+        assert_eq!(metrics.snapshot().retries, 0);
+    }
+
+    #[test]
+    fn test_exhausted_attempts_return_last_error() {
+        let metrics = RetryMetrics::default();
+
+        let result: Result<(), _> = instant_policy(3).run(&metrics, || Err(transient()));
+
+        assert_eq!(result, Err(transient()));
+        assert_eq!(
+            metrics.snapshot(),
+            RetrySnapshot {
+                attempts: 3,
+                retries: 2,
+                exhausted: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_no_retry_policy_runs_once() {
+        let metrics = RetryMetrics::default();
+
+        let result: Result<(), _> = RetryPolicy::no_retry().run(&metrics, || Err(transient()));
+
+        assert!(result.is_err());
+        assert_eq!(metrics.snapshot().attempts, 1);
+    }
+
+    #[test]
+    fn test_unit_sample_range() {
+        let mut state = 42;
+        for _ in 0..1000 {
+            let sample = next_unit_sample(&mut state);
+            assert!((0.0..1.0).contains(&sample));
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Host-side proving with the RISC Zero zkVM.
+//!
+//! For teams standardized on RISC Zero receipts, this module (behind the
+//! `risc0` feature) proves the guest in `src/risc0/methods/guest`, which
+//! runs the Jolt guest's `verify_aggregation` logic from the shared `guest`
+//! crate. Batches are validated, encoded and verified exactly as with the
+//! Jolt [`prover`](crate::prover), and the receipt is returned as the same
+//! [`AggregationProof`], with the serialized receipt as its proof bytes and
+//! the guest's [`GuestOutput`] as its journal.
+//!
+//! [`ProofMetadata::guest_hash`] of the proofs is the guest's image ID,
+//! which verifiers pass to [`verify_risc0_proof`]. [`Risc0Backend`] exposes
+//! the prover as a [`ZkVmBackend`]. RISC Zero needs no URS
+//! and the guest has no fixed memory size or trace limit, so the
+//! `urs_digest`, `memory_size` and `max_trace_length` metadata are zero;
+//! `trace_length` is the number of cycles executed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::risc0::{Risc0Prover, Risc0ProverConfig};
+//! # let batch: sig_agg::AggregationBatch = unimplemented!();
+//!
+//! let prover = Risc0Prover::new(&Risc0ProverConfig::default());
+//! let proof = prover.prove(batch).expect("Proving failed");
+//! println!("{} bytes", proof.proof.len());
+//! ```
+
+use risc0_zkvm::{ExecutorEnv, ProverOpts, Receipt, compute_image_id, default_prover};
+use sig_agg_risc0_methods::{VERIFY_AGGREGATION_ELF, VERIFY_AGGREGATION_ID};
+
+use crate::{
+    aggregator::validate,
+    backend::{ZkVmBackend, build_proof, check_input_digest, expected_output},
+    error::AggregationError,
+    guest::input_digest,
+    native::{GuestOutput, to_guest_batch},
+    report::CostMeter,
+    types::{AggregationBatch, AggregationProof},
+};
+
+/// Image ID of the embedded RISC Zero guest, as recorded in
+/// [`ProofMetadata::guest_hash`].
+#[must_use]
+pub fn image_id() -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (chunk, word) in bytes.chunks_exact_mut(4).zip(VERIFY_AGGREGATION_ID) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    bytes
+}
+
+/// Receipt type to generate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Risc0ReceiptKind {
+    /// One receipt per execution segment; fastest to prove, size grows with
+    /// the batch
+    Composite,
+    /// Segment receipts recursively compressed into a single constant-size
+    /// receipt
+    #[default]
+    Succinct,
+}
+
+/// How the [`Risc0Prover`] proves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Risc0ProverConfig {
+    /// Receipt type to generate
+    pub receipt_kind: Risc0ReceiptKind,
+}
+
+/// RISC Zero prover of the aggregation guest.
+///
+/// The prover itself is selected by the `RISC0_PROVER` environment variable
+/// (local proving by default) when a batch is proven.
+#[derive(Clone)]
+pub struct Risc0Prover {
+    config: Risc0ProverConfig,
+    elf: &'static [u8],
+    image_id: [u8; 32],
+}
+
+impl std::fmt::Debug for Risc0Prover {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Risc0Prover")
+            .field("config", &self.config)
+            .field("image_id", &hex::encode(self.image_id))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Risc0Prover {
+    /// Creates a prover of the embedded guest with `config`.
+    #[must_use]
+    pub fn new(config: &Risc0ProverConfig) -> Self {
+        Self {
+            config: config.clone(),
+            elf: VERIFY_AGGREGATION_ELF,
+            image_id: image_id(),
+        }
+    }
+
+    /// Creates a prover of another build of the guest, given its ELF.
+    ///
+    /// Fails with [`AggregationError::CompilationError`] if `elf` is not a
+    /// RISC Zero guest.
+    pub fn with_program(
+        config: &Risc0ProverConfig,
+        elf: &'static [u8],
+    ) -> Result<Self, AggregationError> {
+        let image_id = compute_image_id(elf).map_err(|e| AggregationError::CompilationError {
+            message: format!("Invalid RISC Zero guest: {}", e),
+        })?;
+        Ok(Self {
+            config: config.clone(),
+            elf,
+            image_id: image_id.into(),
+        })
+    }
+
+    /// Image ID of this prover's guest, for [`verify_risc0_proof`].
+    #[must_use]
+    pub const fn image_id(&self) -> [u8; 32] {
+        self.image_id
+    }
+
+    /// Verifies a proof produced by this prover.
+    ///
+    /// Same as [`verify_risc0_proof`] with this prover's image ID.
+    pub fn verify(
+        &self,
+        proof: &AggregationProof,
+        batch: &AggregationBatch,
+    ) -> Result<u32, AggregationError> {
+        verify_risc0_proof(proof, batch, &self.image_id)
+    }
+
+    /// Validates and proves `batch`.
+    ///
+    /// # Errors
+    ///
+    /// * any error of [`validate`](crate::validate())
+    /// * `SerializationError` - the batch, journal or receipt could not be
+    ///   encoded or decoded
+    /// * `ProofGenerationError` - proving failed, e.g. because the guest
+    ///   panicked
+    pub fn prove(&self, batch: AggregationBatch) -> Result<AggregationProof, AggregationError> {
+        validate(&batch.items)?;
+        let batch_size = batch.items.len();
+        let guest_batch = to_guest_batch(&batch)?;
+        let expected_digest = input_digest(&batch)?;
+
+        let meter = CostMeter::start();
+        let env = ExecutorEnv::builder()
+            .write(&guest_batch)
+            .and_then(|builder| builder.build())
+            .map_err(|e| AggregationError::SerializationError {
+                message: format!("Failed to write guest input: {}", e),
+            })?;
+        let opts = match self.config.receipt_kind {
+            Risc0ReceiptKind::Composite => ProverOpts::composite(),
+            Risc0ReceiptKind::Succinct => ProverOpts::succinct(),
+        };
+        let prove_info = default_prover()
+            .prove_with_opts(env, self.elf, &opts)
+            .map_err(|e| AggregationError::ProofGenerationError {
+                message: format!("RISC Zero proving failed: {}", e),
+            })?;
+        let receipt = prove_info.receipt;
+
+        let output = decode_journal(&receipt)?;
+        check_input_digest(&output, &expected_digest)?;
+        let bytes =
+            bincode::serialize(&receipt).map_err(|e| AggregationError::SerializationError {
+                message: format!("Failed to serialize receipt: {}", e),
+            })?;
+        let cost = meter.finish(1);
+
+        Ok(build_proof(
+            output,
+            bytes,
+            batch_size,
+            prove_info.stats.total_cycles as usize,
+            self.image_id,
+            cost,
+        ))
+    }
+}
+
+/// The RISC Zero zkVM as a [`ZkVmBackend`].
+///
+/// The program is the guest ELF embedded by the build script, and
+/// preprocessing derives its image ID, the verifier key.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Risc0Backend;
+
+impl ZkVmBackend for Risc0Backend {
+    const NAME: &'static str = "risc0";
+
+    type Config = Risc0ProverConfig;
+    type Program = &'static [u8];
+    type Prover = Risc0Prover;
+    type VerifierKey = [u8; 32];
+
+    fn compile(_config: &Risc0ProverConfig) -> Result<&'static [u8], AggregationError> {
+        Ok(VERIFY_AGGREGATION_ELF)
+    }
+
+    fn preprocess(
+        config: &Risc0ProverConfig,
+        program: &'static [u8],
+    ) -> Result<(Risc0Prover, [u8; 32]), AggregationError> {
+        let prover = Risc0Prover::with_program(config, program)?;
+        let image_id = prover.image_id();
+        Ok((prover, image_id))
+    }
+
+    fn prove(
+        prover: &Risc0Prover,
+        batch: AggregationBatch,
+    ) -> Result<AggregationProof, AggregationError> {
+        prover.prove(batch)
+    }
+
+    fn verify(
+        image_id: &[u8; 32],
+        proof: &AggregationProof,
+        batch: &AggregationBatch,
+    ) -> Result<u32, AggregationError> {
+        verify_risc0_proof(proof, batch, image_id)
+    }
+}
+
+/// Verifies a RISC Zero `proof` over `batch`, returning the number of valid
+/// signatures it attests.
+///
+/// As with [`verify_aggregation_proof`](crate::prover::verify_aggregation_proof),
+/// the verifier needs the exact batch that was proven; `image_id` must be
+/// that of the guest recorded in the proof's metadata (see
+/// [`Risc0Prover::image_id`]).
+///
+/// # Errors
+///
+/// * `LengthMismatch` - the batch size differs from the proof metadata
+/// * `SerializationError` - the batch, receipt or journal cannot be decoded
+/// * `InvalidProof` - the receipt does not verify or attests another output
+pub fn verify_risc0_proof(
+    proof: &AggregationProof,
+    batch: &AggregationBatch,
+    image_id: &[u8; 32],
+) -> Result<u32, AggregationError> {
+    let expected_output = expected_output(proof, batch)?;
+    let receipt: Receipt =
+        bincode::deserialize(&proof.proof).map_err(|e| AggregationError::SerializationError {
+            message: format!("Invalid receipt bytes: {}", e),
+        })?;
+    receipt
+        .verify(*image_id)
+        .map_err(|_| AggregationError::InvalidProof)?;
+
+    // The receipt binds the journal; it must hold this batch's output
+    if decode_journal(&receipt)? != expected_output {
+        return Err(AggregationError::InvalidProof);
+    }
+    Ok(proof.verified_count)
+}
+
+fn decode_journal(receipt: &Receipt) -> Result<GuestOutput, AggregationError> {
+    receipt
+        .journal
+        .decode()
+        .map_err(|e| AggregationError::SerializationError {
+            message: format!("Invalid receipt journal: {}", e),
+        })
+}
@@ -0,0 +1,6 @@
+//! Builds the RISC Zero guest in `guest` and generates its ELF and image ID
+//! constants.
+
+fn main() {
+    risc0_build::embed_methods();
+}
@@ -0,0 +1,18 @@
+//! RISC Zero guest verifying an aggregation batch.
+//!
+//! Mirrors the Jolt guest's `verify_aggregation` entry point by calling the
+//! same `guest` crate function, so both proving systems attest the same
+//! [`GuestOutput`] for a batch. The batch is read from the executor
+//! environment and the output committed to the receipt's journal.
+
+#![no_main]
+risc0_zkvm::guest::entry!(main);
+
+use guest::{verify_aggregation_checked, AggregationBatch, GuestOutput};
+use risc0_zkvm::guest::env;
+
+fn main() {
+    let batch: AggregationBatch = env::read();
+    let output: GuestOutput = verify_aggregation_checked(&batch);
+    env::commit(&output);
+}
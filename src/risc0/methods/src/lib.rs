@@ -0,0 +1,4 @@
+//! ELF and image ID of the RISC Zero guest (`VERIFY_AGGREGATION_ELF`,
+//! `VERIFY_AGGREGATION_ID`), generated by the build script.
+
+include!(concat!(env!("OUT_DIR"), "/methods.rs"));
@@ -0,0 +1,258 @@
+//! Batches under XMSS instantiations other than the default one.
+//!
+//! [`VerificationItem`](crate::VerificationItem) is fixed to
+//! [`XMSSSignature`]. A larger Winternitz parameter shortens signatures at
+//! the cost of more hashing per verification, which pays off when batches
+//! are bandwidth-bound, and a key with a lifetime of 2^20 epochs lasts four
+//! times as long for high-frequency signers. Long-lived validator keys use
+//! the 2^32 lifetime, whose signatures need more guest memory and trace per
+//! item. Ecosystems that mandate standard hashes use the SHA-3
+//! instantiation, which is costlier to prove than Poseidon since the zkVM has
+//! no native SHA-3 support. [`SchemeItem`] and [`SchemeBatch`] carry
+//! signatures of any [`Instantiation`], and [`aggregate_scheme`] applies the
+//! same validation as [`aggregate`](crate::aggregate), plus a check that
+//! every epoch is within the instantiation's lifetime.
+//!
+//! Each instantiation has its own guest entry point, since the verification
+//! code is monomorphized per scheme, and its own Cargo feature. None is
+//! enabled by default; enable the needed ones, or `all-schemes` for every
+//! one, to build them into the library and, under `native`, the guest:
+//!
+//! | Instantiation | Feature | Entry point |
+//! |---|---|---|
+//! | `XMSSSignatureW2` | `w2-lifetime18` | `verify_aggregation_w2` |
+//! | `XMSSSignatureW4` | `w4-lifetime18` | `verify_aggregation_w4` |
+//! | `XMSSSignatureW8` | `w8-lifetime18` | `verify_aggregation_w8` |
+//! | `XMSSSignatureLifetime20` | `w1-lifetime20` | `verify_aggregation_lifetime_20` |
+//! | `XMSSSignatureLifetime32` | `lifetime32` | `verify_aggregation_lifetime_32` |
+//! | `XMSSSignatureSha3` | `sha3` | `verify_aggregation_sha3` |
+//!
+//! The default instantiation is always built, since
+//! [`VerificationItem`](crate::VerificationItem) and
+//! the guest's main entry points use it.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # #[cfg(feature = "w2-lifetime18")] {
+//! use sig_agg::scheme::{SchemeItem, XMSSSignatureW2, aggregate_scheme};
+//!
+//! let items: Vec<SchemeItem<XMSSSignatureW2>> = vec![/* ... */];
+//! let batch = aggregate_scheme(items).expect("Aggregation failed");
+//! # }
+//! ```
+
+use hashsig::signature::SignatureScheme;
+#[cfg(feature = "lifetime32")]
+use hashsig::signature::generalized_xmss::instantiations_poseidon_top_level::lifetime_2_to_the_32::hashing_optimized::SIGTopLevelTargetSumLifetime32Dim64Base8;
+#[cfg(feature = "sha3")]
+use hashsig::signature::generalized_xmss::instantiations_sha::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W1 as SIGSha3WinternitzLifetime18W1;
+#[cfg(feature = "w1-lifetime20")]
+use hashsig::signature::generalized_xmss::instantiations_poseidon::lifetime_2_to_the_20::winternitz::SIGWinternitzLifetime20W1;
+#[cfg(feature = "w2-lifetime18")]
+use hashsig::signature::generalized_xmss::instantiations_poseidon::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W2;
+#[cfg(feature = "w4-lifetime18")]
+use hashsig::signature::generalized_xmss::instantiations_poseidon::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W4;
+#[cfg(feature = "w8-lifetime18")]
+use hashsig::signature::generalized_xmss::instantiations_poseidon::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W8;
+
+use crate::{
+    aggregator::validate_items,
+    error::AggregationError,
+    types::{SIGNATURE_SCHEME, XMSSSignature},
+};
+
+pub use crate::types::{SchemeBatch, SchemeItem};
+
+/// Winternitz parameter 2 instantiation, verified by the
+/// `verify_aggregation_w2` guest entry point.
+#[cfg(feature = "w2-lifetime18")]
+pub type XMSSSignatureW2 = SIGWinternitzLifetime18W2;
+
+/// Winternitz parameter 4 instantiation, verified by the
+/// `verify_aggregation_w4` guest entry point.
+#[cfg(feature = "w4-lifetime18")]
+pub type XMSSSignatureW4 = SIGWinternitzLifetime18W4;
+
+/// Winternitz parameter 8 instantiation, verified by the
+/// `verify_aggregation_w8` guest entry point.
+#[cfg(feature = "w8-lifetime18")]
+pub type XMSSSignatureW8 = SIGWinternitzLifetime18W8;
+
+/// Winternitz parameter 1 instantiation with a lifetime of 2^20 epochs,
+/// verified by the `verify_aggregation_lifetime_20` guest entry point.
+#[cfg(feature = "w1-lifetime20")]
+pub type XMSSSignatureLifetime20 = SIGWinternitzLifetime20W1;
+
+/// Hashing-optimized target-sum instantiation with a lifetime of 2^32 epochs,
+/// verified by the `verify_aggregation_lifetime_32` guest entry point.
+#[cfg(feature = "lifetime32")]
+pub type XMSSSignatureLifetime32 = SIGTopLevelTargetSumLifetime32Dim64Base8;
+
+/// Winternitz parameter 1 instantiation hashing with SHA-3 instead of
+/// Poseidon, verified by the `verify_aggregation_sha3` guest entry point.
+#[cfg(feature = "sha3")]
+pub type XMSSSignatureSha3 = SIGSha3WinternitzLifetime18W1;
+
+/// An XMSS instantiation with its own guest entry point.
+pub trait Instantiation: SignatureScheme {
+    /// Scheme name, bound into the
+    /// [input digest](crate::guest::scheme_input_digest) so a batch cannot
+    /// be passed off as one of another instantiation.
+    const NAME: &'static str;
+}
+
+impl Instantiation for XMSSSignature {
+    const NAME: &'static str = SIGNATURE_SCHEME;
+}
+
+#[cfg(feature = "w2-lifetime18")]
+impl Instantiation for XMSSSignatureW2 {
+    const NAME: &'static str = "SIGWinternitzLifetime18W2";
+}
+
+#[cfg(feature = "w4-lifetime18")]
+impl Instantiation for XMSSSignatureW4 {
+    const NAME: &'static str = "SIGWinternitzLifetime18W4";
+}
+
+#[cfg(feature = "w8-lifetime18")]
+impl Instantiation for XMSSSignatureW8 {
+    const NAME: &'static str = "SIGWinternitzLifetime18W8";
+}
+
+#[cfg(feature = "w1-lifetime20")]
+impl Instantiation for XMSSSignatureLifetime20 {
+    const NAME: &'static str = "SIGWinternitzLifetime20W1";
+}
+
+#[cfg(feature = "lifetime32")]
+impl Instantiation for XMSSSignatureLifetime32 {
+    const NAME: &'static str = "SIGTopLevelTargetSumLifetime32Dim64Base8";
+}
+
+// Shares its type name with the Poseidon instantiation, so the name is
+// qualified by the hashsig module
+#[cfg(feature = "sha3")]
+impl Instantiation for XMSSSignatureSha3 {
+    const NAME: &'static str = "instantiations_sha::SIGWinternitzLifetime18W1";
+}
+
+/// Checks `items` against the batch rules of
+/// [`validate`](crate::validate()), and their epochs against the
+/// lifetime of `S`.
+///
+/// # Errors
+///
+/// * `EmptyBatch` - `items` is empty
+/// * `EpochOutOfRange` - an epoch is not below `S::LIFETIME`
+/// * `DuplicateKeyEpochPair` - a (public key, epoch) pair occurs twice
+/// * `SerializationError` - a public key failed to serialize
+pub fn validate_scheme<S: SignatureScheme>(
+    items: &[SchemeItem<S>],
+) -> Result<(), AggregationError> {
+    if let Some((index, item)) = items
+        .iter()
+        .enumerate()
+        .find(|(_, item)| u64::from(item.epoch) >= S::LIFETIME)
+    {
+        return Err(AggregationError::EpochOutOfRange {
+            index,
+            epoch: item.epoch,
+            lifetime: S::LIFETIME,
+        });
+    }
+    validate_items(items)
+}
+
+/// Validates `items` and wraps them in a batch, like
+/// [`aggregate`](crate::aggregate) does for the default instantiation.
+pub fn aggregate_scheme<S: SignatureScheme>(
+    items: Vec<SchemeItem<S>>,
+) -> Result<SchemeBatch<S>, AggregationError> {
+    validate_scheme(&items)?;
+    Ok(SchemeBatch { items })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hashsig::MESSAGE_LENGTH;
+
+    #[test]
+    fn test_default_instantiation_is_bounded_by_its_lifetime() {
+        let mut rng = rand::rng();
+        let (pk, sk) = XMSSSignature::key_gen(&mut rng, 0, 4);
+        let item = SchemeItem::<XMSSSignature> {
+            message: [0; MESSAGE_LENGTH],
+            epoch: 1,
+            signature: XMSSSignature::sign(&sk, 1, &[0; MESSAGE_LENGTH])
+                .expect("Signing should succeed"),
+            public_key: pk,
+        };
+        let mut batch = aggregate_scheme(vec![item]).unwrap();
+        assert_eq!(XMSSSignature::NAME, SIGNATURE_SCHEME);
+
+        batch.items[0].epoch = 1 << 18;
+        assert!(matches!(
+            validate_scheme(&batch.items),
+            Err(AggregationError::EpochOutOfRange {
+                lifetime: 262_144,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "w2-lifetime18")]
+    fn test_w2_batch_rejects_reused_pairs() {
+        let mut rng = rand::rng();
+        let (pk, sk) = XMSSSignatureW2::key_gen(&mut rng, 0, 10);
+        let pk_bytes = bincode::serialize(&pk).unwrap();
+        let item = |epoch: u32| SchemeItem::<XMSSSignatureW2> {
+            message: [epoch as u8; MESSAGE_LENGTH],
+            epoch,
+            signature: XMSSSignatureW2::sign(&sk, epoch, &[epoch as u8; MESSAGE_LENGTH])
+                .expect("Signing should succeed"),
+            public_key: bincode::deserialize(&pk_bytes).unwrap(),
+        };
+
+        let batch = aggregate_scheme(vec![item(0), item(1)]).unwrap();
+        assert_eq!(batch.items.len(), 2);
+        assert!(matches!(
+            aggregate_scheme(vec![item(0), item(1), item(0)]),
+            Err(AggregationError::DuplicateKeyEpochPair { epoch: 0, .. })
+        ));
+        assert!(matches!(
+            aggregate_scheme::<XMSSSignatureW2>(vec![]),
+            Err(AggregationError::EmptyBatch)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "w1-lifetime20")]
+    fn test_epochs_are_bounded_by_the_lifetime() {
+        let mut rng = rand::rng();
+        let (pk, sk) = XMSSSignatureLifetime20::key_gen(&mut rng, 0, 10);
+        let epoch = 1 << 19;
+        let item = SchemeItem::<XMSSSignatureLifetime20> {
+            message: [0; MESSAGE_LENGTH],
+            epoch,
+            signature: XMSSSignatureLifetime20::sign(&sk, epoch, &[0; MESSAGE_LENGTH])
+                .expect("Signing should succeed"),
+            public_key: pk,
+        };
+        // Beyond 2^18, but within 2^20
+        let mut batch = aggregate_scheme(vec![item]).unwrap();
+
+        batch.items[0].epoch = 1 << 20;
+        assert_eq!(
+            validate_scheme(&batch.items),
+            Err(AggregationError::EpochOutOfRange {
+                index: 0,
+                epoch: 1 << 20,
+                lifetime: 1 << 20,
+            })
+        );
+    }
+}
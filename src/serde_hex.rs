@@ -0,0 +1,45 @@
+//! Serde helpers encoding 32-byte digests as hex strings in human-readable
+//! exports.
+
+// `unreachable_pub` asks for `pub(crate)`, which this nursery lint flags.
+#![allow(clippy::redundant_pub_crate)]
+
+use serde::{Deserialize, Deserializer, Serializer, de::Error, ser::SerializeSeq};
+
+pub(crate) fn serialize<S: Serializer>(bytes: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(bytes))
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<[u8; 32], D::Error> {
+    decode(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+}
+
+/// [`serialize`] for a list of digests.
+pub(crate) fn serialize_list<S: Serializer>(
+    list: &[[u8; 32]],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(list.len()))?;
+    for bytes in list {
+        seq.serialize_element(&hex::encode(bytes))?;
+    }
+    seq.end()
+}
+
+/// [`deserialize`] for a list of digests.
+pub(crate) fn deserialize_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<[u8; 32]>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|encoded| decode(encoded).map_err(D::Error::custom))
+        .collect()
+}
+
+fn decode(encoded: &str) -> Result<[u8; 32], hex::FromHexError> {
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(encoded, &mut bytes)?;
+    Ok(bytes)
+}
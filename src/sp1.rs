@@ -0,0 +1,286 @@
+//! Host-side proving with the SP1 zkVM.
+//!
+//! An alternative to the Jolt [`prover`](crate::prover) for deployments that
+//! standardize on SP1. This module (behind the `sp1` feature) proves the
+//! program in `src/sp1/program`, which the build script compiles and this
+//! module embeds. The program runs the Jolt guest's `verify_aggregation`
+//! logic from the shared `guest` crate, so batches are validated, encoded and
+//! verified exactly as with Jolt, and the result is the same
+//! [`AggregationProof`] carrying an SP1 proof instead of a Jolt one.
+//!
+//! Setup derives the proving and verifying keys from the program. A
+//! [`Sp1Prover`] does it once and can then prove any number of batches.
+//! [`ProofMetadata::guest_hash`] of its proofs is the hash of the verifying
+//! key, which verifiers pass to [`verify_sp1_proof`]. [`Sp1Backend`] exposes
+//! the prover as a [`ZkVmBackend`].
+//!
+//! SP1 proofs need no URS and the program has no fixed memory size or trace
+//! limit, so the `urs_digest`, `memory_size` and `max_trace_length` metadata
+//! are zero; `trace_length` is the number of cycles executed.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::sp1::{Sp1Prover, Sp1ProverConfig};
+//! # let batches: Vec<sig_agg::AggregationBatch> = vec![];
+//!
+//! let prover = Sp1Prover::new(&Sp1ProverConfig::default()).expect("Setup failed");
+//! for batch in batches {
+//!     let proof = prover.prove(batch).expect("Proving failed");
+//!     println!("{} bytes", proof.proof.len());
+//! }
+//! ```
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use sp1_sdk::{
+    EnvProver, HashableKey, ProverClient, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin,
+    SP1VerifyingKey, include_elf,
+};
+
+use crate::{
+    aggregator::validate,
+    backend::{ZkVmBackend, build_proof, check_input_digest, expected_output},
+    error::{AggregationError, panic_message},
+    guest::input_digest,
+    native::{GuestOutput, to_guest_batch},
+    report::CostMeter,
+    types::{AggregationBatch, AggregationProof},
+};
+
+/// ELF of the SP1 program, built by the build script.
+pub const PROGRAM_ELF: &[u8] = include_elf!("sig-agg-sp1-program");
+
+/// SP1 proof type to generate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Sp1ProofMode {
+    /// One STARK proof per execution shard; fastest to prove, size grows
+    /// with the batch
+    Core,
+    /// Shard proofs recursively compressed into a single constant-size proof
+    #[default]
+    Compressed,
+}
+
+/// How the [`Sp1Prover`] proves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sp1ProverConfig {
+    /// Proof type to generate
+    pub mode: Sp1ProofMode,
+}
+
+/// SP1 prover with the program's keys, ready to prove any number of batches.
+pub struct Sp1Prover {
+    config: Sp1ProverConfig,
+    client: EnvProver,
+    elf: &'static [u8],
+    proving_key: SP1ProvingKey,
+    verifying_key: SP1VerifyingKey,
+}
+
+impl std::fmt::Debug for Sp1Prover {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sp1Prover")
+            .field("config", &self.config)
+            .field("guest_hash", &hex::encode(self.guest_hash()))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Sp1Prover {
+    /// Sets up the proving and verifying keys of the program, with the
+    /// prover selected by the `SP1_PROVER` environment variable (local CPU
+    /// proving by default).
+    ///
+    /// Fails with [`AggregationError::CompilationError`] if setup panics.
+    pub fn new(config: &Sp1ProverConfig) -> Result<Self, AggregationError> {
+        Self::with_program(config, PROGRAM_ELF)
+    }
+
+    /// Same as [`Sp1Prover::new`] for another build of the program, given
+    /// its ELF.
+    pub fn with_program(
+        config: &Sp1ProverConfig,
+        elf: &'static [u8],
+    ) -> Result<Self, AggregationError> {
+        let client = ProverClient::from_env();
+        let (proving_key, verifying_key) = catch_unwind(AssertUnwindSafe(|| client.setup(elf)))
+            .map_err(|panic| AggregationError::CompilationError {
+                message: panic_message(&*panic),
+            })?;
+        Ok(Self {
+            config: config.clone(),
+            client,
+            elf,
+            proving_key,
+            verifying_key,
+        })
+    }
+
+    /// Verifying key matching this prover's program, for
+    /// [`verify_sp1_proof`].
+    #[must_use]
+    pub const fn verifying_key(&self) -> &SP1VerifyingKey {
+        &self.verifying_key
+    }
+
+    /// Hash of the verifying key, recorded in [`ProofMetadata::guest_hash`].
+    #[must_use]
+    pub fn guest_hash(&self) -> [u8; 32] {
+        self.verifying_key.hash_bytes()
+    }
+
+    /// Verifies a proof produced by this prover.
+    ///
+    /// Same as [`verify_sp1_proof`] with this prover's verifying key.
+    pub fn verify(
+        &self,
+        proof: &AggregationProof,
+        batch: &AggregationBatch,
+    ) -> Result<u32, AggregationError> {
+        verify_with(&self.client, proof, batch, &self.verifying_key)
+    }
+
+    /// Validates and proves `batch`.
+    ///
+    /// # Errors
+    ///
+    /// * any error of [`validate`](crate::validate())
+    /// * `SerializationError` - the batch or proof could not be encoded
+    /// * `ProofGenerationError` - execution or proving failed, e.g. because
+    ///   the program panicked
+    pub fn prove(&self, batch: AggregationBatch) -> Result<AggregationProof, AggregationError> {
+        validate(&batch.items)?;
+        let batch_size = batch.items.len();
+        let guest_batch = to_guest_batch(&batch)?;
+        let expected_digest = input_digest(&batch)?;
+
+        let meter = CostMeter::start();
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&guest_batch);
+
+        // Executing first measures the cycle count and surfaces a panicking
+        // program before the far more expensive proving run
+        let (_, report) = self.client.execute(self.elf, &stdin).run().map_err(|e| {
+            AggregationError::ProofGenerationError {
+                message: format!("SP1 execution failed: {}", e),
+            }
+        })?;
+        let request = self.client.prove(&self.proving_key, &stdin);
+        let request = match self.config.mode {
+            Sp1ProofMode::Core => request.core(),
+            Sp1ProofMode::Compressed => request.compressed(),
+        };
+        let sp1_proof = request
+            .run()
+            .map_err(|e| AggregationError::ProofGenerationError {
+                message: format!("SP1 proving failed: {}", e),
+            })?;
+
+        // Reading advances the buffer's cursor, which is serialized with the
+        // proof, so verifiers read from the start
+        let output: GuestOutput = sp1_proof.public_values.clone().read();
+        check_input_digest(&output, &expected_digest)?;
+        let bytes =
+            bincode::serialize(&sp1_proof).map_err(|e| AggregationError::SerializationError {
+                message: format!("Failed to serialize proof: {}", e),
+            })?;
+        let cost = meter.finish(1);
+
+        Ok(build_proof(
+            output,
+            bytes,
+            batch_size,
+            report.total_instruction_count() as usize,
+            self.guest_hash(),
+            cost,
+        ))
+    }
+}
+
+/// The SP1 zkVM as a [`ZkVmBackend`].
+///
+/// The program is the ELF embedded by the build script, and preprocessing
+/// sets up its proving and verifying keys.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sp1Backend;
+
+impl ZkVmBackend for Sp1Backend {
+    const NAME: &'static str = "sp1";
+
+    type Config = Sp1ProverConfig;
+    type Program = &'static [u8];
+    type Prover = Sp1Prover;
+    type VerifierKey = SP1VerifyingKey;
+
+    fn compile(_config: &Sp1ProverConfig) -> Result<&'static [u8], AggregationError> {
+        Ok(PROGRAM_ELF)
+    }
+
+    fn preprocess(
+        config: &Sp1ProverConfig,
+        program: &'static [u8],
+    ) -> Result<(Sp1Prover, SP1VerifyingKey), AggregationError> {
+        let prover = Sp1Prover::with_program(config, program)?;
+        let verifying_key = prover.verifying_key().clone();
+        Ok((prover, verifying_key))
+    }
+
+    fn prove(
+        prover: &Sp1Prover,
+        batch: AggregationBatch,
+    ) -> Result<AggregationProof, AggregationError> {
+        prover.prove(batch)
+    }
+
+    fn verify(
+        verifying_key: &SP1VerifyingKey,
+        proof: &AggregationProof,
+        batch: &AggregationBatch,
+    ) -> Result<u32, AggregationError> {
+        verify_sp1_proof(proof, batch, verifying_key)
+    }
+}
+
+/// Verifies an SP1 `proof` over `batch`, returning the number of valid
+/// signatures it attests.
+///
+/// As with [`verify_aggregation_proof`](crate::prover::verify_aggregation_proof),
+/// the verifier needs the exact batch that was proven; `verifying_key` must
+/// belong to the program recorded in the proof's metadata.
+///
+/// # Errors
+///
+/// * `LengthMismatch` - the batch size differs from the proof metadata
+/// * `SerializationError` - the batch or proof bytes cannot be decoded
+/// * `InvalidProof` - the proof does not verify or attests another output
+pub fn verify_sp1_proof(
+    proof: &AggregationProof,
+    batch: &AggregationBatch,
+    verifying_key: &SP1VerifyingKey,
+) -> Result<u32, AggregationError> {
+    verify_with(&ProverClient::from_env(), proof, batch, verifying_key)
+}
+
+fn verify_with(
+    client: &EnvProver,
+    proof: &AggregationProof,
+    batch: &AggregationBatch,
+    verifying_key: &SP1VerifyingKey,
+) -> Result<u32, AggregationError> {
+    let expected_output = expected_output(proof, batch)?;
+    let mut sp1_proof: SP1ProofWithPublicValues =
+        bincode::deserialize(&proof.proof).map_err(|e| AggregationError::SerializationError {
+            message: format!("Invalid proof bytes: {}", e),
+        })?;
+    client
+        .verify(&sp1_proof, verifying_key)
+        .map_err(|_| AggregationError::InvalidProof)?;
+
+    // The proof binds the public values; they must be this batch's output
+    let output: GuestOutput = sp1_proof.public_values.read();
+    if output != expected_output {
+        return Err(AggregationError::InvalidProof);
+    }
+    Ok(proof.verified_count)
+}
@@ -0,0 +1,17 @@
+//! SP1 program verifying an aggregation batch.
+//!
+//! Mirrors the Jolt guest's `verify_aggregation` entry point by calling the
+//! same `guest` crate function, so both proving systems attest the same
+//! [`GuestOutput`] for a batch. The batch is read from stdin and the output
+//! committed as the proof's public values.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use guest::{verify_aggregation_checked, AggregationBatch, GuestOutput};
+
+pub fn main() {
+    let batch: AggregationBatch = sp1_zkvm::io::read();
+    let output: GuestOutput = verify_aggregation_checked(&batch);
+    sp1_zkvm::io::commit(&output);
+}
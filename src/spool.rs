@@ -0,0 +1,776 @@
+//! Disk-spooled batch construction for memory-constrained hosts.
+//!
+//! A 1000-item batch is already several megabytes once serialized, and the
+//! deserialized XMSS structures are larger still. [`SpooledBatch`] keeps
+//! serialized items in memory only up to a configurable ceiling; beyond that it
+//! spills every item to a temporary spool file and keeps nothing but a counter
+//! in RAM.
+//!
+//! At prove time the spooled items can either be streamed one by one via
+//! [`SpooledBatch::items`] or written out as the exact bincode encoding of an
+//! [`AggregationBatch`] via [`SpooledBatch::write_batch`], without the whole
+//! batch ever being materialized on the host.
+//!
+//! With the `encryption` feature, setting `SpoolConfig::encryption_key` seals
+//! every record written to the spool file, so staged messages are never stored
+//! on disk in the clear. Records held in memory are not encrypted. Each record
+//! is bound to its spool file and position, and the record count is kept in
+//! memory, so records reordered, duplicated, dropped, appended or copied from
+//! another spool file fail to open.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::spool::{SpoolConfig, SpooledBatch};
+//! use sig_agg::VerificationItem;
+//! # let incoming: Vec<VerificationItem> = vec![];
+//!
+//! let config = SpoolConfig {
+//!     memory_ceiling: 64 * 1024 * 1024, // 64 MiB
+//!     ..SpoolConfig::default()
+//! };
+//! let mut spool = SpooledBatch::new(config);
+//!
+//! for item in incoming {
+//!     spool.push(&item).expect("Spooling failed");
+//! }
+//!
+//! let mut encoded = Vec::new();
+//! spool.write_batch(&mut encoded).expect("Streaming failed");
+//! ```
+
+use std::{
+    borrow::Cow,
+    env, fs,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "encryption")]
+use crate::encryption::ArtifactKey;
+use crate::{
+    error::AggregationError,
+    retry::{RetryMetrics, RetryPolicy},
+    types::{AggregationBatch, VerificationItem},
+};
+
+static SPOOL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Largest record accepted from a spool file. A sealed item is a few
+/// kilobytes, so a longer length prefix means the file is corrupt.
+const MAX_RECORD_SIZE: u64 = 1024 * 1024;
+
+#[cfg(feature = "encryption")]
+const SPOOL_RECORD_CONTEXT: &str = "sig-agg/spool-record";
+
+/// Configuration for [`SpooledBatch`].
+///
+/// # Fields
+///
+/// * `memory_ceiling` - Maximum bytes of serialized items held in memory
+/// * `spool_dir` - Directory in which the spool file is created
+/// * `encryption_key` - Key sealing spilled records (`encryption` feature)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpoolConfig {
+    /// Maximum bytes of serialized items held in memory before spilling
+    pub memory_ceiling: usize,
+    /// Directory in which the spool file is created
+    pub spool_dir: PathBuf,
+    /// Key used to encrypt records written to the spool file
+    #[cfg(feature = "encryption")]
+    pub encryption_key: Option<ArtifactKey>,
+}
+
+impl Default for SpoolConfig {
+    fn default() -> Self {
+        Self {
+            memory_ceiling: 256 * 1024 * 1024,
+            spool_dir: env::temp_dir(),
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
+        }
+    }
+}
+
+/// Batch under construction whose items may live on disk.
+///
+/// Items are stored in insertion order. Once the in-memory buffer would exceed
+/// [`SpoolConfig::memory_ceiling`], all buffered items are moved to the spool
+/// file and every later item is appended there directly. The spool file is
+/// removed when the `SpooledBatch` is dropped.
+///
+/// Opening and creating the spool file are retried with
+/// [`RetryPolicy::default`] on IO errors; see [`SpooledBatch::with_retry`].
+///
+/// A failed write to the spool file poisons the batch: every later
+/// [`push`](Self::push), [`items`](Self::items) and
+/// [`write_batch`](Self::write_batch) call fails with
+/// [`AggregationError::SpoolPoisoned`], since items may have been lost.
+///
+/// Validation is not performed while spooling; run the items through
+/// [`validate`](crate::validate) or a batch builder before proving.
+#[derive(Debug)]
+pub struct SpooledBatch {
+    config: SpoolConfig,
+    buffered: Vec<Vec<u8>>,
+    buffered_bytes: usize,
+    spill: Option<Spill>,
+    len: usize,
+    poisoned: bool,
+    retry: RetryPolicy,
+    retry_metrics: RetryMetrics,
+}
+
+#[derive(Debug)]
+struct Spill {
+    // Names the spool in the context sealed records are bound to
+    id: String,
+    path: PathBuf,
+    writer: BufWriter<fs::File>,
+}
+
+impl SpooledBatch {
+    /// Creates an empty spooled batch.
+    #[must_use]
+    pub fn new(config: SpoolConfig) -> Self {
+        Self {
+            config,
+            buffered: Vec::new(),
+            buffered_bytes: 0,
+            spill: None,
+            len: 0,
+            poisoned: false,
+            retry: RetryPolicy::default(),
+            retry_metrics: RetryMetrics::new(),
+        }
+    }
+
+    /// Replaces the policy for retrying spool file opens and creates.
+    ///
+    /// Appending to an open spool file is never retried, since a partial
+    /// write cannot be repeated safely.
+    #[must_use]
+    pub const fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Retry activity of this batch's spool file operations.
+    #[must_use]
+    pub const fn retry_metrics(&self) -> &RetryMetrics {
+        &self.retry_metrics
+    }
+
+    /// Appends an item, spilling to disk if the memory ceiling is reached.
+    pub fn push(&mut self, item: &VerificationItem) -> Result<(), AggregationError> {
+        self.check_poisoned()?;
+        let encoded =
+            bincode::serialize(item).map_err(|e| AggregationError::SerializationError {
+                message: format!("Failed to serialize item for spooling: {}", e),
+            })?;
+
+        if self.spill.is_none() && self.buffered_bytes + encoded.len() > self.config.memory_ceiling
+        {
+            self.spill_buffered()?;
+        }
+
+        if let Some(spill) = self.spill.as_mut() {
+            let sealed = seal_record(&self.config, &spill.id, self.len, &encoded)?;
+            if let Err(err) = write_record(&mut spill.writer, &sealed) {
+                // Part of the record may sit in the writer, breaking the
+                // framing of every later record
+                self.poisoned = true;
+                return Err(err.into());
+            }
+        } else {
+            self.buffered_bytes += encoded.len();
+            self.buffered.push(encoded);
+        }
+
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Number of items in the batch.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no items have been pushed.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` once items have been moved to the spool file.
+    #[must_use]
+    pub const fn is_spilled(&self) -> bool {
+        self.spill.is_some()
+    }
+
+    /// Bytes of serialized items currently held in memory.
+    #[must_use]
+    pub const fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes
+    }
+
+    /// Streams the serialized items back in insertion order.
+    ///
+    /// Only one item is held in memory at a time when the batch is spilled.
+    pub fn items(&mut self) -> Result<SpooledItems<'_>, AggregationError> {
+        self.check_poisoned()?;
+        let source = match self.spill.as_mut() {
+            Some(spill) => {
+                spill.writer.flush()?;
+                let file = retry_io(&self.retry, &self.retry_metrics, || {
+                    fs::File::open(&spill.path)
+                })?;
+                Source::File {
+                    id: &spill.id,
+                    reader: BufReader::new(file),
+                }
+            }
+            None => Source::Memory(self.buffered.iter()),
+        };
+
+        Ok(SpooledItems {
+            config: &self.config,
+            source,
+            index: 0,
+            remaining: self.len,
+        })
+    }
+
+    /// Writes the bincode encoding of the equivalent [`AggregationBatch`].
+    ///
+    /// The output is byte-for-byte identical to
+    /// `bincode::serialize(&AggregationBatch { items })` but is produced by
+    /// copying spooled records, so the batch is never deserialized.
+    pub fn write_batch<W: Write>(&mut self, writer: &mut W) -> Result<(), AggregationError> {
+        self.check_poisoned()?;
+        writer.write_all(&(self.len as u64).to_le_bytes())?;
+
+        match self.spill.as_mut() {
+            Some(spill) => {
+                spill.writer.flush()?;
+                let file = retry_io(&self.retry, &self.retry_metrics, || {
+                    fs::File::open(&spill.path)
+                })?;
+                let mut reader = BufReader::new(file);
+                let mut record = Vec::new();
+                for index in 0..self.len {
+                    read_record(&mut reader, &mut record)?;
+                    let record =
+                        open_record(&self.config, &spill.id, index, std::mem::take(&mut record))?;
+                    writer.write_all(&record)?;
+                }
+                expect_end(&mut reader)?;
+            }
+            None => {
+                for record in &self.buffered {
+                    writer.write_all(record)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Materializes the spooled items into an [`AggregationBatch`].
+    ///
+    /// This defeats the memory bound and is intended for batches small enough
+    /// to fit in RAM, e.g. before handing them to a prover that needs owned input.
+    pub fn into_batch(mut self) -> Result<AggregationBatch, AggregationError> {
+        let items = self.items()?.collect::<Result<Vec<_>, _>>()?;
+        Ok(AggregationBatch { items })
+    }
+
+    fn spill_buffered(&mut self) -> Result<(), AggregationError> {
+        let id = spool_file_name();
+        let path = self.config.spool_dir.join(&id);
+        let file = retry_io(&self.retry, &self.retry_metrics, || {
+            fs::create_dir_all(&self.config.spool_dir)?;
+            fs::File::create(&path)
+        })?;
+        let mut writer = BufWriter::new(file);
+
+        // Buffered records are released only once all of them are written
+        let written = self
+            .buffered
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, record)| {
+                let sealed = seal_record(&self.config, &id, index, record)?;
+                write_record(&mut writer, &sealed).map_err(AggregationError::from)
+            });
+        if let Err(err) = written {
+            self.poisoned = true;
+            drop(writer);
+            let _ = fs::remove_file(&path);
+            return Err(err);
+        }
+
+        self.buffered.clear();
+        self.buffered_bytes = 0;
+        self.spill = Some(Spill { id, path, writer });
+        Ok(())
+    }
+
+    const fn check_poisoned(&self) -> Result<(), AggregationError> {
+        if self.poisoned {
+            Err(AggregationError::SpoolPoisoned)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for SpooledBatch {
+    fn drop(&mut self) {
+        if let Some(spill) = self.spill.take() {
+            drop(spill.writer);
+            let _ = fs::remove_file(spill.path);
+        }
+    }
+}
+
+/// Iterator over the items of a [`SpooledBatch`].
+#[derive(Debug)]
+pub struct SpooledItems<'a> {
+    config: &'a SpoolConfig,
+    source: Source<'a>,
+    index: usize,
+    remaining: usize,
+}
+
+#[derive(Debug)]
+enum Source<'a> {
+    Memory(std::slice::Iter<'a, Vec<u8>>),
+    File {
+        id: &'a str,
+        reader: BufReader<fs::File>,
+    },
+}
+
+impl Iterator for SpooledItems<'_> {
+    type Item = Result<VerificationItem, AggregationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let index = self.index;
+        self.index += 1;
+
+        let decoded = match &mut self.source {
+            Source::Memory(records) => records.next().map(|record| decode_item(record)),
+            Source::File { id, reader } => {
+                let mut record = Vec::new();
+                let last = self.remaining == 0;
+                Some(
+                    read_record(reader, &mut record)
+                        .and_then(|()| if last { expect_end(reader) } else { Ok(()) })
+                        .map_err(AggregationError::from)
+                        .and_then(|()| open_record(self.config, id, index, record))
+                        .and_then(|record| decode_item(&record)),
+                )
+            }
+        };
+
+        if decoded.is_none() {
+            self.remaining = 0;
+        }
+        decoded
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+fn decode_item(record: &[u8]) -> Result<VerificationItem, AggregationError> {
+    bincode::deserialize(record).map_err(|e| AggregationError::SerializationError {
+        message: format!("Failed to deserialize spooled item: {}", e),
+    })
+}
+
+// Spilled records are sealed individually so they can still be streamed,
+// each bound to its spool and index.
+#[cfg(feature = "encryption")]
+fn seal_record<'r>(
+    config: &SpoolConfig,
+    id: &str,
+    index: usize,
+    record: &'r [u8],
+) -> Result<Cow<'r, [u8]>, AggregationError> {
+    config
+        .encryption_key
+        .as_ref()
+        .map_or(Ok(Cow::Borrowed(record)), |key| {
+            key.seal(record, &record_context(id, index)).map(Cow::Owned)
+        })
+}
+
+#[cfg(feature = "encryption")]
+fn open_record(
+    config: &SpoolConfig,
+    id: &str,
+    index: usize,
+    record: Vec<u8>,
+) -> Result<Vec<u8>, AggregationError> {
+    match &config.encryption_key {
+        Some(key) => key.open(&record, &record_context(id, index)),
+        None => Ok(record),
+    }
+}
+
+#[cfg(feature = "encryption")]
+fn record_context(id: &str, index: usize) -> String {
+    format!("{}/{}/{}", SPOOL_RECORD_CONTEXT, id, index)
+}
+
+#[cfg(not(feature = "encryption"))]
+#[allow(clippy::unnecessary_wraps)]
+const fn seal_record<'r>(
+    _config: &SpoolConfig,
+    _id: &str,
+    _index: usize,
+    record: &'r [u8],
+) -> Result<Cow<'r, [u8]>, AggregationError> {
+    Ok(Cow::Borrowed(record))
+}
+
+#[cfg(not(feature = "encryption"))]
+#[allow(clippy::unnecessary_wraps)]
+const fn open_record(
+    _config: &SpoolConfig,
+    _id: &str,
+    _index: usize,
+    record: Vec<u8>,
+) -> Result<Vec<u8>, AggregationError> {
+    Ok(record)
+}
+
+// Spool records are length-prefixed so they can be copied without decoding.
+fn write_record<W: Write>(writer: &mut W, record: &[u8]) -> io::Result<()> {
+    writer.write_all(&(record.len() as u64).to_le_bytes())?;
+    writer.write_all(record)
+}
+
+fn read_record<R: Read>(reader: &mut R, record: &mut Vec<u8>) -> io::Result<()> {
+    let mut len = [0u8; 8];
+    reader.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len);
+    if len > MAX_RECORD_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Spool record of {} bytes exceeds the {} byte limit",
+                len, MAX_RECORD_SIZE
+            ),
+        ));
+    }
+    record.resize(len as usize, 0);
+    reader.read_exact(record)
+}
+
+fn retry_io<T>(
+    policy: &RetryPolicy,
+    metrics: &RetryMetrics,
+    mut operation: impl FnMut() -> io::Result<T>,
+) -> Result<T, AggregationError> {
+    policy.run(metrics, || operation().map_err(AggregationError::from))
+}
+
+/// Fails if the spool file holds more records than the batch, whose count
+/// is kept in memory.
+fn expect_end<R: Read>(reader: &mut R) -> io::Result<()> {
+    if reader.read(&mut [0u8; 1])? != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Spool file holds records beyond the batch",
+        ));
+    }
+    Ok(())
+}
+
+fn spool_file_name() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let counter = SPOOL_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("sig-agg-spool-{}-{}-{}.bin", process::id(), nanos, counter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hashsig::MESSAGE_LENGTH;
+    use hashsig::signature::SignatureScheme;
+    use hashsig::signature::generalized_xmss::instantiations_poseidon::lifetime_2_to_the_18::winternitz::SIGWinternitzLifetime18W1;
+    use std::{sync::OnceLock, time::Duration};
+
+    type XMSSSignature = SIGWinternitzLifetime18W1;
+
+    static TEST_KEYPAIR: OnceLock<(
+        <XMSSSignature as SignatureScheme>::PublicKey,
+        <XMSSSignature as SignatureScheme>::SecretKey,
+    )> = OnceLock::new();
+
+    fn create_test_item(epoch: u32) -> VerificationItem {
+        let (pk, sk) = TEST_KEYPAIR.get_or_init(|| {
+            let mut rng = rand::rng();
+            XMSSSignature::key_gen(&mut rng, 0, 20)
+        });
+
+        let message = [epoch as u8; MESSAGE_LENGTH];
+        VerificationItem {
+            message,
+            epoch,
+            signature: XMSSSignature::sign(sk, epoch, &message).expect("Signing should succeed"),
+            public_key: bincode::deserialize(&bincode::serialize(pk).unwrap()).unwrap(),
+        }
+    }
+
+    fn test_config(memory_ceiling: usize) -> SpoolConfig {
+        SpoolConfig {
+            memory_ceiling,
+            spool_dir: env::temp_dir().join("sig-agg-spool-tests"),
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
+        }
+    }
+
+    #[test]
+    fn test_small_batch_stays_in_memory() {
+        let mut spool = SpooledBatch::new(test_config(usize::MAX));
+        for epoch in 0..3 {
+            spool.push(&create_test_item(epoch)).unwrap();
+        }
+
+        assert_eq!(spool.len(), 3);
+        assert!(!spool.is_spilled());
+
+        let epochs: Vec<u32> = spool.items().unwrap().map(|i| i.unwrap().epoch).collect();
+        assert_eq!(epochs, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_spills_when_ceiling_reached() {
+        let item_size = bincode::serialize(&create_test_item(0)).unwrap().len();
+        let mut spool = SpooledBatch::new(test_config(item_size * 2));
+
+        for epoch in 0..5 {
+            spool.push(&create_test_item(epoch)).unwrap();
+        }
+
+        assert!(spool.is_spilled());
+        assert_eq!(spool.buffered_bytes(), 0);
+
+        let epochs: Vec<u32> = spool.items().unwrap().map(|i| i.unwrap().epoch).collect();
+        assert_eq!(epochs, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_write_batch_matches_bincode_encoding() {
+        for ceiling in [0, usize::MAX] {
+            // XMSS signing is randomized, so compare against the very same items
+            let expected = AggregationBatch {
+                items: (0..4).map(create_test_item).collect(),
+            };
+            let mut spool = SpooledBatch::new(test_config(ceiling));
+            for item in &expected.items {
+                spool.push(item).unwrap();
+            }
+
+            let mut streamed = Vec::new();
+            spool.write_batch(&mut streamed).unwrap();
+            assert_eq!(streamed, bincode::serialize(&expected).unwrap());
+
+            let decoded: AggregationBatch = bincode::deserialize(&streamed).unwrap();
+            assert_eq!(decoded.items.len(), 4);
+        }
+    }
+
+    #[test]
+    fn test_into_batch_and_cleanup() {
+        let mut spool = SpooledBatch::new(test_config(0));
+        spool.push(&create_test_item(7)).unwrap();
+        let path = spool.spill.as_ref().unwrap().path.clone();
+        assert!(path.exists());
+
+        let batch = spool.into_batch().unwrap();
+        assert_eq!(batch.items.len(), 1);
+        assert_eq!(batch.items[0].epoch, 7);
+        assert!(!path.exists());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_spill() {
+        let expected = AggregationBatch {
+            items: (0..3).map(create_test_item).collect(),
+        };
+        let mut spool = SpooledBatch::new(SpoolConfig {
+            encryption_key: Some(ArtifactKey::from_bytes([9u8; 32])),
+            ..test_config(0)
+        });
+        for item in &expected.items {
+            spool.push(item).unwrap();
+        }
+
+        // The spool file must not contain the plaintext record
+        let plain = bincode::serialize(&expected.items[0]).unwrap();
+        spool.spill.as_mut().unwrap().writer.flush().unwrap();
+        let on_disk = fs::read(&spool.spill.as_ref().unwrap().path).unwrap();
+        assert!(!on_disk.windows(plain.len()).any(|w| w == plain.as_slice()));
+
+        let mut streamed = Vec::new();
+        spool.write_batch(&mut streamed).unwrap();
+        assert_eq!(streamed, bincode::serialize(&expected).unwrap());
+
+        let epochs: Vec<u32> = spool.items().unwrap().map(|i| i.unwrap().epoch).collect();
+        assert_eq!(epochs, vec![0, 1, 2]);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_spill_detects_rearranged_records() {
+        fn spill(items: &[VerificationItem]) -> SpooledBatch {
+            let mut spool = SpooledBatch::new(SpoolConfig {
+                encryption_key: Some(ArtifactKey::from_bytes([9u8; 32])),
+                ..test_config(0)
+            });
+            for item in items {
+                spool.push(item).unwrap();
+            }
+            spool.spill.as_mut().unwrap().writer.flush().unwrap();
+            spool
+        }
+        fn records(spool: &SpooledBatch) -> Vec<Vec<u8>> {
+            let file = fs::read(&spool.spill.as_ref().unwrap().path).unwrap();
+            let mut reader = file.as_slice();
+            let mut records = Vec::new();
+            while !reader.is_empty() {
+                let mut record = Vec::new();
+                read_record(&mut reader, &mut record).unwrap();
+                records.push(record);
+            }
+            records
+        }
+        fn rewrite(spool: &SpooledBatch, records: &[Vec<u8>]) {
+            let mut file = Vec::new();
+            for record in records {
+                write_record(&mut file, record).unwrap();
+            }
+            fs::write(&spool.spill.as_ref().unwrap().path, file).unwrap();
+        }
+
+        let items: Vec<_> = (0..3).map(create_test_item).collect();
+        let mut spool = spill(&items);
+        let original = records(&spool);
+
+        for tampered in [
+            vec![
+                original[1].clone(),
+                original[0].clone(),
+                original[2].clone(),
+            ],
+            vec![
+                original[0].clone(),
+                original[0].clone(),
+                original[2].clone(),
+            ],
+            vec![original[0].clone(), original[1].clone()],
+            [original.clone(), vec![original[2].clone()]].concat(),
+            records(&spill(&items)),
+        ] {
+            rewrite(&spool, &tampered);
+            assert!(spool.write_batch(&mut Vec::new()).is_err());
+            assert!(spool.items().unwrap().any(|item| item.is_err()));
+        }
+
+        rewrite(&spool, &original);
+        assert!(spool.write_batch(&mut Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn test_retries_spool_file_creation() {
+        // A file where the spool directory should be fails every attempt
+        let blocker = env::temp_dir().join("sig-agg-spool-tests-blocker");
+        fs::write(&blocker, b"").unwrap();
+        let mut spool = SpooledBatch::new(SpoolConfig {
+            spool_dir: blocker.join("spool"),
+            ..test_config(0)
+        })
+        .with_retry(RetryPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::ZERO,
+            ..RetryPolicy::default()
+        });
+
+        assert!(matches!(
+            spool.push(&create_test_item(0)),
+            Err(AggregationError::IoError { .. })
+        ));
+        let metrics = spool.retry_metrics().snapshot();
+        assert_eq!(
+            (metrics.attempts, metrics.retries, metrics.exhausted),
+            (2, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_failed_write_poisons_spool() {
+        let mut spool = SpooledBatch::new(test_config(0));
+        spool.push(&create_test_item(0)).unwrap();
+        assert!(spool.is_spilled());
+
+        // Writes through a read-only handle fail
+        let spill = spool.spill.as_mut().unwrap();
+        spill.writer.flush().unwrap();
+        spill.writer = BufWriter::with_capacity(0, fs::File::open(&spill.path).unwrap());
+
+        assert!(matches!(
+            spool.push(&create_test_item(1)),
+            Err(AggregationError::IoError { .. })
+        ));
+        assert_eq!(
+            spool.push(&create_test_item(2)),
+            Err(AggregationError::SpoolPoisoned)
+        );
+        assert_eq!(spool.items().err(), Some(AggregationError::SpoolPoisoned));
+        assert_eq!(
+            spool.write_batch(&mut Vec::new()),
+            Err(AggregationError::SpoolPoisoned)
+        );
+        assert_eq!(spool.len(), 1);
+    }
+
+    #[test]
+    fn test_rejects_oversized_record() {
+        let mut file = (MAX_RECORD_SIZE + 1).to_le_bytes().to_vec();
+        file.extend_from_slice(&[0u8; 16]);
+
+        let mut record = Vec::new();
+        let err = read_record(&mut file.as_slice(), &mut record).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(record.is_empty());
+    }
+
+    #[test]
+    fn test_empty_spool() {
+        let mut spool = SpooledBatch::new(test_config(0));
+        assert!(spool.is_empty());
+        assert_eq!(spool.items().unwrap().count(), 0);
+
+        let mut streamed = Vec::new();
+        spool.write_batch(&mut streamed).unwrap();
+        assert_eq!(streamed, 0u64.to_le_bytes());
+    }
+}
@@ -0,0 +1,142 @@
+//! Sampled native verification for cheap spot checks.
+//!
+//! Proving a batch costs minutes of prover time, and a batch containing
+//! invalid signatures wastes it. [`spot_check`] verifies a sample of `k`
+//! items outside the zkVM first. If a fraction `f` of the batch is invalid,
+//! the sample misses all of them with probability at most `(1 - f)^k`.
+//!
+//! The sample is seeded by the batch commitment: the same batch always
+//! samples the same items, so a spot check can be reproduced by anyone, and
+//! a submitter cannot predict the sample without fixing the batch first.
+//! Draws are `SHA-256(commitment || counter)`, so the sample does not depend
+//! on the version of any random number generator.
+//!
+//! A spot check gives no guarantee about unsampled items; it is a
+//! pre-flight check, not a replacement for the proof.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::spot_check::spot_check;
+//! # let batch: sig_agg::AggregationBatch = unimplemented!();
+//!
+//! let check = spot_check(&batch, 16).expect("Spot check failed to run");
+//! if !check.passed() {
+//!     eprintln!("Invalid signatures at {:?}; not proving", check.invalid);
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+
+use hashsig::signature::SignatureScheme;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    commitment::batch_commitment,
+    error::AggregationError,
+    types::{AggregationBatch, XMSSSignature},
+};
+
+/// Outcome of a spot check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpotCheck {
+    /// Indices of the sampled items, ascending
+    pub sampled: Vec<usize>,
+    /// Sampled indices whose signature did not verify
+    pub invalid: Vec<usize>,
+}
+
+impl SpotCheck {
+    /// Returns `true` if every sampled signature verified.
+    #[must_use]
+    pub const fn passed(&self) -> bool {
+        self.invalid.is_empty()
+    }
+}
+
+/// Natively verifies `k` items sampled from `batch`, seeded by its
+/// commitment.
+///
+/// Samples every item if the batch has at most `k`.
+///
+/// Fails with [`AggregationError::SerializationError`] if the batch
+/// commitment cannot be computed.
+pub fn spot_check(batch: &AggregationBatch, k: usize) -> Result<SpotCheck, AggregationError> {
+    let sampled = sample_indices(batch, k)?;
+
+    let invalid = sampled
+        .iter()
+        .copied()
+        .filter(|&index| {
+            let item = &batch.items[index];
+            !XMSSSignature::verify(&item.public_key, item.epoch, &item.message, &item.signature)
+        })
+        .collect();
+
+    Ok(SpotCheck { sampled, invalid })
+}
+
+fn sample_indices(batch: &AggregationBatch, k: usize) -> Result<Vec<usize>, AggregationError> {
+    let len = batch.items.len();
+    if k >= len {
+        return Ok((0..len).collect());
+    }
+    Ok(sample_from_seed(&batch_commitment(batch)?, len, k))
+}
+
+/// Samples `k < len` distinct indices below `len`, ascending, with Floyd's
+/// algorithm driven by [`draw`].
+fn sample_from_seed(seed: &[u8; 32], len: usize, k: usize) -> Vec<usize> {
+    let mut sampled = BTreeSet::new();
+    for (counter, bound) in (len - k + 1..=len).enumerate() {
+        let pick = draw(seed, counter as u64, bound);
+        if !sampled.insert(pick) {
+            sampled.insert(bound - 1);
+        }
+    }
+    sampled.into_iter().collect()
+}
+
+/// Index below `bound` from `SHA-256(seed || counter)`.
+///
+/// Reduces a 64-bit value modulo `bound`; the bias is below `bound / 2^64`.
+fn draw(seed: &[u8; 32], counter: u64, bound: usize) -> usize {
+    let digest = Sha256::new()
+        .chain_update(seed)
+        .chain_update(counter.to_le_bytes())
+        .finalize();
+    let value = u64::from_le_bytes(digest[..8].try_into().expect("digest has 32 bytes"));
+    (value % bound as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_batch;
+
+    #[test]
+    fn test_sample_is_deterministic_per_batch() {
+        let batch = test_batch(8);
+        let first = spot_check(&batch, 3).unwrap();
+        assert_eq!(first.sampled.len(), 3);
+        assert!(first.passed());
+        assert_eq!(spot_check(&batch, 3).unwrap(), first);
+    }
+
+    #[test]
+    fn test_small_batch_is_checked_in_full() {
+        let mut batch = test_batch(3);
+        batch.items[1].message[0] ^= 1;
+
+        let check = spot_check(&batch, 5).unwrap();
+        assert_eq!(check.sampled, vec![0, 1, 2]);
+        assert_eq!(check.invalid, vec![1]);
+        assert!(!check.passed());
+    }
+
+    #[test]
+    fn test_sample_is_stable() {
+        let sampled = sample_from_seed(&[0u8; 32], 100, 5);
+        assert_eq!(sampled, vec![10, 12, 49, 58, 89]);
+    }
+}
@@ -0,0 +1,733 @@
+//! Incremental admission of items ahead of aggregation.
+//!
+//! [`aggregate`](crate::aggregate()) validates a complete batch at once and
+//! fails it as a whole. An aggregator that receives signatures one at a time
+//! instead offers each to a [`StagingPool`], which admits or rejects it
+//! individually and records the decision in its [`AuditLog`]. Rejected items
+//! never enter the batch, so one bad submission cannot block the others.
+//!
+//! Items are checked, in order, against:
+//!
+//! 1. [`AdmissionRule::PoolFull`] - the pool already holds `max_items` items
+//! 2. [`AdmissionRule::DuplicateKeyEpochPair`] - the (public_key, epoch) pair is staged
+//! 3. [`AdmissionRule::InvalidSignature`] - the signature does not verify
+//!    (only if `verify_signatures` is set)
+//!
+//! With `record_invalidity` set, each item rejected as an invalid signature
+//! also yields an [`InvalidityTranscript`] for dispute resolution.
+//!
+//! # Withdrawal
+//!
+//! A submitter who admits an item with [`StagingPool::admit_withdrawable`]
+//! passes the [`withdrawal_commitment`] of a secret [`WithdrawalToken`]. Until
+//! the batch is [cut](StagingPool::cut), revealing the token to
+//! [`StagingPool::withdraw`] removes the item and records the withdrawal in
+//! the audit log. Afterwards the same request returns
+//! [`Withdrawal::TooLate`] with the batch the item went into and that batch's
+//! commitment, so the submitter can follow it through proving.
+//!
+//! # Migration
+//!
+//! [`StagingPool::into_snapshot`] captures everything admission depends on:
+//! the configuration, staged items, every reserved (public_key, epoch) pair
+//! including those of cut items, withdrawal state, invalidity transcripts and
+//! the audit log. [`StagingPool::from_snapshot`] restores it on another node,
+//! so submissions are neither lost nor admitted twice. The snapshot consumes
+//! the pool, leaving nothing on the old node to keep admitting. With the
+//! `encryption` feature, `StagingSnapshot::write_encrypted` seals it for
+//! the transfer.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::staging::{StagingConfig, StagingPool, Withdrawal, withdrawal_commitment};
+//! # let (item, token): (sig_agg::VerificationItem, [u8; 32]) = unimplemented!();
+//!
+//! let mut pool = StagingPool::new(StagingConfig::default());
+//! let digest = sig_agg::audit::item_digest(&item).expect("Serialization failed");
+//! pool.admit_withdrawable(item, withdrawal_commitment(&token))
+//!     .expect("Item rejected");
+//!
+//! match pool.withdraw(&digest, &token).expect("Withdrawal rejected") {
+//!     Withdrawal::Withdrawn => println!("Item removed before the cut"),
+//!     Withdrawal::TooLate { batch_id, .. } => println!("Already in batch {}", batch_id),
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use hashsig::signature::SignatureScheme;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "encryption")]
+use crate::encryption::{self, ArtifactKey};
+use crate::{
+    aggregator::{aggregate, validate},
+    audit::{AdmissionDecision, AdmissionRule, AuditEntry, AuditLog, item_digest},
+    commitment::{BatchCommitment, batch_commitment},
+    dispute::InvalidityTranscript,
+    error::AggregationError,
+    types::{AggregationBatch, VerificationItem, XMSSSignature},
+};
+
+/// Domain tag of [`withdrawal_commitment`].
+const WITHDRAWAL_DOMAIN: &[u8] = b"sig-agg/withdrawal/v1";
+
+/// Version of the [`StagingSnapshot`] encoding.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Encryption context of sealed snapshots.
+#[cfg(feature = "encryption")]
+const SNAPSHOT_CONTEXT: &str = "staging-snapshot";
+
+/// Secret a submitter reveals to withdraw a staged item.
+pub type WithdrawalToken = [u8; 32];
+
+/// Commitment to `token` passed at admission: SHA-256 over a domain tag and
+/// the token.
+#[must_use]
+pub fn withdrawal_commitment(token: &WithdrawalToken) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(WITHDRAWAL_DOMAIN);
+    hasher.update(token);
+    hasher.finalize().into()
+}
+
+/// Outcome of an authenticated [`StagingPool::withdraw`] request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Withdrawal {
+    /// The item was removed from the pool and will not be aggregated
+    Withdrawn,
+    /// The item was already cut into a batch
+    TooLate {
+        /// [`BatchCut::batch_id`] of the batch holding the item
+        batch_id: u64,
+        /// Commitment of that batch, which is pending proof
+        #[serde(with = "crate::serde_hex")]
+        commitment: BatchCommitment,
+    },
+}
+
+/// Batch cut from a [`StagingPool`].
+#[derive(Debug)]
+pub struct BatchCut {
+    /// Position of the cut among the pool's cuts, starting at 0
+    pub batch_id: u64,
+    /// [`batch_commitment`] of `batch`
+    pub commitment: BatchCommitment,
+    /// The items staged since the previous cut
+    pub batch: AggregationBatch,
+}
+
+/// Admission limits of a [`StagingPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StagingConfig {
+    /// Maximum number of staged items
+    pub max_items: usize,
+    /// Verify each signature natively before admitting it
+    pub verify_signatures: bool,
+    /// Keep an [`InvalidityTranscript`] for every invalid signature
+    pub record_invalidity: bool,
+}
+
+impl Default for StagingConfig {
+    fn default() -> Self {
+        Self {
+            max_items: 1024,
+            verify_signatures: true,
+            record_invalidity: false,
+        }
+    }
+}
+
+/// Admission state of a [`StagingPool`], moved between nodes.
+///
+/// Produced by [`StagingPool::into_snapshot`] and consumed by
+/// [`StagingPool::from_snapshot`]. Hash-based collections are stored sorted,
+/// so equal pools encode to equal bytes.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StagingSnapshot {
+    version: u32,
+    config: StagingConfig,
+    items: Vec<VerificationItem>,
+    key_epoch_pairs: Vec<(Vec<u8>, u32)>,
+    withdrawal_commitments: Vec<([u8; 32], [u8; 32])>,
+    cut_items: Vec<([u8; 32], u64)>,
+    cuts: Vec<BatchCommitment>,
+    audit_log: Vec<AuditEntry>,
+    transcripts: Vec<InvalidityTranscript>,
+}
+
+impl StagingSnapshot {
+    /// Number of staged items the snapshot carries.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the snapshot carries no staged item.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Encodes the snapshot.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, AggregationError> {
+        bincode::serialize(self).map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to serialize staging snapshot: {}", e),
+        })
+    }
+
+    /// Decodes a snapshot written by [`StagingSnapshot::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AggregationError> {
+        bincode::deserialize(bytes).map_err(|e| AggregationError::SerializationError {
+            message: format!("Invalid staging snapshot: {}", e),
+        })
+    }
+
+    /// Seals the snapshot with `key` and writes it to `path`.
+    #[cfg(feature = "encryption")]
+    pub fn write_encrypted(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        key: &ArtifactKey,
+    ) -> Result<(), AggregationError> {
+        encryption::write_encrypted(path, &self.to_bytes()?, key, SNAPSHOT_CONTEXT)
+    }
+
+    /// Reads and opens a snapshot written by
+    /// [`StagingSnapshot::write_encrypted`].
+    #[cfg(feature = "encryption")]
+    pub fn read_encrypted(
+        path: impl AsRef<std::path::Path>,
+        key: &ArtifactKey,
+    ) -> Result<Self, AggregationError> {
+        Self::from_bytes(&encryption::read_encrypted(path, key, SNAPSHOT_CONTEXT)?)
+    }
+}
+
+/// Items admitted so far, with an audit trail of every decision.
+#[derive(Debug)]
+pub struct StagingPool {
+    config: StagingConfig,
+    items: Vec<VerificationItem>,
+    /// [`item_digest`] of each staged item, parallel to `items`
+    digests: Vec<[u8; 32]>,
+    key_epoch_pairs: HashSet<(Vec<u8>, u32)>,
+    audit_log: AuditLog,
+    transcripts: Vec<InvalidityTranscript>,
+    /// Withdrawal commitments by item digest, kept after the cut to
+    /// authenticate late requests
+    withdrawal_commitments: HashMap<[u8; 32], [u8; 32]>,
+    /// Batch id of each withdrawable item that has been cut
+    cut_items: HashMap<[u8; 32], u64>,
+    /// Commitment of every cut, indexed by batch id
+    cuts: Vec<BatchCommitment>,
+}
+
+impl StagingPool {
+    /// Creates an empty pool.
+    #[must_use]
+    pub fn new(config: StagingConfig) -> Self {
+        Self {
+            config,
+            items: Vec::new(),
+            digests: Vec::new(),
+            key_epoch_pairs: HashSet::new(),
+            audit_log: AuditLog::new(),
+            transcripts: Vec::new(),
+            withdrawal_commitments: HashMap::new(),
+            cut_items: HashMap::new(),
+            cuts: Vec::new(),
+        }
+    }
+
+    /// Offers `item` to the pool.
+    ///
+    /// The decision is appended to the audit log either way. A rejected item
+    /// is dropped and the error for the rule that fired is returned:
+    /// [`BatchTooLarge`](AggregationError::BatchTooLarge),
+    /// [`DuplicateKeyEpochPair`](AggregationError::DuplicateKeyEpochPair) or
+    /// [`InvalidSignature`](AggregationError::InvalidSignature) (with the index
+    /// the item would have had).
+    pub fn admit(&mut self, item: VerificationItem) -> Result<(), AggregationError> {
+        self.stage(item).map(|_| ())
+    }
+
+    /// Offers `item` like [`StagingPool::admit`], and lets whoever knows the
+    /// token behind `withdrawal_commitment` withdraw it until the next cut.
+    pub fn admit_withdrawable(
+        &mut self,
+        item: VerificationItem,
+        withdrawal_commitment: [u8; 32],
+    ) -> Result<(), AggregationError> {
+        let digest = self.stage(item)?;
+        self.withdrawal_commitments
+            .insert(digest, withdrawal_commitment);
+        Ok(())
+    }
+
+    /// Withdraws the staged item with digest `item_digest`.
+    ///
+    /// Fails with [`AggregationError::WithdrawalRejected`] if no withdrawable
+    /// item has that digest or `token` does not match its commitment. Once
+    /// the item has been cut, returns [`Withdrawal::TooLate`] and leaves the
+    /// audit log untouched.
+    pub fn withdraw(
+        &mut self,
+        item_digest: &[u8; 32],
+        token: &WithdrawalToken,
+    ) -> Result<Withdrawal, AggregationError> {
+        let rejected = |reason: &str| AggregationError::WithdrawalRejected {
+            reason: reason.to_string(),
+        };
+        let commitment = self
+            .withdrawal_commitments
+            .get(item_digest)
+            .ok_or_else(|| rejected("item was not staged as withdrawable"))?;
+        if *commitment != withdrawal_commitment(token) {
+            return Err(rejected("withdrawal token does not match"));
+        }
+        if let Some(&batch_id) = self.cut_items.get(item_digest) {
+            return Ok(Withdrawal::TooLate {
+                batch_id,
+                commitment: self.cuts[batch_id as usize],
+            });
+        }
+
+        let index = self
+            .digests
+            .iter()
+            .position(|digest| digest == item_digest)
+            .ok_or_else(|| rejected("item is no longer staged"))?;
+        let item = self.items.remove(index);
+        self.digests.remove(index);
+        self.withdrawal_commitments.remove(item_digest);
+        self.key_epoch_pairs
+            .remove(&(public_key_bytes(&item)?, item.epoch));
+        self.audit_log
+            .append(*item_digest, AdmissionDecision::Withdraw);
+        Ok(Withdrawal::Withdrawn)
+    }
+
+    /// Cuts a batch from the items staged since the previous cut.
+    ///
+    /// The pool stays usable. Pairs of cut items stay reserved, so an epoch
+    /// cannot be staged again for a later batch.
+    pub fn cut(&mut self) -> Result<BatchCut, AggregationError> {
+        validate(&self.items)?;
+        let batch = AggregationBatch {
+            items: std::mem::take(&mut self.items),
+        };
+        let commitment = batch_commitment(&batch)?;
+        let batch_id = self.cuts.len() as u64;
+        for digest in self.digests.drain(..) {
+            if self.withdrawal_commitments.contains_key(&digest) {
+                self.cut_items.insert(digest, batch_id);
+            }
+        }
+        self.cuts.push(commitment);
+        Ok(BatchCut {
+            batch_id,
+            commitment,
+            batch,
+        })
+    }
+
+    /// Checks `item` against the admission rules, recording the decision, and
+    /// returns its digest if it was staged.
+    fn stage(&mut self, item: VerificationItem) -> Result<[u8; 32], AggregationError> {
+        let digest = item_digest(&item)?;
+        let pair = (public_key_bytes(&item)?, item.epoch);
+
+        let rejection = if self.items.len() >= self.config.max_items {
+            Some((
+                AdmissionRule::PoolFull,
+                AggregationError::BatchTooLarge {
+                    size: self.items.len() + 1,
+                    max: self.config.max_items,
+                },
+            ))
+        } else if self.key_epoch_pairs.contains(&pair) {
+            let pk_str = format!("{}...", hex::encode(&pair.0[..8.min(pair.0.len())]));
+            Some((
+                AdmissionRule::DuplicateKeyEpochPair,
+                AggregationError::DuplicateKeyEpochPair {
+                    public_key: pk_str,
+                    epoch: item.epoch,
+                },
+            ))
+        } else if self.config.verify_signatures
+            && !XMSSSignature::verify(&item.public_key, item.epoch, &item.message, &item.signature)
+        {
+            Some((
+                AdmissionRule::InvalidSignature,
+                AggregationError::InvalidSignature {
+                    index: self.items.len(),
+                },
+            ))
+        } else {
+            None
+        };
+
+        if let Some((rule, error)) = rejection {
+            if rule == AdmissionRule::InvalidSignature && self.config.record_invalidity {
+                self.transcripts.extend(InvalidityTranscript::new(&item)?);
+            }
+            self.audit_log
+                .append(digest, AdmissionDecision::Reject { rule });
+            return Err(error);
+        }
+
+        self.audit_log.append(digest, AdmissionDecision::Admit);
+        self.key_epoch_pairs.insert(pair);
+        self.items.push(item);
+        self.digests.push(digest);
+        Ok(digest)
+    }
+
+    /// Admitted items in admission order.
+    #[must_use]
+    pub fn items(&self) -> &[VerificationItem] {
+        &self.items
+    }
+
+    /// Number of admitted items.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if no item has been admitted.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Audit trail of every admission decision.
+    #[must_use]
+    pub const fn audit_log(&self) -> &AuditLog {
+        &self.audit_log
+    }
+
+    /// Transcripts of items rejected as invalid signatures, in rejection order.
+    ///
+    /// Empty unless `record_invalidity` is set.
+    #[must_use]
+    pub fn invalidity_transcripts(&self) -> &[InvalidityTranscript] {
+        &self.transcripts
+    }
+
+    /// Captures the pool's complete state for [`StagingPool::from_snapshot`].
+    #[must_use]
+    pub fn into_snapshot(self) -> StagingSnapshot {
+        let mut key_epoch_pairs: Vec<_> = self.key_epoch_pairs.into_iter().collect();
+        key_epoch_pairs.sort_unstable();
+        let mut withdrawal_commitments: Vec<_> = self.withdrawal_commitments.into_iter().collect();
+        withdrawal_commitments.sort_unstable();
+        let mut cut_items: Vec<_> = self.cut_items.into_iter().collect();
+        cut_items.sort_unstable();
+
+        StagingSnapshot {
+            version: SNAPSHOT_VERSION,
+            config: self.config,
+            items: self.items,
+            key_epoch_pairs,
+            withdrawal_commitments,
+            cut_items,
+            cuts: self.cuts,
+            audit_log: self.audit_log.entries().to_vec(),
+            transcripts: self.transcripts,
+        }
+    }
+
+    /// Restores a pool from `snapshot`.
+    ///
+    /// Fails with [`AggregationError::AuditChainBroken`] if the audit log was
+    /// altered and [`AggregationError::SerializationError`] if the snapshot
+    /// has another version or is inconsistent, e.g. a staged item whose pair
+    /// is not reserved or a cut item without a cut.
+    pub fn from_snapshot(snapshot: StagingSnapshot) -> Result<Self, AggregationError> {
+        let invalid = |reason: &str| AggregationError::SerializationError {
+            message: format!("Invalid staging snapshot: {}", reason),
+        };
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(invalid(&format!(
+                "unsupported version {}",
+                snapshot.version
+            )));
+        }
+        let audit_log = AuditLog::from_entries(snapshot.audit_log)?;
+
+        let key_epoch_pairs: HashSet<_> = snapshot.key_epoch_pairs.into_iter().collect();
+        let mut digests = Vec::with_capacity(snapshot.items.len());
+        for item in &snapshot.items {
+            if !key_epoch_pairs.contains(&(public_key_bytes(item)?, item.epoch)) {
+                return Err(invalid("staged item's pair is not reserved"));
+            }
+            digests.push(item_digest(item)?);
+        }
+        let cut_items: HashMap<_, _> = snapshot.cut_items.into_iter().collect();
+        if cut_items
+            .values()
+            .any(|&batch_id| batch_id >= snapshot.cuts.len() as u64)
+        {
+            return Err(invalid("cut item refers to an unknown cut"));
+        }
+
+        Ok(Self {
+            config: snapshot.config,
+            items: snapshot.items,
+            digests,
+            key_epoch_pairs,
+            audit_log,
+            transcripts: snapshot.transcripts,
+            withdrawal_commitments: snapshot.withdrawal_commitments.into_iter().collect(),
+            cut_items,
+            cuts: snapshot.cuts,
+        })
+    }
+
+    /// Builds the batch from the admitted items, returning it with the audit
+    /// log.
+    pub fn into_batch(self) -> Result<(AggregationBatch, AuditLog), AggregationError> {
+        Ok((aggregate(self.items)?, self.audit_log))
+    }
+}
+
+fn public_key_bytes(item: &VerificationItem) -> Result<Vec<u8>, AggregationError> {
+    bincode::serialize(&item.public_key).map_err(|e| AggregationError::SerializationError {
+        message: format!("Failed to serialize public key: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{audit::verify_chain, test_support::test_batch};
+
+    fn clone_item(item: &VerificationItem) -> VerificationItem {
+        bincode::deserialize(&bincode::serialize(item).unwrap()).unwrap()
+    }
+
+    fn decisions(pool: &StagingPool) -> Vec<AdmissionDecision> {
+        pool.audit_log()
+            .entries()
+            .iter()
+            .map(|entry| entry.decision)
+            .collect()
+    }
+
+    #[test]
+    fn test_admits_and_rejects_with_audit_trail() {
+        let items = test_batch(3).items;
+        let mut tampered = clone_item(&items[2]);
+        tampered.message[0] ^= 1;
+
+        let mut pool = StagingPool::new(StagingConfig {
+            record_invalidity: true,
+            ..StagingConfig::default()
+        });
+        assert!(pool.admit(clone_item(&items[0])).is_ok());
+        assert!(matches!(
+            pool.admit(clone_item(&items[0])),
+            Err(AggregationError::DuplicateKeyEpochPair { epoch: 0, .. })
+        ));
+        assert_eq!(
+            pool.admit(tampered),
+            Err(AggregationError::InvalidSignature { index: 1 })
+        );
+        assert!(pool.admit(clone_item(&items[1])).is_ok());
+
+        assert_eq!(pool.len(), 2);
+        assert_eq!(
+            decisions(&pool),
+            vec![
+                AdmissionDecision::Admit,
+                AdmissionDecision::Reject {
+                    rule: AdmissionRule::DuplicateKeyEpochPair
+                },
+                AdmissionDecision::Reject {
+                    rule: AdmissionRule::InvalidSignature
+                },
+                AdmissionDecision::Admit,
+            ]
+        );
+        assert_eq!(
+            pool.audit_log().entries()[1].item_digest,
+            item_digest(&items[0]).unwrap()
+        );
+        assert!(verify_chain(pool.audit_log().entries()).is_ok());
+
+        let [transcript] = pool.invalidity_transcripts() else {
+            panic!("Expected one invalidity transcript");
+        };
+        assert!(transcript.check().is_ok());
+        assert!(transcript.matches(&pool.audit_log().entries()[2]));
+
+        let (batch, log) = pool.into_batch().unwrap();
+        assert_eq!(batch.items.len(), 2);
+        assert_eq!(log.len(), 4);
+    }
+
+    #[test]
+    fn test_pool_full() {
+        let items = test_batch(2).items;
+        let mut pool = StagingPool::new(StagingConfig {
+            max_items: 1,
+            verify_signatures: false,
+            record_invalidity: false,
+        });
+
+        assert!(pool.admit(clone_item(&items[0])).is_ok());
+        assert_eq!(
+            pool.admit(clone_item(&items[1])),
+            Err(AggregationError::BatchTooLarge { size: 2, max: 1 })
+        );
+        assert_eq!(
+            decisions(&pool)[1],
+            AdmissionDecision::Reject {
+                rule: AdmissionRule::PoolFull
+            }
+        );
+    }
+
+    #[test]
+    fn test_withdrawal_before_and_after_cut() {
+        let items = test_batch(3).items;
+        let digests: Vec<[u8; 32]> = items
+            .iter()
+            .map(|item| item_digest(item).unwrap())
+            .collect();
+        let token = [7u8; 32];
+
+        let mut pool = StagingPool::new(StagingConfig::default());
+        for item in &items {
+            pool.admit_withdrawable(clone_item(item), withdrawal_commitment(&token))
+                .unwrap();
+        }
+
+        assert!(matches!(
+            pool.withdraw(&digests[0], &[8u8; 32]),
+            Err(AggregationError::WithdrawalRejected { .. })
+        ));
+        assert_eq!(
+            pool.withdraw(&digests[0], &token).unwrap(),
+            Withdrawal::Withdrawn
+        );
+        assert_eq!(pool.len(), 2);
+        assert!(matches!(
+            pool.withdraw(&digests[0], &token),
+            Err(AggregationError::WithdrawalRejected { .. })
+        ));
+        assert_eq!(decisions(&pool)[3..], [AdmissionDecision::Withdraw]);
+
+        let cut = pool.cut().unwrap();
+        assert_eq!(cut.batch_id, 0);
+        assert_eq!(cut.batch.items.len(), 2);
+        assert_eq!(cut.commitment, batch_commitment(&cut.batch).unwrap());
+        assert!(pool.is_empty());
+
+        assert_eq!(
+            pool.withdraw(&digests[1], &token).unwrap(),
+            Withdrawal::TooLate {
+                batch_id: 0,
+                commitment: cut.commitment
+            }
+        );
+        assert_eq!(pool.audit_log().len(), 4);
+        assert!(verify_chain(pool.audit_log().entries()).is_ok());
+
+        // A cut pair stays reserved; a withdrawn one can be staged again
+        assert!(pool.admit(clone_item(&items[1])).is_err());
+        assert!(pool.admit(clone_item(&items[0])).is_ok());
+        assert_eq!(pool.cut().unwrap().batch_id, 1);
+    }
+
+    #[test]
+    fn test_snapshot_migration() {
+        let items = test_batch(4).items;
+        let token = [7u8; 32];
+        let mut pool = StagingPool::new(StagingConfig::default());
+        pool.admit_withdrawable(clone_item(&items[0]), withdrawal_commitment(&token))
+            .unwrap();
+        let cut = pool.cut().unwrap();
+        pool.admit(clone_item(&items[1])).unwrap();
+        pool.admit_withdrawable(clone_item(&items[2]), withdrawal_commitment(&token))
+            .unwrap();
+        let head = pool.audit_log().head();
+
+        let bytes = pool.into_snapshot().to_bytes().unwrap();
+        let snapshot = StagingSnapshot::from_bytes(&bytes).unwrap();
+        assert_eq!(snapshot.len(), 2);
+        let mut pool = StagingPool::from_snapshot(snapshot).unwrap();
+        assert_eq!(pool.audit_log().head(), head);
+
+        // Neither staged nor cut items can be admitted again
+        assert!(pool.admit(clone_item(&items[0])).is_err());
+        assert!(pool.admit(clone_item(&items[1])).is_err());
+        assert!(pool.admit(clone_item(&items[3])).is_ok());
+
+        assert_eq!(
+            pool.withdraw(&item_digest(&items[0]).unwrap(), &token)
+                .unwrap(),
+            Withdrawal::TooLate {
+                batch_id: 0,
+                commitment: cut.commitment
+            }
+        );
+        assert_eq!(
+            pool.withdraw(&item_digest(&items[2]).unwrap(), &token)
+                .unwrap(),
+            Withdrawal::Withdrawn
+        );
+        assert_eq!(pool.cut().unwrap().batch.items.len(), 2);
+    }
+
+    #[test]
+    fn test_snapshot_rejects_tampering() {
+        let items = test_batch(1).items;
+        let mut pool = StagingPool::new(StagingConfig::default());
+        pool.admit(clone_item(&items[0])).unwrap();
+
+        let mut snapshot = pool.into_snapshot();
+        snapshot.key_epoch_pairs.clear();
+        assert!(matches!(
+            StagingPool::from_snapshot(snapshot),
+            Err(AggregationError::SerializationError { .. })
+        ));
+
+        let mut pool = StagingPool::new(StagingConfig::default());
+        pool.admit(clone_item(&items[0])).unwrap();
+        let mut snapshot = pool.into_snapshot();
+        snapshot.audit_log[0].decision = AdmissionDecision::Reject {
+            rule: AdmissionRule::PoolFull,
+        };
+        assert_eq!(
+            StagingPool::from_snapshot(snapshot).map(|_| ()),
+            Err(AggregationError::AuditChainBroken { sequence: 0 })
+        );
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_snapshot_file() {
+        let path = std::env::temp_dir()
+            .join("sig-agg-staging-tests")
+            .join("snapshot.bin");
+        let key = ArtifactKey::from_bytes([5u8; 32]);
+        let mut pool = StagingPool::new(StagingConfig::default());
+        pool.admit(test_batch(1).items.remove(0)).unwrap();
+
+        pool.into_snapshot().write_encrypted(&path, &key).unwrap();
+        assert_eq!(
+            StagingSnapshot::read_encrypted(&path, &key).unwrap().len(),
+            1
+        );
+        assert!(
+            StagingSnapshot::read_encrypted(&path, &ArtifactKey::from_bytes([6u8; 32])).is_err()
+        );
+    }
+}
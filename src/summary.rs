@@ -0,0 +1,273 @@
+//! Human-readable proof summaries for release notes and audit packages.
+//!
+//! A [`ProofSummary`] collects what a reader of an audit package needs to
+//! know about a proof without tooling: how many signatures it verifies, which
+//! signers contributed and over which epochs, the batch commitment, the guest
+//! and URS it was produced with, and how to verify it independently. It
+//! renders as Markdown ([`ProofSummary::to_markdown`]) or JSON
+//! ([`ProofSummary::to_json`]).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::summary::ProofSummary;
+//! # let (proof, batch): (sig_agg::AggregationProof, sig_agg::AggregationBatch) = unimplemented!();
+//!
+//! let summary = ProofSummary::new(&proof, &batch).expect("Batch does not match proof");
+//! std::fs::write("PROOF.md", summary.to_markdown()).expect("Write failed");
+//! ```
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commitment::{
+        BatchCommitment, CommitmentAlgorithm, CommitmentHash, KeyFingerprint, Sha256Hash,
+        batch_commitment_using, public_key_fingerprint,
+    },
+    error::AggregationError,
+    guest::GuestHash,
+    profile::ComplianceProfile,
+    template::TemplateRef,
+    types::{AggregationBatch, AggregationProof},
+    urs::UrsDigest,
+};
+
+/// Contribution of one signer to a proven batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerSummary {
+    /// Fingerprint of the signer's public key
+    #[serde(with = "crate::serde_hex")]
+    pub fingerprint: KeyFingerprint,
+    /// Number of items signed by this signer
+    pub count: usize,
+    /// Lowest epoch signed at
+    pub first_epoch: u32,
+    /// Highest epoch signed at
+    pub last_epoch: u32,
+}
+
+/// Description of a proof and the batch it covers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofSummary {
+    /// Number of signatures the proof attests as valid
+    pub verified_count: u32,
+    /// Number of items in the batch
+    pub batch_size: usize,
+    /// Signers ordered by fingerprint
+    pub signers: Vec<SignerSummary>,
+    /// Batch commitment
+    #[serde(with = "crate::serde_hex")]
+    pub commitment: BatchCommitment,
+    /// Hash backend of `commitment`
+    pub commitment_hash: CommitmentAlgorithm,
+    /// Guest program that produced the proof
+    #[serde(with = "crate::serde_hex")]
+    pub guest_hash: GuestHash,
+    /// URS the proof was generated with
+    #[serde(with = "crate::serde_hex")]
+    pub urs_digest: UrsDigest,
+    /// Unix timestamp (seconds) of proof generation
+    pub timestamp: u64,
+    /// Batch template, for templated jobs
+    pub template: Option<TemplateRef>,
+    /// Compliance profile of the prover build
+    pub profile: ComplianceProfile,
+}
+
+impl ProofSummary {
+    /// Summarizes `proof` over `batch` with a SHA-256 commitment.
+    pub fn new(
+        proof: &AggregationProof,
+        batch: &AggregationBatch,
+    ) -> Result<Self, AggregationError> {
+        Self::new_using::<Sha256Hash>(proof, batch)
+    }
+
+    /// Summarizes `proof` over `batch` with a commitment under hash backend
+    /// `H`, which should match the proof's `commitment_hash`.
+    ///
+    /// Fails with [`AggregationError::LengthMismatch`] if the batch size
+    /// differs from the one recorded in the proof.
+    pub fn new_using<H: CommitmentHash>(
+        proof: &AggregationProof,
+        batch: &AggregationBatch,
+    ) -> Result<Self, AggregationError> {
+        let metadata = &proof.metadata;
+        if batch.items.len() != metadata.batch_size {
+            return Err(AggregationError::LengthMismatch {
+                what: "batch items".to_string(),
+                expected: metadata.batch_size,
+                actual: batch.items.len(),
+            });
+        }
+
+        let mut signers: BTreeMap<KeyFingerprint, SignerSummary> = BTreeMap::new();
+        for item in &batch.items {
+            let fingerprint = public_key_fingerprint(&item.public_key)?;
+            signers
+                .entry(fingerprint)
+                .and_modify(|signer| {
+                    signer.count += 1;
+                    signer.first_epoch = signer.first_epoch.min(item.epoch);
+                    signer.last_epoch = signer.last_epoch.max(item.epoch);
+                })
+                .or_insert(SignerSummary {
+                    fingerprint,
+                    count: 1,
+                    first_epoch: item.epoch,
+                    last_epoch: item.epoch,
+                });
+        }
+
+        Ok(Self {
+            verified_count: proof.verified_count,
+            batch_size: batch.items.len(),
+            signers: signers.into_values().collect(),
+            commitment: batch_commitment_using::<H>(batch)?,
+            commitment_hash: H::ALGORITHM,
+            guest_hash: metadata.guest_hash,
+            urs_digest: metadata.urs_digest,
+            timestamp: metadata.timestamp,
+            template: metadata.template.clone(),
+            profile: metadata.profile,
+        })
+    }
+
+    /// Renders the summary as a Markdown document.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut doc = String::from("# Aggregation proof summary\n\n");
+        let _ = writeln!(
+            doc,
+            "- Verified signatures: {} of {}",
+            self.verified_count, self.batch_size
+        );
+        let _ = writeln!(
+            doc,
+            "- Batch commitment ({:?}): `{}`",
+            self.commitment_hash,
+            hex::encode(self.commitment)
+        );
+        let _ = writeln!(doc, "- Guest hash: `{}`", hex::encode(self.guest_hash));
+        let _ = writeln!(doc, "- URS digest: `{}`", hex::encode(self.urs_digest));
+        let _ = writeln!(doc, "- Generated at (Unix time): {}", self.timestamp);
+        if let Some(template) = &self.template {
+            let _ = writeln!(doc, "- Template: {} v{}", template.name, template.version);
+        }
+        let _ = writeln!(doc, "- Compliance profile: {}", self.profile.id());
+
+        let _ = write!(
+            doc,
+            "\n## Signers ({})\n\n\
+             | Fingerprint | Signatures | Epochs |\n\
+             |---|---:|---|\n",
+            self.signers.len()
+        );
+        for signer in &self.signers {
+            let _ = writeln!(
+                doc,
+                "| `{}` | {} | {}..={} |",
+                hex::encode(signer.fingerprint),
+                signer.count,
+                signer.first_epoch,
+                signer.last_epoch
+            );
+        }
+
+        let _ = write!(
+            doc,
+            "\n## Verification\n\n\
+             1. Obtain the verifier preprocessing for guest `{}`.\n\
+             2. Check that the verifier uses URS `{}` (`sha256sum` of the URS file).\n\
+             3. Recompute the {:?} batch commitment from the published batch and compare it to `{}`.\n\
+             4. Verify the proof bytes against the batch with the Jolt verifier.\n",
+            hex::encode(self.guest_hash),
+            hex::encode(self.urs_digest),
+            self.commitment_hash,
+            hex::encode(self.commitment)
+        );
+        doc
+    }
+
+    /// Renders the summary as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, AggregationError> {
+        serde_json::to_string_pretty(self).map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to serialize proof summary: {}", e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_support::{signed_item, test_batch},
+        types::ProofMetadata,
+    };
+
+    fn proof_for(batch: &AggregationBatch) -> AggregationProof {
+        AggregationProof {
+            proof: vec![],
+            verified_count: batch.items.len() as u32,
+            metadata: ProofMetadata {
+                timestamp: 1_700_000_000,
+                batch_size: batch.items.len(),
+                memory_size: 0,
+                trace_length: 0,
+                max_trace_length: 0,
+                proof_size: 0,
+                guest_hash: [1u8; 32],
+                urs_digest: [2u8; 32],
+                commitment_hash: CommitmentAlgorithm::Sha256,
+                template: None,
+                cost: None,
+                profile: ComplianceProfile::Standard,
+            },
+            failed: vec![],
+            verified_root: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_summarizes_signers_and_epochs() {
+        let mut items: Vec<_> = (2..5).map(|epoch| signed_item(0, epoch)).collect();
+        items.push(signed_item(1, 0));
+        let batch = AggregationBatch { items };
+        let summary = ProofSummary::new(&proof_for(&batch), &batch).unwrap();
+
+        assert_eq!(summary.verified_count, 4);
+        assert_eq!(summary.signers.len(), 2);
+        let counts: Vec<_> = summary
+            .signers
+            .iter()
+            .map(|signer| (signer.count, signer.first_epoch, signer.last_epoch))
+            .collect();
+        assert!(counts.contains(&(3, 2, 4)));
+        assert!(counts.contains(&(1, 0, 0)));
+
+        let markdown = summary.to_markdown();
+        assert!(markdown.contains("Verified signatures: 4 of 4"));
+        assert!(markdown.contains(&hex::encode(summary.commitment)));
+        assert!(markdown.contains("| 3 | 2..=4 |"));
+
+        let decoded: ProofSummary = serde_json::from_str(&summary.to_json().unwrap()).unwrap();
+        assert_eq!(decoded, summary);
+    }
+
+    #[test]
+    fn test_rejects_batch_of_wrong_size() {
+        let proof = proof_for(&test_batch(2));
+        let shorter = test_batch(1);
+
+        assert_eq!(
+            ProofSummary::new(&proof, &shorter),
+            Err(AggregationError::LengthMismatch {
+                what: "batch items".to_string(),
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! Named batch templates for recurring aggregation jobs.
+//!
+//! Recurring jobs (checkpoint attestations, daily snapshots, ...) should be
+//! aggregated with the same settings every time. A [`BatchTemplate`] fixes the
+//! application context, the policies a batch must satisfy, the signature
+//! scheme, the commitment hash and an expiry under one name. Operators keep
+//! templates in a [`TemplateRegistry`], usually loaded from a JSON file, and
+//! build batches with [`TemplateRegistry::aggregate_with_template`].
+//!
+//! Each template carries an operator-assigned version. The resulting
+//! [`TemplatedBatch`] records which template version produced it, and
+//! [`TemplatedBatch::apply_to`] copies that into the proof's
+//! [`ProofMetadata`] so a proof can be traced back to the exact settings.
+//! [`TemplatedBatch::policy`] lists the rules that were enforced, for a
+//! [policy-bound commitment](crate::commitment::batch_commitment_with_policy).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::template::TemplateRegistry;
+//! # let items: Vec<sig_agg::VerificationItem> = vec![];
+//! # let mut metadata: sig_agg::ProofMetadata = unimplemented!();
+//!
+//! let file = std::fs::File::open("templates.json").expect("Missing templates");
+//! let registry = TemplateRegistry::from_json(std::io::BufReader::new(file))
+//!     .expect("Invalid templates");
+//!
+//! let templated = registry
+//!     .aggregate_with_template("checkpoints", items)
+//!     .expect("Batch violates template");
+//! // ... prove templated.batch ...
+//! templated.apply_to(&mut metadata);
+//! ```
+
+use std::{
+    collections::HashMap,
+    io::Read,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use hashsig::signature::SignatureScheme;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aggregator::aggregate,
+    commitment::{CommitmentAlgorithm, PolicyMetadata},
+    error::AggregationError,
+    types::{AggregationBatch, ProofMetadata, SIGNATURE_SCHEME, VerificationItem, XMSSSignature},
+};
+
+/// Constraint a templated batch must satisfy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchPolicy {
+    /// At most this many items
+    MaxBatchSize(usize),
+    /// Every epoch lies in `min..=max`
+    EpochRange { min: u32, max: u32 },
+    /// Every signature verifies natively before the batch is built
+    RequireValidSignatures,
+}
+
+/// Reusable settings for a recurring aggregation job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchTemplate {
+    /// Name the template is looked up by
+    pub name: String,
+    /// Operator-assigned version, recorded in proof metadata
+    pub version: u32,
+    /// Application context the signed messages belong to
+    pub context: String,
+    /// Policies checked before the batch is built
+    #[serde(default)]
+    pub policies: Vec<BatchPolicy>,
+    /// Signature scheme, must be [`SIGNATURE_SCHEME`]
+    pub scheme: String,
+    /// Hash backend for the batch commitment
+    #[serde(default)]
+    pub commitment_hash: CommitmentAlgorithm,
+    /// Unix timestamp (seconds) after which the template is rejected
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+/// Name and version of the template a batch was built with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TemplateRef {
+    /// Template name
+    pub name: String,
+    /// Template version
+    pub version: u32,
+}
+
+/// Batch built from a template, with the settings the prover must honour.
+#[derive(Debug)]
+pub struct TemplatedBatch {
+    /// Validated batch
+    pub batch: AggregationBatch,
+    /// Template the batch was built with
+    pub template: TemplateRef,
+    /// Application context from the template
+    pub context: String,
+    /// Hash backend to compute the batch commitment with
+    pub commitment_hash: CommitmentAlgorithm,
+    /// Policies the batch was checked against
+    pub policy: PolicyMetadata,
+}
+
+impl TemplatedBatch {
+    /// Records the template and commitment hash in a proof's metadata.
+    pub fn apply_to(&self, metadata: &mut ProofMetadata) {
+        metadata.template = Some(self.template.clone());
+        metadata.commitment_hash = self.commitment_hash;
+    }
+}
+
+/// Batch templates indexed by name.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateRegistry {
+    templates: HashMap<String, BatchTemplate>,
+}
+
+impl TemplateRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a JSON array of templates.
+    pub fn from_json<R: Read>(reader: R) -> Result<Self, AggregationError> {
+        let templates: Vec<BatchTemplate> =
+            serde_json::from_reader(reader).map_err(|e| AggregationError::SerializationError {
+                message: format!("Invalid template file: {}", e),
+            })?;
+
+        let mut registry = Self::new();
+        for template in templates {
+            registry.register(template)?;
+        }
+        Ok(registry)
+    }
+
+    /// Registers a template, returning the one it replaced.
+    ///
+    /// Fails with [`AggregationError::TemplateRejected`] if the template names
+    /// a signature scheme other than [`SIGNATURE_SCHEME`].
+    pub fn register(
+        &mut self,
+        template: BatchTemplate,
+    ) -> Result<Option<BatchTemplate>, AggregationError> {
+        if template.scheme != SIGNATURE_SCHEME {
+            return Err(AggregationError::TemplateRejected {
+                name: template.name,
+                reason: format!("unsupported signature scheme {}", template.scheme),
+            });
+        }
+        Ok(self.templates.insert(template.name.clone(), template))
+    }
+
+    /// Returns the template named `name`.
+    pub fn get(&self, name: &str) -> Result<&BatchTemplate, AggregationError> {
+        self.templates
+            .get(name)
+            .ok_or_else(|| AggregationError::UnknownTemplate {
+                name: name.to_string(),
+            })
+    }
+
+    /// Builds a batch from `items` under the template named `name`.
+    pub fn aggregate_with_template(
+        &self,
+        name: &str,
+        items: Vec<VerificationItem>,
+    ) -> Result<TemplatedBatch, AggregationError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        self.aggregate_with_template_at(name, items, now)
+    }
+
+    /// Like [`aggregate_with_template`](Self::aggregate_with_template), with
+    /// the current Unix time supplied by the caller.
+    ///
+    /// # Errors
+    ///
+    /// * `UnknownTemplate` - no template is registered under `name`
+    /// * `TemplateRejected` - the template expired or an epoch is out of range
+    /// * `BatchTooLarge` - the batch exceeds the template's size limit
+    /// * `InvalidSignature` - a signature fails and the template requires
+    ///   valid signatures
+    /// * any error of [`aggregate`](crate::aggregate())
+    pub fn aggregate_with_template_at(
+        &self,
+        name: &str,
+        items: Vec<VerificationItem>,
+        now: u64,
+    ) -> Result<TemplatedBatch, AggregationError> {
+        let template = self.get(name)?;
+        let rejected = |reason: String| AggregationError::TemplateRejected {
+            name: template.name.clone(),
+            reason,
+        };
+
+        if let Some(expires_at) = template.expires_at
+            && now >= expires_at
+        {
+            return Err(rejected(format!("expired at {}", expires_at)));
+        }
+
+        let mut policy = PolicyMetadata {
+            template: Some(TemplateRef {
+                name: template.name.clone(),
+                version: template.version,
+            }),
+            ..PolicyMetadata::default()
+        };
+        for batch_policy in &template.policies {
+            match *batch_policy {
+                BatchPolicy::MaxBatchSize(max) if items.len() > max => {
+                    return Err(AggregationError::BatchTooLarge {
+                        size: items.len(),
+                        max,
+                    });
+                }
+                BatchPolicy::EpochRange { min, max } => {
+                    if let Some(item) = items.iter().find(|item| !(min..=max).contains(&item.epoch))
+                    {
+                        return Err(rejected(format!(
+                            "epoch {} outside {}..={}",
+                            item.epoch, min, max
+                        )));
+                    }
+                    policy.epoch_window = Some(min..=max);
+                }
+                BatchPolicy::RequireValidSignatures => {
+                    if let Some(index) = items.iter().position(|item| {
+                        !XMSSSignature::verify(
+                            &item.public_key,
+                            item.epoch,
+                            &item.message,
+                            &item.signature,
+                        )
+                    }) {
+                        return Err(AggregationError::InvalidSignature { index });
+                    }
+                    // Every item had to verify
+                    policy.threshold = u32::try_from(items.len()).ok();
+                }
+                BatchPolicy::MaxBatchSize(_) => {}
+            }
+        }
+
+        Ok(TemplatedBatch {
+            batch: aggregate(items)?,
+            template: TemplateRef {
+                name: template.name.clone(),
+                version: template.version,
+            },
+            context: template.context.clone(),
+            commitment_hash: template.commitment_hash,
+            policy,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{profile::ComplianceProfile, test_support::test_batch};
+
+    fn checkpoints() -> BatchTemplate {
+        BatchTemplate {
+            name: "checkpoints".to_string(),
+            version: 3,
+            context: "chain-a/checkpoint".to_string(),
+            policies: vec![
+                BatchPolicy::MaxBatchSize(4),
+                BatchPolicy::EpochRange { min: 0, max: 5 },
+                BatchPolicy::RequireValidSignatures,
+            ],
+            scheme: SIGNATURE_SCHEME.to_string(),
+            commitment_hash: CommitmentAlgorithm::Keccak256,
+            expires_at: Some(1_000),
+        }
+    }
+
+    fn registry() -> TemplateRegistry {
+        let mut registry = TemplateRegistry::new();
+        registry.register(checkpoints()).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_templated_batch_records_template() {
+        let templated = registry()
+            .aggregate_with_template_at("checkpoints", test_batch(3).items, 0)
+            .unwrap();
+
+        assert_eq!(templated.batch.items.len(), 3);
+        assert_eq!(templated.context, "chain-a/checkpoint");
+        assert_eq!(templated.policy.epoch_window, Some(0..=5));
+        assert_eq!(templated.policy.threshold, Some(3));
+        assert_eq!(templated.policy.template, Some(templated.template.clone()));
+
+        let mut metadata = ProofMetadata {
+            timestamp: 0,
+            batch_size: 3,
+            memory_size: 0,
+            trace_length: 0,
+            max_trace_length: 0,
+            proof_size: 0,
+            guest_hash: [0u8; 32],
+            urs_digest: [0u8; 32],
+            commitment_hash: CommitmentAlgorithm::Sha256,
+            template: None,
+            cost: None,
+            profile: ComplianceProfile::Standard,
+        };
+        templated.apply_to(&mut metadata);
+        assert_eq!(
+            metadata.template,
+            Some(TemplateRef {
+                name: "checkpoints".to_string(),
+                version: 3
+            })
+        );
+        assert_eq!(metadata.commitment_hash, CommitmentAlgorithm::Keccak256);
+    }
+
+    #[test]
+    fn test_policies_are_enforced() {
+        let registry = registry();
+
+        assert_eq!(
+            registry
+                .aggregate_with_template_at("checkpoints", test_batch(5).items, 0)
+                .unwrap_err(),
+            AggregationError::BatchTooLarge { size: 5, max: 4 }
+        );
+
+        let mut items = test_batch(2).items;
+        items[1].epoch = 9;
+        assert!(matches!(
+            registry.aggregate_with_template_at("checkpoints", items, 0),
+            Err(AggregationError::TemplateRejected { .. })
+        ));
+
+        let mut items = test_batch(2).items;
+        items[1].message[0] ^= 1;
+        assert_eq!(
+            registry
+                .aggregate_with_template_at("checkpoints", items, 0)
+                .unwrap_err(),
+            AggregationError::InvalidSignature { index: 1 }
+        );
+    }
+
+    #[test]
+    fn test_expired_and_unknown_templates() {
+        let registry = registry();
+
+        assert!(matches!(
+            registry.aggregate_with_template_at("checkpoints", test_batch(1).items, 1_000),
+            Err(AggregationError::TemplateRejected { .. })
+        ));
+        assert_eq!(
+            registry
+                .aggregate_with_template_at("snapshots", test_batch(1).items, 0)
+                .unwrap_err(),
+            AggregationError::UnknownTemplate {
+                name: "snapshots".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_json() {
+        let json = format!(
+            r#"[{{
+                "name": "snapshots",
+                "version": 1,
+                "context": "daily",
+                "policies": [{{"max_batch_size": 100}}, "require_valid_signatures"],
+                "scheme": "{}"
+            }}]"#,
+            SIGNATURE_SCHEME
+        );
+        let registry = TemplateRegistry::from_json(json.as_bytes()).unwrap();
+        let template = registry.get("snapshots").unwrap();
+
+        assert_eq!(template.commitment_hash, CommitmentAlgorithm::Sha256);
+        assert_eq!(template.expires_at, None);
+        assert_eq!(template.policies[0], BatchPolicy::MaxBatchSize(100));
+
+        let mut other = checkpoints();
+        other.scheme = "SPHINCS+".to_string();
+        assert!(matches!(
+            TemplateRegistry::new().register(other),
+            Err(AggregationError::TemplateRejected { .. })
+        ));
+    }
+}
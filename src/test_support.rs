@@ -0,0 +1,59 @@
+//! Fixtures shared by unit tests.
+//!
+//! XMSS key generation dominates test time, so tests sign with a few
+//! keypairs generated once per test binary.
+
+// `unreachable_pub` asks for `pub(crate)`, which this nursery lint flags.
+#![allow(clippy::redundant_pub_crate)]
+
+use std::sync::OnceLock;
+
+use hashsig::{MESSAGE_LENGTH, signature::SignatureScheme};
+
+use crate::{
+    keys::PublicKey,
+    types::{AggregationBatch, VerificationItem, XMSSSignature},
+};
+
+type SecretKey = <XMSSSignature as SignatureScheme>::SecretKey;
+
+/// Number of distinct test signers.
+pub(crate) const SIGNERS: usize = 3;
+
+/// Test keys can sign epochs `0..TEST_LIFETIME`.
+pub(crate) const TEST_LIFETIME: u32 = 64;
+
+static TEST_KEYPAIRS: [OnceLock<(PublicKey, SecretKey)>; SIGNERS] =
+    [const { OnceLock::new() }; SIGNERS];
+
+fn keypair(signer: usize) -> &'static (PublicKey, SecretKey) {
+    TEST_KEYPAIRS[signer].get_or_init(|| {
+        let mut rng = rand::rng();
+        XMSSSignature::key_gen(&mut rng, 0, TEST_LIFETIME as usize)
+    })
+}
+
+/// A copy of the public key of test signer `signer` (`< SIGNERS`).
+pub(crate) fn public_key(signer: usize) -> PublicKey {
+    let bytes = bincode::serialize(&keypair(signer).0).expect("Serialization should succeed");
+    bincode::deserialize(&bytes).expect("Deserialization should succeed")
+}
+
+/// Item of test signer `signer` at `epoch`, signing `[epoch as u8; MESSAGE_LENGTH]`.
+pub(crate) fn signed_item(signer: usize, epoch: u32) -> VerificationItem {
+    let message = [epoch as u8; MESSAGE_LENGTH];
+    VerificationItem {
+        message,
+        epoch,
+        signature: XMSSSignature::sign(&keypair(signer).1, epoch, &message)
+            .expect("Signing should succeed"),
+        public_key: public_key(signer),
+    }
+}
+
+/// Batch of signer 0's items at epochs `0..n`.
+pub(crate) fn test_batch(n: u32) -> AggregationBatch {
+    AggregationBatch {
+        items: (0..n).map(|epoch| signed_item(0, epoch)).collect(),
+    }
+}
@@ -9,8 +9,17 @@ use hashsig::{
 };
 use serde::{Deserialize, Serialize};
 
-// Type alias for the XMSS signature scheme we're using
-type XMSSSignature = SIGWinternitzLifetime18W1;
+use crate::{
+    commitment::CommitmentAlgorithm, error::AggregationError, profile::ComplianceProfile,
+    report::CostReport, template::TemplateRef,
+};
+
+/// XMSS instantiation of [`VerificationItem`] and [`AggregationBatch`], and
+/// the one the `verify_aggregation` guest entry point verifies.
+pub type XMSSSignature = SIGWinternitzLifetime18W1;
+
+/// Name of the XMSS instantiation items are verified under.
+pub const SIGNATURE_SCHEME: &str = "SIGWinternitzLifetime18W1";
 
 /// Represents a single XMSS signature with its verification context.
 ///
@@ -18,6 +27,10 @@ type XMSSSignature = SIGWinternitzLifetime18W1;
 /// signature: the message, the epoch (one-time signature index), the signature,
 /// and the public key.
 ///
+/// It is the [`SchemeItem`] of [`XMSSSignature`]; items of [other
+/// instantiations](crate::scheme) are proven by their own guest entry
+/// points.
+///
 /// # Fields
 ///
 /// * `message` - The message that was signed (fixed-length array)
@@ -56,16 +69,22 @@ type XMSSSignature = SIGWinternitzLifetime18W1;
 ///
 /// `VerificationItem` implements `Serialize` and `Deserialize` for zkVM I/O
 /// compatibility. Items are serialized when passed to the zkVM guest program.
+pub type VerificationItem = SchemeItem<XMSSSignature>;
+
+/// A signature under instantiation `S` with its verification context.
+///
+/// [`VerificationItem`] is the item of the default instantiation.
 #[derive(Serialize, Deserialize)]
-pub struct VerificationItem {
+#[serde(bound = "")]
+pub struct SchemeItem<S: SignatureScheme> {
     /// Message that was signed (fixed-length byte array)
     pub message: [u8; MESSAGE_LENGTH],
     /// Epoch (XMSS one-time signature index) when signature was created
     pub epoch: u32,
     /// XMSS signature data
-    pub signature: <XMSSSignature as SignatureScheme>::Signature,
+    pub signature: S::Signature,
     /// Public key used to create this signature
-    pub public_key: <XMSSSignature as SignatureScheme>::PublicKey,
+    pub public_key: S::PublicKey,
 }
 
 /// Batch of signatures ready for zkVM verification.
@@ -102,14 +121,48 @@ pub struct VerificationItem {
 ///
 /// - **Host side**: Batches are created and serialized for zkVM input
 /// - **Guest side**: Batches are deserialized and verified within zkVM
+pub type AggregationBatch = SchemeBatch<XMSSSignature>;
+
+/// A batch of items under instantiation `S`.
+///
+/// [`AggregationBatch`] is the batch of the default instantiation.
 #[derive(Serialize, Deserialize)]
-pub struct AggregationBatch {
+#[serde(bound = "")]
+pub struct SchemeBatch<S: SignatureScheme> {
     /// Collection of verification items to verify
-    pub items: Vec<VerificationItem>,
+    pub items: Vec<SchemeItem<S>>,
+}
+
+impl<S: SignatureScheme> SchemeItem<S> {
+    /// Returns the size in bytes of this item's bincode encoding.
+    ///
+    /// This is the number of bytes the item contributes to a serialized
+    /// [`AggregationBatch`], and therefore to the zkVM input.
+    pub fn encoded_size(&self) -> Result<usize, AggregationError> {
+        encoded_size(self)
+    }
+}
+
+impl<S: SignatureScheme> SchemeBatch<S> {
+    /// Returns the size in bytes of this batch's bincode encoding.
+    ///
+    /// Equal to the 8-byte length prefix plus the sum of
+    /// [`VerificationItem::encoded_size`] over all items.
+    pub fn encoded_size(&self) -> Result<usize, AggregationError> {
+        encoded_size(self)
+    }
+}
+
+fn encoded_size<T: Serialize>(value: &T) -> Result<usize, AggregationError> {
+    bincode::serialized_size(value)
+        .map(|size| size as usize)
+        .map_err(|e| AggregationError::SerializationError {
+            message: format!("Failed to compute encoded size: {}", e),
+        })
 }
 
 // Debug implementations for types containing non-Debug XMSS cryptographic primitives
-impl std::fmt::Debug for VerificationItem {
+impl<S: SignatureScheme> std::fmt::Debug for SchemeItem<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("VerificationItem")
             .field("epoch", &self.epoch)
@@ -130,7 +183,7 @@ impl std::fmt::Debug for VerificationItem {
     }
 }
 
-impl std::fmt::Debug for AggregationBatch {
+impl<S: SignatureScheme> std::fmt::Debug for SchemeBatch<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AggregationBatch")
             .field("items", &format_args!("[{} items]", self.items.len()))
@@ -138,6 +191,28 @@ impl std::fmt::Debug for AggregationBatch {
     }
 }
 
+/// How public keys are supplied and which uniqueness rule applies to a
+/// [`ModeBatch`](crate::mode::ModeBatch).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AggregationMode {
+    /// All signatures are by one signer, whose public key the batch carries
+    /// once; each epoch may occur only once
+    SingleKey,
+    /// Each item carries its own public key; each (public key, epoch) pair
+    /// may occur only once
+    #[default]
+    MultiKey,
+}
+
+impl std::fmt::Display for AggregationMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SingleKey => write!(f, "single-key"),
+            Self::MultiKey => write!(f, "multi-key"),
+        }
+    }
+}
+
 /// Metadata about zkVM proof generation.
 ///
 /// Contains information about when and how a proof was generated, including
@@ -148,18 +223,35 @@ impl std::fmt::Debug for AggregationBatch {
 /// * `timestamp` - Unix timestamp (seconds since epoch) when proof was generated
 /// * `batch_size` - Number of signatures verified in this proof
 /// * `memory_size` - zkVM memory size used during proof generation
-/// * `trace_length` - Maximum trace length configured for zkVM
+/// * `trace_length` - Execution trace length (cycles) of the proven run
+/// * `max_trace_length` - Maximum trace length configured for the guest
+/// * `guest_hash` - SHA-256 of the guest program that produced the proof
+/// * `urs_digest` - SHA-256 of the URS used for proving
+/// * `commitment_hash` - Hash backend used for the batch commitment
+/// * `template` - Batch template the proven batch was built with, if any
+/// * `cost` - Resources spent producing the proof, if measured
+/// * `profile` - Compliance profile of the build that produced the proof
 ///
 /// # Examples
 ///
 /// ```
 /// use sig_agg::ProofMetadata;
+/// use sig_agg::commitment::CommitmentAlgorithm;
+/// use sig_agg::profile::ComplianceProfile;
 ///
 /// let metadata = ProofMetadata {
 ///     timestamp: 1234567890,
 ///     batch_size: 1000,
 ///     memory_size: 10240,   // 10MB
-///     trace_length: 65536,  // Max trace entries
+///     trace_length: 48213,      // Cycles actually executed
+///     max_trace_length: 65536,  // Configured limit
+///     proof_size: 262144,       // Serialized proof bytes
+///     guest_hash: [0u8; 32],
+///     urs_digest: [0u8; 32],
+///     commitment_hash: CommitmentAlgorithm::Sha256,
+///     template: None,
+///     cost: None,
+///     profile: ComplianceProfile::ACTIVE,
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -168,10 +260,39 @@ pub struct ProofMetadata {
     pub timestamp: u64,
     /// Number of signatures verified in this proof
     pub batch_size: usize,
-    /// zkVM memory size used (in MB)
+    /// zkVM memory size of the proven run (in MB)
     pub memory_size: usize,
-    /// Maximum zkVM trace length configured
+    /// Execution trace length (cycles) actually used by the proven run
     pub trace_length: usize,
+    /// Maximum zkVM trace length configured for the guest
+    pub max_trace_length: usize,
+    /// Size in bytes of the serialized proof
+    pub proof_size: usize,
+    /// Hash of the guest program build, used to select the matching verifier
+    pub guest_hash: [u8; 32],
+    /// Digest of the URS (commitment parameters) used for proving
+    pub urs_digest: [u8; 32],
+    /// Hash backend the batch commitment was computed with
+    pub commitment_hash: CommitmentAlgorithm,
+    /// Name and version of the batch template, for templated jobs
+    pub template: Option<TemplateRef>,
+    /// Prover resources spent on this proof, for cost attribution
+    pub cost: Option<CostReport>,
+    /// Compliance profile of the prover build, for audits
+    pub profile: ComplianceProfile,
+}
+
+impl ProofMetadata {
+    /// Fraction of the configured trace limit used by the proven run.
+    ///
+    /// Values close to 1.0 mean the batch size is near the guest's capacity.
+    #[must_use]
+    pub fn trace_utilization(&self) -> f64 {
+        if self.max_trace_length == 0 {
+            return 0.0;
+        }
+        self.trace_length as f64 / self.max_trace_length as f64
+    }
 }
 
 /// Succinct aggregation proof from zkVM verification.
@@ -184,9 +305,17 @@ pub struct ProofMetadata {
 ///
 /// * `proof` - Serialized Jolt zkVM proof bytes
 /// * `verified_count` - Number of signatures successfully verified
-/// * `mode` - Aggregation mode used (SingleKey or MultiKey)
 /// * `metadata` - Proof generation metadata (timestamp, batch size, zkVM config)
 ///
+/// # Aggregation Mode
+///
+/// The proof does not record the [`AggregationMode`] of its batch. A
+/// `SingleKey` batch is proven with the single-key guest entry point, whose
+/// output echoes the [`single_key_input_digest`](crate::guest::single_key_input_digest)
+/// of the batch, under a domain tag distinct from that of the multi-key
+/// [`input_digest`](crate::guest::input_digest). Checking the echoed digest
+/// against the expected batch therefore also checks the mode.
+///
 /// # Proof Verification
 ///
 /// The proof can be verified independently by anyone with:
@@ -209,6 +338,8 @@ pub struct ProofMetadata {
 ///
 /// ```no_run
 /// use sig_agg::{AggregationProof, ProofMetadata};
+/// use sig_agg::commitment::CommitmentAlgorithm;
+/// use sig_agg::profile::ComplianceProfile;
 ///
 /// # let proof_bytes = vec![];
 /// // After zkVM proof generation
@@ -219,8 +350,18 @@ pub struct ProofMetadata {
 ///         timestamp: 1234567890,
 ///         batch_size: 1000,
 ///         memory_size: 10240,
-///         trace_length: 65536,
+///         trace_length: 48213,
+///         max_trace_length: 65536,
+///         proof_size: 0,
+///         guest_hash: [0u8; 32],
+///         urs_digest: [0u8; 32],
+///         commitment_hash: CommitmentAlgorithm::Sha256,
+///         template: None,
+///         cost: None,
+///         profile: ComplianceProfile::ACTIVE,
 ///     },
+///     failed: vec![],
+///     verified_root: [0u8; 32],
 /// };
 ///
 /// println!("Verified {} signatures", proof.verified_count);
@@ -234,6 +375,31 @@ pub struct AggregationProof {
     pub verified_count: u32,
     /// Proof generation metadata
     pub metadata: ProofMetadata,
+    /// Packed bitmap of items that failed verification, as output by the
+    /// guest: bit `i % 8` of byte `i / 8` is set if item `i` failed.
+    pub failed: Vec<u8>,
+    /// Merkle root of the items that verified, as output by the guest; see
+    /// [`inclusion`](crate::inclusion).
+    pub verified_root: [u8; 32],
+}
+
+impl AggregationProof {
+    /// Indices of the batch items that failed verification, ascending.
+    ///
+    /// Lets callers identify and evict bad signers rather than only learn
+    /// how many signatures passed.
+    #[must_use]
+    pub fn failed_indices(&self) -> Vec<usize> {
+        self.failed
+            .iter()
+            .enumerate()
+            .flat_map(|(byte_index, byte)| {
+                (0..8)
+                    .filter(move |bit| byte & (1 << bit) != 0)
+                    .map(move |bit| byte_index * 8 + bit)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -335,13 +501,26 @@ mod tests {
             timestamp: 1234567890,
             batch_size: 100,
             memory_size: 10240,
-            trace_length: 65536,
+            trace_length: 48213,
+            max_trace_length: 65536,
+            proof_size: 0,
+            guest_hash: [7u8; 32],
+            urs_digest: [9u8; 32],
+            commitment_hash: CommitmentAlgorithm::Blake3,
+            template: Some(TemplateRef {
+                name: "checkpoints".to_string(),
+                version: 2,
+            }),
+            cost: None,
+            profile: ComplianceProfile::Fips,
         };
 
         let proof = AggregationProof {
             proof: vec![1, 2, 3, 4, 5],
             verified_count: 100,
             metadata,
+            failed: vec![0b0000_0100, 0b1000_0000],
+            verified_root: [3u8; 32],
         };
 
         // Test serialization
@@ -353,7 +532,44 @@ mod tests {
 
         assert_eq!(deserialized.proof, proof.proof);
         assert_eq!(deserialized.verified_count, 100);
+        assert_eq!(deserialized.failed_indices(), vec![2, 15]);
         assert_eq!(deserialized.metadata.batch_size, 100);
+        assert_eq!(deserialized.metadata.guest_hash, [7u8; 32]);
+        assert_eq!(deserialized.metadata.trace_length, 48213);
+        assert_eq!(
+            deserialized.metadata.commitment_hash,
+            CommitmentAlgorithm::Blake3
+        );
+        assert_eq!(
+            deserialized
+                .metadata
+                .template
+                .map(|template| template.version),
+            Some(2)
+        );
+        assert_eq!(deserialized.metadata.profile, ComplianceProfile::Fips);
+    }
+
+    #[test]
+    fn test_trace_utilization() {
+        let mut metadata = ProofMetadata {
+            timestamp: 0,
+            batch_size: 1,
+            memory_size: 0,
+            trace_length: 16384,
+            max_trace_length: 65536,
+            proof_size: 0,
+            guest_hash: [0u8; 32],
+            urs_digest: [0u8; 32],
+            commitment_hash: CommitmentAlgorithm::Sha256,
+            template: None,
+            cost: None,
+            profile: ComplianceProfile::Standard,
+        };
+        assert!((metadata.trace_utilization() - 0.25).abs() < f64::EPSILON);
+
+        metadata.max_trace_length = 0;
+        assert!(metadata.trace_utilization().abs() < f64::EPSILON);
     }
 
     #[test]
@@ -393,6 +609,31 @@ mod tests {
         assert_eq!(batch.items.len(), 1);
     }
 
+    #[test]
+    fn test_encoded_size_matches_serialization() {
+        let (pk, sk) = get_test_keypair();
+        let pk_bytes = bincode::serialize(pk).unwrap();
+
+        let items: Vec<VerificationItem> = (0..2)
+            .map(|i| VerificationItem {
+                message: [i as u8; MESSAGE_LENGTH],
+                epoch: i,
+                signature: XMSSSignature::sign(sk, i, &[i as u8; MESSAGE_LENGTH])
+                    .expect("Signing should succeed"),
+                public_key: bincode::deserialize(&pk_bytes).unwrap(),
+            })
+            .collect();
+
+        let item_size = items[0].encoded_size().unwrap();
+        assert_eq!(item_size, bincode::serialize(&items[0]).unwrap().len());
+
+        let item_sizes: usize = items.iter().map(|i| i.encoded_size().unwrap()).sum();
+        let batch = AggregationBatch { items };
+        let batch_size = batch.encoded_size().unwrap();
+        assert_eq!(batch_size, bincode::serialize(&batch).unwrap().len());
+        assert_eq!(batch_size, 8 + item_sizes);
+    }
+
     #[test]
     fn test_verification_item_debug() {
         let (pk, sk) = get_test_keypair();
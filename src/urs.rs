@@ -0,0 +1,125 @@
+//! Integrity pinning of the prover's commitment parameters.
+//!
+//! A Jolt proof only verifies against the same Dory URS it was generated
+//! with. When prover and verifier load different URS files, verification
+//! fails with an opaque PCS error. Recording the URS digest in
+//! [`ProofMetadata::urs_digest`] lets the verifier detect the mismatch up front
+//! via [`check_urs_digest`] and report it plainly.
+//!
+//! The digest is the plain SHA-256 of the URS file, so it can be cross-checked
+//! with `sha256sum`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use sig_agg::urs::{check_urs_digest, urs_file_digest};
+//! # let proof: sig_agg::AggregationProof = unimplemented!();
+//!
+//! let verifier_urs = urs_file_digest("dory_urs_33_variables.urs").expect("Missing URS");
+//! check_urs_digest(&proof.metadata, &verifier_urs).expect("Proof uses a different URS");
+//! ```
+
+use std::{fs, io, path::Path};
+
+use sha2::{Digest, Sha256};
+
+use crate::{error::AggregationError, types::ProofMetadata};
+
+/// SHA-256 digest of a URS file.
+pub type UrsDigest = [u8; 32];
+
+/// Computes the digest of URS bytes read from `reader`.
+pub fn urs_digest<R: io::Read>(mut reader: R) -> Result<UrsDigest, AggregationError> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+/// Computes the digest of the URS file at `path`.
+pub fn urs_file_digest(path: impl AsRef<Path>) -> Result<UrsDigest, AggregationError> {
+    urs_digest(io::BufReader::new(fs::File::open(path)?))
+}
+
+/// Checks that a proof was generated with the verifier's URS.
+///
+/// Returns [`AggregationError::ProofVerificationError`] naming both digests
+/// if they differ.
+pub fn check_urs_digest(
+    metadata: &ProofMetadata,
+    verifier_digest: &UrsDigest,
+) -> Result<(), AggregationError> {
+    if metadata.urs_digest == *verifier_digest {
+        return Ok(());
+    }
+
+    Err(AggregationError::ProofVerificationError {
+        message: format!(
+            "URS mismatch: proof was generated with URS {} but verifier uses {}",
+            hex::encode(metadata.urs_digest),
+            hex::encode(verifier_digest)
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commitment::CommitmentAlgorithm, profile::ComplianceProfile};
+    use std::env;
+
+    fn metadata_with(urs_digest: UrsDigest) -> ProofMetadata {
+        ProofMetadata {
+            timestamp: 0,
+            batch_size: 1,
+            memory_size: 0,
+            trace_length: 0,
+            max_trace_length: 0,
+            proof_size: 0,
+            guest_hash: [0u8; 32],
+            urs_digest,
+            commitment_hash: CommitmentAlgorithm::Sha256,
+            template: None,
+            cost: None,
+            profile: ComplianceProfile::Standard,
+        }
+    }
+
+    #[test]
+    fn test_digest_matches_sha256() {
+        let digest = urs_digest(&b"abc"[..]).unwrap();
+        assert_eq!(
+            hex::encode(digest),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_file_digest() {
+        let dir = env::temp_dir().join("sig-agg-urs-tests");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.urs");
+        fs::write(&path, b"abc").unwrap();
+
+        assert_eq!(
+            urs_file_digest(&path).unwrap(),
+            urs_digest(&b"abc"[..]).unwrap()
+        );
+        assert!(matches!(
+            urs_file_digest(dir.join("missing.urs")),
+            Err(AggregationError::IoError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_check_urs_digest() {
+        let metadata = metadata_with([1u8; 32]);
+        assert!(check_urs_digest(&metadata, &[1u8; 32]).is_ok());
+
+        let err = check_urs_digest(&metadata, &[2u8; 32]).unwrap_err();
+        let AggregationError::ProofVerificationError { message } = err else {
+            panic!("Expected ProofVerificationError, got {:?}", err);
+        };
+        assert!(message.contains(&hex::encode([1u8; 32])));
+        assert!(message.contains(&hex::encode([2u8; 32])));
+    }
+}
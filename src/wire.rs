@@ -0,0 +1,464 @@
+//! Fixed-layout encoding of guest input and output.
+//!
+//! The host and the guest are built separately and must agree byte for byte
+//! on the batch: the guest's [input digest](crate::guest::input_digest) is
+//! computed over this encoding, and the host hands batches to the guest
+//! crate through it. Relying on a serializer's default layout would let a
+//! dependency upgrade on either side silently break that agreement, so the
+//! framing is written out by hand and versioned.
+//!
+//! # Layout (version 3)
+//!
+//! All integers are little-endian.
+//!
+//! | Field | Size |
+//! |---|---|
+//! | version, [`WIRE_VERSION`] | 1 |
+//! | item count `n` | 4 (`u32`) |
+//! | `n` items | variable |
+//!
+//! Each item:
+//!
+//! | Field | Size |
+//! |---|---|
+//! | message | [`MESSAGE_LENGTH`] |
+//! | epoch | 4 (`u32`) |
+//! | signature length `s` | 4 (`u32`) |
+//! | signature | `s` |
+//! | public key length `p` | 4 (`u32`) |
+//! | public key | `p` |
+//!
+//! Signatures and public keys are opaque to the framing. Their bytes are the
+//! bincode encoding of the `hashsig` types with fixed-width little-endian
+//! integers, configured explicitly rather than through bincode's defaults.
+//!
+//! Single-key batches, proven by the `verify_aggregation_single_key` entry
+//! point, carry the public key once:
+//!
+//! | Field | Size |
+//! |---|---|
+//! | version, [`WIRE_VERSION`] | 1 |
+//! | public key length `p` | 4 (`u32`) |
+//! | public key | `p` |
+//! | item count `n` | 4 (`u32`) |
+//! | `n` items: message, epoch, signature length and signature | variable |
+//!
+//! [Key-table batches](crate::key_table), proven by the
+//! `verify_aggregation_key_table` entry point, carry each distinct key once:
+//!
+//! | Field | Size |
+//! |---|---|
+//! | version, [`WIRE_VERSION`] | 1 |
+//! | key count `k` | 4 (`u32`) |
+//! | `k` keys: public key length and public key | variable |
+//! | item count `n` | 4 (`u32`) |
+//! | `n` items: message, epoch, key index (`u32`), signature length and signature | variable |
+//!
+//! [Batches of other instantiations](crate::scheme), proven by their own
+//! entry points, use the first layout with that scheme's signatures and
+//! public keys.
+//!
+//! The guest output is the verified count (`u32`), the 32-byte input digest,
+//! the 32-byte [verified root](crate::inclusion), the failure bitmap length
+//! (`u32`) and the failure bitmap, in which bit `i % 8` of byte `i / 8` is
+//! set if item `i` did not verify.
+//!
+//! Version 2 added the failure bitmap to the output and version 3 the
+//! verified root; the batch layout is unchanged from version 1.
+//!
+//! The tests pin encodings of fixed inputs; any change to them is a wire
+//! format change and needs a new [`WIRE_VERSION`].
+
+use bincode::Options;
+use hashsig::{MESSAGE_LENGTH, signature::SignatureScheme};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    codec::Message,
+    error::AggregationError,
+    guest::InputDigest,
+    inclusion::VerifiedRoot,
+    key_table::KeyTableBatch,
+    scheme::SchemeBatch,
+    types::{AggregationBatch, VerificationItem},
+};
+
+/// Version byte leading every encoded batch.
+pub const WIRE_VERSION: u8 = 3;
+
+/// Encodes `batch` in the fixed layout, shared by the batches of every
+/// [instantiation](crate::scheme).
+pub fn encode_batch<S: SignatureScheme>(
+    batch: &SchemeBatch<S>,
+) -> Result<Vec<u8>, AggregationError> {
+    let mut bytes = vec![WIRE_VERSION];
+    write_len(&mut bytes, batch.items.len())?;
+    for item in &batch.items {
+        write_item(
+            &mut bytes,
+            &item.message,
+            item.epoch,
+            &encode_blob(&item.signature)?,
+            &encode_blob(&item.public_key)?,
+        )?;
+    }
+    Ok(bytes)
+}
+
+/// Encodes `batch` in the single-key layout.
+///
+/// Fails with [`AggregationError::EmptyBatch`] if there is no key to share
+/// and [`AggregationError::MixedPublicKeys`] if an item's key differs from
+/// the first item's.
+pub fn encode_single_key_batch(batch: &AggregationBatch) -> Result<Vec<u8>, AggregationError> {
+    let first = batch.items.first().ok_or(AggregationError::EmptyBatch)?;
+    let public_key = encode_blob(&first.public_key)?;
+
+    let mut bytes = vec![WIRE_VERSION];
+    write_len(&mut bytes, public_key.len())?;
+    bytes.extend_from_slice(&public_key);
+    write_len(&mut bytes, batch.items.len())?;
+    for (index, item) in batch.items.iter().enumerate() {
+        if encode_blob(&item.public_key)? != public_key {
+            return Err(AggregationError::MixedPublicKeys { index });
+        }
+        let signature = encode_blob(&item.signature)?;
+        bytes.extend_from_slice(&item.message);
+        bytes.extend_from_slice(&item.epoch.to_le_bytes());
+        write_len(&mut bytes, signature.len())?;
+        bytes.extend_from_slice(&signature);
+    }
+    Ok(bytes)
+}
+
+/// Encodes `batch` in the key-table layout.
+///
+/// Fails with [`AggregationError::UnknownKeyIndex`] if an item refers to a
+/// key outside the table.
+pub fn encode_key_table_batch(batch: &KeyTableBatch) -> Result<Vec<u8>, AggregationError> {
+    let mut bytes = vec![WIRE_VERSION];
+    write_len(&mut bytes, batch.keys.len())?;
+    for public_key in &batch.keys {
+        let public_key = encode_blob(public_key)?;
+        write_len(&mut bytes, public_key.len())?;
+        bytes.extend_from_slice(&public_key);
+    }
+    write_len(&mut bytes, batch.items.len())?;
+    for (index, item) in batch.items.iter().enumerate() {
+        if item.key_index as usize >= batch.keys.len() {
+            return Err(AggregationError::UnknownKeyIndex {
+                index,
+                key_index: item.key_index,
+            });
+        }
+        let signature = encode_blob(&item.signature)?;
+        bytes.extend_from_slice(&item.message);
+        bytes.extend_from_slice(&item.epoch.to_le_bytes());
+        bytes.extend_from_slice(&item.key_index.to_le_bytes());
+        write_len(&mut bytes, signature.len())?;
+        bytes.extend_from_slice(&signature);
+    }
+    Ok(bytes)
+}
+
+/// Decodes a batch encoded with [`encode_batch`].
+///
+/// Fails with [`AggregationError::SerializationError`] on an unknown
+/// version, truncated or trailing bytes, or undecodable key material.
+pub fn decode_batch(bytes: &[u8]) -> Result<AggregationBatch, AggregationError> {
+    let mut reader = Reader { bytes };
+    let version = reader.take(1)?[0];
+    if version != WIRE_VERSION {
+        return Err(malformed(&format!("unsupported wire version {}", version)));
+    }
+
+    let count = reader.read_u32()?;
+    // Every item takes at least this many bytes, which bounds the allocation
+    let min_item_len = MESSAGE_LENGTH + 3 * 4;
+    if count as usize > reader.bytes.len() / min_item_len {
+        return Err(malformed(&format!("item count {} exceeds input", count)));
+    }
+
+    let mut items = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let item = reader.read_item()?;
+        items.push(VerificationItem {
+            message: item.message,
+            epoch: item.epoch,
+            signature: decode_blob(item.signature)?,
+            public_key: decode_blob(item.public_key)?,
+        });
+    }
+    if !reader.bytes.is_empty() {
+        return Err(malformed(&format!("{} trailing bytes", reader.bytes.len())));
+    }
+    Ok(AggregationBatch { items })
+}
+
+/// Encodes a guest output in the fixed layout.
+pub fn encode_output(
+    verified_count: u32,
+    input_digest: &InputDigest,
+    verified_root: &VerifiedRoot,
+    failed: &[u8],
+) -> Result<Vec<u8>, AggregationError> {
+    let mut bytes = Vec::with_capacity(4 + 32 + 32 + 4 + failed.len());
+    bytes.extend_from_slice(&verified_count.to_le_bytes());
+    bytes.extend_from_slice(input_digest);
+    bytes.extend_from_slice(verified_root);
+    write_len(&mut bytes, failed.len())?;
+    bytes.extend_from_slice(failed);
+    Ok(bytes)
+}
+
+/// Decodes a guest output into its verified count, input digest, verified
+/// root and failure bitmap.
+pub fn decode_output(
+    bytes: &[u8],
+) -> Result<(u32, InputDigest, VerifiedRoot, Vec<u8>), AggregationError> {
+    let mut reader = Reader { bytes };
+    let verified_count = reader.read_u32()?;
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(reader.take(32)?);
+    let mut verified_root = [0u8; 32];
+    verified_root.copy_from_slice(reader.take(32)?);
+    let failed = reader.read_blob()?.to_vec();
+    if !reader.bytes.is_empty() {
+        return Err(malformed(&format!("{} trailing bytes", reader.bytes.len())));
+    }
+    Ok((verified_count, digest, verified_root, failed))
+}
+
+/// Bincode configuration for signatures and public keys.
+fn blob_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+        .reject_trailing_bytes()
+}
+
+fn encode_blob<T: Serialize>(value: &T) -> Result<Vec<u8>, AggregationError> {
+    blob_options()
+        .serialize(value)
+        .map_err(|e| malformed(&e.to_string()))
+}
+
+fn decode_blob<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, AggregationError> {
+    blob_options()
+        .deserialize(bytes)
+        .map_err(|e| malformed(&e.to_string()))
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) -> Result<(), AggregationError> {
+    let len = u32::try_from(len).map_err(|_| malformed(&format!("length {} exceeds u32", len)))?;
+    bytes.extend_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+fn write_item(
+    bytes: &mut Vec<u8>,
+    message: &Message,
+    epoch: u32,
+    signature: &[u8],
+    public_key: &[u8],
+) -> Result<(), AggregationError> {
+    bytes.extend_from_slice(message);
+    bytes.extend_from_slice(&epoch.to_le_bytes());
+    write_len(bytes, signature.len())?;
+    bytes.extend_from_slice(signature);
+    write_len(bytes, public_key.len())?;
+    bytes.extend_from_slice(public_key);
+    Ok(())
+}
+
+fn malformed(reason: &str) -> AggregationError {
+    AggregationError::SerializationError {
+        message: format!("Malformed guest wire encoding: {}", reason),
+    }
+}
+
+/// Item fields with signature and public key still encoded.
+struct RawItem<'a> {
+    message: Message,
+    epoch: u32,
+    signature: &'a [u8],
+    public_key: &'a [u8],
+}
+
+/// Cursor over an encoded batch.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AggregationError> {
+        if self.bytes.len() < len {
+            return Err(malformed("unexpected end of input"));
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, AggregationError> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_blob(&mut self) -> Result<&'a [u8], AggregationError> {
+        let len = self.read_u32()? as usize;
+        self.take(len)
+    }
+
+    fn read_item(&mut self) -> Result<RawItem<'a>, AggregationError> {
+        let mut message = [0u8; MESSAGE_LENGTH];
+        message.copy_from_slice(self.take(MESSAGE_LENGTH)?);
+        let epoch = self.read_u32()?;
+        let signature = self.read_blob()?;
+        let public_key = self.read_blob()?;
+        Ok(RawItem {
+            message,
+            epoch,
+            signature,
+            public_key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_batch;
+
+    #[test]
+    fn test_batch_round_trip() {
+        let batch = test_batch(3);
+        let bytes = encode_batch(&batch).unwrap();
+        let decoded = decode_batch(&bytes).unwrap();
+        assert_eq!(encode_batch(&decoded).unwrap(), bytes);
+        assert_eq!(decoded.items[2].epoch, 2);
+    }
+
+    #[test]
+    fn test_item_framing_fixture() {
+        let mut bytes = vec![WIRE_VERSION];
+        write_len(&mut bytes, 1).unwrap();
+        write_item(&mut bytes, &[0xab; MESSAGE_LENGTH], 7, &[1, 2, 3], &[9]).unwrap();
+
+        let expected = format!(
+            "03{}{}{}{}{}{}",
+            "01000000",
+            "ab".repeat(MESSAGE_LENGTH),
+            "07000000",
+            "03000000010203",
+            "01000000",
+            "09"
+        );
+        assert_eq!(hex::encode(&bytes), expected);
+
+        let mut reader = Reader { bytes: &bytes[5..] };
+        let item = reader.read_item().unwrap();
+        assert_eq!(item.message, [0xab; MESSAGE_LENGTH]);
+        assert_eq!(item.epoch, 7);
+        assert_eq!(item.signature, [1, 2, 3]);
+        assert_eq!(item.public_key, [9]);
+        assert!(reader.bytes.is_empty());
+    }
+
+    #[test]
+    fn test_blob_options_fixture() {
+        // Fixed-width little-endian integers and a u64 sequence length
+        let value: (u32, u64, Vec<u16>) = (1, 2, vec![3, 4]);
+        assert_eq!(
+            hex::encode(encode_blob(&value).unwrap()),
+            "010000000200000000000000020000000000000003000400"
+        );
+        assert_eq!(
+            decode_blob::<(u32, u64, Vec<u16>)>(&encode_blob(&value).unwrap()).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_output_fixture() {
+        let bytes = encode_output(258, &[0xcd; 32], &[0xef; 32], &[0b0000_0101]).unwrap();
+        assert_eq!(
+            hex::encode(&bytes),
+            format!("02010000{}{}0100000005", "cd".repeat(32), "ef".repeat(32))
+        );
+        assert_eq!(
+            decode_output(&bytes).unwrap(),
+            (258, [0xcd; 32], [0xef; 32], vec![0b0000_0101])
+        );
+        assert!(decode_output(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_single_key_layout_stores_the_key_once() {
+        let batch = test_batch(3);
+        let single_key = encode_single_key_batch(&batch).unwrap();
+        let public_key = encode_blob(&batch.items[0].public_key).unwrap();
+        assert_eq!(
+            encode_batch(&batch).unwrap().len() - single_key.len(),
+            2 * (public_key.len() + 4)
+        );
+        assert_eq!(&single_key[5..5 + public_key.len()], &public_key[..]);
+
+        assert!(matches!(
+            encode_single_key_batch(&AggregationBatch { items: vec![] }),
+            Err(AggregationError::EmptyBatch)
+        ));
+    }
+
+    #[test]
+    fn test_key_table_layout_stores_each_key_once() {
+        let batch = test_batch(3);
+        let full = encode_batch(&batch).unwrap();
+        let public_key = encode_blob(&batch.items[0].public_key).unwrap();
+        let mut table = KeyTableBatch::from_batch(batch).unwrap();
+        let encoded = encode_key_table_batch(&table).unwrap();
+
+        // Two fewer keys, but a key index per item and a key count
+        assert_eq!(
+            full.len() - encoded.len(),
+            2 * (public_key.len() + 4) - 3 * 4 - 4
+        );
+        assert_eq!(&encoded[1..5], &1u32.to_le_bytes());
+        assert_eq!(&encoded[9..9 + public_key.len()], &public_key[..]);
+
+        table.items[2].key_index = 1;
+        assert_eq!(
+            encode_key_table_batch(&table),
+            Err(AggregationError::UnknownKeyIndex {
+                index: 2,
+                key_index: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        let bytes = encode_batch(&test_batch(1)).unwrap();
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = WIRE_VERSION + 1;
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        let mut huge_count = bytes.clone();
+        huge_count[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        for input in [
+            &wrong_version[..],
+            &trailing[..],
+            &huge_count[..],
+            &bytes[..bytes.len() - 1],
+            &[][..],
+        ] {
+            assert!(matches!(
+                decode_batch(input),
+                Err(AggregationError::SerializationError { .. })
+            ));
+        }
+    }
+}